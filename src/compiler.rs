@@ -0,0 +1,297 @@
+use crate::errors::MichelsonError;
+use crate::instructions::Instruction;
+use crate::m_types::{MType, MValue};
+use crate::macros;
+use crate::micheline::Micheline;
+use crate::parser::SourceMap;
+
+/// Decoded arguments of an instruction
+#[derive(Debug, Clone, PartialEq)]
+pub enum Args {
+    None,
+    // DIG, DUG, DUP and DROP
+    Count(usize),
+    // GET and UPDATE, the position of the field in a right-combed pair
+    Index(Option<usize>),
+    // NIL, NONE, LEFT and RIGHT
+    Type(MType),
+    // EMPTY_SET, EMPTY_MAP and EMPTY_BIG_MAP, the value type is only set for maps
+    Types(MType, Option<MType>),
+    // PUSH, LAMBDA and LAMBDA_REC
+    Value(MValue),
+    // ITER, LOOP, LOOP_LEFT and MAP
+    Block(Block),
+    // DIP, the number of protected elements and the code to run below them
+    CountAndBlock(usize, Block),
+    // IF, IF_CONS, IF_LEFT and IF_NONE
+    Branches(Block, Block),
+}
+
+/// Instruction ready to be run, with its decoded arguments
+#[derive(Debug, Clone, PartialEq)]
+pub struct Node {
+    pub instruction: Instruction,
+    pub args: Args,
+    // location of the instruction in the code after macro expansion
+    pub location: usize,
+    // annotations of the instruction, e.g. `@amount` or `%owner`
+    pub annots: Vec<String>,
+}
+
+/// Sequence of compiled instructions
+pub type Block = Vec<Node>;
+
+/// compiles the code block passed as argument to an instruction
+fn compile_block(
+    block: &Micheline,
+    instruction: &Instruction,
+    location: usize,
+) -> Result<Block, (MichelsonError, usize)> {
+    match block {
+        // the first instruction follows the sequence
+        Micheline::Seq(code) => compile_seq(code, location + 1),
+        _ => Err((
+            MichelsonError::Message(format!(
+                "Expected a sequence of instructions for {:?} instruction, got `{}`",
+                instruction, block
+            )),
+            location,
+        )),
+    }
+}
+
+/// compiles an instruction and its arguments
+/// the error is returned with the location of the node that couldn't be compiled
+fn compile_node(node: &Micheline, location: usize) -> Result<Node, (MichelsonError, usize)> {
+    let (instruction, args, annots) = match node {
+        Micheline::Prim { name, args, annots } => (
+            Instruction::from_str(name).map_err(|err| (err, location))?,
+            args,
+            annots,
+        ),
+        _ => {
+            return Err((
+                MichelsonError::Message(format!("Expected an instruction, got `{}`", node)),
+                location,
+            ))
+        }
+    };
+    // the first argument follows the instruction
+    let args_location = location + 1;
+    let args = match (&instruction, args.as_slice()) {
+        (
+            Instruction::IF | Instruction::IF_CONS | Instruction::IF_LEFT | Instruction::IF_NONE,
+            [branch_if, branch_else],
+        ) => Ok(Args::Branches(
+            compile_block(branch_if, &instruction, args_location)?,
+            compile_block(branch_else, &instruction, args_location + branch_if.size())?,
+        )),
+        (
+            Instruction::ITER | Instruction::LOOP | Instruction::LOOP_LEFT | Instruction::MAP,
+            [body],
+        ) => Ok(Args::Block(compile_block(
+            body,
+            &instruction,
+            args_location,
+        )?)),
+        (Instruction::DIP, [body]) => Ok(Args::CountAndBlock(
+            1,
+            compile_block(body, &instruction, args_location)?,
+        )),
+        (Instruction::DIP, [Micheline::Int(count), body]) => match count.parse::<usize>() {
+            Ok(count) => Ok(Args::CountAndBlock(
+                count,
+                compile_block(body, &instruction, args_location + 1)?,
+            )),
+            Err(_) => Err(MichelsonError::Message(format!(
+                "Argument for {:?} is not a valid number: {}",
+                instruction, count
+            ))),
+        },
+        (
+            Instruction::DIP
+            | Instruction::IF
+            | Instruction::IF_CONS
+            | Instruction::IF_LEFT
+            | Instruction::IF_NONE
+            | Instruction::ITER
+            | Instruction::LOOP
+            | Instruction::LOOP_LEFT
+            | Instruction::MAP,
+            [],
+        ) => {
+            return Err((MichelsonError::NoArgument(instruction), location))
+        }
+        (
+            Instruction::IF
+            | Instruction::IF_CONS
+            | Instruction::IF_LEFT
+            | Instruction::IF_NONE
+            | Instruction::DIP,
+            _,
+        ) => {
+            return Err((
+                MichelsonError::UnexpectedArgsNumber((2, args.len())),
+                location,
+            ))
+        }
+        (
+            Instruction::ITER | Instruction::LOOP | Instruction::LOOP_LEFT | Instruction::MAP,
+            _,
+        ) => {
+            return Err((
+                MichelsonError::UnexpectedArgsNumber((1, args.len())),
+                location,
+            ))
+        }
+        (_, []) => instruction.parse_args(None),
+        (_, _) => instruction.parse_args(Some(args)),
+    }
+    .map_err(|err| (err, location))?;
+
+    Ok(Node {
+        instruction,
+        args,
+        location,
+        annots: annots.clone(),
+    })
+}
+
+/// compiles a sequence of instructions starting at the provided location
+fn compile_seq(code: &[Micheline], mut location: usize) -> Result<Block, (MichelsonError, usize)> {
+    let mut block = Vec::with_capacity(code.len());
+    for node in code {
+        block.push(compile_node(node, location)?);
+        location += node.size();
+    }
+    Ok(block)
+}
+
+/// expands the macros and compiles the code into a tree of instructions
+pub fn compile(code: &[Micheline]) -> Result<Block, MichelsonError> {
+    // the first instruction follows the root sequence
+    compile_seq(&macros::expand(code)?, 1).map_err(|(err, _)| err)
+}
+
+/// expands the macros and compiles the code into a tree of instructions
+/// returns the source map of the expanded code, errors report the position of the faulty node
+pub fn compile_with_locations(
+    code: &[Micheline],
+    source_map: &SourceMap,
+) -> Result<(Block, SourceMap), MichelsonError> {
+    let (code, source_map) = macros::expand_with_locations(code, source_map)?;
+    match compile_seq(&code, 1) {
+        Ok(block) => Ok((block, source_map)),
+        Err((err, location)) => Err(source_map.locate_error(err, location)),
+    }
+}
+
+/// compiles the code of a test, written in the Michelson syntax (e.g. `{ PUSH nat 1 ; ADD }`)
+/// or as an array of instructions in Micheline JSON
+#[cfg(test)]
+pub fn compile_test_code(code: &str) -> Block {
+    let code = if code.trim_start().starts_with('[') {
+        Micheline::from_json(code).unwrap()
+    } else {
+        code.parse::<Micheline>().unwrap()
+    };
+    match code {
+        Micheline::Seq(nodes) => compile(&nodes).unwrap(),
+        node => compile(&[node]).unwrap(),
+    }
+}
+
+/**
+ * TESTS
+ */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser;
+
+    fn compile_str(code: &str) -> Result<Block, MichelsonError> {
+        let (code, source_map) = parser::parse_with_locations(code)?;
+        compile_with_locations(&code, &source_map).map(|(block, _)| block)
+    }
+
+    // PASSING
+    #[test]
+    fn compile_decoded_arguments() {
+        let block = compile_str("PUSH nat 5 ; DUP ; DIG 2 ; NIL int ; EMPTY_MAP string nat ; GET 3 ; ADD").unwrap();
+        let args: Vec<Args> = block.into_iter().map(|node| node.args).collect();
+        assert_eq!(
+            args,
+            vec![
+                Args::Value(MValue::new_nat(5)),
+                Args::Count(1),
+                Args::Count(2),
+                Args::Type(MType::Int),
+                Args::Types(MType::String, Some(MType::Nat)),
+                Args::Index(Some(3)),
+                Args::None,
+            ]
+        );
+    }
+
+    #[test]
+    fn compile_nested_blocks() {
+        let block = compile_str("IF { MAP { DROP ; PUSH nat 1 } } { DROP } ; UNIT").unwrap();
+        assert_eq!(block.len(), 2);
+        assert_eq!(block[1].location, 11);
+        match &block[0].args {
+            Args::Branches(branch_if, branch_else) => {
+                assert_eq!(branch_if.len(), 1);
+                assert_eq!(branch_if[0].instruction, Instruction::MAP);
+                assert_eq!(branch_if[0].location, 3);
+                match &branch_if[0].args {
+                    Args::Block(body) => {
+                        assert_eq!(body[0].instruction, Instruction::DROP);
+                        assert_eq!(body[0].location, 5);
+                        assert_eq!(body[1].args, Args::Value(MValue::new_nat(1)));
+                        assert_eq!(body[1].location, 6);
+                    }
+                    args => panic!("Unexpected arguments for MAP: {:?}", args),
+                }
+                assert_eq!(branch_else[0].instruction, Instruction::DROP);
+                assert_eq!(branch_else[0].location, 10);
+            }
+            args => panic!("Unexpected arguments for IF: {:?}", args),
+        }
+    }
+
+    #[test]
+    fn compile_expands_nested_macros() {
+        let block = compile(&[Micheline::prim(
+            "MAP",
+            vec![Micheline::Seq(vec![Micheline::prim("CDAR", vec![])])],
+        )])
+        .unwrap();
+        match &block[0].args {
+            Args::Block(body) => assert_eq!(
+                body.iter().map(|node| node.instruction).collect::<Vec<Instruction>>(),
+                vec![Instruction::CDR, Instruction::CAR]
+            ),
+            args => panic!("Unexpected arguments for MAP: {:?}", args),
+        }
+    }
+
+    // FAILING
+    #[test]
+    #[should_panic(expected = "Unknown type 'natural' at line 2, column 15")]
+    fn compile_invalid_type_in_branch() {
+        match compile_str("UNIT ;\nIF { DROP } { NIL natural }") {
+            Ok(_) => assert!(false),
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Unexpected number of arguments, expected `2`, got `1`")]
+    fn compile_if_missing_branch() {
+        match compile_str("IF { DROP }") {
+            Ok(_) => assert!(false),
+            Err(err) => panic!("{}", err),
+        }
+    }
+}
@@ -0,0 +1,875 @@
+use crate::compiler::{self, Block};
+use crate::errors::MichelsonError;
+use crate::instructions::{Instruction, RunOptions, RunOptionsContext};
+use crate::m_types::{MType, MValue, MapValue, Or, OrValue, PairValue, TypeAnnots};
+use crate::micheline::Micheline;
+use crate::parser::{self, Failed, RunResult, SourceMap};
+use crate::stack::{Stack, StackElement};
+use crate::typechecker::{self, Typed};
+use std::fs;
+use std::path::Path;
+
+// https://tezos.gitlab.io/active/michelson.html#concrete-syntax
+
+/// On-chain view declared in a contract
+#[derive(Debug, Clone, PartialEq)]
+pub struct View {
+    pub name: String,
+    pub input: MType,
+    pub output: MType,
+    pub code: Vec<Micheline>,
+}
+
+/// Branch of an `or` type on the way to an entrypoint
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Branch {
+    Left,
+    Right,
+}
+
+/// Entrypoint of a contract, named by a field annotation on its parameter type
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entrypoint {
+    pub name: String,
+    // type of the argument expected by the entrypoint
+    pub m_type: MType,
+    // branches to follow from the root of the parameter type, empty for the root
+    pub path: Vec<Branch>,
+}
+
+/// Collects the entrypoints declared with field annotations in the parameter type,
+/// annotated branches of `or` types are searched for nested entrypoints too
+fn collect_entrypoints(
+    m_type: &MType,
+    annots: &TypeAnnots,
+    path: Vec<Branch>,
+    entrypoints: &mut Vec<Entrypoint>,
+) {
+    if let Some(name) = &annots.field {
+        entrypoints.push(Entrypoint {
+            name: name.clone(),
+            m_type: m_type.clone(),
+            path: path.clone(),
+        });
+    }
+    if let MType::Or(types, branch_annots) = m_type {
+        for (branch, branch_type, annots) in [
+            (Branch::Left, &types.0, &branch_annots.0),
+            (Branch::Right, &types.1, &branch_annots.1),
+        ] {
+            let mut branch_path = path.clone();
+            branch_path.push(branch);
+            collect_entrypoints(branch_type, annots, branch_path, entrypoints);
+        }
+    }
+}
+
+/// Change made by a contract call to an entry of a big map of its storage
+#[derive(Debug, Clone, PartialEq)]
+pub struct BigMapDiff {
+    // index of the big map in the storage, big maps are numbered from left to right
+    pub big_map: usize,
+    pub key: MValue,
+    // new value of the entry, `None` if the entry was removed
+    pub value: Option<MValue>,
+}
+
+/// Result of a contract call that ran to the end of the code
+#[derive(Debug, Clone)]
+pub struct ContractResult {
+    pub operations: Vec<MValue>,
+    pub storage: MValue,
+    // number of instructions run, used as a measure of the consumed gas
+    pub steps: usize,
+    pub big_map_diff: Vec<BigMapDiff>,
+}
+
+/// Outcome of a contract call, the call either returns a new storage or fails with FAILWITH
+#[derive(Debug, Clone)]
+pub enum ContractOutcome {
+    Success(ContractResult),
+    Failure { failed: Failed, steps: usize },
+}
+
+/// Collects the big maps of a value from left to right
+fn collect_big_maps<'a>(value: &'a MValue, big_maps: &mut Vec<&'a MapValue>) {
+    match value {
+        MValue::Big_map(big_map) => big_maps.push(big_map),
+        MValue::Pair(pair) => {
+            collect_big_maps(&pair.value.0, big_maps);
+            collect_big_maps(&pair.value.1, big_maps);
+        }
+        MValue::Or(or) => match &*or.value {
+            Or::Left(value) | Or::Right(value) => collect_big_maps(value, big_maps),
+        },
+        MValue::Option(option) => {
+            if let Some(value) = &*option.value {
+                collect_big_maps(value, big_maps)
+            }
+        }
+        _ => (),
+    }
+}
+
+/// Compares the big maps of the storage before and after a call
+/// big maps are matched by their index in the storage
+fn diff_big_maps(old_storage: &MValue, new_storage: &MValue) -> Vec<BigMapDiff> {
+    let mut old_big_maps = vec![];
+    collect_big_maps(old_storage, &mut old_big_maps);
+    let mut new_big_maps = vec![];
+    collect_big_maps(new_storage, &mut new_big_maps);
+
+    let mut diff = vec![];
+    for (index, new_big_map) in new_big_maps.into_iter().enumerate() {
+        let old_entries = old_big_maps.get(index).map(|big_map| &big_map.value);
+        // added and updated entries
+        for (key, value) in &new_big_map.value {
+            if old_entries.and_then(|entries| entries.get(key)) != Some(value) {
+                diff.push(BigMapDiff {
+                    big_map: index,
+                    key: key.clone(),
+                    value: Some(value.clone()),
+                });
+            }
+        }
+        // removed entries
+        if let Some(old_entries) = old_entries {
+            for key in old_entries.keys() {
+                if !new_big_map.value.contains_key(key) {
+                    diff.push(BigMapDiff {
+                        big_map: index,
+                        key: key.clone(),
+                        value: None,
+                    });
+                }
+            }
+        }
+    }
+    diff
+}
+
+/// Calls a contract at an entrypoint with the provided argument and storage
+/// Checks the types of the argument and the storage and the type of the stack at the end of the code
+pub fn run_contract(
+    contract: &Contract,
+    entrypoint: &str,
+    parameter: MValue,
+    storage: MValue,
+    context: RunOptionsContext,
+) -> Result<ContractOutcome, MichelsonError> {
+    let options = RunOptions { context, pos: 0 };
+    let result = contract.run_entrypoint(entrypoint, parameter, storage.clone(), &options)?;
+    // the initial stack is the first snapshot
+    let steps = result.stack_snapshots.len().saturating_sub(1);
+    if let Some(failed) = result.failed {
+        return Ok(ContractOutcome::Failure { failed, steps });
+    }
+
+    let stack_type = result.stack.iter().map(|el| el.value.get_type()).collect();
+    typechecker::check_contract_stack(&Typed::Stack(stack_type), &contract.storage)?;
+    match &result.stack[0].value {
+        MValue::Pair(pair) => match &*pair.value {
+            (MValue::List(operations), new_storage) => {
+                Ok(ContractOutcome::Success(ContractResult {
+                    operations: operations.value.to_vec(),
+                    big_map_diff: diff_big_maps(&storage, new_storage),
+                    storage: new_storage.clone(),
+                    steps,
+                }))
+            }
+            _ => Err(MichelsonError::Message(String::from(
+                "Expected a list of operations at the end of the contract",
+            ))),
+        },
+        _ => Err(MichelsonError::Message(String::from(
+            "Expected a pair at the end of the contract",
+        ))),
+    }
+}
+
+/// Contract script with its parameter and storage types, its code and its views
+#[derive(Debug, Clone)]
+pub struct Contract {
+    pub parameter: MType,
+    pub storage: MType,
+    pub code: Vec<Micheline>,
+    pub views: Vec<View>,
+    // field and type annotations of the root of the parameter and storage types
+    pub parameter_annots: TypeAnnots,
+    pub storage_annots: TypeAnnots,
+    // code compiled when the contract is loaded and the source map locating its instructions
+    pub block: Block,
+    pub source_map: SourceMap,
+}
+
+// the compiled code follows from the code and the source map from where the contract was loaded
+impl PartialEq for Contract {
+    fn eq(&self, other: &Self) -> bool {
+        self.parameter == other.parameter
+            && self.storage == other.storage
+            && self.code == other.code
+            && self.views == other.views
+    }
+}
+
+/// Unwraps the code of a `code` or `view` section
+fn parse_code_block(node: &Micheline) -> Vec<Micheline> {
+    match node {
+        Micheline::Seq(nodes) => nodes.clone(),
+        node => vec![node.clone()],
+    }
+}
+
+impl Contract {
+    /// Builds a contract from the sections of a parsed script
+    /// The code is compiled and typechecked, the source map locates the errors in the script
+    fn from_sections(sections: &[Micheline], source_map: &SourceMap) -> Result<Contract, MichelsonError> {
+        let mut parameter: Option<(MType, TypeAnnots)> = None;
+        let mut storage: Option<(MType, TypeAnnots)> = None;
+        let mut code: Option<(Vec<Micheline>, SourceMap)> = None;
+        let mut views: Vec<View> = vec![];
+
+        // the first section follows the root sequence
+        let mut location = 1;
+        for section in sections {
+            let section_location = location;
+            location += section.size();
+            match section {
+                Micheline::Prim { name, args, .. } => match (name.as_str(), args.as_slice()) {
+                    ("parameter", [param_type]) if parameter.is_none() => {
+                        parameter = Some(MType::from_micheline_annotated(param_type)?)
+                    }
+                    ("storage", [storage_type]) if storage.is_none() => {
+                        storage = Some(MType::from_micheline_annotated(storage_type)?)
+                    }
+                    ("code", [code_block]) if code.is_none() => {
+                        // the instructions of the code start at location 1 of its source map
+                        let code_location = match code_block {
+                            Micheline::Seq(_) => section_location + 1,
+                            _ => section_location,
+                        };
+                        code = Some((
+                            parse_code_block(code_block),
+                            source_map.sub_map(code_location),
+                        ))
+                    }
+                    ("view", [Micheline::String(view_name), input, output, code_block]) => {
+                        if views.iter().any(|view| &view.name == view_name) {
+                            return Err(MichelsonError::Message(format!(
+                                "Duplicate view `{}` in contract",
+                                view_name
+                            )));
+                        }
+                        views.push(View {
+                            name: view_name.clone(),
+                            input: MType::from_micheline(input)?,
+                            output: MType::from_micheline(output)?,
+                            code: parse_code_block(code_block),
+                        })
+                    }
+                    ("parameter" | "storage" | "code", [_]) => {
+                        return Err(MichelsonError::Message(format!(
+                            "Duplicate `{}` section in contract",
+                            name
+                        )))
+                    }
+                    _ => {
+                        return Err(MichelsonError::Message(format!(
+                            "Invalid section `{}` in contract",
+                            name
+                        )))
+                    }
+                },
+                _ => {
+                    return Err(MichelsonError::Message(format!(
+                        "Invalid section in contract: {}",
+                        section
+                    )))
+                }
+            }
+        }
+
+        match (parameter, storage, code) {
+            (
+                Some((parameter, parameter_annots)),
+                Some((storage, storage_annots)),
+                Some((code, code_source_map)),
+            ) => {
+                // entrypoint names must be unique
+                let mut entrypoints = vec![];
+                collect_entrypoints(&parameter, &parameter_annots, vec![], &mut entrypoints);
+                for (index, entrypoint) in entrypoints.iter().enumerate() {
+                    if entrypoints[..index]
+                        .iter()
+                        .any(|other| other.name == entrypoint.name)
+                    {
+                        return Err(MichelsonError::Message(format!(
+                            "Duplicate entrypoint `{}` in contract",
+                            entrypoint.name
+                        )));
+                    }
+                }
+                // the code is compiled once and must typecheck
+                let (block, source_map) = compiler::compile_with_locations(&code, &code_source_map)?;
+                typechecker::typecheck_compiled_contract(&block, &source_map, &parameter, &storage)?;
+                let contract = Contract {
+                    parameter,
+                    storage,
+                    code,
+                    views,
+                    parameter_annots,
+                    storage_annots,
+                    block,
+                    source_map,
+                };
+                Ok(contract)
+            }
+            (None, _, _) => Err(MichelsonError::Message(String::from(
+                "Missing `parameter` section in contract",
+            ))),
+            (_, None, _) => Err(MichelsonError::Message(String::from(
+                "Missing `storage` section in contract",
+            ))),
+            (_, _, None) => Err(MichelsonError::Message(String::from(
+                "Missing `code` section in contract",
+            ))),
+        }
+    }
+
+    /// Lists the entrypoints of the contract from the field annotations of its parameter type
+    /// If no branch is annotated with `%default`, the whole parameter is the `default` entrypoint
+    pub fn entrypoints(&self) -> Vec<Entrypoint> {
+        let mut entrypoints = vec![];
+        collect_entrypoints(
+            &self.parameter,
+            &self.parameter_annots,
+            vec![],
+            &mut entrypoints,
+        );
+        if !entrypoints.iter().any(|entrypoint| entrypoint.name == "default") {
+            entrypoints.push(Entrypoint {
+                name: String::from("default"),
+                m_type: self.parameter.clone(),
+                path: vec![],
+            });
+        }
+        entrypoints
+    }
+
+    /// Finds an entrypoint of the contract by its name
+    pub fn entrypoint(&self, name: &str) -> Result<Entrypoint, MichelsonError> {
+        self.entrypoints()
+            .into_iter()
+            .find(|entrypoint| entrypoint.name == name)
+            .ok_or_else(|| {
+                MichelsonError::Message(format!("Contract has no entrypoint `{}`", name))
+            })
+    }
+
+    /// Builds the parameter of the contract to call an entrypoint with the provided argument
+    /// The argument is wrapped in the `Left` and `Right` values leading to the entrypoint
+    pub fn build_parameter(
+        &self,
+        entrypoint: &str,
+        argument: MValue,
+    ) -> Result<MValue, MichelsonError> {
+        let entrypoint = self.entrypoint(entrypoint)?;
+        if argument.get_type() != entrypoint.m_type {
+            return Err(MichelsonError::Message(format!(
+                "Invalid argument for entrypoint `{}`, expected `{}`, but got `{}`",
+                entrypoint.name,
+                entrypoint.m_type.to_string(),
+                argument.get_type().to_string()
+            )));
+        }
+        // finds the types of the `or` values on the path to the entrypoint
+        let mut or_types = Vec::with_capacity(entrypoint.path.len());
+        let mut m_type = &self.parameter;
+        for branch in &entrypoint.path {
+            if let MType::Or(types, _) = m_type {
+                or_types.push((branch, (types.0.clone(), types.1.clone())));
+                m_type = match branch {
+                    Branch::Left => &types.0,
+                    Branch::Right => &types.1,
+                };
+            }
+        }
+        // wraps the argument from the entrypoint up to the root of the parameter
+        let parameter = or_types
+            .into_iter()
+            .rev()
+            .fold(argument, |value, (branch, types)| {
+                let value = match branch {
+                    Branch::Left => Or::Left(value),
+                    Branch::Right => Or::Right(value),
+                };
+                MValue::Or(OrValue::new(value, types))
+            });
+        Ok(parameter)
+    }
+
+    /// Runs the code of the contract called at an entrypoint with the provided argument and storage
+    pub fn run_entrypoint(
+        &self,
+        entrypoint: &str,
+        argument: MValue,
+        storage: MValue,
+        options: &RunOptions,
+    ) -> Result<RunResult, MichelsonError> {
+        let parameter = self.build_parameter(entrypoint, argument)?;
+        if storage.get_type() != self.storage {
+            return Err(MichelsonError::Message(format!(
+                "Invalid storage, expected `{}`, but got `{}`",
+                self.storage.to_string(),
+                storage.get_type().to_string()
+            )));
+        }
+        // the code starts with the pair of the parameter and the storage on the stack
+        let stack: Stack = vec![StackElement::new(
+            MValue::Pair(PairValue::new(parameter, storage)),
+            Instruction::INIT,
+        )
+        .with_type(MType::Pair(
+            Box::new((self.parameter.clone(), self.storage.clone())),
+            Box::new((self.parameter_annots.clone(), self.storage_annots.clone())),
+        ))];
+        let stack_snapshots = vec![stack.clone()];
+        parser::run_block_with_locations(&self.block, &self.source_map, stack, stack_snapshots, options)
+    }
+
+    /// Parses a contract from Michelson source code (.tz)
+    pub fn from_str(script: &str) -> Result<Contract, MichelsonError> {
+        let (sections, source_map) = parser::parse_with_locations(script)?;
+        Contract::from_sections(&sections, &source_map)
+    }
+
+    /// Parses a contract from Micheline JSON
+    pub fn from_json(json: &str) -> Result<Contract, MichelsonError> {
+        match Micheline::from_json(json).map_err(MichelsonError::InvalidJson)? {
+            Micheline::Seq(sections) => Contract::from_sections(&sections, &SourceMap::default()),
+            _ => Err(MichelsonError::InvalidJson(String::from(
+                "Unexpected JSON value for contract, expected an array of sections",
+            ))),
+        }
+    }
+
+    /// Loads a contract from a file, Micheline JSON is expected if the extension is `.json`
+    pub fn from_file(path: &Path) -> Result<Contract, MichelsonError> {
+        let script = fs::read_to_string(path).map_err(|err| {
+            MichelsonError::Message(format!("Couldn't read file {}: {}", path.display(), err))
+        })?;
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("json") => Contract::from_json(&script),
+            _ => Contract::from_str(&script),
+        }
+    }
+}
+
+/**
+ * TESTS
+ */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::instructions::RunOptionsContext;
+    use crate::m_types::MAX_MUTEZ;
+
+    const COUNTER_CONTRACT: &str = r#"
+        parameter (or (or (int %decrement) (int %increment)) (unit %reset)) ;
+        storage int ;
+        code { UNPAIR ;
+               IF_LEFT { IF_LEFT { SWAP ; SUB } { ADD } } { DROP 2 ; PUSH int 0 } ;
+               NIL operation ;
+               PAIR } ;
+        view "get_counter" unit int { CDR }
+    "#;
+
+    // PASSING
+    #[test]
+    fn contract_from_str() {
+        match Contract::from_str(COUNTER_CONTRACT) {
+            Err(err) => panic!("{}", err),
+            Ok(contract) => {
+                assert_eq!(
+                    contract.parameter,
+                    MType::or(
+                        MType::or(MType::Int, MType::Int),
+                        MType::Unit
+                    )
+                );
+                assert_eq!(contract.storage, MType::Int);
+                assert_eq!(contract.code.len(), 4);
+                assert_eq!(contract.views.len(), 1);
+                assert_eq!(contract.views[0].name, String::from("get_counter"));
+                assert_eq!(contract.views[0].input, MType::Unit);
+                assert_eq!(contract.views[0].output, MType::Int);
+                assert_eq!(contract.views[0].code.len(), 1);
+            }
+        }
+    }
+
+    #[test]
+    fn contract_from_json() {
+        let json = r#"[
+            { "prim": "parameter", "args": [ { "prim": "list", "args": [ { "prim": "nat" } ] } ] },
+            { "prim": "storage", "args": [ { "prim": "pair", "args": [ { "prim": "nat" }, { "prim": "string" }, { "prim": "bool" } ] } ] },
+            { "prim": "code", "args": [ [ { "prim": "CDR" }, { "prim": "NIL", "args": [ { "prim": "operation" } ] }, { "prim": "PAIR" } ] ] }
+        ]"#;
+        match Contract::from_json(json) {
+            Err(err) => panic!("{}", err),
+            Ok(contract) => {
+                assert_eq!(contract.parameter, MType::List(Box::new(MType::Nat)));
+                assert_eq!(
+                    contract.storage,
+                    MType::pair(
+                        MType::Nat,
+                        MType::pair(MType::String, MType::Bool)
+                    )
+                );
+                assert_eq!(contract.code.len(), 3);
+                assert_eq!(contract.views.len(), 0);
+            }
+        }
+    }
+
+    #[test]
+    fn contract_text_and_json_match() {
+        let contract = Contract::from_str(COUNTER_CONTRACT).unwrap();
+        let sections = parser::parse(String::from(COUNTER_CONTRACT)).unwrap();
+        let json = Micheline::Seq(sections).to_json();
+        assert_eq!(Contract::from_json(&json), Ok(contract));
+    }
+
+    #[test]
+    fn contract_from_file() {
+        let path = std::env::temp_dir().join("michelson_rust_contract_from_file.tz");
+        fs::write(&path, COUNTER_CONTRACT).unwrap();
+        let contract = Contract::from_file(&path);
+        fs::remove_file(&path).unwrap();
+        assert_eq!(contract, Contract::from_str(COUNTER_CONTRACT));
+    }
+
+    #[test]
+    fn contract_entrypoints() {
+        let contract = Contract::from_str(COUNTER_CONTRACT).unwrap();
+        let entrypoints: Vec<(String, Vec<Branch>)> = contract
+            .entrypoints()
+            .into_iter()
+            .map(|entrypoint| (entrypoint.name, entrypoint.path))
+            .collect();
+        assert_eq!(
+            entrypoints,
+            vec![
+                (String::from("decrement"), vec![Branch::Left, Branch::Left]),
+                (String::from("increment"), vec![Branch::Left, Branch::Right]),
+                (String::from("reset"), vec![Branch::Right]),
+                (String::from("default"), vec![]),
+            ]
+        );
+        assert_eq!(contract.entrypoint("reset").unwrap().m_type, MType::Unit);
+        assert_eq!(contract.entrypoint("default").unwrap().m_type, contract.parameter);
+    }
+
+    #[test]
+    fn contract_explicit_default_entrypoint() {
+        let contract = Contract::from_str(
+            "parameter (or (nat %default) (pair %transfer address nat)) ; storage nat ; code { CDR ; NIL operation ; PAIR }",
+        )
+        .unwrap();
+        let entrypoint = contract.entrypoint("default").unwrap();
+        assert_eq!(entrypoint.m_type, MType::Nat);
+        assert_eq!(entrypoint.path, vec![Branch::Left]);
+        assert_eq!(contract.entrypoints().len(), 2);
+    }
+
+    #[test]
+    fn contract_build_parameter() {
+        let contract = Contract::from_str(COUNTER_CONTRACT).unwrap();
+        let inner_type = (MType::Int, MType::Int);
+        let outer_type = (MType::or(MType::Int, MType::Int), MType::Unit);
+        assert_eq!(
+            contract.build_parameter("increment", MValue::new_int(3)),
+            Ok(MValue::Or(OrValue::new(
+                Or::Left(MValue::Or(OrValue::new(Or::Right(MValue::new_int(3)), inner_type))),
+                outer_type.clone()
+            )))
+        );
+        assert_eq!(
+            contract.build_parameter("reset", MValue::Unit),
+            Ok(MValue::Or(OrValue::new(Or::Right(MValue::Unit), outer_type)))
+        );
+    }
+
+    #[test]
+    fn contract_run_entrypoint() {
+        let contract = Contract::from_str(COUNTER_CONTRACT).unwrap();
+        let options = RunOptions {
+            context: RunOptionsContext::mock(),
+            pos: 0,
+        };
+        match contract.run_entrypoint("decrement", MValue::new_int(3), MValue::new_int(5), &options) {
+            Err(err) => panic!("{}", err),
+            Ok(result) => {
+                assert_eq!(result.stack.len(), 1);
+                assert_eq!(
+                    result.stack[0].value,
+                    MValue::Pair(PairValue::new(
+                        MValue::new_list(vec![], MType::Operation),
+                        MValue::new_int(2)
+                    ))
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn run_contract_success() {
+        let contract = Contract::from_str(COUNTER_CONTRACT).unwrap();
+        match run_contract(
+            &contract,
+            "increment",
+            MValue::new_int(3),
+            MValue::new_int(5),
+            RunOptionsContext::mock(),
+        ) {
+            Ok(ContractOutcome::Success(result)) => {
+                assert!(result.operations.is_empty());
+                assert_eq!(result.storage, MValue::new_int(8));
+                // UNPAIR, IF_LEFT, IF_LEFT, ADD, NIL and PAIR
+                assert_eq!(result.steps, 6);
+                assert!(result.big_map_diff.is_empty());
+            }
+            Ok(outcome) => panic!("Unexpected outcome: {:?}", outcome),
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    #[test]
+    fn run_contract_failure() {
+        let contract = Contract::from_str(
+            "parameter nat ; storage nat ; code { CAR ; PUSH nat 10 ; COMPARE ; LT ; IF { PUSH string \"TOO_HIGH\" ; FAILWITH } { PUSH nat 0 ; NIL operation ; PAIR } }",
+        )
+        .unwrap();
+        match run_contract(
+            &contract,
+            "default",
+            MValue::new_nat(12),
+            MValue::new_nat(0),
+            RunOptionsContext::mock(),
+        ) {
+            Ok(ContractOutcome::Failure { failed, steps }) => {
+                assert_eq!(failed.value, MValue::new_string("TOO_HIGH"));
+                assert_eq!(steps, 7);
+            }
+            Ok(outcome) => panic!("Unexpected outcome: {:?}", outcome),
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    #[test]
+    fn run_contract_lambda_in_storage() {
+        let contract = Contract::from_str(
+            "parameter nat ; storage (pair (lambda nat nat) nat) ; code { UNPAIR ; SWAP ; CAR ; DUP ; DIG 2 ; EXEC ; SWAP ; PAIR ; NIL operation ; PAIR }",
+        )
+        .unwrap();
+        let storage = MValue::from_micheline(
+            &"Pair { PUSH nat 2 ; MUL } 0".parse().unwrap(),
+            &MType::from_string("pair (lambda nat nat) nat").unwrap(),
+        )
+        .unwrap();
+        match run_contract(
+            &contract,
+            "default",
+            MValue::new_nat(21),
+            storage.clone(),
+            RunOptionsContext::mock(),
+        ) {
+            Ok(ContractOutcome::Success(result)) => match (result.storage, storage) {
+                (MValue::Pair(new_storage), MValue::Pair(old_storage)) => {
+                    assert_eq!(new_storage.car(), old_storage.car());
+                    assert_eq!(new_storage.cdr(), MValue::new_nat(42));
+                }
+                (new_storage, _) => panic!("Unexpected storage: {:?}", new_storage),
+            },
+            Ok(outcome) => panic!("Unexpected outcome: {:?}", outcome),
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    #[test]
+    fn run_contract_big_map_diff() {
+        let contract = Contract::from_str(
+            "parameter unit ; storage (pair nat (big_map string nat)) ; code { CDR ; CAR ; EMPTY_BIG_MAP string nat ; SWAP ; PAIR ; NIL operation ; PAIR }",
+        )
+        .unwrap();
+        let storage = MValue::Pair(PairValue::new(
+            MValue::new_nat(1),
+            MValue::new_big_map(
+                MType::String,
+                MType::Nat,
+                vec![(MValue::new_string("tezos"), MValue::new_nat(2))],
+            ).unwrap(),
+        ));
+        match run_contract(&contract, "default", MValue::Unit, storage, RunOptionsContext::mock()) {
+            Ok(ContractOutcome::Success(result)) => assert_eq!(
+                result.big_map_diff,
+                vec![BigMapDiff {
+                    big_map: 0,
+                    key: MValue::new_string("tezos"),
+                    value: None
+                }]
+            ),
+            Ok(outcome) => panic!("Unexpected outcome: {:?}", outcome),
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    #[test]
+    fn diff_big_maps_updates() {
+        let old_storage = MValue::new_big_map(
+            MType::String,
+            MType::Nat,
+            vec![
+                (MValue::new_string("a"), MValue::new_nat(1)),
+                (MValue::new_string("b"), MValue::new_nat(2)),
+            ],
+        ).unwrap();
+        let new_storage = MValue::new_big_map(
+            MType::String,
+            MType::Nat,
+            vec![
+                (MValue::new_string("a"), MValue::new_nat(1)),
+                (MValue::new_string("b"), MValue::new_nat(3)),
+                (MValue::new_string("c"), MValue::new_nat(4)),
+            ],
+        ).unwrap();
+        let diff = diff_big_maps(&old_storage, &new_storage);
+        assert_eq!(diff.len(), 2);
+        assert!(diff.contains(&BigMapDiff {
+            big_map: 0,
+            key: MValue::new_string("b"),
+            value: Some(MValue::new_nat(3))
+        }));
+        assert!(diff.contains(&BigMapDiff {
+            big_map: 0,
+            key: MValue::new_string("c"),
+            value: Some(MValue::new_nat(4))
+        }));
+    }
+
+    // FAILING
+    #[test]
+    #[should_panic(expected = "Missing `storage` section in contract")]
+    fn contract_missing_storage() {
+        match Contract::from_str("parameter unit ; code { CDR ; NIL operation ; PAIR }") {
+            Ok(_) => assert!(false),
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Duplicate `storage` section in contract")]
+    fn contract_duplicate_section() {
+        match Contract::from_str("parameter unit ; storage nat ; storage int ; code {}") {
+            Ok(_) => assert!(false),
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Unknown type 'natural'")]
+    fn contract_invalid_type() {
+        match Contract::from_str("parameter unit ; storage natural ; code {}") {
+            Ok(_) => assert!(false),
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Contract has no entrypoint `transfer`")]
+    fn contract_unknown_entrypoint() {
+        let contract = Contract::from_str(COUNTER_CONTRACT).unwrap();
+        match contract.build_parameter("transfer", MValue::new_int(3)) {
+            Ok(_) => assert!(false),
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Invalid argument for entrypoint `increment`, expected `int`, but got `nat`"
+    )]
+    fn contract_entrypoint_wrong_argument() {
+        let contract = Contract::from_str(COUNTER_CONTRACT).unwrap();
+        match contract.build_parameter("increment", MValue::new_nat(3)) {
+            Ok(_) => assert!(false),
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Duplicate entrypoint `add` in contract")]
+    fn contract_duplicate_entrypoint() {
+        match Contract::from_str("parameter (or (nat %add) (int %add)) ; storage nat ; code {}") {
+            Ok(_) => assert!(false),
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid storage, expected `int`, but got `nat`")]
+    fn run_contract_wrong_storage() {
+        let contract = Contract::from_str(COUNTER_CONTRACT).unwrap();
+        match run_contract(
+            &contract,
+            "reset",
+            MValue::Unit,
+            MValue::new_nat(5),
+            RunOptionsContext::mock(),
+        ) {
+            Ok(_) => assert!(false),
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Unexpected stack type at the end of the contract, expected [pair (list operation) nat], but got [nat]"
+    )]
+    fn contract_invalid_final_stack() {
+        match Contract::from_str("parameter unit ; storage nat ; code { CDR }") {
+            Ok(_) => assert!(false),
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "but got [string : nat] at line 3, column 32")]
+    fn contract_ill_typed_code() {
+        let script = "parameter unit ;\nstorage nat ;\ncode { CDR ; PUSH string \"a\" ; ADD ; NIL operation ; PAIR }";
+        match Contract::from_str(script) {
+            Ok(_) => assert!(false),
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Overflow of mutez value for instruction ADD, the maximum is 9223372036854775807 at line 4, column 8"
+    )]
+    fn run_contract_error_location() {
+        let script = "parameter mutez ;\nstorage mutez ;\ncode { UNPAIR ;\n       ADD ;\n       NIL operation ;\n       PAIR }";
+        let contract = Contract::from_str(script).unwrap();
+        match run_contract(
+            &contract,
+            "default",
+            MValue::Mutez(MAX_MUTEZ),
+            MValue::Mutez(1),
+            RunOptionsContext::mock(),
+        ) {
+            Ok(_) => assert!(false),
+            Err(err) => panic!("{}", err),
+        }
+    }
+}
@@ -1,8 +1,21 @@
 use crate::instructions::Instruction;
-use crate::m_types::{mutez, nat, MType};
+use crate::m_types::{mutez, nat, MType, MAX_MUTEZ};
+use crate::parser::{ParseError, Span};
+use std::error::Error;
+use std::fmt;
 
-#[derive(Debug)]
-pub enum ErrorCode {
+/// formats the types of a stack from top to bottom, e.g. `int : nat`
+fn stack_type_to_string(stack: &[MType]) -> String {
+    stack
+        .iter()
+        .map(|m_type| m_type.to_string())
+        .collect::<Vec<String>>()
+        .join(" : ")
+}
+
+/// Error returned by the parser, the compiler and the interpreter
+#[derive(Debug, Clone, PartialEq)]
+pub enum MichelsonError {
     InvalidMutez(mutez),
     InvalidNat(nat),
     InvalidArgument((String, String)),
@@ -14,55 +27,132 @@ pub enum ErrorCode {
     InvalidType((Vec<MType>, MType, Instruction)),
     Unknown,
     WrongType((String, String, Instruction)),
+    // expected and actual types of the stack found by the typechecker
+    InvalidStackType((String, Vec<MType>, Instruction)),
+    // types of the stack at the end of the two branches of an instruction
+    BranchesMismatch((Vec<MType>, Vec<MType>, Instruction)),
+    // the loop ran more than the maximum number of iterations
+    TooManyIterations((usize, Instruction)),
+    // the result of the instruction is out of the range of mutez values
+    MutezOverflow(Instruction),
+    // the instruction is recognized but this case is not handled yet
+    Unsupported(String),
+    InvalidJson(String),
+    Parse(ParseError),
+    // error raised by the node at the provided span in the source code
+    Located {
+        error: Box<MichelsonError>,
+        span: Span,
+        excerpt: String,
+        macro_name: Option<String>,
+    },
+    Message(String),
 }
 
-pub fn display_error(error_code: ErrorCode) -> String {
-    match error_code {
-        ErrorCode::StackNotDeepEnough((expected, got, instruction)) => format!(
-            "Unexpected stack length, expected a length of {} for instruction {:?}, got {}",
-            expected, instruction, got
-        ),
-        ErrorCode::WrongType((expected, got, instruction)) => {
-            format!(
+impl fmt::Display for MichelsonError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MichelsonError::StackNotDeepEnough((expected, got, instruction)) => write!(
+                f,
+                "Unexpected stack length, expected a length of {} for instruction {:?}, got {}",
+                expected, instruction, got
+            ),
+            MichelsonError::WrongType((expected, got, instruction)) => write!(
+                f,
                 "Wrong type, expected `{}` for instruction {:?}, got `{}`",
                 expected, instruction, got
-            )
-        }
-        ErrorCode::Unknown => String::from("An unknown error has occured"),
-        ErrorCode::UnexpectedArgsNumber((expected, got)) => format!(
-            "Unexpected number of arguments, expected `{}`, got `{}`",
-            expected, got
-        ),
-        ErrorCode::InvalidNat(val) => format!("Invalid nat value {}", val),
-        ErrorCode::InvalidMutez(val) => format!("Invalid mutez value {}", val),
-        ErrorCode::Noop(val) => format!("Noop performed: {}", val),
-        ErrorCode::NoArgument(instruction) => {
-            format!("No argument provided for the {:?} instruction", instruction)
-        }
-        ErrorCode::InvalidArgument((expected, got)) => format!(
-            "Invalid argument provided, expected `{}`, but got `{}`",
-            expected, got
-        ),
-        ErrorCode::InvalidType((expected, got, instruction)) => format!(
-            "Invalid type for `{:?}` expected {}, but got {}",
-            instruction,
-            if expected.len() == 1 {
-                expected[0].to_string()
-            } else {
+            ),
+            MichelsonError::Unknown => write!(f, "An unknown error has occured"),
+            MichelsonError::UnexpectedArgsNumber((expected, got)) => write!(
+                f,
+                "Unexpected number of arguments, expected `{}`, got `{}`",
+                expected, got
+            ),
+            MichelsonError::InvalidNat(val) => write!(f, "Invalid nat value {}", val),
+            MichelsonError::InvalidMutez(val) => write!(f, "Invalid mutez value {}", val),
+            MichelsonError::Noop(val) => write!(f, "Noop performed: {}", val),
+            MichelsonError::NoArgument(instruction) => {
+                write!(f, "No argument provided for the {:?} instruction", instruction)
+            }
+            MichelsonError::InvalidArgument((expected, got)) => write!(
+                f,
+                "Invalid argument provided, expected `{}`, but got `{}`",
+                expected, got
+            ),
+            MichelsonError::InvalidType((expected, got, instruction)) => write!(
+                f,
+                "Invalid type for `{:?}` expected {}, but got {}",
+                instruction,
                 expected
                     .iter()
                     .map(|t| t.to_string())
                     .collect::<Vec<String>>()
-                    .join(" | ")
-            },
-            got.to_string()
-        ),
-        ErrorCode::InvalidStack((pos, expected, got, instruction)) => format!(
-            "Expected element at position {} to be of type {}, but got {} for instruction {:?}",
-            pos,
-            expected.to_string(),
-            got.to_string(),
-            instruction
-        ),
+                    .join(" | "),
+                got.to_string()
+            ),
+            MichelsonError::InvalidStack((pos, expected, got, instruction)) => write!(
+                f,
+                "Expected element at position {} to be of type {}, but got {} for instruction {:?}",
+                pos,
+                expected.to_string(),
+                got.to_string(),
+                instruction
+            ),
+            MichelsonError::InvalidStackType((expected, got, instruction)) => write!(
+                f,
+                "Invalid stack type for instruction {:?}, expected [{}], but got [{}]",
+                instruction,
+                expected,
+                stack_type_to_string(got)
+            ),
+            MichelsonError::BranchesMismatch((branch_if, branch_else, instruction)) => write!(
+                f,
+                "Branches of instruction {:?} end with different stack types: [{}] and [{}]",
+                instruction,
+                stack_type_to_string(branch_if),
+                stack_type_to_string(branch_else)
+            ),
+            MichelsonError::TooManyIterations((max, instruction)) => write!(
+                f,
+                "Instruction {:?} exceeded the maximum number of iterations ({})",
+                instruction, max
+            ),
+            MichelsonError::MutezOverflow(instruction) => write!(
+                f,
+                "Overflow of mutez value for instruction {:?}, the maximum is {}",
+                instruction, MAX_MUTEZ
+            ),
+            MichelsonError::Unsupported(val) => write!(f, "Not supported yet: {}", val),
+            MichelsonError::InvalidJson(val) => write!(f, "Invalid JSON: {}", val),
+            MichelsonError::Parse(err) => write!(f, "{}", err),
+            MichelsonError::Located {
+                error,
+                span,
+                excerpt,
+                macro_name: None,
+            } => write!(f, "{} at {}\n{}", error, span, excerpt),
+            MichelsonError::Located {
+                error,
+                span,
+                excerpt,
+                macro_name: Some(name),
+            } => write!(f, "{} at {} (in macro {})\n{}", error, span, name, excerpt),
+            MichelsonError::Message(val) => write!(f, "{}", val),
+        }
+    }
+}
+
+impl Error for MichelsonError {}
+
+// helpers of the other modules still report their errors as strings
+impl From<String> for MichelsonError {
+    fn from(message: String) -> MichelsonError {
+        MichelsonError::Message(message)
+    }
+}
+
+impl From<ParseError> for MichelsonError {
+    fn from(error: ParseError) -> MichelsonError {
+        MichelsonError::Parse(error)
     }
 }
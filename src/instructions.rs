@@ -1,13 +1,16 @@
-use crate::errors::{display_error, ErrorCode};
-use crate::m_types::{address, mutez, nat};
+use crate::compiler::Args;
+use crate::errors::MichelsonError;
+use crate::m_types::{address, mutez, timestamp};
+use crate::micheline::Micheline;
+use crate::parser::RunResult;
 use crate::stack::{Stack, StackSnapshots};
 use regex::Regex;
-use serde_json::Value;
 mod ABS;
 mod ADD;
 mod ADDRESS;
 mod AMOUNT;
 mod AND;
+mod APPLY;
 mod BALANCE;
 mod CAR;
 mod CDR;
@@ -16,23 +19,31 @@ mod COMPARE;
 mod CONCAT;
 mod CONS;
 mod DIG;
+mod DIP;
 mod DROP;
 mod DUG;
 mod DUP;
 mod EDIV;
 mod EMPTY_COLLECTION;
 mod EQ;
+mod EXEC;
 mod GE;
 mod GET;
 mod GT;
 mod IF;
+mod IF_CONS;
 mod IF_LEFT;
+mod IF_NONE;
 mod INT;
 mod ISNAT;
+mod ITER;
 mod KECCAK;
+mod LAMBDA;
 mod LE;
 mod LEFT_RIGHT;
 mod LEVEL;
+mod LOOP;
+mod LOOP_LEFT;
 mod LT;
 mod MAP;
 mod MEM;
@@ -68,6 +79,7 @@ pub enum Instruction {
     ADD,
     ADDRESS,
     AND,
+    APPLY,
     BALANCE,
     AMOUNT,
     CAR,
@@ -77,6 +89,7 @@ pub enum Instruction {
     CONCAT,
     CONS,
     DIG,
+    DIP,
     DROP,
     DUG,
     DUP,
@@ -85,18 +98,26 @@ pub enum Instruction {
     EMPTY_MAP,
     EMPTY_SET,
     EQ,
+    EXEC,
     FAILWITH,
     GE,
     GET,
     GT,
     IF,
+    IF_CONS,
     IF_LEFT,
+    IF_NONE,
     INT,
     ISNAT,
+    ITER,
     KECCAK,
+    LAMBDA,
+    LAMBDA_REC,
     LE,
     LEFT,
     LEVEL,
+    LOOP,
+    LOOP_LEFT,
     LT,
     MAP,
     MEM,
@@ -129,14 +150,22 @@ pub enum Instruction {
     INIT, // used to initialize the stack
 }
 
+/// Default number of iterations after which LOOP and LOOP_LEFT are aborted
+pub const MAX_LOOP_ITERATIONS: usize = 10_000;
+
+#[derive(Clone)]
 pub struct RunOptionsContext {
     pub amount: mutez,
     pub sender: address,
     pub source: address,
     pub self_address: address,
     pub balance: mutez,
-    pub level: nat,
+    pub level: u128,
+    // timestamp of the current block, in seconds since the epoch
+    pub now: timestamp,
     pub chain_id: String,
+    // number of iterations after which LOOP and LOOP_LEFT fail, so a contract can't loop forever
+    pub max_loop_iterations: usize,
 }
 
 impl RunOptionsContext {
@@ -148,16 +177,43 @@ impl RunOptionsContext {
             self_address: String::from("KT1L7GvUxZH5tfa6cgZKnH6vpp2uVxnFVHKu"),
             balance: 50_000_000,
             level: 11,
+            // 2022-01-01T00:00:00Z
+            now: 1_640_995_200,
             chain_id: String::from("chain_id"),
+            max_loop_iterations: MAX_LOOP_ITERATIONS,
         }
     }
+
+    /// Moves the timestamp of the current block forward by the provided number of seconds
+    pub fn advance_time(&mut self, seconds: timestamp) {
+        self.now += seconds;
+    }
+
+    /// Moves the context forward by the provided number of blocks
+    /// the level is incremented and the timestamp moves by `block_time` seconds for each block
+    pub fn advance_blocks(&mut self, blocks: u128, block_time: timestamp) {
+        self.level += blocks;
+        self.now += blocks as timestamp * block_time;
+    }
 }
 
+#[derive(Clone)]
 pub struct RunOptions {
     pub context: RunOptionsContext,
     pub pos: usize,
 }
 
+#[cfg(test)]
+impl RunOptions {
+    /// Options used by the tests, with the mock context and the top of the stack as position
+    pub fn mock() -> RunOptions {
+        RunOptions {
+            context: RunOptionsContext::mock(),
+            pos: 0,
+        }
+    }
+}
+
 // for LEFT and RIGHT instructions
 pub enum LeftOrRight {
     Left,
@@ -173,7 +229,7 @@ pub enum EmptyCollection {
 
 impl Instruction {
     /// Converts a string to an instruction type
-    pub fn from_str(input: &str) -> Result<Instruction, String> {
+    pub fn from_str(input: &str) -> Result<Instruction, MichelsonError> {
         let format_regex = Regex::new(r"[^A-Z_]").unwrap();
         let formatted_input: &str = &format_regex.replace_all(input, "").to_string();
         match formatted_input {
@@ -181,6 +237,7 @@ impl Instruction {
             "ADD" => Ok(Instruction::ADD),
             "ADDRESS" => Ok(Instruction::ADDRESS),
             "AND" => Ok(Instruction::AND),
+            "APPLY" => Ok(Instruction::APPLY),
             "AMOUNT" => Ok(Instruction::AMOUNT),
             "BALANCE" => Ok(Instruction::BALANCE),
             "CAR" => Ok(Instruction::CAR),
@@ -190,6 +247,7 @@ impl Instruction {
             "CONCAT" => Ok(Instruction::CONCAT),
             "CONS" => Ok(Instruction::CONS),
             "DIG" => Ok(Instruction::DIG),
+            "DIP" => Ok(Instruction::DIP),
             "DROP" => Ok(Instruction::DROP),
             "DUG" => Ok(Instruction::DUG),
             "DUP" => Ok(Instruction::DUP),
@@ -198,17 +256,26 @@ impl Instruction {
             "EMPTY_MAP" => Ok(Instruction::EMPTY_MAP),
             "EMPTY_SET" => Ok(Instruction::EMPTY_SET),
             "EQ" => Ok(Instruction::EQ),
+            "EXEC" => Ok(Instruction::EXEC),
+            "FAILWITH" => Ok(Instruction::FAILWITH),
             "IF" => Ok(Instruction::IF),
+            "IF_CONS" => Ok(Instruction::IF_CONS),
             "IF_LEFT" => Ok(Instruction::IF_LEFT),
+            "IF_NONE" => Ok(Instruction::IF_NONE),
             "INT" => Ok(Instruction::INT),
             "ISNAT" => Ok(Instruction::ISNAT),
+            "ITER" => Ok(Instruction::ITER),
             "KECCAK" => Ok(Instruction::KECCAK),
+            "LAMBDA" => Ok(Instruction::LAMBDA),
+            "LAMBDA_REC" => Ok(Instruction::LAMBDA_REC),
             "GE" => Ok(Instruction::GE),
             "GET" => Ok(Instruction::GET),
             "GT" => Ok(Instruction::GT),
             "LE" => Ok(Instruction::LE),
             "LEFT" => Ok(Instruction::LEFT),
             "LEVEL" => Ok(Instruction::LEVEL),
+            "LOOP" => Ok(Instruction::LOOP),
+            "LOOP_LEFT" => Ok(Instruction::LOOP_LEFT),
             "LT" => Ok(Instruction::LT),
             "MAP" => Ok(Instruction::MAP),
             "MEM" => Ok(Instruction::MEM),
@@ -238,13 +305,13 @@ impl Instruction {
             "UNPAIR" => Ok(Instruction::UNPAIR),
             "UPDATE" => Ok(Instruction::UPDATE),
             "XOR" => Ok(Instruction::XOR),
-            _ => Err(format!("Unknown instruction {}", input)),
+            _ => Err(MichelsonError::Message(format!("Unknown instruction {}", input))),
         }
     }
 
     /// Checks if the provided argument is correct
     /// Returns the numeric value from the argument
-    pub fn check_num_arg(&self, arg: &Option<&Vec<Value>>) -> Result<usize, String> {
+    pub fn check_num_arg(&self, arg: &Option<&Vec<Micheline>>) -> Result<usize, MichelsonError> {
         // instruction argument type
         enum ArgType {
             Required,
@@ -259,179 +326,216 @@ impl Instruction {
         };
 
         match (arg, arg_type) {
-            (_, ArgType::None) => Err(String::from(
+            (_, ArgType::None) => Err(MichelsonError::Message(String::from(
                 "The {:?} instruction doesn't need a numeric argument",
-            )),
-            (None, ArgType::Required) => Err(display_error(ErrorCode::NoArgument(self.clone()))),
+            ))),
+            (None, ArgType::Required) => Err(MichelsonError::NoArgument(self.clone())),
             (None, ArgType::Optional) => Ok(1),
-            (Some(arg), _) => {
-                if arg.len() == 1 {
-                    let arg = &arg[0];
-                    if arg.is_object() && arg.get("int").is_some() {
-                        // gets the int value that will be stored as a string
-                        match arg.get("int").unwrap().as_str() {
-                            None => Err(String::from(format!(
-                                "Expected a string in JSON value for {:?}",
-                                self
-                            ))),
-                            Some(str) =>
-                            // parse the string into a number
-                            {
-                                match str.parse::<usize>() {
-                                    Err(_) => Err(format!(
-                                        "JSON value for {:?} argument is not a valid number: {}",
-                                        self, str
-                                    )),
-                                    Ok(val) => {
-                                        // INSTRUCTION 0 is a noop
-                                        if val == 0 {
-                                            Err(format!(
-                                                "{:?}",
-                                                ErrorCode::Noop(String::from(format!(
-                                                    "{:?} 0 is a noop",
-                                                    self
-                                                )))
-                                            ))
-                                        } else {
-                                            Ok(val)
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                    } else {
-                        Err(format!(
-                            "Unexpected format for {:?} argument: {:?}",
-                            self, arg
-                        ))
-                    }
-                } else {
-                    Err(format!(
+            (Some(arg), _) => match arg.as_slice() {
+                [Micheline::Int(str)] => match str.parse::<usize>() {
+                    Err(_) => Err(MichelsonError::Message(format!(
+                        "Argument for {:?} is not a valid number: {}",
+                        self, str
+                    ))),
+                    // INSTRUCTION 0 is a noop
+                    Ok(0) => Err(MichelsonError::Message(format!(
                         "{:?}",
-                        display_error(ErrorCode::UnexpectedArgsNumber((1, arg.len())))
-                    ))
-                }
+                        MichelsonError::Noop(format!("{:?} 0 is a noop", self))
+                    ))),
+                    Ok(val) => Ok(val),
+                },
+                [arg] => Err(MichelsonError::Message(format!(
+                    "Unexpected format for {:?} argument: {}",
+                    self, arg
+                ))),
+                _ => Err(MichelsonError::Message(format!(
+                    "{:?}",
+                    MichelsonError::UnexpectedArgsNumber((1, arg.len()))
+                ))),
+            },
+        }
+    }
+
+    /// Decodes the arguments of the instruction
+    /// Blocks of code passed to DIP, IF, IF_CONS, IF_LEFT, IF_NONE, ITER, LOOP, LOOP_LEFT and MAP
+    /// are compiled by the compiler, the code of LAMBDA and LAMBDA_REC is kept in the lambda value
+    pub fn parse_args(&self, args: Option<&Vec<Micheline>>) -> Result<Args, MichelsonError> {
+        match self {
+            Instruction::DIG | Instruction::DROP | Instruction::DUG | Instruction::DUP => {
+                Ok(Args::Count(self.check_num_arg(&args)?))
+            }
+            Instruction::EMPTY_BIG_MAP => {
+                let (key_type, value_type) = EMPTY_COLLECTION::parse_args(args, &EmptyCollection::Bigmap)?;
+                Ok(Args::Types(key_type, value_type))
             }
+            Instruction::EMPTY_MAP => {
+                let (key_type, value_type) = EMPTY_COLLECTION::parse_args(args, &EmptyCollection::Map)?;
+                Ok(Args::Types(key_type, value_type))
+            }
+            Instruction::EMPTY_SET => {
+                let (element_type, _) = EMPTY_COLLECTION::parse_args(args, &EmptyCollection::Set)?;
+                Ok(Args::Types(element_type, None))
+            }
+            Instruction::GET => Ok(Args::Index(GET::parse_args(args)?)),
+            Instruction::LAMBDA | Instruction::LAMBDA_REC => Ok(Args::Value(LAMBDA::parse_args(args, *self)?)),
+            Instruction::LEFT => Ok(Args::Type(LEFT_RIGHT::parse_args(args, LeftOrRight::Left)?)),
+            Instruction::NIL => Ok(Args::Type(NIL::parse_args(args)?)),
+            Instruction::NONE => Ok(Args::Type(NONE::parse_args(args)?)),
+            Instruction::PUSH => Ok(Args::Value(PUSH::parse_args(args)?)),
+            Instruction::RIGHT => Ok(Args::Type(LEFT_RIGHT::parse_args(args, LeftOrRight::Right)?)),
+            Instruction::UPDATE => Ok(Args::Index(UPDATE::parse_args(args)?)),
+            _ => match args {
+                None => Ok(Args::None),
+                Some(args) => Err(MichelsonError::Message(format!(
+                    "Unexpected arguments for {:?} instruction: {}",
+                    self,
+                    args.iter().map(|arg| arg.to_string()).collect::<Vec<String>>().join(" ")
+                ))),
+            },
         }
     }
 
     /// Runs the provided instruction against the provided stack, returns the new stack
+    /// or the error that made the instruction fail
+    /// Instructions running blocks of code return the failure of FAILWITH in the result
     pub fn run(
         &self,
-        args: Option<&Vec<Value>>,
+        args: &Args,
         initial_stack: Stack,
         stack_snapshots: StackSnapshots,
         options: &RunOptions,
-    ) -> (Stack, StackSnapshots) {
-        let result = match self {
-            Instruction::ABS => ABS::run(initial_stack, options, stack_snapshots),
-            Instruction::ADD => ADD::run(initial_stack, options, stack_snapshots),
-            Instruction::ADDRESS => ADDRESS::run(initial_stack, options, stack_snapshots),
-            Instruction::AND => AND::run(initial_stack, options, stack_snapshots),
-            Instruction::AMOUNT => AMOUNT::run(initial_stack, options, stack_snapshots),
-            Instruction::BALANCE => BALANCE::run(initial_stack, options, stack_snapshots),
-            Instruction::CAR => CAR::run(initial_stack, options, stack_snapshots),
-            Instruction::CDR => CDR::run(initial_stack, options, stack_snapshots),
-            Instruction::CHAIN_ID => CHAIN_ID::run(initial_stack, options, stack_snapshots),
-            Instruction::COMPARE => COMPARE::run(initial_stack, options, stack_snapshots),
-            Instruction::CONCAT => CONCAT::run(initial_stack, options, stack_snapshots),
-            Instruction::CONS => CONS::run(initial_stack, options, stack_snapshots),
-            Instruction::DIG => DIG::run(initial_stack, args, options, stack_snapshots),
-            Instruction::DROP => DROP::run(initial_stack, args, options, stack_snapshots),
-            Instruction::DUG => DUG::run(initial_stack, args, options, stack_snapshots),
-            Instruction::DUP => DUP::run(initial_stack, args, options, stack_snapshots),
-            Instruction::EDIV => EDIV::run(initial_stack, options, stack_snapshots),
-            Instruction::EMPTY_BIG_MAP => EMPTY_COLLECTION::run(
+    ) -> Result<RunResult, MichelsonError> {
+        let (stack, stack_snapshots) = match (self, args) {
+            (Instruction::ABS, _) => ABS::run(initial_stack, options, stack_snapshots),
+            (Instruction::ADD, _) => ADD::run(initial_stack, options, stack_snapshots),
+            (Instruction::ADDRESS, _) => ADDRESS::run(initial_stack, options, stack_snapshots),
+            (Instruction::AND, _) => AND::run(initial_stack, options, stack_snapshots),
+            (Instruction::APPLY, _) => APPLY::run(initial_stack, options, stack_snapshots),
+            (Instruction::AMOUNT, _) => AMOUNT::run(initial_stack, options, stack_snapshots),
+            (Instruction::BALANCE, _) => BALANCE::run(initial_stack, options, stack_snapshots),
+            (Instruction::CAR, _) => CAR::run(initial_stack, options, stack_snapshots),
+            (Instruction::CDR, _) => CDR::run(initial_stack, options, stack_snapshots),
+            (Instruction::CHAIN_ID, _) => CHAIN_ID::run(initial_stack, options, stack_snapshots),
+            (Instruction::COMPARE, _) => COMPARE::run(initial_stack, options, stack_snapshots),
+            (Instruction::CONCAT, _) => CONCAT::run(initial_stack, options, stack_snapshots),
+            (Instruction::CONS, _) => CONS::run(initial_stack, options, stack_snapshots),
+            (Instruction::DIG, Args::Count(count)) => DIG::run(initial_stack, *count, options, stack_snapshots),
+            (Instruction::DIP, Args::CountAndBlock(count, body)) => {
+                return DIP::run(initial_stack, *count, body, options, stack_snapshots)
+            }
+            (Instruction::DROP, Args::Count(count)) => DROP::run(initial_stack, *count, options, stack_snapshots),
+            (Instruction::DUG, Args::Count(count)) => DUG::run(initial_stack, *count, options, stack_snapshots),
+            (Instruction::DUP, Args::Count(count)) => DUP::run(initial_stack, *count, options, stack_snapshots),
+            (Instruction::EDIV, _) => EDIV::run(initial_stack, options, stack_snapshots),
+            (Instruction::EMPTY_BIG_MAP, Args::Types(element_type, value_type)) => EMPTY_COLLECTION::run(
                 initial_stack,
-                args,
+                element_type,
+                value_type.as_ref(),
                 options,
                 stack_snapshots,
                 EmptyCollection::Bigmap,
             ),
-            Instruction::EMPTY_MAP => EMPTY_COLLECTION::run(
+            (Instruction::EMPTY_MAP, Args::Types(element_type, value_type)) => EMPTY_COLLECTION::run(
                 initial_stack,
-                args,
+                element_type,
+                value_type.as_ref(),
                 options,
                 stack_snapshots,
                 EmptyCollection::Map,
             ),
-            Instruction::EMPTY_SET => EMPTY_COLLECTION::run(
+            (Instruction::EMPTY_SET, Args::Types(element_type, value_type)) => EMPTY_COLLECTION::run(
                 initial_stack,
-                args,
+                element_type,
+                value_type.as_ref(),
                 options,
                 stack_snapshots,
                 EmptyCollection::Set,
             ),
-            Instruction::EQ => EQ::run(initial_stack, options, stack_snapshots),
-            Instruction::GE => GE::run(initial_stack, options, stack_snapshots),
-            Instruction::GET => GET::run(initial_stack, args, options, stack_snapshots),
-            Instruction::GT => GT::run(initial_stack, options, stack_snapshots),
-            Instruction::IF => {
-                match IF::run(initial_stack, args, options, stack_snapshots) {
-                    // the boolean value in RunResult is not necessary here
-                    Ok(run_result) => Ok((run_result.stack, run_result.stack_snapshots)),
-                    Err(err) => Err(err),
-                }
+            (Instruction::EQ, _) => EQ::run(initial_stack, options, stack_snapshots),
+            (Instruction::EXEC, _) => return EXEC::run(initial_stack, options, stack_snapshots),
+            (Instruction::GE, _) => GE::run(initial_stack, options, stack_snapshots),
+            (Instruction::GET, Args::Index(el_pos)) => GET::run(initial_stack, *el_pos, options, stack_snapshots),
+            (Instruction::GT, _) => GT::run(initial_stack, options, stack_snapshots),
+            (Instruction::IF, Args::Branches(branch_if, branch_else)) => {
+                return IF::run(initial_stack, (branch_if, branch_else), options, stack_snapshots)
+            }
+            (Instruction::IF_CONS, Args::Branches(branch_if, branch_else)) => {
+                return IF_CONS::run(initial_stack, (branch_if, branch_else), options, stack_snapshots)
+            }
+            (Instruction::IF_LEFT, Args::Branches(branch_if, branch_else)) => {
+                return IF_LEFT::run(initial_stack, (branch_if, branch_else), options, stack_snapshots)
+            }
+            (Instruction::IF_NONE, Args::Branches(branch_if, branch_else)) => {
+                return IF_NONE::run(initial_stack, (branch_if, branch_else), options, stack_snapshots)
+            }
+            (Instruction::INT, _) => INT::run(initial_stack, options, stack_snapshots),
+            (Instruction::ISNAT, _) => ISNAT::run(initial_stack, options, stack_snapshots),
+            (Instruction::ITER, Args::Block(body)) => {
+                return ITER::run(initial_stack, body, options, stack_snapshots)
             }
-            Instruction::IF_LEFT => {
-                match IF_LEFT::run(initial_stack, args, options, stack_snapshots) {
-                    // the boolean value in RunResult is not necessary here
-                    Ok(run_result) => Ok((run_result.stack, run_result.stack_snapshots)),
-                    Err(err) => Err(err),
-                }
+            (Instruction::KECCAK, _) => KECCAK::run(initial_stack, options, stack_snapshots),
+            (Instruction::LAMBDA | Instruction::LAMBDA_REC, Args::Value(value)) => {
+                LAMBDA::run(initial_stack, value, *self, options, stack_snapshots)
             }
-            Instruction::INT => INT::run(initial_stack, options, stack_snapshots),
-            Instruction::ISNAT => ISNAT::run(initial_stack, options, stack_snapshots),
-            Instruction::KECCAK => KECCAK::run(initial_stack, options, stack_snapshots),
-            Instruction::LE => LE::run(initial_stack, options, stack_snapshots),
-            Instruction::LEFT => LEFT_RIGHT::run(
+            (Instruction::LE, _) => LE::run(initial_stack, options, stack_snapshots),
+            (Instruction::LEFT, Args::Type(second_type)) => LEFT_RIGHT::run(
                 initial_stack,
-                args,
+                second_type,
                 options,
                 stack_snapshots,
                 LeftOrRight::Left,
             ),
-            Instruction::LEVEL => LEVEL::run(initial_stack, options, stack_snapshots),
-            Instruction::LT => LT::run(initial_stack, options, stack_snapshots),
-            Instruction::MAP => MAP::run(initial_stack, args, options, stack_snapshots),
-            Instruction::MEM => MEM::run(initial_stack, options, stack_snapshots),
-            Instruction::MUL => MUL::run(initial_stack, options, stack_snapshots),
-            Instruction::NEG => NEG::run(initial_stack, options, stack_snapshots),
-            Instruction::NEQ => NEQ::run(initial_stack, options, stack_snapshots),
-            Instruction::NEVER => NEVER::run(initial_stack, options, stack_snapshots),
-            Instruction::NIL => NIL::run(initial_stack, args, options, stack_snapshots),
-            Instruction::NONE => NONE::run(initial_stack, args, options, stack_snapshots),
-            Instruction::NOT => NOT::run(initial_stack, options, stack_snapshots),
-            Instruction::NOW => NOW::run(initial_stack, options, stack_snapshots),
-            Instruction::OR => OR::run(initial_stack, options, stack_snapshots),
-            Instruction::PAIR => PAIR::run(initial_stack, options, stack_snapshots),
-            Instruction::PUSH => PUSH::run(initial_stack, args, options, stack_snapshots),
-            Instruction::RIGHT => LEFT_RIGHT::run(
+            (Instruction::LEVEL, _) => LEVEL::run(initial_stack, options, stack_snapshots),
+            (Instruction::LOOP, Args::Block(body)) => {
+                return LOOP::run(initial_stack, body, options, stack_snapshots)
+            }
+            (Instruction::LOOP_LEFT, Args::Block(body)) => {
+                return LOOP_LEFT::run(initial_stack, body, options, stack_snapshots)
+            }
+            (Instruction::LT, _) => LT::run(initial_stack, options, stack_snapshots),
+            (Instruction::MAP, Args::Block(body)) => {
+                return MAP::run(initial_stack, body, options, stack_snapshots)
+            }
+            (Instruction::MEM, _) => MEM::run(initial_stack, options, stack_snapshots),
+            (Instruction::MUL, _) => MUL::run(initial_stack, options, stack_snapshots),
+            (Instruction::NEG, _) => NEG::run(initial_stack, options, stack_snapshots),
+            (Instruction::NEQ, _) => NEQ::run(initial_stack, options, stack_snapshots),
+            (Instruction::NEVER, _) => NEVER::run(initial_stack, options, stack_snapshots),
+            (Instruction::NIL, Args::Type(m_type)) => NIL::run(initial_stack, m_type, options, stack_snapshots),
+            (Instruction::NONE, Args::Type(m_type)) => NONE::run(initial_stack, m_type, options, stack_snapshots),
+            (Instruction::NOT, _) => NOT::run(initial_stack, options, stack_snapshots),
+            (Instruction::NOW, _) => NOW::run(initial_stack, options, stack_snapshots),
+            (Instruction::OR, _) => OR::run(initial_stack, options, stack_snapshots),
+            (Instruction::PAIR, _) => PAIR::run(initial_stack, options, stack_snapshots),
+            (Instruction::PUSH, Args::Value(value)) => PUSH::run(initial_stack, value, options, stack_snapshots),
+            (Instruction::RIGHT, Args::Type(second_type)) => LEFT_RIGHT::run(
                 initial_stack,
-                args,
+                second_type,
                 options,
                 stack_snapshots,
                 LeftOrRight::Right,
             ),
-            Instruction::SELF_ADDRESS => SELF_ADDRESS::run(initial_stack, options, stack_snapshots),
-            Instruction::SENDER => SENDER::run(initial_stack, options, stack_snapshots),
-            Instruction::SIZE => SIZE::run(initial_stack, options, stack_snapshots),
-            Instruction::SLICE => SLICE::run(initial_stack, options, stack_snapshots),
-            Instruction::SOME => SOME::run(initial_stack, options, stack_snapshots),
-            Instruction::SOURCE => SOURCE::run(initial_stack, options, stack_snapshots),
-            Instruction::SUB => SUB::run(initial_stack, options, stack_snapshots),
-            Instruction::SUB_MUTEZ => SUB_MUTEZ::run(initial_stack, options, stack_snapshots),
-            Instruction::SWAP => SWAP::run(initial_stack, options, stack_snapshots),
-            Instruction::TICKET => TICKET::run(initial_stack, options, stack_snapshots),
-            Instruction::UNIT => UNIT::run(initial_stack, options, stack_snapshots),
-            Instruction::UNPAIR => UNPAIR::run(initial_stack, options, stack_snapshots),
-            Instruction::UPDATE => UPDATE::run(initial_stack, args, options, stack_snapshots),
-            Instruction::XOR => XOR::run(initial_stack, options, stack_snapshots),
-            _ => panic!("Invalid instruction {:?}", self),
-        };
-        match result {
-            Ok(res) => res,
-            Err(err) => panic!("{}", err),
-        }
+            (Instruction::SELF_ADDRESS, _) => SELF_ADDRESS::run(initial_stack, options, stack_snapshots),
+            (Instruction::SENDER, _) => SENDER::run(initial_stack, options, stack_snapshots),
+            (Instruction::SIZE, _) => SIZE::run(initial_stack, options, stack_snapshots),
+            (Instruction::SLICE, _) => SLICE::run(initial_stack, options, stack_snapshots),
+            (Instruction::SOME, _) => SOME::run(initial_stack, options, stack_snapshots),
+            (Instruction::SOURCE, _) => SOURCE::run(initial_stack, options, stack_snapshots),
+            (Instruction::SUB, _) => SUB::run(initial_stack, options, stack_snapshots),
+            (Instruction::SUB_MUTEZ, _) => SUB_MUTEZ::run(initial_stack, options, stack_snapshots),
+            (Instruction::SWAP, _) => SWAP::run(initial_stack, options, stack_snapshots),
+            (Instruction::TICKET, _) => TICKET::run(initial_stack, options, stack_snapshots),
+            (Instruction::UNIT, _) => UNIT::run(initial_stack, options, stack_snapshots),
+            (Instruction::UNPAIR, _) => UNPAIR::run(initial_stack, options, stack_snapshots),
+            (Instruction::UPDATE, Args::Index(el_pos)) => UPDATE::run(initial_stack, *el_pos, options, stack_snapshots),
+            (Instruction::XOR, _) => XOR::run(initial_stack, options, stack_snapshots),
+            _ => Err(MichelsonError::Message(format!("Invalid instruction {:?}", self))),
+        }?;
+
+        Ok(RunResult {
+            stack,
+            stack_snapshots,
+            failed: None,
+        })
     }
 }
@@ -1,6 +1,6 @@
-use crate::errors::{display_error, ErrorCode};
+use crate::errors::MichelsonError;
 use crate::instructions::{Instruction, RunOptions};
-use crate::m_types::{nat, MType, MValue};
+use crate::m_types::{MType, MValue};
 use crate::stack::{Stack, StackElement, StackFuncs, StackSnapshots};
 
 // https://tezos.gitlab.io/michelson-reference/#instr-ABS
@@ -9,20 +9,20 @@ pub fn run(
     stack: Stack,
     options: &RunOptions,
     mut stack_snapshots: StackSnapshots,
-) -> Result<(Stack, StackSnapshots), String> {
+) -> Result<(Stack, StackSnapshots), MichelsonError> {
     // checks the stack
     stack.check_depth(options.pos + 1, Instruction::ABS)?;
     // verifies that the value at options.pos is an int
-    let new_val_res: Result<MValue, String> = match stack[options.pos].value {
+    let new_val_res: Result<MValue, MichelsonError> = match &stack[options.pos].value {
         MValue::Int(val) => {
-            let new_nat = val.abs() as nat;
+            let new_nat = val.magnitude().clone();
             Ok(MValue::Nat(new_nat))
         }
-        _ => Err(display_error(ErrorCode::InvalidType((
+        _ => Err(MichelsonError::InvalidType((
             vec![MType::Int],
             stack[options.pos].value.get_type(),
             Instruction::ABS,
-        )))),
+        ))),
     };
 
     match new_val_res {
@@ -45,15 +45,15 @@ pub fn run(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::instructions::RunOptionsContext;
+    use crate::instructions::{RunOptionsContext, MAX_LOOP_ITERATIONS};
 
     // PASSING
     // Simple test of cast int to nat
     #[test]
     fn abs_success() {
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Int(-5), Instruction::INIT),
-            StackElement::new(MValue::Int(6), Instruction::INIT),
+            StackElement::new(MValue::new_int(-5), Instruction::INIT),
+            StackElement::new(MValue::new_int(6), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
         let options = RunOptions {
@@ -64,7 +64,9 @@ mod tests {
                 self_address: String::from("KT1L7GvUxZH5tfa6cgZKnH6vpp2uVxnFVHKu"),
                 balance: 50_000_000,
                 level: 11,
+                now: 1_640_995_200,
                 chain_id: String::from("chain_id"),
+                max_loop_iterations: MAX_LOOP_ITERATIONS,
             },
             pos: 0,
         };
@@ -75,7 +77,7 @@ mod tests {
             Err(_) => assert!(false),
             Ok((stack, _)) => {
                 assert!(stack.len() == 2);
-                assert_eq!(stack[options.pos].value, MValue::Nat(5));
+                assert_eq!(stack[options.pos].value, MValue::new_nat(5));
             }
         }
     }
@@ -84,8 +86,8 @@ mod tests {
     #[test]
     fn abs_success_pos() {
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Int(5), Instruction::INIT),
-            StackElement::new(MValue::Int(-6), Instruction::INIT),
+            StackElement::new(MValue::new_int(5), Instruction::INIT),
+            StackElement::new(MValue::new_int(-6), Instruction::INIT),
             StackElement::new(MValue::Mutez(7_000_000), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
@@ -97,7 +99,9 @@ mod tests {
                 self_address: String::from("KT1L7GvUxZH5tfa6cgZKnH6vpp2uVxnFVHKu"),
                 balance: 50_000_000,
                 level: 11,
+                now: 1_640_995_200,
                 chain_id: String::from("chain_id"),
+                max_loop_iterations: MAX_LOOP_ITERATIONS,
             },
             pos: 1,
         };
@@ -108,8 +112,8 @@ mod tests {
             Err(_) => assert!(false),
             Ok((stack, _)) => {
                 assert!(stack.len() == 3);
-                assert_eq!(stack[0].value, MValue::Int(5));
-                assert_eq!(stack[1].value, MValue::Nat(6));
+                assert_eq!(stack[0].value, MValue::new_int(5));
+                assert_eq!(stack[1].value, MValue::new_nat(6));
                 assert_eq!(stack[2].value, MValue::Mutez(7_000_000));
             }
         }
@@ -119,8 +123,8 @@ mod tests {
     #[test]
     fn abs_positive_int() {
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Int(5), Instruction::INIT),
-            StackElement::new(MValue::Int(6), Instruction::INIT),
+            StackElement::new(MValue::new_int(5), Instruction::INIT),
+            StackElement::new(MValue::new_int(6), Instruction::INIT),
             StackElement::new(MValue::Mutez(7_000_000), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
@@ -132,7 +136,9 @@ mod tests {
                 self_address: String::from("KT1L7GvUxZH5tfa6cgZKnH6vpp2uVxnFVHKu"),
                 balance: 50_000_000,
                 level: 11,
+                now: 1_640_995_200,
                 chain_id: String::from("chain_id"),
+                max_loop_iterations: MAX_LOOP_ITERATIONS,
             },
             pos: 0,
         };
@@ -143,7 +149,7 @@ mod tests {
             Err(_) => assert!(false),
             Ok((stack, _)) => {
                 assert!(stack.len() == 3);
-                assert_eq!(stack[0].value, MValue::Nat(5));
+                assert_eq!(stack[0].value, MValue::new_nat(5));
             }
         }
     }
@@ -162,7 +168,9 @@ mod tests {
                 self_address: String::from("KT1L7GvUxZH5tfa6cgZKnH6vpp2uVxnFVHKu"),
                 balance: 50_000_000,
                 level: 11,
+                now: 1_640_995_200,
                 chain_id: String::from("chain_id"),
+                max_loop_iterations: MAX_LOOP_ITERATIONS,
             },
             pos: 0,
         };
@@ -171,7 +179,7 @@ mod tests {
 
         match run(initial_stack, &options, stack_snapshots) {
             Err(err) => assert_eq!(
-                err,
+                err.to_string(),
                 "Unexpected stack length, expected a length of 1 for instruction ABS, got 0"
             ),
             Ok(_) => assert!(false),
@@ -197,7 +205,9 @@ mod tests {
                 self_address: String::from("KT1L7GvUxZH5tfa6cgZKnH6vpp2uVxnFVHKu"),
                 balance: 50_000_000,
                 level: 11,
+                now: 1_640_995_200,
                 chain_id: String::from("chain_id"),
+                max_loop_iterations: MAX_LOOP_ITERATIONS,
             },
             pos: 1,
         };
@@ -227,7 +237,9 @@ mod tests {
                 self_address: String::from("KT1L7GvUxZH5tfa6cgZKnH6vpp2uVxnFVHKu"),
                 balance: 50_000_000,
                 level: 11,
+                now: 1_640_995_200,
                 chain_id: String::from("chain_id"),
+                max_loop_iterations: MAX_LOOP_ITERATIONS,
             },
             pos: 0,
         };
@@ -1,6 +1,7 @@
-use crate::errors::{display_error, ErrorCode};
+use crate::errors::MichelsonError;
 use crate::instructions::{Instruction, RunOptions};
-use crate::m_types::{int, timestamp, MValue};
+use crate::m_types::{int, MValue, MAX_MUTEZ};
+use num_traits::ToPrimitive;
 use crate::stack::{Stack, StackElement, StackFuncs, StackSnapshots};
 
 // https://tezos.gitlab.io/michelson-reference/#instr-ADD
@@ -9,63 +10,50 @@ pub fn run(
     stack: Stack,
     options: &RunOptions,
     mut stack_snapshots: StackSnapshots,
-) -> Result<(Stack, StackSnapshots), String> {
+) -> Result<(Stack, StackSnapshots), MichelsonError> {
     let this_instruction = Instruction::ADD;
 
     // checks the stack
-    match stack.check_depth(options.pos + 2, this_instruction) {
-        Ok(_) => (),
-        Err(err) => panic!("{}", err),
-    };
+    stack.check_depth(options.pos + 2, this_instruction)?;
     // pattern matches the different numeric types
     let new_val: MValue = match (
         stack[options.pos].get_val(),
         stack[options.pos + 1].get_val(),
     ) {
         (MValue::Int(left), MValue::Int(right)) => MValue::Int(left + right),
-        (MValue::Int(left), MValue::Nat(right)) => {
-            if MValue::Nat(right).check_nat() {
-                MValue::Int(left + right as int)
-            } else {
-                panic!("{}", display_error(ErrorCode::InvalidNat(right)))
+        (MValue::Int(left), MValue::Nat(right)) => MValue::Int(left + int::from(right)), // int
+        (MValue::Nat(left), MValue::Int(right)) => MValue::Int(int::from(left) + right), // int
+        (MValue::Nat(left), MValue::Nat(right)) => MValue::Nat(left + right), // nat
+        (MValue::Timestamp(left), MValue::Int(seconds)) | (MValue::Int(seconds), MValue::Timestamp(left)) => {
+            match (int::from(left) + seconds).to_i64() {
+                Some(new_timestamp) => MValue::Timestamp(new_timestamp),
+                None => {
+                    return Err(MichelsonError::Message(String::from(
+                        "The result of ADD is out of the range of timestamps",
+                    )))
+                }
             }
-        } // int
-        (MValue::Nat(left), MValue::Int(right)) => {
-            if MValue::Nat(left).check_nat() {
-                MValue::Int(left as int + right)
-            } else {
-                panic!("{}", display_error(ErrorCode::InvalidNat(left)))
-            }
-        } // int
-        (MValue::Nat(left), MValue::Nat(right)) => {
-            if MValue::Nat(left).check_nat() == false {
-                panic!("{}", display_error(ErrorCode::InvalidNat(left)))
-            } else if MValue::Nat(right).check_nat() == false {
-                panic!("{}", display_error(ErrorCode::InvalidNat(right)))
-            } else {
-                MValue::Nat(left + right)
-            }
-        } // nat
-        (MValue::Timestamp(left), MValue::Int(right)) => {
-            MValue::Timestamp((left as int + right) as timestamp)
-        } // timestamp
-        (MValue::Int(left), MValue::Timestamp(right)) => {
-            MValue::Timestamp((left + right as int) as timestamp)
         } // timestamp
         (MValue::Mutez(left), MValue::Mutez(right)) => {
             if MValue::Mutez(left).check_mutez() == false {
-                panic!("{}", display_error(ErrorCode::InvalidMutez(left)))
+                return Err(MichelsonError::InvalidMutez(left))
             } else if MValue::Mutez(right).check_mutez() == false {
-                panic!("{}", display_error(ErrorCode::InvalidMutez(right)))
+                return Err(MichelsonError::InvalidMutez(right))
             } else {
-                MValue::Mutez(left + right)
+                // the sum must stay in the range of mutez values
+                match left.checked_add(right).filter(|sum| *sum <= MAX_MUTEZ) {
+                    Some(sum) => MValue::Mutez(sum),
+                    None => return Err(MichelsonError::MutezOverflow(this_instruction)),
+                }
             }
         } // mutez
-        (m_val_left, m_val_right) => panic!(
-            "Cannot add together values of type {} and {}",
-            m_val_left.to_string(),
-            m_val_right.to_string()
-        ),
+        (m_val_left, m_val_right) => {
+            return Err(MichelsonError::Message(format!(
+                "Cannot add together values of type {} and {}",
+                m_val_left.to_string(),
+                m_val_right.to_string()
+            )))
+        }
     };
     // removes the first element of the addition
     let (_, new_stack) = stack.remove_at(options.pos);
@@ -96,8 +84,8 @@ mod tests {
     #[test]
     fn add_int_int() -> () {
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Int(5), Instruction::INIT),
-            StackElement::new(MValue::Int(6), Instruction::INIT),
+            StackElement::new(MValue::new_int(5), Instruction::INIT),
+            StackElement::new(MValue::new_int(6), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
         let options = RunOptions {
@@ -109,7 +97,7 @@ mod tests {
             Err(_) => assert!(false),
             Ok((stack, _)) => {
                 assert!(stack.len() == 1);
-                assert_eq!(stack[0].value, MValue::Int(11));
+                assert_eq!(stack[0].value, MValue::new_int(11));
             }
         }
     }
@@ -118,8 +106,8 @@ mod tests {
     #[test]
     fn add_int_nat() -> () {
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Int(5), Instruction::INIT),
-            StackElement::new(MValue::Nat(6), Instruction::INIT),
+            StackElement::new(MValue::new_int(5), Instruction::INIT),
+            StackElement::new(MValue::new_nat(6), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
         let options = RunOptions {
@@ -131,7 +119,7 @@ mod tests {
             Err(_) => assert!(false),
             Ok((stack, _)) => {
                 assert!(stack.len() == 1);
-                assert_eq!(stack[0].value, MValue::Int(11));
+                assert_eq!(stack[0].value, MValue::new_int(11));
             }
         }
     }
@@ -140,8 +128,8 @@ mod tests {
     #[test]
     fn add_nat_nat() -> () {
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Nat(5), Instruction::INIT),
-            StackElement::new(MValue::Nat(6), Instruction::INIT),
+            StackElement::new(MValue::new_nat(5), Instruction::INIT),
+            StackElement::new(MValue::new_nat(6), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
         let options = RunOptions {
@@ -153,7 +141,36 @@ mod tests {
             Err(_) => assert!(false),
             Ok((stack, _)) => {
                 assert!(stack.len() == 1);
-                assert_eq!(stack[0].value, MValue::Nat(11));
+                assert_eq!(stack[0].value, MValue::new_nat(11));
+            }
+        }
+    }
+
+    // Tests ADD with a timestamp and an int, in both orders
+    #[test]
+    fn add_timestamp_int() {
+        let options = RunOptions {
+            context: RunOptionsContext::mock(),
+            pos: 0,
+        };
+        let stacks: Vec<Stack> = vec![
+            vec![
+                StackElement::new(MValue::Timestamp(1_640_995_200), Instruction::INIT),
+                StackElement::new(MValue::new_int(-60), Instruction::INIT),
+            ],
+            vec![
+                StackElement::new(MValue::new_int(-60), Instruction::INIT),
+                StackElement::new(MValue::Timestamp(1_640_995_200), Instruction::INIT),
+            ],
+        ];
+
+        for initial_stack in stacks {
+            match run(initial_stack, &options, vec![]) {
+                Err(err) => panic!("{}", err),
+                Ok((stack, _)) => {
+                    assert!(stack.len() == 1);
+                    assert_eq!(stack[0].value, MValue::Timestamp(1_640_995_140));
+                }
             }
         }
     }
@@ -165,7 +182,7 @@ mod tests {
     fn add_string_nat() {
         let initial_stack: Stack = vec![
             StackElement::new(MValue::String(String::from("5")), Instruction::INIT),
-            StackElement::new(MValue::Nat(6), Instruction::INIT),
+            StackElement::new(MValue::new_nat(6), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
         let options = RunOptions {
@@ -184,7 +201,26 @@ mod tests {
     fn add_mutez_nat() {
         let initial_stack: Stack = vec![
             StackElement::new(MValue::Mutez(5), Instruction::INIT),
-            StackElement::new(MValue::Nat(6), Instruction::INIT),
+            StackElement::new(MValue::new_nat(6), Instruction::INIT),
+        ];
+        let stack_snapshots = vec![];
+        let options = RunOptions {
+            context: RunOptionsContext::mock(),
+            pos: 0,
+        };
+
+        match run(initial_stack, &options, stack_snapshots) {
+            Err(err) => panic!("{}", err),
+            Ok(_) => assert!(false),
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Overflow of mutez value for instruction ADD, the maximum is 9223372036854775807")]
+    fn add_mutez_overflow() {
+        let initial_stack: Stack = vec![
+            StackElement::new(MValue::Mutez(MAX_MUTEZ), Instruction::INIT),
+            StackElement::new(MValue::Mutez(1), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
         let options = RunOptions {
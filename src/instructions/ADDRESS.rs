@@ -1,4 +1,4 @@
-use crate::errors::{display_error, ErrorCode};
+use crate::errors::MichelsonError;
 use crate::instructions::{Instruction, RunOptions};
 use crate::m_types::MValue;
 use crate::stack::{Stack, StackElement, StackFuncs, StackSnapshots};
@@ -9,20 +9,20 @@ pub fn run(
     stack: Stack,
     options: &RunOptions,
     mut stack_snapshots: StackSnapshots,
-) -> Result<(Stack, StackSnapshots), String> {
+) -> Result<(Stack, StackSnapshots), MichelsonError> {
     // checks the stack
     stack.check_depth(options.pos + 1, Instruction::ADDRESS)?;
     // verifies that the value at options.pos is a contract
-    let new_val_res: Result<MValue, String> = match &stack[options.pos].value {
+    let new_val_res: Result<MValue, MichelsonError> = match &stack[options.pos].value {
         MValue::Contract(contract) => {
             let address = contract.get_address();
             Ok(MValue::Address(address))
         }
-        _ => Err(display_error(ErrorCode::WrongType((
+        _ => Err(MichelsonError::WrongType((
             String::from("contract"),
             stack[options.pos].value.get_type().to_string(),
             Instruction::ADDRESS,
-        )))),
+        ))),
     };
 
     match new_val_res {
@@ -61,7 +61,7 @@ mod tests {
         };
         let initial_stack: Stack = vec![
             StackElement::new(new_contract, Instruction::INIT),
-            StackElement::new(MValue::Int(6), Instruction::INIT),
+            StackElement::new(MValue::new_int(6), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
         let options = RunOptions {
@@ -80,7 +80,7 @@ mod tests {
                     MValue::Address(expected_address.to_string())
                 );
                 assert_eq!(stack[0].instruction, Instruction::ADDRESS);
-                assert_eq!(stack[1].value, MValue::Int(6));
+                assert_eq!(stack[1].value, MValue::new_int(6));
                 assert_eq!(stack[1].instruction, Instruction::INIT)
             }
         }
@@ -91,7 +91,7 @@ mod tests {
     fn address_wrong_type() {
         let initial_stack: Stack = vec![
             StackElement::new(MValue::Mutez(50_000_000), Instruction::INIT),
-            StackElement::new(MValue::Int(6), Instruction::INIT),
+            StackElement::new(MValue::new_int(6), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
         let options = RunOptions {
@@ -104,7 +104,7 @@ mod tests {
         match run(initial_stack, &options, stack_snapshots) {
             Ok(_) => assert!(false),
             Err(err) => assert_eq!(
-                err,
+                err.to_string(),
                 format!("Wrong type, expected `contract` for instruction ADDRESS, got `mutez`")
             ),
         }
@@ -124,7 +124,7 @@ mod tests {
         match run(initial_stack, &options, stack_snapshots) {
             Ok(_) => assert!(false),
             Err(err) => assert_eq!(
-                err,
+                err.to_string(),
                 format!("Unexpected stack length, expected a length of 1 for instruction ADDRESS, got 0")
             ),
         }
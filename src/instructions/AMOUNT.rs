@@ -1,3 +1,4 @@
+use crate::errors::MichelsonError;
 use crate::instructions::{Instruction, RunOptions};
 use crate::m_types::MValue;
 use crate::stack::{Stack, StackElement, StackFuncs, StackSnapshots};
@@ -8,12 +9,9 @@ pub fn run(
     stack: Stack,
     options: &RunOptions,
     mut stack_snapshots: StackSnapshots,
-) -> Result<(Stack, StackSnapshots), String> {
+) -> Result<(Stack, StackSnapshots), MichelsonError> {
     // checks the stack
-    match stack.check_depth(options.pos, Instruction::AMOUNT) {
-        Ok(_) => (),
-        Err(err) => panic!("{}", err),
-    };
+    stack.check_depth(options.pos, Instruction::AMOUNT)?;
     // updates the stack
     let new_el = StackElement::new(MValue::Mutez(options.context.amount), Instruction::AMOUNT);
     let new_stack = stack.insert_at(vec![new_el], options.pos);
@@ -29,7 +27,7 @@ pub fn run(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::instructions::RunOptionsContext;
+    use crate::instructions::{RunOptionsContext, MAX_LOOP_ITERATIONS};
     use crate::m_types::mutez;
     use rand::Rng;
 
@@ -37,7 +35,7 @@ mod tests {
     fn amount_success() {
         // should push 0 mutez to the stack
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Int(22), Instruction::INIT),
+            StackElement::new(MValue::new_int(22), Instruction::INIT),
             StackElement::new(MValue::Mutez(6_000_000), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
@@ -54,7 +52,7 @@ mod tests {
                 assert!(stack.len() == 3);
                 assert_eq!(stack[0].value, MValue::Mutez(0));
                 assert_eq!(stack[0].instruction, Instruction::AMOUNT);
-                assert_eq!(stack[1].value, MValue::Int(22));
+                assert_eq!(stack[1].value, MValue::new_int(22));
                 assert_eq!(stack[1].instruction, Instruction::INIT);
                 assert_eq!(stack[2].value, MValue::Mutez(6_000_000));
                 assert_eq!(stack[2].instruction, Instruction::INIT);
@@ -66,7 +64,7 @@ mod tests {
     fn amount_rand_success() {
         // should push a random mutez number to the stack
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Int(22), Instruction::INIT),
+            StackElement::new(MValue::new_int(22), Instruction::INIT),
             StackElement::new(MValue::Mutez(6_000_000), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
@@ -79,7 +77,9 @@ mod tests {
                 self_address: String::from("KT1L7GvUxZH5tfa6cgZKnH6vpp2uVxnFVHKu"),
                 balance: 50_000_000,
                 level: 11,
+                now: 1_640_995_200,
                 chain_id: String::from("chain_id"),
+                max_loop_iterations: MAX_LOOP_ITERATIONS,
             },
             pos: 0,
         };
@@ -92,7 +92,7 @@ mod tests {
                 assert!(stack.len() == 3);
                 assert_eq!(stack[0].value, MValue::Mutez(random_amount));
                 assert_eq!(stack[0].instruction, Instruction::AMOUNT);
-                assert_eq!(stack[1].value, MValue::Int(22));
+                assert_eq!(stack[1].value, MValue::new_int(22));
                 assert_eq!(stack[1].instruction, Instruction::INIT);
                 assert_eq!(stack[2].value, MValue::Mutez(6_000_000));
                 assert_eq!(stack[2].instruction, Instruction::INIT);
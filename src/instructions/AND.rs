@@ -1,5 +1,6 @@
+use crate::errors::MichelsonError;
 use crate::instructions::{Instruction, RunOptions};
-use crate::m_types::{int, nat, MValue};
+use crate::m_types::{int, MValue};
 use crate::stack::{Stack, StackElement, StackFuncs, StackSnapshots};
 
 // https://tezos.gitlab.io/michelson-reference/#instr-AND
@@ -8,12 +9,9 @@ pub fn run(
     stack: Stack,
     options: &RunOptions,
     mut stack_snapshots: StackSnapshots,
-) -> Result<(Stack, StackSnapshots), String> {
+) -> Result<(Stack, StackSnapshots), MichelsonError> {
     // checks the stack
-    match stack.check_depth(options.pos + 2, Instruction::AND) {
-        Ok(_) => (),
-        Err(err) => panic!("{}", err),
-    };
+    stack.check_depth(options.pos + 2, Instruction::AND)?;
     // pattern matches the different numeric types
     let new_val: MValue = match (
         stack[options.pos].get_val(),
@@ -22,13 +20,14 @@ pub fn run(
         // boolean AND
         (MValue::Bool(left), MValue::Bool(right)) => Ok(MValue::Bool(left && right)),
         // bitwise AND
-        (MValue::Int(left), MValue::Nat(right)) => Ok(MValue::Nat((left & right as int) as nat)),
+        // the result is positive as the nat operand is positive
+        (MValue::Int(left), MValue::Nat(right)) => Ok(MValue::Nat((left & int::from(right)).magnitude().clone())),
         (MValue::Nat(left), MValue::Nat(right)) => Ok(MValue::Nat(left & right)),
-        _ => Err(format!(
+        _ => Err(MichelsonError::Message(format!(
             "Invalid types for `AND` expected `bool/bool`, `int/nat` or `nat/nat`, but got `{}/{}`",
             stack[options.pos].value.get_type().to_string(),
             stack[options.pos + 1].value.get_type().to_string()
-        )),
+        ))),
     }?;
     // removes the 2 elements being compared from the stack
     let (_, new_stack) = stack.remove_at(options.pos);
@@ -86,9 +85,9 @@ mod tests {
     #[test]
     fn and_nat_nat() -> () {
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Nat(2), Instruction::INIT),
-            StackElement::new(MValue::Nat(3), Instruction::INIT),
-            StackElement::new(MValue::Nat(44), Instruction::INIT),
+            StackElement::new(MValue::new_nat(2), Instruction::INIT),
+            StackElement::new(MValue::new_nat(3), Instruction::INIT),
+            StackElement::new(MValue::new_nat(44), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
         let options = RunOptions {
@@ -102,9 +101,9 @@ mod tests {
             Err(_) => assert!(false),
             Ok((stack, _)) => {
                 assert!(stack.len() == 2);
-                assert_eq!(stack[0].value, MValue::Nat(2));
+                assert_eq!(stack[0].value, MValue::new_nat(2));
                 assert_eq!(stack[0].instruction, Instruction::AND);
-                assert_eq!(stack[1].value, MValue::Nat(44));
+                assert_eq!(stack[1].value, MValue::new_nat(44));
                 assert_eq!(stack[1].instruction, Instruction::INIT);
             }
         }
@@ -114,9 +113,9 @@ mod tests {
     #[test]
     fn and_int_nat() -> () {
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Int(2), Instruction::INIT),
-            StackElement::new(MValue::Nat(3), Instruction::INIT),
-            StackElement::new(MValue::Nat(44), Instruction::INIT),
+            StackElement::new(MValue::new_int(2), Instruction::INIT),
+            StackElement::new(MValue::new_nat(3), Instruction::INIT),
+            StackElement::new(MValue::new_nat(44), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
         let options = RunOptions {
@@ -130,9 +129,9 @@ mod tests {
             Err(_) => assert!(false),
             Ok((stack, _)) => {
                 assert!(stack.len() == 2);
-                assert_eq!(stack[0].value, MValue::Nat(2));
+                assert_eq!(stack[0].value, MValue::new_nat(2));
                 assert_eq!(stack[0].instruction, Instruction::AND);
-                assert_eq!(stack[1].value, MValue::Nat(44));
+                assert_eq!(stack[1].value, MValue::new_nat(44));
                 assert_eq!(stack[1].instruction, Instruction::INIT);
             }
         }
@@ -147,8 +146,8 @@ mod tests {
     fn and_wrong_types() -> () {
         let initial_stack: Stack = vec![
             StackElement::new(MValue::String(String::from("test")), Instruction::INIT),
-            StackElement::new(MValue::Nat(3), Instruction::INIT),
-            StackElement::new(MValue::Nat(44), Instruction::INIT),
+            StackElement::new(MValue::new_nat(3), Instruction::INIT),
+            StackElement::new(MValue::new_nat(44), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
         let options = RunOptions {
@@ -170,7 +169,7 @@ mod tests {
         expected = "Unexpected stack length, expected a length of 2 for instruction AND, got 1"
     )]
     fn and_wrong_stack_depth() -> () {
-        let initial_stack: Stack = vec![StackElement::new(MValue::Nat(3), Instruction::INIT)];
+        let initial_stack: Stack = vec![StackElement::new(MValue::new_nat(3), Instruction::INIT)];
         let stack_snapshots = vec![];
         let options = RunOptions {
             context: RunOptionsContext::mock(),
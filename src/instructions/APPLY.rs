@@ -0,0 +1,115 @@
+use crate::errors::MichelsonError;
+use crate::instructions::{Instruction, RunOptions};
+use crate::m_types::MValue;
+use crate::stack::{Stack, StackElement, StackFuncs, StackSnapshots};
+
+// https://tezos.gitlab.io/michelson-reference/#instr-APPLY
+
+/// partially applies the lambda to the value on top of the stack
+/// the value becomes the left side of the pair the lambda takes as parameter
+pub fn run(
+    stack: Stack,
+    options: &RunOptions,
+    mut stack_snapshots: StackSnapshots,
+) -> Result<(Stack, StackSnapshots), MichelsonError> {
+    // checks the stack
+    stack.check_depth(options.pos + 2, Instruction::APPLY)?;
+    match (stack[options.pos].get_val(), &stack[options.pos + 1].value) {
+        (value, MValue::Lambda(lambda)) => {
+            let new_lambda = lambda.apply(value)?;
+            // updates the stack
+            let (_, new_stack) = stack.remove_at(options.pos);
+            let new_stack = new_stack.replace(
+                vec![StackElement::new(MValue::Lambda(new_lambda), Instruction::APPLY)],
+                options.pos,
+            );
+            // updates the stack snapshots
+            stack_snapshots.push(new_stack.clone());
+
+            Ok((new_stack, stack_snapshots))
+        }
+        (_, val) => Err(MichelsonError::WrongType((
+            String::from("lambda"),
+            val.get_type().to_string(),
+            Instruction::APPLY,
+        ))),
+    }
+}
+
+/*
+    TESTS
+*/
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::m_types::{LambdaValue, MType, PairValue};
+
+    // PASSING
+    #[test]
+    fn apply_success() {
+        let param_type = MType::pair(MType::String, MType::Nat);
+        let lambda = LambdaValue::new(param_type, MType::Nat, "{ CDR }".parse().unwrap(), false).unwrap();
+        let initial_stack: Stack = vec![
+            StackElement::new(MValue::new_string("test"), Instruction::INIT),
+            StackElement::new(MValue::Lambda(lambda), Instruction::INIT),
+        ];
+
+        match run(initial_stack, &RunOptions::mock(), vec![]) {
+            Ok((new_stack, _)) => {
+                assert_eq!(new_stack.len(), 1);
+                assert_eq!(new_stack[0].instruction, Instruction::APPLY);
+                assert_eq!(
+                    new_stack[0].value.get_type(),
+                    MType::Lambda(Box::new((MType::Nat, MType::Nat)))
+                );
+                match &new_stack[0].value {
+                    MValue::Lambda(lambda) => {
+                        assert_eq!(lambda.applied, vec![MValue::new_string("test")]);
+                        assert_eq!(
+                            lambda.build_param(MValue::new_nat(3)),
+                            MValue::Pair(PairValue::new(
+                                MValue::new_string("test"),
+                                MValue::new_nat(3)
+                            ))
+                        );
+                    }
+                    val => panic!("Expected a lambda, got {}", val.to_string()),
+                }
+            }
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    // FAILING
+    #[test]
+    #[should_panic(expected = "Cannot apply a value of type nat to a lambda taking pair string nat")]
+    fn apply_wrong_value_type() {
+        let param_type = MType::pair(MType::String, MType::Nat);
+        let lambda = LambdaValue::new(param_type, MType::Nat, "{ CDR }".parse().unwrap(), false).unwrap();
+        let initial_stack: Stack = vec![
+            StackElement::new(MValue::new_nat(5), Instruction::INIT),
+            StackElement::new(MValue::Lambda(lambda), Instruction::INIT),
+        ];
+
+        match run(initial_stack, &RunOptions::mock(), vec![]) {
+            Ok(_) => assert!(false),
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot apply a value of type nat to a lambda taking nat")]
+    fn apply_lambda_without_pair() {
+        let lambda = LambdaValue::new(MType::Nat, MType::Nat, "{}".parse().unwrap(), false).unwrap();
+        let initial_stack: Stack = vec![
+            StackElement::new(MValue::new_nat(5), Instruction::INIT),
+            StackElement::new(MValue::Lambda(lambda), Instruction::INIT),
+        ];
+
+        match run(initial_stack, &RunOptions::mock(), vec![]) {
+            Ok(_) => assert!(false),
+            Err(err) => panic!("{}", err),
+        }
+    }
+}
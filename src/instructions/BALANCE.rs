@@ -1,3 +1,4 @@
+use crate::errors::MichelsonError;
 use crate::instructions::{Instruction, RunOptions};
 use crate::m_types::MValue;
 use crate::stack::{Stack, StackElement, StackFuncs, StackSnapshots};
@@ -8,7 +9,7 @@ pub fn run(
     stack: Stack,
     options: &RunOptions,
     mut stack_snapshots: StackSnapshots,
-) -> Result<(Stack, StackSnapshots), String> {
+) -> Result<(Stack, StackSnapshots), MichelsonError> {
     // no stack check required
     // updates the stack
     let new_el = StackElement::new(MValue::Mutez(options.context.balance), Instruction::BALANCE);
@@ -31,7 +32,7 @@ mod tests {
     fn balance_success() {
         // should push the address to the stack
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Int(22), Instruction::INIT),
+            StackElement::new(MValue::new_int(22), Instruction::INIT),
             StackElement::new(MValue::Mutez(6_000_000), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
@@ -48,7 +49,7 @@ mod tests {
                 assert!(stack.len() == 3);
                 assert_eq!(stack[0].value, MValue::Mutez(50_000_000));
                 assert_eq!(stack[0].instruction, Instruction::BALANCE);
-                assert_eq!(stack[1].value, MValue::Int(22));
+                assert_eq!(stack[1].value, MValue::new_int(22));
                 assert_eq!(stack[1].instruction, Instruction::INIT);
                 assert_eq!(stack[2].value, MValue::Mutez(6_000_000));
                 assert_eq!(stack[2].instruction, Instruction::INIT);
@@ -1,4 +1,4 @@
-use crate::errors::{display_error, ErrorCode};
+use crate::errors::MichelsonError;
 use crate::instructions::{Instruction, RunOptions};
 use crate::m_types::MValue;
 use crate::stack::{Stack, StackElement, StackFuncs, StackSnapshots};
@@ -9,7 +9,7 @@ pub fn run(
     stack: Stack,
     options: &RunOptions,
     mut stack_snapshots: StackSnapshots,
-) -> Result<(Stack, StackSnapshots), String> {
+) -> Result<(Stack, StackSnapshots), MichelsonError> {
     let this_instruction = Instruction::CDR;
     // checks the stack
     stack.check_depth(options.pos + 1, this_instruction)?;
@@ -19,14 +19,15 @@ pub fn run(
             // extracts the left field of the pair
             Ok(pair.cdr())
         }
-        val => Err(display_error(ErrorCode::WrongType((
+        val => Err(MichelsonError::WrongType((
             String::from("pair"),
             val.get_type().to_string(),
             this_instruction,
-        )))),
+        ))),
     }?;
 
-    let new_el = StackElement::new(new_val, this_instruction);
+    // the field keeps its annotations
+    let new_el = stack[options.pos].annotate_field(1, StackElement::new(new_val, this_instruction));
     let new_stack = stack.replace(vec![new_el], options.pos);
     // updates the stack snapshots
     stack_snapshots.push(new_stack.clone());
@@ -48,10 +49,10 @@ mod test {
     fn cdr_success() {
         let initial_stack: Stack = vec![
             StackElement::new(
-                MValue::Pair(PairValue::new(MValue::Nat(7), MValue::Mutez(5_000_000))),
+                MValue::Pair(PairValue::new(MValue::new_nat(7), MValue::Mutez(5_000_000))),
                 Instruction::INIT,
             ),
-            StackElement::new(MValue::Int(4), Instruction::INIT),
+            StackElement::new(MValue::new_int(4), Instruction::INIT),
             StackElement::new(MValue::String(String::from("taquito")), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
@@ -68,7 +69,7 @@ mod test {
                 assert!(stack.len() == initial_stack_len); // initial stack and new stack must have the same lengths
                 assert_eq!(stack[0].value, MValue::Mutez(5_000_000));
                 assert_eq!(stack[0].instruction, Instruction::CDR);
-                assert_eq!(stack[1].value, MValue::Int(4));
+                assert_eq!(stack[1].value, MValue::new_int(4));
                 assert_eq!(stack[2].value, MValue::String(String::from("taquito")));
             }
         }
@@ -86,7 +87,7 @@ mod test {
 
         match run(initial_stack, &options, stack_snapshots) {
             Err(err) => assert_eq!(
-                err,
+                err.to_string(),
                 String::from(
                     "Unexpected stack length, expected a length of 1 for instruction CDR, got 0"
                 )
@@ -98,7 +99,7 @@ mod test {
     #[test]
     fn cdr_wrong_type() {
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Int(4), Instruction::INIT),
+            StackElement::new(MValue::new_int(4), Instruction::INIT),
             StackElement::new(MValue::String(String::from("taquito")), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
@@ -109,7 +110,7 @@ mod test {
 
         match run(initial_stack, &options, stack_snapshots) {
             Err(err) => assert_eq!(
-                err,
+                err.to_string(),
                 String::from("Wrong type, expected `pair` for instruction CDR, got `int`")
             ),
             Ok(_) => assert!(false),
@@ -1,3 +1,4 @@
+use crate::errors::MichelsonError;
 use crate::instructions::{Instruction, RunOptions};
 use crate::m_types::MValue;
 use crate::stack::{Stack, StackElement, StackFuncs, StackSnapshots};
@@ -8,7 +9,7 @@ pub fn run(
     stack: Stack,
     options: &RunOptions,
     mut stack_snapshots: StackSnapshots,
-) -> Result<(Stack, StackSnapshots), String> {
+) -> Result<(Stack, StackSnapshots), MichelsonError> {
     // checks the stack
     stack.check_depth(options.pos, Instruction::CHAIN_ID)?;
     // checks if the provided address is valid
@@ -30,13 +31,13 @@ pub fn run(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::instructions::RunOptionsContext;
+    use crate::instructions::{RunOptionsContext, MAX_LOOP_ITERATIONS};
 
     #[test]
     fn chain_id_success() {
         // should push the address to the stack
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Int(22), Instruction::INIT),
+            StackElement::new(MValue::new_int(22), Instruction::INIT),
             StackElement::new(MValue::Mutez(6_000_000), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
@@ -48,7 +49,9 @@ mod tests {
                 self_address: String::from("KT1L7GvUxZH5tfa6cgZKnH6vpp2uVxnFVHKu"),
                 balance: 50_000_000,
                 level: 11,
+                now: 1_640_995_200,
                 chain_id: String::from("chain_id"),
+                max_loop_iterations: MAX_LOOP_ITERATIONS,
             },
             pos: 0,
         };
@@ -61,7 +64,7 @@ mod tests {
                 assert!(stack.len() == 3);
                 assert_eq!(stack[0].value, MValue::ChainId(String::from("chain_id")));
                 assert_eq!(stack[0].instruction, Instruction::CHAIN_ID);
-                assert_eq!(stack[1].value, MValue::Int(22));
+                assert_eq!(stack[1].value, MValue::new_int(22));
                 assert_eq!(stack[1].instruction, Instruction::INIT);
                 assert_eq!(stack[2].value, MValue::Mutez(6_000_000));
                 assert_eq!(stack[2].instruction, Instruction::INIT);
@@ -1,162 +1,37 @@
+use crate::errors::MichelsonError;
 use crate::instructions::{Instruction, RunOptions};
-use crate::m_types::{AddressType, MValue, Or};
+use crate::m_types::MValue;
 use crate::stack::{Stack, StackElement, StackFuncs, StackSnapshots};
 use std::cmp::Ordering;
 
 // https://tezos.gitlab.io/michelson-reference/#instr-COMPARE
 
+/// compares 2 values of the same comparable type, returns -1, 0 or 1 as an int value
+/// the values are compared with the ordering of `MValue`, which follows the protocol for comparable types
+pub fn compare(first_val: &MValue, last_val: &MValue) -> Result<MValue, MichelsonError> {
+    let m_type = first_val.get_type();
+    match (first_val, last_val) {
+        // TODO: implementation of comparison of never may be incorrect
+        (MValue::Never, MValue::Never) => Err(MichelsonError::Message(String::from("Forbidden comparison of never"))),
+        _ if m_type == last_val.get_type() && m_type.is_comparable() => match first_val.cmp(last_val) {
+            Ordering::Less => Ok(MValue::new_int(-1)),
+            Ordering::Equal => Ok(MValue::new_int(0)),
+            Ordering::Greater => Ok(MValue::new_int(1)),
+        },
+        _ => Err(MichelsonError::Message(format!(
+            "{:?} and {:?} are not comparable",
+            first_val, last_val
+        ))),
+    }
+}
+
 pub fn run(
     stack: Stack,
     options: &RunOptions,
     mut stack_snapshots: StackSnapshots,
-) -> Result<(Stack, StackSnapshots), String> {
+) -> Result<(Stack, StackSnapshots), MichelsonError> {
     // checks the stack
-    match stack.check_depth(options.pos + 2, Instruction::COMPARE) {
-        Ok(_) => (),
-        Err(err) => panic!("{}", err),
-    };
-    // creates a separate function to recursively call it for complex types
-    fn compare(first_val: &MValue, last_val: &MValue) -> Result<MValue, String> {
-        match (first_val, last_val) {
-            // int
-            (MValue::Int(first_val), MValue::Int(last_val)) => {
-                if first_val < last_val {
-                    Ok(MValue::Int(-1))
-                } else if first_val == last_val {
-                    Ok(MValue::Int(0))
-                } else {
-                    Ok(MValue::Int(1))
-                }
-            }
-            // nat and mutez
-            (MValue::Nat(first_val), MValue::Nat(last_val))
-            | (MValue::Mutez(first_val), MValue::Mutez(last_val)) => {
-                if first_val < last_val {
-                    Ok(MValue::Int(-1))
-                } else if first_val == last_val {
-                    Ok(MValue::Int(0))
-                } else {
-                    Ok(MValue::Int(1))
-                }
-            }
-            // timestamp
-            (MValue::Timestamp(first_val), MValue::Timestamp(last_val)) => {
-                if first_val < last_val {
-                    Ok(MValue::Int(-1))
-                } else if first_val == last_val {
-                    Ok(MValue::Int(0))
-                } else {
-                    Ok(MValue::Int(1))
-                }
-            }
-            // string values
-            (MValue::String(first_val), MValue::String(last_val))
-            | (MValue::Bytes(first_val), MValue::Bytes(last_val))
-            | (MValue::KeyHash(first_val), MValue::KeyHash(last_val))
-            | (MValue::Key(first_val), MValue::Key(last_val))
-            | (MValue::Signature(first_val), MValue::Signature(last_val))
-            | (MValue::ChainId(first_val), MValue::ChainId(last_val)) => {
-                match first_val.cmp(&last_val) {
-                    Ordering::Less => Ok(MValue::Int(-1)),
-                    Ordering::Equal => Ok(MValue::Int(0)),
-                    Ordering::Greater => Ok(MValue::Int(1)),
-                }
-            }
-            // addresses
-            (MValue::Address(first_val), MValue::Address(last_val)) => {
-                let first_addr = first_val.clone();
-                let last_addr = last_val.clone();
-
-                match (
-                    MValue::Address(first_val.to_string()).get_address_type(),
-                    MValue::Address(last_val.to_string()).get_address_type(),
-                ) {
-                    (Ok(addr_1), Ok(addr_2)) => match (addr_1, addr_2) {
-                        (AddressType::ImplicitAccount, AddressType::ImplicitAccount)
-                        | (AddressType::Contract, AddressType::Contract) => {
-                            match first_addr.cmp(&last_addr) {
-                                Ordering::Less => Ok(MValue::Int(-1)),
-                                Ordering::Equal => Ok(MValue::Int(0)),
-                                Ordering::Greater => Ok(MValue::Int(1)),
-                            }
-                        }
-                        (AddressType::ImplicitAccount, AddressType::Contract) => {
-                            Ok(MValue::Int(-1))
-                        }
-                        (AddressType::Contract, AddressType::ImplicitAccount) => Ok(MValue::Int(1)),
-                    },
-                    (Err(err), _) | (_, Err(err)) => Err(err),
-                }
-            }
-            // bool
-            (MValue::Bool(first_val), MValue::Bool(last_val)) => {
-                if first_val == &false && last_val == &true {
-                    Ok(MValue::Int(-1))
-                } else if first_val == &true && last_val == &false {
-                    Ok(MValue::Int(1))
-                } else {
-                    Ok(MValue::Int(0))
-                }
-            }
-            // unit
-            (MValue::Unit, MValue::Unit) => Ok(MValue::Int(0)),
-            // pairs
-            (MValue::Pair(left_pair), MValue::Pair(right_pair)) => {
-                let (left_pair_left, left_pair_right) = &*left_pair.value;
-                let (right_pair_left, right_pair_right) = &*right_pair.value;
-
-                match (
-                    compare(left_pair_left, right_pair_left),
-                    compare(left_pair_right, right_pair_right),
-                ) {
-                    (Ok(val_1), Ok(val_2)) => {
-                        if let MValue::Int(_val @ 0) = val_1 {
-                            Ok(val_2)
-                        } else {
-                            Ok(val_1)
-                        }
-                    }
-                    (Ok(_), Err(err)) => Err(err),
-                    (Err(err), Ok(_)) => Err(err),
-                    (Err(err_1), Err(err_2)) => Err(format!("{} / {}", err_1, err_2)),
-                }
-            }
-            // options
-            (MValue::Option(first_val), MValue::Option(last_val)) => {
-                // unwraps the values in the boxes
-                let first_opt = &*first_val.value;
-                let last_opt = &*last_val.value;
-                // compares the values
-                match (first_opt, last_opt) {
-                    (None, None) => Ok(MValue::Int(0)),
-                    (Some(_), None) => Ok(MValue::Int(1)),
-                    (None, Some(_)) => Ok(MValue::Int(-1)),
-                    (Some(val_1), Some(val_2)) => compare(&val_1, &val_2),
-                }
-            }
-            // unions
-            (MValue::Or(first_val), MValue::Or(last_val)) => {
-                let first_or = &*first_val.value;
-                let last_or = &*last_val.value;
-
-                match (first_or, last_or) {
-                    (Or::Left(_), Or::Right(_)) => Ok(MValue::Int(-1)),
-                    (Or::Right(_), Or::Left(_)) => Ok(MValue::Int(1)),
-                    (Or::Left(first_left), Or::Left(last_left)) => compare(first_left, last_left),
-                    (Or::Right(first_right), Or::Right(last_right)) => {
-                        compare(first_right, last_right)
-                    }
-                }
-            }
-            // never
-            // TODO: implementation of comparison of never may be incorrect
-            (MValue::Never, MValue::Never) => Err(String::from("Forbidden comparison of never")),
-            _ => Err(format!(
-                "{:?} and {:?} are not comparable",
-                first_val, last_val
-            )),
-        }
-    }
+    stack.check_depth(options.pos + 2, Instruction::COMPARE)?;
     // pattern match the values according to their types
     let new_val = compare(&stack[options.pos].value, &stack[options.pos + 1].value)?;
     // removes the 2 elements being compared from the stack
@@ -190,8 +65,8 @@ mod tests {
     fn compare_int_int() {
         // should output -1
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Int(5), Instruction::INIT),
-            StackElement::new(MValue::Int(6), Instruction::INIT),
+            StackElement::new(MValue::new_int(5), Instruction::INIT),
+            StackElement::new(MValue::new_int(6), Instruction::INIT),
             StackElement::new(MValue::Mutez(6_000_000), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
@@ -206,7 +81,7 @@ mod tests {
             Err(_) => assert!(false),
             Ok((stack, _)) => {
                 assert!(stack.len() == 2);
-                assert_eq!(stack[0].value, MValue::Int(-1));
+                assert_eq!(stack[0].value, MValue::new_int(-1));
                 assert_eq!(stack[0].instruction, Instruction::COMPARE);
                 assert_eq!(stack[1].value, MValue::Mutez(6_000_000));
                 assert_eq!(stack[1].instruction, Instruction::INIT);
@@ -215,8 +90,8 @@ mod tests {
 
         // should output 0
         let new_stack: Stack = vec![
-            StackElement::new(MValue::Int(6), Instruction::INIT),
-            StackElement::new(MValue::Int(6), Instruction::INIT),
+            StackElement::new(MValue::new_int(6), Instruction::INIT),
+            StackElement::new(MValue::new_int(6), Instruction::INIT),
             StackElement::new(MValue::Mutez(6_000_000), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
@@ -227,7 +102,7 @@ mod tests {
             Err(_) => assert!(false),
             Ok((stack, _)) => {
                 assert!(stack.len() == 2);
-                assert_eq!(stack[0].value, MValue::Int(0));
+                assert_eq!(stack[0].value, MValue::new_int(0));
                 assert_eq!(stack[0].instruction, Instruction::COMPARE);
                 assert_eq!(stack[1].value, MValue::Mutez(6_000_000));
                 assert_eq!(stack[1].instruction, Instruction::INIT);
@@ -236,8 +111,8 @@ mod tests {
 
         // should output 1
         let new_stack: Stack = vec![
-            StackElement::new(MValue::Int(16), Instruction::INIT),
-            StackElement::new(MValue::Int(6), Instruction::INIT),
+            StackElement::new(MValue::new_int(16), Instruction::INIT),
+            StackElement::new(MValue::new_int(6), Instruction::INIT),
             StackElement::new(MValue::Mutez(6_000_000), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
@@ -248,7 +123,7 @@ mod tests {
             Err(_) => assert!(false),
             Ok((stack, _)) => {
                 assert!(stack.len() == 2);
-                assert_eq!(stack[0].value, MValue::Int(1));
+                assert_eq!(stack[0].value, MValue::new_int(1));
                 assert_eq!(stack[0].instruction, Instruction::COMPARE);
                 assert_eq!(stack[1].value, MValue::Mutez(6_000_000));
                 assert_eq!(stack[1].instruction, Instruction::INIT);
@@ -261,8 +136,8 @@ mod tests {
     fn compare_nat_nat() {
         // should output -1
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Nat(5), Instruction::INIT),
-            StackElement::new(MValue::Nat(6), Instruction::INIT),
+            StackElement::new(MValue::new_nat(5), Instruction::INIT),
+            StackElement::new(MValue::new_nat(6), Instruction::INIT),
             StackElement::new(MValue::Mutez(6_000_000), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
@@ -277,7 +152,7 @@ mod tests {
             Err(_) => assert!(false),
             Ok((stack, _)) => {
                 assert!(stack.len() == 2);
-                assert_eq!(stack[0].value, MValue::Int(-1));
+                assert_eq!(stack[0].value, MValue::new_int(-1));
                 assert_eq!(stack[0].instruction, Instruction::COMPARE);
                 assert_eq!(stack[1].value, MValue::Mutez(6_000_000));
                 assert_eq!(stack[1].instruction, Instruction::INIT);
@@ -286,8 +161,8 @@ mod tests {
 
         // should output 0
         let new_stack: Stack = vec![
-            StackElement::new(MValue::Nat(6), Instruction::INIT),
-            StackElement::new(MValue::Nat(6), Instruction::INIT),
+            StackElement::new(MValue::new_nat(6), Instruction::INIT),
+            StackElement::new(MValue::new_nat(6), Instruction::INIT),
             StackElement::new(MValue::Mutez(6_000_000), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
@@ -298,7 +173,7 @@ mod tests {
             Err(_) => assert!(false),
             Ok((stack, _)) => {
                 assert!(stack.len() == 2);
-                assert_eq!(stack[0].value, MValue::Int(0));
+                assert_eq!(stack[0].value, MValue::new_int(0));
                 assert_eq!(stack[0].instruction, Instruction::COMPARE);
                 assert_eq!(stack[1].value, MValue::Mutez(6_000_000));
                 assert_eq!(stack[1].instruction, Instruction::INIT);
@@ -307,8 +182,8 @@ mod tests {
 
         // should output 1
         let new_stack: Stack = vec![
-            StackElement::new(MValue::Nat(16), Instruction::INIT),
-            StackElement::new(MValue::Nat(6), Instruction::INIT),
+            StackElement::new(MValue::new_nat(16), Instruction::INIT),
+            StackElement::new(MValue::new_nat(6), Instruction::INIT),
             StackElement::new(MValue::Mutez(6_000_000), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
@@ -319,7 +194,7 @@ mod tests {
             Err(_) => assert!(false),
             Ok((stack, _)) => {
                 assert!(stack.len() == 2);
-                assert_eq!(stack[0].value, MValue::Int(1));
+                assert_eq!(stack[0].value, MValue::new_int(1));
                 assert_eq!(stack[0].instruction, Instruction::COMPARE);
                 assert_eq!(stack[1].value, MValue::Mutez(6_000_000));
                 assert_eq!(stack[1].instruction, Instruction::INIT);
@@ -334,7 +209,7 @@ mod tests {
         let initial_stack: Stack = vec![
             StackElement::new(MValue::Mutez(5_000_000), Instruction::INIT),
             StackElement::new(MValue::Mutez(6_000_000), Instruction::INIT),
-            StackElement::new(MValue::Nat(6), Instruction::INIT),
+            StackElement::new(MValue::new_nat(6), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
         let options = RunOptions {
@@ -348,9 +223,9 @@ mod tests {
             Err(_) => assert!(false),
             Ok((stack, _)) => {
                 assert!(stack.len() == 2);
-                assert_eq!(stack[0].value, MValue::Int(-1));
+                assert_eq!(stack[0].value, MValue::new_int(-1));
                 assert_eq!(stack[0].instruction, Instruction::COMPARE);
-                assert_eq!(stack[1].value, MValue::Nat(6));
+                assert_eq!(stack[1].value, MValue::new_nat(6));
                 assert_eq!(stack[1].instruction, Instruction::INIT);
             }
         }
@@ -359,7 +234,7 @@ mod tests {
         let new_stack: Stack = vec![
             StackElement::new(MValue::Mutez(6_000_000), Instruction::INIT),
             StackElement::new(MValue::Mutez(6_000_000), Instruction::INIT),
-            StackElement::new(MValue::Nat(7), Instruction::INIT),
+            StackElement::new(MValue::new_nat(7), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
 
@@ -369,9 +244,9 @@ mod tests {
             Err(_) => assert!(false),
             Ok((stack, _)) => {
                 assert!(stack.len() == 2);
-                assert_eq!(stack[0].value, MValue::Int(0));
+                assert_eq!(stack[0].value, MValue::new_int(0));
                 assert_eq!(stack[0].instruction, Instruction::COMPARE);
-                assert_eq!(stack[1].value, MValue::Nat(7));
+                assert_eq!(stack[1].value, MValue::new_nat(7));
                 assert_eq!(stack[1].instruction, Instruction::INIT);
             }
         }
@@ -380,7 +255,7 @@ mod tests {
         let new_stack: Stack = vec![
             StackElement::new(MValue::Mutez(50_000_000), Instruction::INIT),
             StackElement::new(MValue::Mutez(6_000_000), Instruction::INIT),
-            StackElement::new(MValue::Nat(6), Instruction::INIT),
+            StackElement::new(MValue::new_nat(6), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
 
@@ -390,9 +265,9 @@ mod tests {
             Err(_) => assert!(false),
             Ok((stack, _)) => {
                 assert!(stack.len() == 2);
-                assert_eq!(stack[0].value, MValue::Int(1));
+                assert_eq!(stack[0].value, MValue::new_int(1));
                 assert_eq!(stack[0].instruction, Instruction::COMPARE);
-                assert_eq!(stack[1].value, MValue::Nat(6));
+                assert_eq!(stack[1].value, MValue::new_nat(6));
                 assert_eq!(stack[1].instruction, Instruction::INIT);
             }
         }
@@ -409,7 +284,7 @@ mod tests {
         let initial_stack: Stack = vec![
             StackElement::new(MValue::Timestamp(now - 100), Instruction::INIT),
             StackElement::new(MValue::Timestamp(now), Instruction::INIT),
-            StackElement::new(MValue::Nat(6), Instruction::INIT),
+            StackElement::new(MValue::new_nat(6), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
         let options = RunOptions {
@@ -423,9 +298,9 @@ mod tests {
             Err(_) => assert!(false),
             Ok((stack, _)) => {
                 assert!(stack.len() == 2);
-                assert_eq!(stack[0].value, MValue::Int(-1));
+                assert_eq!(stack[0].value, MValue::new_int(-1));
                 assert_eq!(stack[0].instruction, Instruction::COMPARE);
-                assert_eq!(stack[1].value, MValue::Nat(6));
+                assert_eq!(stack[1].value, MValue::new_nat(6));
                 assert_eq!(stack[1].instruction, Instruction::INIT);
             }
         }
@@ -434,7 +309,7 @@ mod tests {
         let new_stack: Stack = vec![
             StackElement::new(MValue::Timestamp(now), Instruction::INIT),
             StackElement::new(MValue::Timestamp(now), Instruction::INIT),
-            StackElement::new(MValue::Nat(7), Instruction::INIT),
+            StackElement::new(MValue::new_nat(7), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
 
@@ -444,9 +319,9 @@ mod tests {
             Err(_) => assert!(false),
             Ok((stack, _)) => {
                 assert!(stack.len() == 2);
-                assert_eq!(stack[0].value, MValue::Int(0));
+                assert_eq!(stack[0].value, MValue::new_int(0));
                 assert_eq!(stack[0].instruction, Instruction::COMPARE);
-                assert_eq!(stack[1].value, MValue::Nat(7));
+                assert_eq!(stack[1].value, MValue::new_nat(7));
                 assert_eq!(stack[1].instruction, Instruction::INIT);
             }
         }
@@ -455,7 +330,7 @@ mod tests {
         let new_stack: Stack = vec![
             StackElement::new(MValue::Timestamp(now + 100), Instruction::INIT),
             StackElement::new(MValue::Timestamp(now), Instruction::INIT),
-            StackElement::new(MValue::Nat(6), Instruction::INIT),
+            StackElement::new(MValue::new_nat(6), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
 
@@ -465,9 +340,9 @@ mod tests {
             Err(_) => assert!(false),
             Ok((stack, _)) => {
                 assert!(stack.len() == 2);
-                assert_eq!(stack[0].value, MValue::Int(1));
+                assert_eq!(stack[0].value, MValue::new_int(1));
                 assert_eq!(stack[0].instruction, Instruction::COMPARE);
-                assert_eq!(stack[1].value, MValue::Nat(6));
+                assert_eq!(stack[1].value, MValue::new_nat(6));
                 assert_eq!(stack[1].instruction, Instruction::INIT);
             }
         }
@@ -480,7 +355,7 @@ mod tests {
         let initial_stack: Stack = vec![
             StackElement::new(MValue::String(String::from("taquito")), Instruction::INIT),
             StackElement::new(MValue::String(String::from("tezos")), Instruction::INIT),
-            StackElement::new(MValue::Nat(6), Instruction::INIT),
+            StackElement::new(MValue::new_nat(6), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
         let options = RunOptions {
@@ -494,9 +369,9 @@ mod tests {
             Err(_) => assert!(false),
             Ok((stack, _)) => {
                 assert!(stack.len() == 2);
-                assert_eq!(stack[0].value, MValue::Int(-1));
+                assert_eq!(stack[0].value, MValue::new_int(-1));
                 assert_eq!(stack[0].instruction, Instruction::COMPARE);
-                assert_eq!(stack[1].value, MValue::Nat(6));
+                assert_eq!(stack[1].value, MValue::new_nat(6));
                 assert_eq!(stack[1].instruction, Instruction::INIT);
             }
         }
@@ -505,7 +380,7 @@ mod tests {
         let new_stack: Stack = vec![
             StackElement::new(MValue::String(String::from("tezos")), Instruction::INIT),
             StackElement::new(MValue::String(String::from("tezos")), Instruction::INIT),
-            StackElement::new(MValue::Nat(7), Instruction::INIT),
+            StackElement::new(MValue::new_nat(7), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
 
@@ -515,9 +390,9 @@ mod tests {
             Err(_) => assert!(false),
             Ok((stack, _)) => {
                 assert!(stack.len() == 2);
-                assert_eq!(stack[0].value, MValue::Int(0));
+                assert_eq!(stack[0].value, MValue::new_int(0));
                 assert_eq!(stack[0].instruction, Instruction::COMPARE);
-                assert_eq!(stack[1].value, MValue::Nat(7));
+                assert_eq!(stack[1].value, MValue::new_nat(7));
                 assert_eq!(stack[1].instruction, Instruction::INIT);
             }
         }
@@ -526,7 +401,7 @@ mod tests {
         let new_stack: Stack = vec![
             StackElement::new(MValue::String(String::from("tezos")), Instruction::INIT),
             StackElement::new(MValue::String(String::from("taquito")), Instruction::INIT),
-            StackElement::new(MValue::Nat(6), Instruction::INIT),
+            StackElement::new(MValue::new_nat(6), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
 
@@ -536,14 +411,32 @@ mod tests {
             Err(_) => assert!(false),
             Ok((stack, _)) => {
                 assert!(stack.len() == 2);
-                assert_eq!(stack[0].value, MValue::Int(1));
+                assert_eq!(stack[0].value, MValue::new_int(1));
                 assert_eq!(stack[0].instruction, Instruction::COMPARE);
-                assert_eq!(stack[1].value, MValue::Nat(6));
+                assert_eq!(stack[1].value, MValue::new_nat(6));
                 assert_eq!(stack[1].instruction, Instruction::INIT);
             }
         }
     }
 
+    // COMPARES BYTES
+    #[test]
+    fn compare_bytes_bytes() {
+        // bytes are compared byte by byte, a prefix comes first
+        assert_eq!(
+            compare(&MValue::new_bytes(&[0x01]), &MValue::new_bytes(&[0x01, 0x00])),
+            Ok(MValue::new_int(-1))
+        );
+        assert_eq!(
+            compare(&MValue::new_bytes(&[0x0a]), &MValue::new_bytes(&[0x0a])),
+            Ok(MValue::new_int(0))
+        );
+        assert_eq!(
+            compare(&MValue::new_bytes(&[0xff]), &MValue::new_bytes(&[0x01, 0xff])),
+            Ok(MValue::new_int(1))
+        );
+    }
+
     // COMPARES ADDRESSES
     #[test]
     fn compare_address_address() {
@@ -558,7 +451,7 @@ mod tests {
                 MValue::Address(String::from("KT1DrZokUnBg35YANi5sQxGfyWgDSAJRfJqY")),
                 Instruction::INIT,
             ),
-            StackElement::new(MValue::Nat(6), Instruction::INIT),
+            StackElement::new(MValue::new_nat(6), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
         let options = RunOptions {
@@ -572,9 +465,9 @@ mod tests {
             Err(_) => assert!(false),
             Ok((stack, _)) => {
                 assert!(stack.len() == 2);
-                assert_eq!(stack[0].value, MValue::Int(-1));
+                assert_eq!(stack[0].value, MValue::new_int(-1));
                 assert_eq!(stack[0].instruction, Instruction::COMPARE);
-                assert_eq!(stack[1].value, MValue::Nat(6));
+                assert_eq!(stack[1].value, MValue::new_nat(6));
                 assert_eq!(stack[1].instruction, Instruction::INIT);
             }
         }
@@ -589,7 +482,7 @@ mod tests {
                 MValue::Address(String::from("tz1Me1MGhK7taay748h4gPnX2cXvbgL6xsYL")),
                 Instruction::INIT,
             ),
-            StackElement::new(MValue::Nat(6), Instruction::INIT),
+            StackElement::new(MValue::new_nat(6), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
 
@@ -599,9 +492,9 @@ mod tests {
             Err(_) => assert!(false),
             Ok((stack, _)) => {
                 assert!(stack.len() == 2);
-                assert_eq!(stack[0].value, MValue::Int(1));
+                assert_eq!(stack[0].value, MValue::new_int(1));
                 assert_eq!(stack[0].instruction, Instruction::COMPARE);
-                assert_eq!(stack[1].value, MValue::Nat(6));
+                assert_eq!(stack[1].value, MValue::new_nat(6));
                 assert_eq!(stack[1].instruction, Instruction::INIT);
             }
         }
@@ -617,7 +510,7 @@ mod tests {
                 MValue::Address(String::from("tz1NhNv9g7rtcjyNsH8Zqu79giY5aTqDDrzB")),
                 Instruction::INIT,
             ),
-            StackElement::new(MValue::Nat(6), Instruction::INIT),
+            StackElement::new(MValue::new_nat(6), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
 
@@ -627,9 +520,9 @@ mod tests {
             Err(_) => assert!(false),
             Ok((stack, _)) => {
                 assert!(stack.len() == 2);
-                assert_eq!(stack[0].value, MValue::Int(-1));
+                assert_eq!(stack[0].value, MValue::new_int(-1));
                 assert_eq!(stack[0].instruction, Instruction::COMPARE);
-                assert_eq!(stack[1].value, MValue::Nat(6));
+                assert_eq!(stack[1].value, MValue::new_nat(6));
                 assert_eq!(stack[1].instruction, Instruction::INIT);
             }
         }
@@ -644,7 +537,7 @@ mod tests {
                 MValue::Address(String::from("tz1Me1MGhK7taay748h4gPnX2cXvbgL6xsYL")),
                 Instruction::INIT,
             ),
-            StackElement::new(MValue::Nat(6), Instruction::INIT),
+            StackElement::new(MValue::new_nat(6), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
 
@@ -654,9 +547,9 @@ mod tests {
             Err(_) => assert!(false),
             Ok((stack, _)) => {
                 assert!(stack.len() == 2);
-                assert_eq!(stack[0].value, MValue::Int(0));
+                assert_eq!(stack[0].value, MValue::new_int(0));
                 assert_eq!(stack[0].instruction, Instruction::COMPARE);
-                assert_eq!(stack[1].value, MValue::Nat(6));
+                assert_eq!(stack[1].value, MValue::new_nat(6));
                 assert_eq!(stack[1].instruction, Instruction::INIT);
             }
         }
@@ -671,7 +564,7 @@ mod tests {
                 MValue::Address(String::from("tz1Me1MGhK7taay748h4gPnX2cXvbgL6xsYL")),
                 Instruction::INIT,
             ),
-            StackElement::new(MValue::Nat(6), Instruction::INIT),
+            StackElement::new(MValue::new_nat(6), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
 
@@ -681,9 +574,9 @@ mod tests {
             Err(_) => assert!(false),
             Ok((stack, _)) => {
                 assert!(stack.len() == 2);
-                assert_eq!(stack[0].value, MValue::Int(1));
+                assert_eq!(stack[0].value, MValue::new_int(1));
                 assert_eq!(stack[0].instruction, Instruction::COMPARE);
-                assert_eq!(stack[1].value, MValue::Nat(6));
+                assert_eq!(stack[1].value, MValue::new_nat(6));
                 assert_eq!(stack[1].instruction, Instruction::INIT);
             }
         }
@@ -699,7 +592,7 @@ mod tests {
                 MValue::Address(String::from("KT1X1LgNkQShpF9nRLYw3Dgdy4qp38MX617z")),
                 Instruction::INIT,
             ),
-            StackElement::new(MValue::Nat(6), Instruction::INIT),
+            StackElement::new(MValue::new_nat(6), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
 
@@ -709,9 +602,9 @@ mod tests {
             Err(_) => assert!(false),
             Ok((stack, _)) => {
                 assert!(stack.len() == 2);
-                assert_eq!(stack[0].value, MValue::Int(-1));
+                assert_eq!(stack[0].value, MValue::new_int(-1));
                 assert_eq!(stack[0].instruction, Instruction::COMPARE);
-                assert_eq!(stack[1].value, MValue::Nat(6));
+                assert_eq!(stack[1].value, MValue::new_nat(6));
                 assert_eq!(stack[1].instruction, Instruction::INIT);
             }
         }
@@ -726,7 +619,7 @@ mod tests {
                 MValue::Address(String::from("KT1X1LgNkQShpF9nRLYw3Dgdy4qp38MX617z")),
                 Instruction::INIT,
             ),
-            StackElement::new(MValue::Nat(6), Instruction::INIT),
+            StackElement::new(MValue::new_nat(6), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
 
@@ -736,9 +629,9 @@ mod tests {
             Err(_) => assert!(false),
             Ok((stack, _)) => {
                 assert!(stack.len() == 2);
-                assert_eq!(stack[0].value, MValue::Int(0));
+                assert_eq!(stack[0].value, MValue::new_int(0));
                 assert_eq!(stack[0].instruction, Instruction::COMPARE);
-                assert_eq!(stack[1].value, MValue::Nat(6));
+                assert_eq!(stack[1].value, MValue::new_nat(6));
                 assert_eq!(stack[1].instruction, Instruction::INIT);
             }
         }
@@ -753,7 +646,7 @@ mod tests {
                 MValue::Address(String::from("KT1DrZokUnBg35YANi5sQxGfyWgDSAJRfJqY")),
                 Instruction::INIT,
             ),
-            StackElement::new(MValue::Nat(6), Instruction::INIT),
+            StackElement::new(MValue::new_nat(6), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
 
@@ -763,9 +656,9 @@ mod tests {
             Err(_) => assert!(false),
             Ok((stack, _)) => {
                 assert!(stack.len() == 2);
-                assert_eq!(stack[0].value, MValue::Int(1));
+                assert_eq!(stack[0].value, MValue::new_int(1));
                 assert_eq!(stack[0].instruction, Instruction::COMPARE);
-                assert_eq!(stack[1].value, MValue::Nat(6));
+                assert_eq!(stack[1].value, MValue::new_nat(6));
                 assert_eq!(stack[1].instruction, Instruction::INIT);
             }
         }
@@ -778,7 +671,7 @@ mod tests {
         let initial_stack: Stack = vec![
             StackElement::new(MValue::Bool(false), Instruction::INIT),
             StackElement::new(MValue::Bool(true), Instruction::INIT),
-            StackElement::new(MValue::Nat(6), Instruction::INIT),
+            StackElement::new(MValue::new_nat(6), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
         let options = RunOptions {
@@ -792,9 +685,9 @@ mod tests {
             Err(_) => assert!(false),
             Ok((stack, _)) => {
                 assert!(stack.len() == 2);
-                assert_eq!(stack[0].value, MValue::Int(-1));
+                assert_eq!(stack[0].value, MValue::new_int(-1));
                 assert_eq!(stack[0].instruction, Instruction::COMPARE);
-                assert_eq!(stack[1].value, MValue::Nat(6));
+                assert_eq!(stack[1].value, MValue::new_nat(6));
                 assert_eq!(stack[1].instruction, Instruction::INIT);
             }
         }
@@ -803,7 +696,7 @@ mod tests {
         let initial_stack: Stack = vec![
             StackElement::new(MValue::Bool(false), Instruction::INIT),
             StackElement::new(MValue::Bool(false), Instruction::INIT),
-            StackElement::new(MValue::Nat(6), Instruction::INIT),
+            StackElement::new(MValue::new_nat(6), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
 
@@ -813,9 +706,9 @@ mod tests {
             Err(_) => assert!(false),
             Ok((stack, _)) => {
                 assert!(stack.len() == 2);
-                assert_eq!(stack[0].value, MValue::Int(0));
+                assert_eq!(stack[0].value, MValue::new_int(0));
                 assert_eq!(stack[0].instruction, Instruction::COMPARE);
-                assert_eq!(stack[1].value, MValue::Nat(6));
+                assert_eq!(stack[1].value, MValue::new_nat(6));
                 assert_eq!(stack[1].instruction, Instruction::INIT);
             }
         }
@@ -824,7 +717,7 @@ mod tests {
         let initial_stack: Stack = vec![
             StackElement::new(MValue::Bool(true), Instruction::INIT),
             StackElement::new(MValue::Bool(false), Instruction::INIT),
-            StackElement::new(MValue::Nat(6), Instruction::INIT),
+            StackElement::new(MValue::new_nat(6), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
 
@@ -834,9 +727,9 @@ mod tests {
             Err(_) => assert!(false),
             Ok((stack, _)) => {
                 assert!(stack.len() == 2);
-                assert_eq!(stack[0].value, MValue::Int(1));
+                assert_eq!(stack[0].value, MValue::new_int(1));
                 assert_eq!(stack[0].instruction, Instruction::COMPARE);
-                assert_eq!(stack[1].value, MValue::Nat(6));
+                assert_eq!(stack[1].value, MValue::new_nat(6));
                 assert_eq!(stack[1].instruction, Instruction::INIT);
             }
         }
@@ -849,7 +742,7 @@ mod tests {
         let initial_stack: Stack = vec![
             StackElement::new(MValue::Unit, Instruction::INIT),
             StackElement::new(MValue::Unit, Instruction::INIT),
-            StackElement::new(MValue::Nat(6), Instruction::INIT),
+            StackElement::new(MValue::new_nat(6), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
         let options = RunOptions {
@@ -863,9 +756,9 @@ mod tests {
             Err(_) => assert!(false),
             Ok((stack, _)) => {
                 assert!(stack.len() == 2);
-                assert_eq!(stack[0].value, MValue::Int(0));
+                assert_eq!(stack[0].value, MValue::new_int(0));
                 assert_eq!(stack[0].instruction, Instruction::COMPARE);
-                assert_eq!(stack[1].value, MValue::Nat(6));
+                assert_eq!(stack[1].value, MValue::new_nat(6));
                 assert_eq!(stack[1].instruction, Instruction::INIT);
             }
         }
@@ -877,8 +770,8 @@ mod tests {
         // should output -1
         let initial_stack: Stack = vec![
             StackElement::new(MValue::Option(OptionValue { m_type: MType::Int, value: Box::new(None) }), Instruction::INIT),
-            StackElement::new(MValue::Option(OptionValue { m_type: MType::Int, value: Box::new(Some(MValue::Int(9))) }), Instruction::INIT),
-            StackElement::new(MValue::Nat(6), Instruction::INIT),
+            StackElement::new(MValue::Option(OptionValue { m_type: MType::Int, value: Box::new(Some(MValue::new_int(9))) }), Instruction::INIT),
+            StackElement::new(MValue::new_nat(6), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
         let options = RunOptions {
@@ -892,18 +785,18 @@ mod tests {
             Err(_) => assert!(false),
             Ok((stack, _)) => {
                 assert!(stack.len() == 2);
-                assert_eq!(stack[0].value, MValue::Int(-1));
+                assert_eq!(stack[0].value, MValue::new_int(-1));
                 assert_eq!(stack[0].instruction, Instruction::COMPARE);
-                assert_eq!(stack[1].value, MValue::Nat(6));
+                assert_eq!(stack[1].value, MValue::new_nat(6));
                 assert_eq!(stack[1].instruction, Instruction::INIT);
             }
         }
 
         // should output 1
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Option(OptionValue { m_type: MType::Int, value: Box::new(Some(MValue::Int(9))) }), Instruction::INIT),
+            StackElement::new(MValue::Option(OptionValue { m_type: MType::Int, value: Box::new(Some(MValue::new_int(9))) }), Instruction::INIT),
             StackElement::new(MValue::Option(OptionValue { m_type: MType::Int, value: Box::new(None) }), Instruction::INIT),
-            StackElement::new(MValue::Nat(6), Instruction::INIT),
+            StackElement::new(MValue::new_nat(6), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
         let options = RunOptions {
@@ -917,9 +810,9 @@ mod tests {
             Err(_) => assert!(false),
             Ok((stack, _)) => {
                 assert!(stack.len() == 2);
-                assert_eq!(stack[0].value, MValue::Int(1));
+                assert_eq!(stack[0].value, MValue::new_int(1));
                 assert_eq!(stack[0].instruction, Instruction::COMPARE);
-                assert_eq!(stack[1].value, MValue::Nat(6));
+                assert_eq!(stack[1].value, MValue::new_nat(6));
                 assert_eq!(stack[1].instruction, Instruction::INIT);
             }
         }
@@ -928,7 +821,7 @@ mod tests {
         let initial_stack: Stack = vec![
             StackElement::new(MValue::Option(OptionValue { m_type: MType::Int, value: Box::new(None) }), Instruction::INIT),
             StackElement::new(MValue::Option(OptionValue { m_type: MType::Int, value: Box::new(None) }), Instruction::INIT),
-            StackElement::new(MValue::Nat(6), Instruction::INIT),
+            StackElement::new(MValue::new_nat(6), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
         let options = RunOptions {
@@ -942,18 +835,18 @@ mod tests {
             Err(_) => assert!(false),
             Ok((stack, _)) => {
                 assert!(stack.len() == 2);
-                assert_eq!(stack[0].value, MValue::Int(0));
+                assert_eq!(stack[0].value, MValue::new_int(0));
                 assert_eq!(stack[0].instruction, Instruction::COMPARE);
-                assert_eq!(stack[1].value, MValue::Nat(6));
+                assert_eq!(stack[1].value, MValue::new_nat(6));
                 assert_eq!(stack[1].instruction, Instruction::INIT);
             }
         }
 
         // should output 0
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Option(OptionValue { m_type: MType::Int, value: Box::new(Some(MValue::Int(9))) }), Instruction::INIT),
-            StackElement::new(MValue::Option(OptionValue { m_type: MType::Int, value: Box::new(Some(MValue::Int(9))) }), Instruction::INIT),
-            StackElement::new(MValue::Nat(6), Instruction::INIT),
+            StackElement::new(MValue::Option(OptionValue { m_type: MType::Int, value: Box::new(Some(MValue::new_int(9))) }), Instruction::INIT),
+            StackElement::new(MValue::Option(OptionValue { m_type: MType::Int, value: Box::new(Some(MValue::new_int(9))) }), Instruction::INIT),
+            StackElement::new(MValue::new_nat(6), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
         let options = RunOptions {
@@ -967,18 +860,18 @@ mod tests {
             Err(_) => assert!(false),
             Ok((stack, _)) => {
                 assert!(stack.len() == 2);
-                assert_eq!(stack[0].value, MValue::Int(0));
+                assert_eq!(stack[0].value, MValue::new_int(0));
                 assert_eq!(stack[0].instruction, Instruction::COMPARE);
-                assert_eq!(stack[1].value, MValue::Nat(6));
+                assert_eq!(stack[1].value, MValue::new_nat(6));
                 assert_eq!(stack[1].instruction, Instruction::INIT);
             }
         }
 
         // should output 1
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Option(OptionValue { m_type: MType::Int, value: Box::new(Some(MValue::Int(19))) }), Instruction::INIT),
-            StackElement::new(MValue::Option(OptionValue { m_type: MType::Int, value: Box::new(Some(MValue::Int(9))) }), Instruction::INIT),
-            StackElement::new(MValue::Nat(6), Instruction::INIT),
+            StackElement::new(MValue::Option(OptionValue { m_type: MType::Int, value: Box::new(Some(MValue::new_int(19))) }), Instruction::INIT),
+            StackElement::new(MValue::Option(OptionValue { m_type: MType::Int, value: Box::new(Some(MValue::new_int(9))) }), Instruction::INIT),
+            StackElement::new(MValue::new_nat(6), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
         let options = RunOptions {
@@ -992,9 +885,9 @@ mod tests {
             Err(_) => assert!(false),
             Ok((stack, _)) => {
                 assert!(stack.len() == 2);
-                assert_eq!(stack[0].value, MValue::Int(1));
+                assert_eq!(stack[0].value, MValue::new_int(1));
                 assert_eq!(stack[0].instruction, Instruction::COMPARE);
-                assert_eq!(stack[1].value, MValue::Nat(6));
+                assert_eq!(stack[1].value, MValue::new_nat(6));
                 assert_eq!(stack[1].instruction, Instruction::INIT);
             }
         }
@@ -1005,9 +898,9 @@ mod tests {
     fn compare_pair_pair() {
         // should output 0
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Pair(PairValue::new(MValue::Int(9), MValue::String(String::from("tezos")))), Instruction::INIT),
-            StackElement::new(MValue::Pair(PairValue::new(MValue::Int(9), MValue::String(String::from("tezos")))), Instruction::INIT),
-            StackElement::new(MValue::Nat(6), Instruction::INIT),
+            StackElement::new(MValue::Pair(PairValue::new(MValue::new_int(9), MValue::String(String::from("tezos")))), Instruction::INIT),
+            StackElement::new(MValue::Pair(PairValue::new(MValue::new_int(9), MValue::String(String::from("tezos")))), Instruction::INIT),
+            StackElement::new(MValue::new_nat(6), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
         let options = RunOptions {
@@ -1021,18 +914,18 @@ mod tests {
             Err(_) => assert!(false),
             Ok((stack, _)) => {
                 assert!(stack.len() == 2);
-                assert_eq!(stack[0].value, MValue::Int(0));
+                assert_eq!(stack[0].value, MValue::new_int(0));
                 assert_eq!(stack[0].instruction, Instruction::COMPARE);
-                assert_eq!(stack[1].value, MValue::Nat(6));
+                assert_eq!(stack[1].value, MValue::new_nat(6));
                 assert_eq!(stack[1].instruction, Instruction::INIT);
             }
         }
 
         // should output -1
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Pair(PairValue::new(MValue::Int(8), MValue::String(String::from("tezos")))), Instruction::INIT),
-            StackElement::new(MValue::Pair(PairValue::new(MValue::Int(9), MValue::String(String::from("tezos")))), Instruction::INIT),
-            StackElement::new(MValue::Nat(6), Instruction::INIT),
+            StackElement::new(MValue::Pair(PairValue::new(MValue::new_int(8), MValue::String(String::from("tezos")))), Instruction::INIT),
+            StackElement::new(MValue::Pair(PairValue::new(MValue::new_int(9), MValue::String(String::from("tezos")))), Instruction::INIT),
+            StackElement::new(MValue::new_nat(6), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
 
@@ -1042,18 +935,18 @@ mod tests {
             Err(_) => assert!(false),
             Ok((stack, _)) => {
                 assert!(stack.len() == 2);
-                assert_eq!(stack[0].value, MValue::Int(-1));
+                assert_eq!(stack[0].value, MValue::new_int(-1));
                 assert_eq!(stack[0].instruction, Instruction::COMPARE);
-                assert_eq!(stack[1].value, MValue::Nat(6));
+                assert_eq!(stack[1].value, MValue::new_nat(6));
                 assert_eq!(stack[1].instruction, Instruction::INIT);
             }
         }
 
         // should output 1
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Pair(PairValue::new(MValue::Int(18), MValue::String(String::from("tezos")))), Instruction::INIT),
-            StackElement::new(MValue::Pair(PairValue::new(MValue::Int(9), MValue::String(String::from("tezos")))), Instruction::INIT),
-            StackElement::new(MValue::Nat(6), Instruction::INIT),
+            StackElement::new(MValue::Pair(PairValue::new(MValue::new_int(18), MValue::String(String::from("tezos")))), Instruction::INIT),
+            StackElement::new(MValue::Pair(PairValue::new(MValue::new_int(9), MValue::String(String::from("tezos")))), Instruction::INIT),
+            StackElement::new(MValue::new_nat(6), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
 
@@ -1063,18 +956,18 @@ mod tests {
             Err(_) => assert!(false),
             Ok((stack, _)) => {
                 assert!(stack.len() == 2);
-                assert_eq!(stack[0].value, MValue::Int(1));
+                assert_eq!(stack[0].value, MValue::new_int(1));
                 assert_eq!(stack[0].instruction, Instruction::COMPARE);
-                assert_eq!(stack[1].value, MValue::Nat(6));
+                assert_eq!(stack[1].value, MValue::new_nat(6));
                 assert_eq!(stack[1].instruction, Instruction::INIT);
             }
         }
 
         // should output 1
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Pair(PairValue::new(MValue::Int(9), MValue::String(String::from("tezos")))), Instruction::INIT),
-            StackElement::new(MValue::Pair(PairValue::new(MValue::Int(9), MValue::String(String::from("taquito")))), Instruction::INIT),
-            StackElement::new(MValue::Nat(6), Instruction::INIT),
+            StackElement::new(MValue::Pair(PairValue::new(MValue::new_int(9), MValue::String(String::from("tezos")))), Instruction::INIT),
+            StackElement::new(MValue::Pair(PairValue::new(MValue::new_int(9), MValue::String(String::from("taquito")))), Instruction::INIT),
+            StackElement::new(MValue::new_nat(6), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
 
@@ -1084,18 +977,18 @@ mod tests {
             Err(_) => assert!(false),
             Ok((stack, _)) => {
                 assert!(stack.len() == 2);
-                assert_eq!(stack[0].value, MValue::Int(1));
+                assert_eq!(stack[0].value, MValue::new_int(1));
                 assert_eq!(stack[0].instruction, Instruction::COMPARE);
-                assert_eq!(stack[1].value, MValue::Nat(6));
+                assert_eq!(stack[1].value, MValue::new_nat(6));
                 assert_eq!(stack[1].instruction, Instruction::INIT);
             }
         }
 
         // should output -1
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Pair(PairValue::new(MValue::Int(9), MValue::String(String::from("taquito")))), Instruction::INIT),
-            StackElement::new(MValue::Pair(PairValue::new(MValue::Int(9), MValue::String(String::from("tezos")))), Instruction::INIT),
-            StackElement::new(MValue::Nat(6), Instruction::INIT),
+            StackElement::new(MValue::Pair(PairValue::new(MValue::new_int(9), MValue::String(String::from("taquito")))), Instruction::INIT),
+            StackElement::new(MValue::Pair(PairValue::new(MValue::new_int(9), MValue::String(String::from("tezos")))), Instruction::INIT),
+            StackElement::new(MValue::new_nat(6), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
 
@@ -1105,9 +998,9 @@ mod tests {
             Err(_) => assert!(false),
             Ok((stack, _)) => {
                 assert!(stack.len() == 2);
-                assert_eq!(stack[0].value, MValue::Int(-1));
+                assert_eq!(stack[0].value, MValue::new_int(-1));
                 assert_eq!(stack[0].instruction, Instruction::COMPARE);
-                assert_eq!(stack[1].value, MValue::Nat(6));
+                assert_eq!(stack[1].value, MValue::new_nat(6));
                 assert_eq!(stack[1].instruction, Instruction::INIT);
             }
         }
@@ -1118,9 +1011,9 @@ mod tests {
     fn compare_union_union() {
         // should output 0
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Or(OrValue::new(Or::Left(MValue::Int(3)), (MType::Int, MType::Nat))), Instruction::INIT),
-            StackElement::new(MValue::Or(OrValue::new(Or::Left(MValue::Int(3)), (MType::Int, MType::Nat))), Instruction::INIT),
-            StackElement::new(MValue::Nat(6), Instruction::INIT),
+            StackElement::new(MValue::Or(OrValue::new(Or::Left(MValue::new_int(3)), (MType::Int, MType::Nat))), Instruction::INIT),
+            StackElement::new(MValue::Or(OrValue::new(Or::Left(MValue::new_int(3)), (MType::Int, MType::Nat))), Instruction::INIT),
+            StackElement::new(MValue::new_nat(6), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
         let options = RunOptions {
@@ -1134,18 +1027,18 @@ mod tests {
             Err(_) => assert!(false),
             Ok((stack, _)) => {
                 assert!(stack.len() == 2);
-                assert_eq!(stack[0].value, MValue::Int(0));
+                assert_eq!(stack[0].value, MValue::new_int(0));
                 assert_eq!(stack[0].instruction, Instruction::COMPARE);
-                assert_eq!(stack[1].value, MValue::Nat(6));
+                assert_eq!(stack[1].value, MValue::new_nat(6));
                 assert_eq!(stack[1].instruction, Instruction::INIT);
             }
         }
 
         // should output -1
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Or(OrValue::new(Or::Left(MValue::Int(3)), (MType::Int, MType::Nat))), Instruction::INIT),
-            StackElement::new(MValue::Or(OrValue::new(Or::Right(MValue::Nat(3)), (MType::Int, MType::Nat))), Instruction::INIT),
-            StackElement::new(MValue::Nat(6), Instruction::INIT),
+            StackElement::new(MValue::Or(OrValue::new(Or::Left(MValue::new_int(3)), (MType::Int, MType::Nat))), Instruction::INIT),
+            StackElement::new(MValue::Or(OrValue::new(Or::Right(MValue::new_nat(3)), (MType::Int, MType::Nat))), Instruction::INIT),
+            StackElement::new(MValue::new_nat(6), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
         let options = RunOptions {
@@ -1159,18 +1052,18 @@ mod tests {
             Err(_) => assert!(false),
             Ok((stack, _)) => {
                 assert!(stack.len() == 2);
-                assert_eq!(stack[0].value, MValue::Int(-1));
+                assert_eq!(stack[0].value, MValue::new_int(-1));
                 assert_eq!(stack[0].instruction, Instruction::COMPARE);
-                assert_eq!(stack[1].value, MValue::Nat(6));
+                assert_eq!(stack[1].value, MValue::new_nat(6));
                 assert_eq!(stack[1].instruction, Instruction::INIT);
             }
         }
 
         // should output 1
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Or(OrValue::new(Or::Right(MValue::Nat(3)), (MType::Int, MType::Nat))), Instruction::INIT),
-            StackElement::new(MValue::Or(OrValue::new(Or::Left(MValue::Int(3)), (MType::Int, MType::Nat))), Instruction::INIT),
-            StackElement::new(MValue::Nat(6), Instruction::INIT),
+            StackElement::new(MValue::Or(OrValue::new(Or::Right(MValue::new_nat(3)), (MType::Int, MType::Nat))), Instruction::INIT),
+            StackElement::new(MValue::Or(OrValue::new(Or::Left(MValue::new_int(3)), (MType::Int, MType::Nat))), Instruction::INIT),
+            StackElement::new(MValue::new_nat(6), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
         let options = RunOptions {
@@ -1184,18 +1077,18 @@ mod tests {
             Err(_) => assert!(false),
             Ok((stack, _)) => {
                 assert!(stack.len() == 2);
-                assert_eq!(stack[0].value, MValue::Int(1));
+                assert_eq!(stack[0].value, MValue::new_int(1));
                 assert_eq!(stack[0].instruction, Instruction::COMPARE);
-                assert_eq!(stack[1].value, MValue::Nat(6));
+                assert_eq!(stack[1].value, MValue::new_nat(6));
                 assert_eq!(stack[1].instruction, Instruction::INIT);
             }
         }
 
         // should output 1
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Or(OrValue::new(Or::Left(MValue::Int(33)), (MType::Int, MType::Nat))), Instruction::INIT),
-            StackElement::new(MValue::Or(OrValue::new(Or::Left(MValue::Int(3)), (MType::Int, MType::Nat))), Instruction::INIT),
-            StackElement::new(MValue::Nat(6), Instruction::INIT),
+            StackElement::new(MValue::Or(OrValue::new(Or::Left(MValue::new_int(33)), (MType::Int, MType::Nat))), Instruction::INIT),
+            StackElement::new(MValue::Or(OrValue::new(Or::Left(MValue::new_int(3)), (MType::Int, MType::Nat))), Instruction::INIT),
+            StackElement::new(MValue::new_nat(6), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
         let options = RunOptions {
@@ -1209,18 +1102,18 @@ mod tests {
             Err(_) => assert!(false),
             Ok((stack, _)) => {
                 assert!(stack.len() == 2);
-                assert_eq!(stack[0].value, MValue::Int(1));
+                assert_eq!(stack[0].value, MValue::new_int(1));
                 assert_eq!(stack[0].instruction, Instruction::COMPARE);
-                assert_eq!(stack[1].value, MValue::Nat(6));
+                assert_eq!(stack[1].value, MValue::new_nat(6));
                 assert_eq!(stack[1].instruction, Instruction::INIT);
             }
         }
 
         // should output -1
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Or(OrValue::new(Or::Left(MValue::Int(3)), (MType::Int, MType::Nat))), Instruction::INIT),
-            StackElement::new(MValue::Or(OrValue::new(Or::Left(MValue::Int(33)), (MType::Int, MType::Nat))), Instruction::INIT),
-            StackElement::new(MValue::Nat(6), Instruction::INIT),
+            StackElement::new(MValue::Or(OrValue::new(Or::Left(MValue::new_int(3)), (MType::Int, MType::Nat))), Instruction::INIT),
+            StackElement::new(MValue::Or(OrValue::new(Or::Left(MValue::new_int(33)), (MType::Int, MType::Nat))), Instruction::INIT),
+            StackElement::new(MValue::new_nat(6), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
         let options = RunOptions {
@@ -1234,9 +1127,9 @@ mod tests {
             Err(_) => assert!(false),
             Ok((stack, _)) => {
                 assert!(stack.len() == 2);
-                assert_eq!(stack[0].value, MValue::Int(-1));
+                assert_eq!(stack[0].value, MValue::new_int(-1));
                 assert_eq!(stack[0].instruction, Instruction::COMPARE);
-                assert_eq!(stack[1].value, MValue::Nat(6));
+                assert_eq!(stack[1].value, MValue::new_nat(6));
                 assert_eq!(stack[1].instruction, Instruction::INIT);
             }
         }
@@ -1250,7 +1143,7 @@ mod tests {
         let initial_stack: Stack = vec![
             StackElement::new(MValue::Unit, Instruction::INIT),
             StackElement::new(MValue::Bool(true), Instruction::INIT),
-            StackElement::new(MValue::Nat(6), Instruction::INIT),
+            StackElement::new(MValue::new_nat(6), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
         let options = RunOptions {
@@ -1261,7 +1154,7 @@ mod tests {
         assert!(initial_stack.len() == 3);
 
         match run(initial_stack, &options, stack_snapshots) {
-            Err(err) => assert_eq!(err, String::from("Unit and Bool(true) are not comparable")),
+            Err(err) => assert_eq!(err.to_string(), String::from("Unit and Bool(true) are not comparable")),
             Ok(_) => assert!(false),
         }
 
@@ -1269,7 +1162,7 @@ mod tests {
         let initial_stack: Stack = vec![
             StackElement::new(MValue::String(String::from("KT1X1LgNkQShpF9nRLYw3Dgdy4qp38MX617z")), Instruction::INIT),
             StackElement::new(MValue::Address(String::from("KT1X1LgNkQShpF9nRLYw3Dgdy4qp38MX617z")), Instruction::INIT),
-            StackElement::new(MValue::Nat(6), Instruction::INIT),
+            StackElement::new(MValue::new_nat(6), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
         let options = RunOptions {
@@ -1281,15 +1174,15 @@ mod tests {
 
         match run(initial_stack, &options, stack_snapshots) {
             Err(err) => 
-                assert_eq!(err, String::from("String(\"KT1X1LgNkQShpF9nRLYw3Dgdy4qp38MX617z\") and Address(\"KT1X1LgNkQShpF9nRLYw3Dgdy4qp38MX617z\") are not comparable")),
+                assert_eq!(err.to_string(), String::from("String(\"KT1X1LgNkQShpF9nRLYw3Dgdy4qp38MX617z\") and Address(\"KT1X1LgNkQShpF9nRLYw3Dgdy4qp38MX617z\") are not comparable")),
             Ok(_) => assert!(false),
         }
 
         // should generate an error
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Nat(6), Instruction::INIT),
-            StackElement::new(MValue::Int(6), Instruction::INIT),
-            StackElement::new(MValue::Nat(667), Instruction::INIT),
+            StackElement::new(MValue::new_nat(6), Instruction::INIT),
+            StackElement::new(MValue::new_int(6), Instruction::INIT),
+            StackElement::new(MValue::new_nat(667), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
         let options = RunOptions {
@@ -1301,7 +1194,7 @@ mod tests {
 
         match run(initial_stack, &options, stack_snapshots) {
             Err(err) => 
-                assert_eq!(err, String::from("Nat(6) and Int(6) are not comparable")),
+                assert_eq!(err.to_string(), String::from("Nat(6) and Int(6) are not comparable")),
             Ok(_) => assert!(false),
         }
     }
@@ -1314,7 +1207,7 @@ mod tests {
         let initial_stack: Stack = vec![
             StackElement::new(MValue::Operation(String::from("test")), Instruction::INIT),
             StackElement::new(MValue::Operation(String::from("test2")), Instruction::INIT),
-            StackElement::new(MValue::Nat(667), Instruction::INIT),
+            StackElement::new(MValue::new_nat(667), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
         let options = RunOptions {
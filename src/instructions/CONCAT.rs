@@ -1,4 +1,4 @@
-use crate::errors::{display_error, ErrorCode};
+use crate::errors::MichelsonError;
 use crate::instructions::{Instruction, RunOptions};
 use crate::m_types::{MType, MValue};
 use crate::stack::{Stack, StackElement, StackFuncs, StackSnapshots};
@@ -9,7 +9,7 @@ pub fn run(
     stack: Stack,
     options: &RunOptions,
     mut stack_snapshots: StackSnapshots,
-) -> Result<(Stack, StackSnapshots), String> {
+) -> Result<(Stack, StackSnapshots), MichelsonError> {
     // checking the stack will depend on the elements in it
     // but there must be at least one element
     stack.check_depth(options.pos + 1, Instruction::CONCAT)?;
@@ -24,28 +24,26 @@ pub fn run(
                 concat_string.push_str(second_string);
                 Ok((MValue::String(concat_string), 2))
             } else {
-                Err(format!(
+                Err(MichelsonError::Message(format!(
                     "Expected an element of type string at position {}, but got {}",
                     options.pos + 1,
                     stack[options.pos + 1].value.get_type().to_string()
-                ))
+                )))
             }
         }
         // stack with 2 bytes
-        // TODO: handle cases when the bytes start with 0x
         else if let MValue::Bytes(first_bytes) = &stack[options.pos].value {
             // the following value on the stack must be a string of bytes too
             if let MValue::Bytes(second_bytes) = &stack[options.pos + 1].value {
-                // concatenates the strings
-                let mut concat_bytes = first_bytes.to_owned();
-                concat_bytes.push_str(second_bytes);
-                Ok((MValue::Bytes(concat_bytes), 2))
+                // concatenates the bytes
+                let concat_bytes = [first_bytes.as_slice(), second_bytes.as_slice()].concat();
+                Ok((MValue::new_bytes(&concat_bytes), 2))
             } else {
-                Err(format!(
+                Err(MichelsonError::Message(format!(
                     "Expected an element of type bytes at position {}, but got {}",
                     options.pos + 1,
                     stack[options.pos + 1].value.get_type().to_string()
-                ))
+                )))
             }
         }
         // stack with a list
@@ -63,10 +61,10 @@ pub fn run(
                         .clone()
                         .into_iter()
                         .map(|val| match val {
-                            MValue::String(str) => str,
-                            _ => panic!("Found value of type {} in a list of strings at CONCAT", val.get_type().to_string())
+                            MValue::String(str) => Ok(str),
+                            _ => Err(MichelsonError::Message(format!("Found value of type {} in a list of strings at CONCAT", val.get_type().to_string())))
                         })
-                        .collect::<Vec<String>>()
+                        .collect::<Result<Vec<String>, MichelsonError>>()?
                         .join("")
                     ), 1))
                 }
@@ -76,32 +74,30 @@ pub fn run(
                     // may not be necessary as it is done again below
                     list.check_elements_type(Instruction::CONCAT)?;
                     // list concatenation
-                    Ok((MValue::Bytes(
-                        list.value
+                    Ok((MValue::new_bytes(
+                        &list.value
                         .clone()
                         .into_iter()
                         .map(|val| match val {
-                            MValue::Bytes(str) => str,
-                            _ => panic!("Found value of type {} in a list of bytes at CONCAT", val.get_type().to_string())
+                            MValue::Bytes(bytes) => Ok(bytes.0),
+                            _ => Err(MichelsonError::Message(format!("Found value of type {} in a list of bytes at CONCAT", val.get_type().to_string())))
                         })
-                        .collect::<Vec<String>>()
-                        .join("")
+                        .collect::<Result<Vec<Vec<u8>>, MichelsonError>>()?
+                        .concat()
                     ), 1))
                 },
                 _ => Err(
-                    display_error(
-                        ErrorCode::InvalidType(
+                    MichelsonError::InvalidType(
                             (vec![MType::String, MType::Bytes], list.m_type.clone(), Instruction::CONCAT)
                         )
-                    )
                 )
             }
         } else {
-            Err(format!(
+            Err(MichelsonError::Message(format!(
                 "Expected an element of type string, bytes or list at position {}, but got {}",
                 options.pos,
                 stack[options.pos].value.get_type().to_string()
-            ))
+            )))
         }?;
 
     // updates the stack
@@ -117,7 +113,10 @@ pub fn run(
             options.pos,
         )
     } else {
-        panic!("Unexpected number of elements to remove for CONCAT, expected 1 or 2, got {}", el_num)
+        return Err(MichelsonError::Message(format!(
+            "Unexpected number of elements to remove for CONCAT, expected 1 or 2, got {}",
+            el_num
+        )));
     };
     // updates the stack snapshots
     stack_snapshots.push(new_stack.clone());
@@ -226,11 +225,11 @@ mod tests {
     #[test]
     // concats a list of bytes
     fn concat_list_of_bytes() {
-        // should output "68656c6c6f20776f726c6421"
+        // should output the bytes of "hello world!"
         let initial_stack: Stack = vec![
             StackElement::new(
                 MValue::new_list(
-                    vec![MValue::new_bytes("68656c6c6f20"), MValue::new_bytes("776f726c64"), MValue::new_bytes("21")], 
+                    vec![MValue::new_bytes(b"hello "), MValue::new_bytes(b"world"), MValue::new_bytes(b"!")], 
                     MType::Bytes
                 ), Instruction::INIT
             ),
@@ -248,7 +247,7 @@ mod tests {
             Err(_) => assert!(false),
             Ok((stack, _)) => {
                 assert!(stack.len() == 2);
-                assert_eq!(stack[0].value, MValue::new_bytes("68656c6c6f20776f726c6421"));
+                assert_eq!(stack[0].value, MValue::new_bytes(b"hello world!"));
                 assert_eq!(stack[0].instruction, Instruction::CONCAT);
                 assert_eq!(stack[1].value, MValue::Mutez(6_000_000));
                 assert_eq!(stack[1].instruction, Instruction::INIT);
@@ -261,7 +260,7 @@ mod tests {
     fn concat_wrong_types() {
         // first element of wrong type
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Nat(6), Instruction::INIT),
+            StackElement::new(MValue::new_nat(6), Instruction::INIT),
             StackElement::new(MValue::new_string("world"), Instruction::INIT),
             StackElement::new(MValue::Mutez(6_000_000), Instruction::INIT),
         ];
@@ -275,7 +274,7 @@ mod tests {
 
         match run(initial_stack, &options, stack_snapshots) {
             Err(err) => assert_eq!(
-                err, 
+                err.to_string(), 
                 String::from("Expected an element of type string, bytes or list at position 0, but got nat")
             ),
             Ok(_) => assert!(false)
@@ -284,7 +283,7 @@ mod tests {
         // second element of wrong type
         let initial_stack: Stack = vec![
             StackElement::new(MValue::new_string("world"), Instruction::INIT),
-            StackElement::new(MValue::Nat(6), Instruction::INIT),
+            StackElement::new(MValue::new_nat(6), Instruction::INIT),
             StackElement::new(MValue::Mutez(6_000_000), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
@@ -297,7 +296,7 @@ mod tests {
 
         match run(initial_stack, &options, stack_snapshots) {
             Err(err) => assert_eq!(
-                err, 
+                err.to_string(), 
                 String::from("Expected an element of type string at position 1, but got nat")
             ),
             Ok(_) => assert!(false)
@@ -305,7 +304,7 @@ mod tests {
 
         // list of elements of wrong type
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::new_list(vec![MValue::Nat(4), MValue::Nat(5), MValue::Nat(6)], MType::Nat), Instruction::INIT),
+            StackElement::new(MValue::new_list(vec![MValue::new_nat(4), MValue::new_nat(5), MValue::new_nat(6)], MType::Nat), Instruction::INIT),
             StackElement::new(MValue::new_string("world"), Instruction::INIT),
             StackElement::new(MValue::Mutez(6_000_000), Instruction::INIT),
         ];
@@ -319,7 +318,7 @@ mod tests {
 
         match run(initial_stack, &options, stack_snapshots) {
             Err(err) => assert_eq!(
-                err, 
+                err.to_string(), 
                 String::from("Invalid type for `CONCAT` expected string | bytes, but got nat")
             ),
             Ok(_) => assert!(false)
@@ -330,7 +329,7 @@ mod tests {
         let initial_stack: Stack = vec![
             StackElement::new(MValue::List(CollectionValue {
                 m_type: MType::String,
-                value: Box::new(vec![MValue::new_string("hello "), MValue::new_string("world"), MValue::Nat(9)]),
+                value: Box::new(vec![MValue::new_string("hello "), MValue::new_string("world"), MValue::new_nat(9)]),
             }), Instruction::INIT),
             StackElement::new(MValue::new_string("world"), Instruction::INIT),
             StackElement::new(MValue::Mutez(6_000_000), Instruction::INIT),
@@ -345,7 +344,7 @@ mod tests {
 
         match run(initial_stack, &options, stack_snapshots) {
             Err(err) => assert_eq!(
-                err, 
+                err.to_string(), 
                 String::from("Expected values of type string in list for `CONCAT`, but got a value of type nat")
             ),
             Ok(_) => assert!(false)
@@ -1,4 +1,4 @@
-use crate::errors::{display_error, ErrorCode};
+use crate::errors::MichelsonError;
 use crate::instructions::{Instruction, RunOptions};
 use crate::m_types::{MType, MValue};
 use crate::stack::{Stack, StackElement, StackFuncs, StackSnapshots};
@@ -9,7 +9,7 @@ pub fn run(
     stack: Stack,
     options: &RunOptions,
     mut stack_snapshots: StackSnapshots,
-) -> Result<(Stack, StackSnapshots), String> {
+) -> Result<(Stack, StackSnapshots), MichelsonError> {
     // checks the stack
     stack.check_depth(options.pos + 2, Instruction::CONS)?;
     // checks if second element is a list
@@ -36,19 +36,19 @@ pub fn run(
                 Ok((new_stack, stack_snapshots))
             } else {
                 // element to prepend is of the wrong type
-                Err(String::from(
+                Err(MichelsonError::Message(String::from(
                     format!("Element to prepend to the list with CONS is of type {}, while the list elements are of type {}", 
                     stack_el_type.to_string(), 
                     list_el_type.to_string())
-                ))
+                )))
             }
         },
-        (first_el, second_el) => Err(display_error(ErrorCode::InvalidStack((
+        (first_el, second_el) => Err(MichelsonError::InvalidStack((
             options.pos + 1,
             MType::List(Box::new(first_el.get_type())),
             second_el.get_type(),
             Instruction::CONS,
-        )))),
+        ))),
     }
 }
 
@@ -60,6 +60,7 @@ pub fn run(
 mod tests {
     use super::*;
     use crate::instructions::RunOptionsContext;
+    use crate::m_types::nat;
 
     // PASSING
     #[test]
@@ -83,7 +84,7 @@ mod tests {
                 assert!(stack.len() == 2);
                 match &stack[0].value {
                     MValue::List(list) => {
-                        assert_eq!(list.size(), 2);
+                        assert_eq!(list.size(), nat::from(2u32));
                         assert_eq!(list.value[0], MValue::new_string("hello"));
                         assert_eq!(list.value[1], MValue::new_string("world"));
                     },
@@ -112,7 +113,7 @@ mod tests {
         assert!(initial_stack.len() == 1);
 
         match run(initial_stack, &options, stack_snapshots) {
-            Err(err) => assert_eq!(err, "Unexpected stack length, expected a length of 2 for instruction CONS, got 1"),
+            Err(err) => assert_eq!(err.to_string(), "Unexpected stack length, expected a length of 2 for instruction CONS, got 1"),
             Ok(_) => assert!(false)
         }
 
@@ -130,7 +131,7 @@ mod tests {
         assert!(initial_stack.len() == 2);
 
         match run(initial_stack, &options, stack_snapshots) {
-            Err(err) => assert_eq!(err, "Expected element at position 1 to be of type list, but got mutez for instruction CONS"),
+            Err(err) => assert_eq!(err.to_string(), "Expected element at position 1 to be of type list string, but got mutez for instruction CONS"),
             Ok(_) => assert!(false)
         }
     }
@@ -138,7 +139,7 @@ mod tests {
     // value to cons doesn't match list type
     fn cons_wrong_element_type() {
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Nat(6), Instruction::INIT),
+            StackElement::new(MValue::new_nat(6), Instruction::INIT),
             StackElement::new(MValue::new_list(vec![MValue::new_string("world")], MType::String), Instruction::INIT),
             StackElement::new(MValue::Mutez(6_000_000), Instruction::INIT),
         ];
@@ -151,7 +152,7 @@ mod tests {
         assert!(initial_stack.len() == 3);
 
         match run(initial_stack, &options, stack_snapshots) {
-            Err(err) => assert_eq!(err, "Element to prepend to the list with CONS is of type nat, while the list elements are of type string"),
+            Err(err) => assert_eq!(err.to_string(), "Element to prepend to the list with CONS is of type nat, while the list elements are of type string"),
             Ok(_) => assert!(false)
         }
     }
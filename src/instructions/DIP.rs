@@ -0,0 +1,242 @@
+use crate::compiler::Block;
+use crate::errors::MichelsonError;
+use crate::instructions::{Instruction, RunOptions};
+use crate::parser;
+use crate::stack::{Stack, StackFuncs, StackSnapshots};
+
+// https://tezos.gitlab.io/michelson-reference/#instr-DIP
+
+/// runs the code on the stack below the `count` elements on top of the stack
+/// the protected elements are put back on top of the stack after the code
+pub fn run(
+    stack: Stack,
+    count: usize,
+    body: &Block,
+    options: &RunOptions,
+    mut stack_snapshots: StackSnapshots,
+) -> Result<parser::RunResult, MichelsonError> {
+    // checks that the stack is deep enough for the protected elements
+    stack.check_depth(options.pos + count, Instruction::DIP)?;
+    // updates the stack snapshots with the whole stack, the protected elements included
+    stack_snapshots.push(stack.clone());
+    // splits the stack under the protected elements
+    let mut protected = stack;
+    let stack_below = protected.split_off(options.pos + count);
+    // runs the code on top of the split stack
+    let body_options = RunOptions {
+        pos: 0,
+        ..options.clone()
+    };
+    let mut result = parser::run_block(body, stack_below, stack_snapshots, &body_options)?;
+    if let Some(failed) = result.failed.as_mut() {
+        // the stack at failure includes the protected elements
+        let stack_below = std::mem::take(&mut failed.stack_at_failure);
+        failed.stack_at_failure = protected.into_iter().chain(stack_below).collect();
+        return Ok(result);
+    }
+    // puts the protected elements back on top of the stack
+    protected.extend(result.stack);
+
+    Ok(parser::RunResult {
+        stack: protected,
+        stack_snapshots: result.stack_snapshots,
+        failed: None,
+    })
+}
+
+/*
+    TESTS
+*/
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler;
+    use crate::instructions::RunOptionsContext;
+    use crate::m_types::MValue;
+    use crate::stack::StackElement;
+
+    // PASSING
+    #[test]
+    fn dip_success_one() {
+        let initial_stack: Stack = vec![
+            StackElement::new(MValue::new_int(5), Instruction::INIT),
+            StackElement::new(MValue::new_nat(6), Instruction::INIT),
+            StackElement::new(MValue::new_nat(7), Instruction::INIT),
+        ];
+        let stack_snapshots = vec![];
+        let options = RunOptions {
+            context: RunOptionsContext::mock(),
+            pos: 0,
+        };
+
+        assert!(initial_stack.len() == 3);
+
+        match run(initial_stack, 1, &compiler::compile_test_code("{ ADD }"), &options, stack_snapshots) {
+            Ok(result) => {
+                assert!(result.stack.len() == 2);
+                assert_eq!(result.stack[0].value, MValue::new_int(5));
+                assert_eq!(result.stack[0].instruction, Instruction::INIT);
+                assert_eq!(result.stack[1].value, MValue::new_nat(13));
+                assert_eq!(result.stack[1].instruction, Instruction::ADD);
+            }
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    #[test]
+    fn dip_success_two() {
+        let initial_stack: Stack = vec![
+            StackElement::new(MValue::new_int(5), Instruction::INIT),
+            StackElement::new(MValue::new_nat(6), Instruction::INIT),
+            StackElement::new(MValue::String(String::from("test")), Instruction::INIT),
+        ];
+        let stack_snapshots = vec![];
+        let options = RunOptions {
+            context: RunOptionsContext::mock(),
+            pos: 0,
+        };
+
+        assert!(initial_stack.len() == 3);
+
+        match run(
+            initial_stack,
+            2,
+            &compiler::compile_test_code("{ DROP ; PUSH nat 1 }"),
+            &options,
+            stack_snapshots,
+        ) {
+            Ok(result) => {
+                assert!(result.stack.len() == 3);
+                assert_eq!(result.stack[0].value, MValue::new_int(5));
+                assert_eq!(result.stack[1].value, MValue::new_nat(6));
+                assert_eq!(result.stack[2].value, MValue::new_nat(1));
+                assert_eq!(result.stack[2].instruction, Instruction::PUSH);
+            }
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    #[test]
+    fn dip_failwith() {
+        let initial_stack: Stack = vec![
+            StackElement::new(MValue::new_int(5), Instruction::INIT),
+            StackElement::new(MValue::new_nat(6), Instruction::INIT),
+        ];
+        let stack_snapshots = vec![];
+        let options = RunOptions {
+            context: RunOptionsContext::mock(),
+            pos: 0,
+        };
+
+        match run(initial_stack, 1, &compiler::compile_test_code("{ FAILWITH }"), &options, stack_snapshots) {
+            Ok(result) => {
+                assert!(result.has_failed());
+                assert_eq!(result.failed.unwrap().value, MValue::new_nat(6));
+            }
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    #[test]
+    fn dip_failwith_reports_whole_stack() {
+        let initial_stack: Stack = vec![
+            StackElement::new(MValue::new_int(5), Instruction::INIT),
+            StackElement::new(MValue::new_nat(6), Instruction::INIT),
+            StackElement::new(MValue::new_nat(7), Instruction::INIT),
+        ];
+        let stack_snapshots = vec![];
+        let options = RunOptions {
+            context: RunOptionsContext::mock(),
+            pos: 0,
+        };
+
+        match run(initial_stack, 2, &compiler::compile_test_code("{ UNIT ; FAILWITH }"), &options, stack_snapshots) {
+            Ok(result) => {
+                let failed = result.failed.unwrap();
+                assert_eq!(failed.value, MValue::Unit);
+                let stack_at_failure: Vec<MValue> =
+                    failed.stack_at_failure.into_iter().map(|el| el.value).collect();
+                assert_eq!(
+                    stack_at_failure,
+                    vec![MValue::new_int(5), MValue::new_nat(6), MValue::Unit, MValue::new_nat(7)]
+                );
+            }
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    #[test]
+    fn dip_snapshots_whole_stack() {
+        let initial_stack: Stack = vec![
+            StackElement::new(MValue::new_int(5), Instruction::INIT),
+            StackElement::new(MValue::new_nat(6), Instruction::INIT),
+            StackElement::new(MValue::new_nat(7), Instruction::INIT),
+        ];
+        let stack_snapshots = vec![];
+        let options = RunOptions {
+            context: RunOptionsContext::mock(),
+            pos: 0,
+        };
+
+        match run(initial_stack, 1, &compiler::compile_test_code("{ ADD }"), &options, stack_snapshots) {
+            Ok(result) => {
+                assert_eq!(result.stack_snapshots.len(), 2);
+                assert_eq!(result.stack_snapshots[0].len(), 3);
+                assert_eq!(result.stack_snapshots[0][0].value, MValue::new_int(5));
+            }
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    #[test]
+    fn dip_with_pos() {
+        let initial_stack: Stack = vec![
+            StackElement::new(MValue::String(String::from("test")), Instruction::INIT),
+            StackElement::new(MValue::new_int(5), Instruction::INIT),
+            StackElement::new(MValue::new_nat(6), Instruction::INIT),
+            StackElement::new(MValue::new_nat(7), Instruction::INIT),
+        ];
+        let stack_snapshots = vec![];
+        let options = RunOptions {
+            context: RunOptionsContext::mock(),
+            pos: 1,
+        };
+
+        match run(initial_stack, 1, &compiler::compile_test_code("{ ADD }"), &options, stack_snapshots) {
+            Ok(result) => {
+                assert!(result.stack.len() == 3);
+                assert_eq!(result.stack[0].value, MValue::String(String::from("test")));
+                assert_eq!(result.stack[1].value, MValue::new_int(5));
+                assert_eq!(result.stack[2].value, MValue::new_nat(13));
+                assert_eq!(result.stack[2].instruction, Instruction::ADD);
+            }
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    // FAILING
+    // stack not deep enough for the protected elements
+    #[test]
+    #[should_panic(
+        expected = "Unexpected stack length, expected a length of 3 for instruction DIP, got 2"
+    )]
+    fn dip_wrong_arg_value() {
+        let initial_stack: Stack = vec![
+            StackElement::new(MValue::new_int(5), Instruction::INIT),
+            StackElement::new(MValue::new_nat(6), Instruction::INIT),
+        ];
+        let stack_snapshots = vec![];
+        let options = RunOptions {
+            context: RunOptionsContext::mock(),
+            pos: 0,
+        };
+
+        assert!(initial_stack.len() == 2);
+
+        match run(initial_stack, 3, &compiler::compile_test_code("{ DROP }"), &options, stack_snapshots) {
+            Ok(_) => assert!(false),
+            Err(err) => panic!("{}", err),
+        }
+    }
+}
@@ -1,41 +1,33 @@
+use crate::errors::MichelsonError;
 use crate::instructions::{Instruction, RunOptions};
 use crate::stack::{Stack, StackFuncs, StackSnapshots};
-use serde_json::Value;
 
 // https://tezos.gitlab.io/michelson-reference/#instr-DUG
 
 pub fn run(
     stack: Stack,
-    args: Option<&Vec<Value>>,
+    el_to_dug_pos: usize,
     options: &RunOptions,
     mut stack_snapshots: StackSnapshots,
-) -> Result<(Stack, StackSnapshots), String> {
+) -> Result<(Stack, StackSnapshots), MichelsonError> {
     // checks the stack
-    match stack.check_depth(options.pos + 1, Instruction::DUG) {
-        Ok(_) => (),
-        Err(err) => panic!("{}", err),
-    };
+    stack.check_depth(options.pos + 1, Instruction::DUG)?;
     // calculates the position of the element to move
-    match Instruction::DUG.check_num_arg(&args) {
-        Err(err) => Err(err), // forwards the error
-        Ok(el_to_dug_pos) => {
-            let el_pos = options.pos + el_to_dug_pos;
-            // checks that the stack is deep enough for the DUG parameter
-            match stack.check_depth(el_pos, Instruction::DUG) {
-                Err(err) => Err(err),
-                Ok(_) => {
-                    // removes the element at el_pos - 1
-                    let (el_to_dug, new_stack) = stack.remove_at(options.pos);
-                    // changes the element instruction
-                    let el_to_dug = el_to_dug.change_instruction(Instruction::DUG);
-                    // insert the element at el_pos - 1
-                    let new_stack = new_stack.insert_at(vec![el_to_dug], el_pos);
-                    // updates the stack snapshots
-                    stack_snapshots.push(new_stack.clone());
-                    // returns the stack and stack snapshots
-                    Ok((new_stack, stack_snapshots))
-                }
-            }
+    let el_pos = options.pos + el_to_dug_pos;
+    // checks that the stack is deep enough for the DUG parameter
+    match stack.check_depth(el_pos, Instruction::DUG) {
+        Err(err) => Err(err),
+        Ok(_) => {
+            // removes the element at el_pos - 1
+            let (el_to_dug, new_stack) = stack.remove_at(options.pos);
+            // changes the element instruction
+            let el_to_dug = el_to_dug.change_instruction(Instruction::DUG);
+            // insert the element at el_pos - 1
+            let new_stack = new_stack.insert_at(vec![el_to_dug], el_pos);
+            // updates the stack snapshots
+            stack_snapshots.push(new_stack.clone());
+            // returns the stack and stack snapshots
+            Ok((new_stack, stack_snapshots))
         }
     }
 }
@@ -47,20 +39,20 @@ pub fn run(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::micheline::Micheline;
     use crate::instructions::RunOptionsContext;
     use crate::m_types::MValue;
     use crate::stack::StackElement;
-    use serde_json::json;
 
     // PASSING
     #[test]
     fn dug_success_one() {
-        let arg_value: Value = json!({ "int": "1" });
+        let arg_value = Micheline::Int(String::from("1"));
         let arg_vec = vec![arg_value];
-        let args: Option<&Vec<Value>> = Some(&arg_vec);
+        let args: Option<&Vec<Micheline>> = Some(&arg_vec);
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Int(5), Instruction::INIT),
-            StackElement::new(MValue::Nat(6), Instruction::INIT),
+            StackElement::new(MValue::new_int(5), Instruction::INIT),
+            StackElement::new(MValue::new_nat(6), Instruction::INIT),
             StackElement::new(MValue::String(String::from("test")), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
@@ -71,12 +63,15 @@ mod tests {
 
         assert!(initial_stack.len() == 3);
 
-        match run(initial_stack, args, &options, stack_snapshots) {
+        match Instruction::DUG
+            .check_num_arg(&args)
+            .and_then(|n| run(initial_stack, n, &options, stack_snapshots))
+        {
             Ok((stack, _)) => {
                 assert!(stack.len() == 3);
-                assert_eq!(stack[0].value, MValue::Nat(6));
+                assert_eq!(stack[0].value, MValue::new_nat(6));
                 assert_eq!(stack[0].instruction, Instruction::INIT);
-                assert_eq!(stack[1].value, MValue::Int(5));
+                assert_eq!(stack[1].value, MValue::new_int(5));
                 assert_eq!(stack[1].instruction, Instruction::DUG);
                 assert_eq!(stack[2].value, MValue::String(String::from("test")));
                 assert_eq!(stack[2].instruction, Instruction::INIT);
@@ -87,12 +82,12 @@ mod tests {
 
     #[test]
     fn dug_success_two() {
-        let arg_value: Value = json!({ "int": "2" });
+        let arg_value = Micheline::Int(String::from("2"));
         let arg_vec = vec![arg_value];
-        let args: Option<&Vec<Value>> = Some(&arg_vec);
+        let args: Option<&Vec<Micheline>> = Some(&arg_vec);
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Int(5), Instruction::INIT),
-            StackElement::new(MValue::Nat(6), Instruction::INIT),
+            StackElement::new(MValue::new_int(5), Instruction::INIT),
+            StackElement::new(MValue::new_nat(6), Instruction::INIT),
             StackElement::new(MValue::String(String::from("test")), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
@@ -103,14 +98,17 @@ mod tests {
 
         assert!(initial_stack.len() == 3);
 
-        match run(initial_stack, args, &options, stack_snapshots) {
+        match Instruction::DUG
+            .check_num_arg(&args)
+            .and_then(|n| run(initial_stack, n, &options, stack_snapshots))
+        {
             Ok((stack, _)) => {
                 assert!(stack.len() == 3);
-                assert_eq!(stack[0].value, MValue::Nat(6));
+                assert_eq!(stack[0].value, MValue::new_nat(6));
                 assert_eq!(stack[0].instruction, Instruction::INIT);
                 assert_eq!(stack[1].value, MValue::String(String::from("test")));
                 assert_eq!(stack[1].instruction, Instruction::INIT);
-                assert_eq!(stack[2].value, MValue::Int(5));
+                assert_eq!(stack[2].value, MValue::new_int(5));
                 assert_eq!(stack[2].instruction, Instruction::DUG);
             }
             Err(_) => assert!(false),
@@ -124,12 +122,12 @@ mod tests {
         expected = "Unexpected stack length, expected a length of 4 for instruction DUG, got 2"
     )]
     fn dug_wrong_arg_value() {
-        let arg_value: Value = json!({ "int": "4" });
+        let arg_value = Micheline::Int(String::from("4"));
         let arg_vec = vec![arg_value];
-        let args: Option<&Vec<Value>> = Some(&arg_vec);
+        let args: Option<&Vec<Micheline>> = Some(&arg_vec);
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Int(5), Instruction::INIT),
-            StackElement::new(MValue::Nat(6), Instruction::INIT),
+            StackElement::new(MValue::new_int(5), Instruction::INIT),
+            StackElement::new(MValue::new_nat(6), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
         let options = RunOptions {
@@ -139,7 +137,10 @@ mod tests {
 
         assert!(initial_stack.len() == 2);
 
-        match run(initial_stack, args, &options, stack_snapshots) {
+        match Instruction::DUG
+            .check_num_arg(&args)
+            .and_then(|n| run(initial_stack, n, &options, stack_snapshots))
+        {
             Ok(_) => assert!(false),
             Err(err) => panic!("{}", err),
         }
@@ -149,12 +150,12 @@ mod tests {
     #[test]
     #[should_panic(expected = "DUG 0 is a noop")]
     fn dig_arg_zero() {
-        let arg_value: Value = json!({ "int": "0" });
+        let arg_value = Micheline::Int(String::from("0"));
         let arg_vec = vec![arg_value];
-        let args: Option<&Vec<Value>> = Some(&arg_vec);
+        let args: Option<&Vec<Micheline>> = Some(&arg_vec);
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Int(5), Instruction::INIT),
-            StackElement::new(MValue::Nat(6), Instruction::INIT),
+            StackElement::new(MValue::new_int(5), Instruction::INIT),
+            StackElement::new(MValue::new_nat(6), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
         let options = RunOptions {
@@ -164,7 +165,10 @@ mod tests {
 
         assert!(initial_stack.len() == 2);
 
-        match run(initial_stack, args, &options, stack_snapshots) {
+        match Instruction::DUG
+            .check_num_arg(&args)
+            .and_then(|n| run(initial_stack, n, &options, stack_snapshots))
+        {
             Ok(_) => assert!(false),
             Err(err) => panic!("{}", err),
         }
@@ -173,15 +177,15 @@ mod tests {
     // incorrect argument
     #[test]
     #[should_panic(
-        expected = "Unexpected format for DUG argument: Object({\"string\": String(\"test\")})"
+        expected = "Unexpected format for DUG argument: \"test\""
     )]
     fn dig_wrong_arg() {
-        let arg_value: Value = json!({ "string": "test" });
+        let arg_value = Micheline::String(String::from("test"));
         let arg_vec = vec![arg_value];
-        let args: Option<&Vec<Value>> = Some(&arg_vec);
+        let args: Option<&Vec<Micheline>> = Some(&arg_vec);
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Int(5), Instruction::INIT),
-            StackElement::new(MValue::Nat(6), Instruction::INIT),
+            StackElement::new(MValue::new_int(5), Instruction::INIT),
+            StackElement::new(MValue::new_nat(6), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
         let options = RunOptions {
@@ -191,7 +195,10 @@ mod tests {
 
         assert!(initial_stack.len() == 2);
 
-        match run(initial_stack, args, &options, stack_snapshots) {
+        match Instruction::DUG
+            .check_num_arg(&args)
+            .and_then(|n| run(initial_stack, n, &options, stack_snapshots))
+        {
             Ok(_) => assert!(false),
             Err(err) => panic!("{}", err),
         }
@@ -1,46 +1,38 @@
+use crate::errors::MichelsonError;
 use crate::instructions::{Instruction, RunOptions};
 use crate::m_types::MValue;
-use crate::stack::{Stack, StackElement, StackFuncs, StackSnapshots};
-use serde_json::Value;
+use crate::stack::{Stack, StackFuncs, StackSnapshots};
 
 // https://tezos.gitlab.io/michelson-reference/#instr-DUP
 
 pub fn run(
     stack: Stack,
-    args: Option<&Vec<Value>>,
+    el_to_dig_pos: usize,
     options: &RunOptions,
     mut stack_snapshots: StackSnapshots,
-) -> Result<(Stack, StackSnapshots), String> {
+) -> Result<(Stack, StackSnapshots), MichelsonError> {
     // checks the stack
-    match stack.check_depth(options.pos + 1, Instruction::DUP) {
-        Ok(_) => (),
-        Err(err) => panic!("{}", err),
-    };
+    stack.check_depth(options.pos + 1, Instruction::DUP)?;
     // calculates the position of the element to duplicate
-    match Instruction::DUP.check_num_arg(&args) {
-        Err(err) => Err(err), // forwards the error
-        Ok(el_to_dig_pos) => {
-            let el_pos = options.pos + el_to_dig_pos;
-            // checks that the stack is deep enough for the DIG parameter
-            match stack.check_depth(el_pos, Instruction::DUP) {
-                Err(err) => Err(err),
-                Ok(_) => {
-                    // duplicates the element at el_to_dup_pos
-                    let dupped_el = stack[el_pos - 1].clone();
-                    // checks if element is not a ticket
-                    if let MValue::Ticket(_) = dupped_el.value {
-                        Err(String::from("Tickets cannot be duplicated"))
-                    } else {
-                        // changes the instruction name of the dupped element
-                        let dupped_el = StackElement::new(dupped_el.value, Instruction::DUP);
-                        // adds the element to the top of the stack
-                        let new_stack = stack.insert_at(vec![dupped_el], options.pos);
-                        // updates the stack snapshots
-                        stack_snapshots.push(new_stack.clone());
-                        // returns the new stack
-                        Ok((new_stack, stack_snapshots))
-                    }
-                }
+    let el_pos = options.pos + el_to_dig_pos;
+    // checks that the stack is deep enough for the DIG parameter
+    match stack.check_depth(el_pos, Instruction::DUP) {
+        Err(err) => Err(err),
+        Ok(_) => {
+            // duplicates the element at el_to_dup_pos
+            let dupped_el = stack[el_pos - 1].clone();
+            // checks if element is not a ticket
+            if let MValue::Ticket(_) = dupped_el.value {
+                Err(MichelsonError::Message(String::from("Tickets cannot be duplicated")))
+            } else {
+                // changes the instruction name of the dupped element, annotations are kept
+                let dupped_el = dupped_el.change_instruction(Instruction::DUP);
+                // adds the element to the top of the stack
+                let new_stack = stack.insert_at(vec![dupped_el], options.pos);
+                // updates the stack snapshots
+                stack_snapshots.push(new_stack.clone());
+                // returns the new stack
+                Ok((new_stack, stack_snapshots))
             }
         }
     }
@@ -53,18 +45,19 @@ pub fn run(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::micheline::Micheline;
     use crate::instructions::RunOptionsContext;
-    use crate::m_types::Ticket;
-    use serde_json::json;
+    use crate::m_types::{nat, Ticket};
+    use crate::stack::StackElement;
 
     // PASSING
     // no arg
     #[test]
     fn dup_no_arg_success() {
-        let args: Option<&Vec<Value>> = None;
+        let args: Option<&Vec<Micheline>> = None;
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Int(5), Instruction::INIT),
-            StackElement::new(MValue::Nat(6), Instruction::INIT),
+            StackElement::new(MValue::new_int(5), Instruction::INIT),
+            StackElement::new(MValue::new_nat(6), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
         let options = RunOptions {
@@ -74,25 +67,47 @@ mod tests {
 
         assert!(initial_stack.len() == 2);
 
-        match run(initial_stack, args, &options, stack_snapshots) {
+        match Instruction::DUP
+            .check_num_arg(&args)
+            .and_then(|n| run(initial_stack, n, &options, stack_snapshots))
+        {
             Ok((stack, _)) => {
                 assert!(stack.len() == 3);
-                assert_eq!(stack[0].value, MValue::Int(5));
+                assert_eq!(stack[0].value, MValue::new_int(5));
                 assert_eq!(stack[0].instruction, Instruction::DUP);
             }
             Err(_) => assert!(false),
         }
     }
 
+    #[test]
+    fn dup_keeps_annotations() {
+        let initial_stack: Stack = vec![StackElement::new(MValue::new_int(5), Instruction::INIT)
+            .with_annot(Some(String::from("amount")))];
+        let options = RunOptions {
+            context: RunOptionsContext::mock(),
+            pos: 0,
+        };
+
+        match run(initial_stack, 1, &options, vec![]) {
+            Ok((stack, _)) => {
+                assert_eq!(stack.len(), 2);
+                assert_eq!(stack[0].annot, Some(String::from("amount")));
+                assert_eq!(stack[0].instruction, Instruction::DUP);
+            }
+            Err(err) => panic!("{}", err),
+        }
+    }
+
     // one arg
     #[test]
     fn dup_one_arg_success() {
-        let arg_value: Value = json!({ "int": "2" });
+        let arg_value = Micheline::Int(String::from("2"));
         let arg_vec = vec![arg_value];
-        let args: Option<&Vec<Value>> = Some(&arg_vec);
+        let args: Option<&Vec<Micheline>> = Some(&arg_vec);
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Int(5), Instruction::INIT),
-            StackElement::new(MValue::Nat(6), Instruction::INIT),
+            StackElement::new(MValue::new_int(5), Instruction::INIT),
+            StackElement::new(MValue::new_nat(6), Instruction::INIT),
             StackElement::new(MValue::String(String::from("test")), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
@@ -103,14 +118,17 @@ mod tests {
 
         assert!(initial_stack.len() == 3);
 
-        match run(initial_stack, args, &options, stack_snapshots) {
+        match Instruction::DUP
+            .check_num_arg(&args)
+            .and_then(|n| run(initial_stack, n, &options, stack_snapshots))
+        {
             Ok((stack, _)) => {
                 assert!(stack.len() == 4);
-                assert_eq!(stack[0].value, MValue::Nat(6));
+                assert_eq!(stack[0].value, MValue::new_nat(6));
                 assert_eq!(stack[0].instruction, Instruction::DUP);
-                assert_eq!(stack[1].value, MValue::Int(5));
+                assert_eq!(stack[1].value, MValue::new_int(5));
                 assert_eq!(stack[1].instruction, Instruction::INIT);
-                assert_eq!(stack[2].value, MValue::Nat(6));
+                assert_eq!(stack[2].value, MValue::new_nat(6));
                 assert_eq!(stack[2].instruction, Instruction::INIT);
                 assert_eq!(stack[3].value, MValue::String(String::from("test")));
                 assert_eq!(stack[3].instruction, Instruction::INIT);
@@ -126,12 +144,12 @@ mod tests {
         expected = "Unexpected stack length, expected a length of 4 for instruction DUP, got 2"
     )]
     fn dup_wrong_arg_value() {
-        let arg_value: Value = json!({ "int": "4" });
+        let arg_value = Micheline::Int(String::from("4"));
         let arg_vec = vec![arg_value];
-        let args: Option<&Vec<Value>> = Some(&arg_vec);
+        let args: Option<&Vec<Micheline>> = Some(&arg_vec);
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Int(5), Instruction::INIT),
-            StackElement::new(MValue::Nat(6), Instruction::INIT),
+            StackElement::new(MValue::new_int(5), Instruction::INIT),
+            StackElement::new(MValue::new_nat(6), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
         let options = RunOptions {
@@ -141,7 +159,10 @@ mod tests {
 
         assert!(initial_stack.len() == 2);
 
-        match run(initial_stack, args, &options, stack_snapshots) {
+        match Instruction::DUP
+            .check_num_arg(&args)
+            .and_then(|n| run(initial_stack, n, &options, stack_snapshots))
+        {
             Ok(_) => assert!(false),
             Err(err) => panic!("{}", err),
         }
@@ -151,12 +172,12 @@ mod tests {
     #[test]
     #[should_panic(expected = "DUP 0 is a noop")]
     fn dup_arg_zero() {
-        let arg_value: Value = json!({ "int": "0" });
+        let arg_value = Micheline::Int(String::from("0"));
         let arg_vec = vec![arg_value];
-        let args: Option<&Vec<Value>> = Some(&arg_vec);
+        let args: Option<&Vec<Micheline>> = Some(&arg_vec);
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Int(5), Instruction::INIT),
-            StackElement::new(MValue::Nat(6), Instruction::INIT),
+            StackElement::new(MValue::new_int(5), Instruction::INIT),
+            StackElement::new(MValue::new_nat(6), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
         let options = RunOptions {
@@ -166,7 +187,10 @@ mod tests {
 
         assert!(initial_stack.len() == 2);
 
-        match run(initial_stack, args, &options, stack_snapshots) {
+        match Instruction::DUP
+            .check_num_arg(&args)
+            .and_then(|n| run(initial_stack, n, &options, stack_snapshots))
+        {
             Ok(_) => assert!(false),
             Err(err) => panic!("{}", err),
         }
@@ -175,15 +199,15 @@ mod tests {
     // incorrect argument
     #[test]
     #[should_panic(
-        expected = "Unexpected format for DUP argument: Object({\"string\": String(\"test\")})"
+        expected = "Unexpected format for DUP argument: \"test\""
     )]
     fn dup_wrong_arg() {
-        let arg_value: Value = json!({ "string": "test" });
+        let arg_value = Micheline::String(String::from("test"));
         let arg_vec = vec![arg_value];
-        let args: Option<&Vec<Value>> = Some(&arg_vec);
+        let args: Option<&Vec<Micheline>> = Some(&arg_vec);
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Int(5), Instruction::INIT),
-            StackElement::new(MValue::Nat(6), Instruction::INIT),
+            StackElement::new(MValue::new_int(5), Instruction::INIT),
+            StackElement::new(MValue::new_nat(6), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
         let options = RunOptions {
@@ -193,7 +217,10 @@ mod tests {
 
         assert!(initial_stack.len() == 2);
 
-        match run(initial_stack, args, &options, stack_snapshots) {
+        match Instruction::DUP
+            .check_num_arg(&args)
+            .and_then(|n| run(initial_stack, n, &options, stack_snapshots))
+        {
             Ok(_) => assert!(false),
             Err(err) => panic!("{}", err),
         }
@@ -203,14 +230,14 @@ mod tests {
     #[test]
     #[should_panic(expected = "Tickets cannot be duplicated")]
     fn dup_ticket() {
-        let args: Option<&Vec<Value>> = None;
-        match Ticket::new(MValue::Int(5), 5, String::from("test_address")) {
+        let args: Option<&Vec<Micheline>> = None;
+        match Ticket::new(MValue::new_int(5), nat::from(5u32), String::from("test_address")) {
             Err(_) => panic!("Unable to create a new ticket"),
             Ok(ticket) => {
                 let initial_stack: Stack = vec![
                     StackElement::new(MValue::Ticket(Box::new(ticket)), Instruction::INIT),
-                    StackElement::new(MValue::Int(5), Instruction::INIT),
-                    StackElement::new(MValue::Nat(6), Instruction::INIT),
+                    StackElement::new(MValue::new_int(5), Instruction::INIT),
+                    StackElement::new(MValue::new_nat(6), Instruction::INIT),
                 ];
                 let stack_snapshots = vec![];
                 let options = RunOptions {
@@ -220,7 +247,10 @@ mod tests {
 
                 assert!(initial_stack.len() == 3);
 
-                match run(initial_stack, args, &options, stack_snapshots) {
+                match Instruction::DUP
+                    .check_num_arg(&args)
+                    .and_then(|n| run(initial_stack, n, &options, stack_snapshots))
+                {
                     Ok(_) => assert!(false),
                     Err(err) => panic!("{}", err),
                 }
@@ -1,29 +1,27 @@
-use crate::errors::{display_error, ErrorCode};
+use crate::errors::MichelsonError;
 use crate::instructions::{Instruction, RunOptions};
-use crate::m_types::{int, mutez, nat, MType, MValue, OptionValue, PairValue};
+use crate::m_types::{int, nat, MType, MValue, OptionValue, PairValue};
+use num_integer::Integer;
+use num_traits::{Signed, ToPrimitive, Zero};
 use crate::stack::{Stack, StackElement, StackFuncs, StackSnapshots};
 
 // https://tezos.gitlab.io/michelson-reference/#instr-EDIV
 
-fn divide_with_int(dividend: int, divisor: int) -> Result<MValue, String> {
-    let result_type = MType::Pair(Box::new((MType::Int, MType::Nat)));
-    if divisor == 0 {
+/// euclidean division, the remainder is always positive, e.g. -7 / 2 = -4 remainder 1
+fn divide_with_int(dividend: int, divisor: int) -> Result<MValue, MichelsonError> {
+    let result_type = MType::pair(MType::Int, MType::Nat);
+    if divisor.is_zero() {
         Ok(MValue::Option(OptionValue::new(None, result_type)))
     } else {
-        let quotient = MValue::Int(dividend / divisor);
-        let remainder = MValue::Nat((dividend % divisor) as nat);
-        // checks that remainder is a nat
-        if remainder.check_nat() {
-            Ok(MValue::Option(OptionValue::new(
-                Some(MValue::Pair(PairValue::new(quotient, remainder))),
-                result_type,
-            )))
-        } else {
-            Err(format!(
-                "The division of {} and {} doesn't yield a remainder compatible with type `nat`",
-                dividend, divisor
-            ))
-        }
+        let remainder = dividend.mod_floor(&divisor.abs());
+        let quotient = (dividend - &remainder) / divisor;
+        Ok(MValue::Option(OptionValue::new(
+            Some(MValue::Pair(PairValue::new(
+                MValue::Int(quotient),
+                MValue::Nat(remainder.magnitude().clone()),
+            ))),
+            result_type,
+        )))
     }
 }
 
@@ -31,7 +29,7 @@ pub fn run(
     stack: Stack,
     options: &RunOptions,
     mut stack_snapshots: StackSnapshots,
-) -> Result<(Stack, StackSnapshots), String> {
+) -> Result<(Stack, StackSnapshots), MichelsonError> {
     let this_instruction = Instruction::EDIV;
     // checks the stack
     stack.check_depth(options.pos + 2, this_instruction)?;
@@ -41,33 +39,32 @@ pub fn run(
         stack[options.pos + 1].get_val(),
     ) {
         (MValue::Int(dividend), MValue::Int(divisor)) => divide_with_int(dividend, divisor),
-        (MValue::Int(dividend), MValue::Nat(divisor)) => divide_with_int(dividend, divisor as int),
-        (MValue::Nat(dividend), MValue::Int(divisor)) => divide_with_int(dividend as int, divisor),
+        (MValue::Int(dividend), MValue::Nat(divisor)) => divide_with_int(dividend, int::from(divisor)),
+        (MValue::Nat(dividend), MValue::Int(divisor)) => divide_with_int(int::from(dividend), divisor),
         (MValue::Nat(dividend), MValue::Nat(divisor)) => {
-            let result_type = MType::Pair(Box::new((MType::Nat, MType::Nat)));
-            if divisor == 0 {
+            let result_type = MType::pair(MType::Nat, MType::Nat);
+            if divisor.is_zero() {
                 Ok(MValue::Option(OptionValue::new(None, result_type)))
             } else {
-                let quotient = MValue::Nat(dividend / divisor);
-                let remainder = MValue::Nat((dividend % divisor) as nat);
-                // checks that remainder is a nat
-                if remainder.check_nat() {
-                    Ok(MValue::Option(OptionValue::new(
-                        Some(MValue::Pair(PairValue::new(quotient, remainder))),
-                        result_type,
-                    )))
-                } else {
-                    Err(format!("The division of {} and {} doesn't yield a remainder compatible with type `nat`", dividend, divisor))
-                }
+                let (quotient, remainder) = dividend.div_rem(&divisor);
+                Ok(MValue::Option(OptionValue::new(
+                    Some(MValue::Pair(PairValue::new(MValue::Nat(quotient), MValue::Nat(remainder)))),
+                    result_type,
+                )))
             }
         }
         (MValue::Mutez(dividend), MValue::Nat(divisor)) => {
-            let result_type = MType::Pair(Box::new((MType::Mutez, MType::Mutez)));
-            if divisor == 0 {
+            let result_type = MType::pair(MType::Mutez, MType::Mutez);
+            if divisor.is_zero() {
                 Ok(MValue::Option(OptionValue::new(None, result_type)))
             } else {
-                let quotient = MValue::Mutez((dividend / divisor) as mutez);
-                let remainder = MValue::Mutez((dividend % divisor) as mutez);
+                // a divisor larger than any mutez value leaves the whole amount as remainder
+                let (quotient, remainder) = match divisor.to_u64() {
+                    Some(divisor) => (dividend / divisor, dividend % divisor),
+                    None => (0, dividend),
+                };
+                let quotient = MValue::Mutez(quotient);
+                let remainder = MValue::Mutez(remainder);
                 // checks that remainder is a nat
                 if remainder.check_mutez() {
                     Ok(MValue::Option(OptionValue::new(
@@ -75,17 +72,17 @@ pub fn run(
                         result_type,
                     )))
                 } else {
-                    Err(format!("The division of {} and {} doesn't yield a remainder compatible with type `mutez`", dividend, divisor))
+                    Err(MichelsonError::Message(format!("The division of {} and {} doesn't yield a remainder compatible with type `mutez`", dividend, divisor)))
                 }
             }
         }
         (MValue::Mutez(dividend), MValue::Mutez(divisor)) => {
-            let result_type = MType::Pair(Box::new((MType::Nat, MType::Mutez)));
+            let result_type = MType::pair(MType::Nat, MType::Mutez);
             if divisor == 0 {
                 Ok(MValue::Option(OptionValue::new(None, result_type)))
             } else {
-                let quotient = MValue::Nat((dividend / divisor) as nat);
-                let remainder = MValue::Mutez((dividend % divisor) as mutez);
+                let quotient = MValue::Nat(nat::from(dividend / divisor));
+                let remainder = MValue::Mutez(dividend % divisor);
                 // checks that remainder is a nat
                 if remainder.check_mutez() {
                     Ok(MValue::Option(OptionValue::new(
@@ -93,25 +90,25 @@ pub fn run(
                         result_type,
                     )))
                 } else {
-                    Err(format!("The division of {} and {} doesn't yield a remainder compatible with type `mutez`", dividend, divisor))
+                    Err(MichelsonError::Message(format!("The division of {} and {} doesn't yield a remainder compatible with type `mutez`", dividend, divisor)))
                 }
             }
         }
         (val, MValue::Int(_) | MValue::Nat(_) | MValue::Mutez(_)) => {
-            Err(display_error(ErrorCode::InvalidType((
+            Err(MichelsonError::InvalidType((
                 vec![MType::Int, MType::Nat, MType::Mutez],
                 val.get_type(),
                 this_instruction,
-            ))))
+            )))
         }
         (MValue::Int(_) | MValue::Nat(_) | MValue::Mutez(_), val) => {
-            Err(display_error(ErrorCode::InvalidType((
+            Err(MichelsonError::InvalidType((
                 vec![MType::Int, MType::Nat, MType::Mutez],
                 val.get_type(),
                 this_instruction,
-            ))))
+            )))
         }
-        _ => Err(String::from("Invalid stack for EDIV instruction")),
+        _ => Err(MichelsonError::Message(String::from("Invalid stack for EDIV instruction"))),
     }?;
 
     // removes the first element of the addition
@@ -141,8 +138,8 @@ mod tests {
     #[test]
     fn ediv_int_int() {
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Int(6), Instruction::INIT),
-            StackElement::new(MValue::Int(4), Instruction::INIT),
+            StackElement::new(MValue::new_int(6), Instruction::INIT),
+            StackElement::new(MValue::new_int(4), Instruction::INIT),
             StackElement::new(MValue::String(String::from("taquito")), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
@@ -158,8 +155,8 @@ mod tests {
                 assert_eq!(
                     stack[0].value,
                     MValue::Option(OptionValue::new(
-                        Some(MValue::Pair(PairValue::new(MValue::Int(1), MValue::Nat(2)))),
-                        MType::Pair(Box::new((MType::Int, MType::Nat)))
+                        Some(MValue::Pair(PairValue::new(MValue::new_int(1), MValue::new_nat(2)))),
+                        MType::pair(MType::Int, MType::Nat)
                     ))
                 );
                 assert_eq!(stack[0].instruction, Instruction::EDIV);
@@ -168,12 +165,41 @@ mod tests {
         }
     }
 
+    // the remainder of the division of a negative int is positive
+    #[test]
+    fn ediv_negative_int() {
+        let options = RunOptions {
+            context: RunOptionsContext::mock(),
+            pos: 0,
+        };
+        let result_type = MType::pair(MType::Int, MType::Nat);
+        for (divisor, quotient) in [(2, -4), (-2, 4)] {
+            let initial_stack: Stack = vec![
+                StackElement::new(MValue::new_int(-7), Instruction::INIT),
+                StackElement::new(MValue::new_int(divisor), Instruction::INIT),
+            ];
+
+            match run(initial_stack, &options, vec![]) {
+                Err(err) => panic!("{}", err),
+                Ok((stack, _)) => {
+                    assert_eq!(
+                        stack[0].value,
+                        MValue::Option(OptionValue::new(
+                            Some(MValue::Pair(PairValue::new(MValue::new_int(quotient), MValue::new_nat(1)))),
+                            result_type.clone()
+                        ))
+                    );
+                }
+            }
+        }
+    }
+
     // divide an int with a nat
     #[test]
     fn ediv_int_nat() {
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Int(6), Instruction::INIT),
-            StackElement::new(MValue::Nat(4), Instruction::INIT),
+            StackElement::new(MValue::new_int(6), Instruction::INIT),
+            StackElement::new(MValue::new_nat(4), Instruction::INIT),
             StackElement::new(MValue::String(String::from("taquito")), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
@@ -189,8 +215,8 @@ mod tests {
                 assert_eq!(
                     stack[0].value,
                     MValue::Option(OptionValue::new(
-                        Some(MValue::Pair(PairValue::new(MValue::Int(1), MValue::Nat(2)))),
-                        MType::Pair(Box::new((MType::Int, MType::Nat)))
+                        Some(MValue::Pair(PairValue::new(MValue::new_int(1), MValue::new_nat(2)))),
+                        MType::pair(MType::Int, MType::Nat)
                     ))
                 );
                 assert_eq!(stack[0].instruction, Instruction::EDIV);
@@ -203,8 +229,8 @@ mod tests {
     #[test]
     fn ediv_nat_int() {
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Nat(6), Instruction::INIT),
-            StackElement::new(MValue::Int(4), Instruction::INIT),
+            StackElement::new(MValue::new_nat(6), Instruction::INIT),
+            StackElement::new(MValue::new_int(4), Instruction::INIT),
             StackElement::new(MValue::String(String::from("taquito")), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
@@ -220,8 +246,8 @@ mod tests {
                 assert_eq!(
                     stack[0].value,
                     MValue::Option(OptionValue::new(
-                        Some(MValue::Pair(PairValue::new(MValue::Int(1), MValue::Nat(2)))),
-                        MType::Pair(Box::new((MType::Int, MType::Nat)))
+                        Some(MValue::Pair(PairValue::new(MValue::new_int(1), MValue::new_nat(2)))),
+                        MType::pair(MType::Int, MType::Nat)
                     ))
                 );
                 assert_eq!(stack[0].instruction, Instruction::EDIV);
@@ -234,8 +260,8 @@ mod tests {
     #[test]
     fn ediv_nat_nat() {
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Nat(6), Instruction::INIT),
-            StackElement::new(MValue::Nat(4), Instruction::INIT),
+            StackElement::new(MValue::new_nat(6), Instruction::INIT),
+            StackElement::new(MValue::new_nat(4), Instruction::INIT),
             StackElement::new(MValue::String(String::from("taquito")), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
@@ -251,8 +277,8 @@ mod tests {
                 assert_eq!(
                     stack[0].value,
                     MValue::Option(OptionValue::new(
-                        Some(MValue::Pair(PairValue::new(MValue::Nat(1), MValue::Nat(2)))),
-                        MType::Pair(Box::new((MType::Nat, MType::Nat)))
+                        Some(MValue::Pair(PairValue::new(MValue::new_nat(1), MValue::new_nat(2)))),
+                        MType::pair(MType::Nat, MType::Nat)
                     ))
                 );
                 assert_eq!(stack[0].instruction, Instruction::EDIV);
@@ -266,7 +292,7 @@ mod tests {
     fn ediv_mutez_nat() {
         let initial_stack: Stack = vec![
             StackElement::new(MValue::Mutez(6), Instruction::INIT),
-            StackElement::new(MValue::Nat(4), Instruction::INIT),
+            StackElement::new(MValue::new_nat(4), Instruction::INIT),
             StackElement::new(MValue::String(String::from("taquito")), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
@@ -286,7 +312,7 @@ mod tests {
                             MValue::Mutez(1),
                             MValue::Mutez(2)
                         ))),
-                        MType::Pair(Box::new((MType::Mutez, MType::Mutez)))
+                        MType::pair(MType::Mutez, MType::Mutez)
                     ))
                 );
                 assert_eq!(stack[0].instruction, Instruction::EDIV);
@@ -317,10 +343,10 @@ mod tests {
                     stack[0].value,
                     MValue::Option(OptionValue::new(
                         Some(MValue::Pair(PairValue::new(
-                            MValue::Nat(1),
+                            MValue::new_nat(1),
                             MValue::Mutez(2)
                         ))),
-                        MType::Pair(Box::new((MType::Nat, MType::Mutez)))
+                        MType::pair(MType::Nat, MType::Mutez)
                     ))
                 );
                 assert_eq!(stack[0].instruction, Instruction::EDIV);
@@ -333,8 +359,8 @@ mod tests {
     #[test]
     fn ediv_int_zero() {
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Int(6), Instruction::INIT),
-            StackElement::new(MValue::Int(0), Instruction::INIT),
+            StackElement::new(MValue::new_int(6), Instruction::INIT),
+            StackElement::new(MValue::new_int(0), Instruction::INIT),
             StackElement::new(MValue::String(String::from("taquito")), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
@@ -351,7 +377,7 @@ mod tests {
                     stack[0].value,
                     MValue::Option(OptionValue::new(
                         None,
-                        MType::Pair(Box::new((MType::Int, MType::Nat)))
+                        MType::pair(MType::Int, MType::Nat)
                     ))
                 );
                 assert_eq!(stack[0].instruction, Instruction::EDIV);
@@ -364,7 +390,7 @@ mod tests {
     // wrong stack
     #[test]
     fn ediv_wrong_stack() {
-        let initial_stack: Stack = vec![StackElement::new(MValue::Int(0), Instruction::INIT)];
+        let initial_stack: Stack = vec![StackElement::new(MValue::new_int(0), Instruction::INIT)];
         let stack_snapshots = vec![];
         let options = RunOptions {
             context: RunOptionsContext::mock(),
@@ -373,7 +399,7 @@ mod tests {
 
         match run(initial_stack, &options, stack_snapshots) {
             Err(err) => assert!(
-                err == String::from(
+                err.to_string() == String::from(
                     "Unexpected stack length, expected a length of 2 for instruction EDIV, got 1"
                 )
             ),
@@ -387,7 +413,7 @@ mod tests {
         let initial_stack: Stack = vec![
             StackElement::new(MValue::String(String::from("tezos")), Instruction::INIT),
             StackElement::new(MValue::String(String::from("taquito")), Instruction::INIT),
-            StackElement::new(MValue::Int(0), Instruction::INIT),
+            StackElement::new(MValue::new_int(0), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
         let options = RunOptions {
@@ -396,7 +422,7 @@ mod tests {
         };
 
         match run(initial_stack, &options, stack_snapshots) {
-            Err(err) => assert!(err == String::from("Invalid stack for EDIV instruction")),
+            Err(err) => assert!(err.to_string() == String::from("Invalid stack for EDIV instruction")),
             Ok(_) => assert!(false),
         }
     }
@@ -405,9 +431,9 @@ mod tests {
     #[test]
     fn ediv_string() {
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Int(6), Instruction::INIT),
+            StackElement::new(MValue::new_int(6), Instruction::INIT),
             StackElement::new(MValue::String(String::from("taquito")), Instruction::INIT),
-            StackElement::new(MValue::Int(0), Instruction::INIT),
+            StackElement::new(MValue::new_int(0), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
         let options = RunOptions {
@@ -417,7 +443,7 @@ mod tests {
 
         match run(initial_stack, &options, stack_snapshots) {
             Err(err) => assert!(
-                err == String::from(
+                err.to_string() == String::from(
                     "Invalid type for `EDIV` expected int | nat | mutez, but got string"
                 )
             ),
@@ -1,99 +1,95 @@
+use crate::errors::MichelsonError;
 use crate::instructions::{EmptyCollection, Instruction, RunOptions};
 use crate::m_types::{MType, MValue};
 use crate::stack::{Stack, StackElement, StackFuncs, StackSnapshots};
-use serde_json::Value;
+use crate::micheline::Micheline;
 
 // https://tezos.gitlab.io/michelson-reference/#instr-EMPTY_SET
 // https://tezos.gitlab.io/michelson-reference/#instr-EMPTY_MAP
 // https://tezos.gitlab.io/michelson-reference/#instr-EMPTY_BIG_MAP
 
-pub fn run(
-    stack: Stack,
-    args: Option<&Vec<Value>>,
-    options: &RunOptions,
-    mut stack_snapshots: StackSnapshots,
-    instr: EmptyCollection,
-) -> Result<(Stack, StackSnapshots), String> {
-    // no need to check the stack, it can be empty
-    // gets the instruction
-    let instruction = match &instr {
+/// gets the instruction matching the kind of collection
+fn get_instruction(instr: &EmptyCollection) -> Instruction {
+    match instr {
         EmptyCollection::Bigmap => Instruction::EMPTY_BIG_MAP,
         EmptyCollection::Map => Instruction::EMPTY_MAP,
         EmptyCollection::Set => Instruction::EMPTY_SET,
-    };
+    }
+}
+
+/// decodes the type of the elements of a set or the types of the keys and values of a map
+pub fn parse_args(
+    args: Option<&Vec<Micheline>>,
+    instr: &EmptyCollection,
+) -> Result<(MType, Option<MType>), MichelsonError> {
+    let instruction = get_instruction(instr);
     match args {
-        None => Err(format!(
+        None => Err(MichelsonError::Message(format!(
             "Arguments for {:?} instruction cannot be empty",
             instruction
-        )),
-        Some(val) => {
-            if val[0].is_object() {
-                let new_collection = match (val[0]["prim"].as_str(), &instr) {
-                    (None, EmptyCollection::Set) => Err(format!(
-                        "Missing 'prim' field in argument for {:?} instruction",
-                        instruction
-                    )),
-                    (Some(str), EmptyCollection::Set) => match MType::from_string(str) {
-                        Err(err) => Err(err),
-                        Ok(collection_type) => Ok(MValue::new_empty_set(collection_type)),
-                    },
-                    (None, EmptyCollection::Map | EmptyCollection::Bigmap) => {
-                        // there has to be an "args" property with the type of the keys and values
-                        match val[0]["args"].as_array() {
-                            None => Err(format!(
-                                "Missing 'args' field in argument for {:?} instruction",
-                                instruction
-                            )),
-                            Some(args) => {
-                                if args.len() != 2 {
-                                    Err(format!(
-                                        "'args' field for {:?} instruction must have 2 elements, got {:?}",
-                                        instruction, args.len()
-                                    ))
-                                } else {
-                                    match (args[0]["prim"].as_str(), args[1]["prim"].as_str()) {
-                                        (None, _) | (_, None) => Err(format!("Unexpected argument for {:?} instruction in 'args' array", instruction)),
-                                        (Some(key_type_str), Some(value_type_str)) => {
-                                            match (MType::from_string(key_type_str), MType::from_string(value_type_str)) {
-                                                (Err(err), _) => Err(format!("Unexpected type for {:?} instruction in 'args' array: {:?}", instruction, err)),
-                                                (_, Err(err)) => Err(format!("Unexpected type for {:?} instruction in 'args' array: {:?}", instruction, err)),
-                                                (Ok(key_type), Ok(value_type)) =>
-                                                    match instr {
-                                                        EmptyCollection::Bigmap => Ok(MValue::new_empty_big_map(key_type, value_type)),
-                                                        EmptyCollection::Map => Ok(MValue::new_empty_map(key_type, value_type)),
-                                                        _ => Err(format!("Unexpected collection type for {:?} instruction", instruction))
-                                                    }
-                                            }
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                    }
-                    _ => Err(format!(
-                        "Unexpected pattern for {:?} instruction to build new empty collection",
-                        instruction
-                    )),
-                }?;
-                // inserts the new element into the stack
-                let new_stack = stack.insert_at(
-                    vec![StackElement::new(new_collection, instruction)],
-                    options.pos,
-                );
-                // updates the stack snapshots
-                stack_snapshots.push(new_stack.clone());
-
-                Ok((new_stack, stack_snapshots))
-            } else {
-                Err(format!(
-                    "Expected a 'serde_json::Value' of type object as argument for {:?} instruction",
-                    instruction
-                ))
+        ))),
+        Some(val) => match (val.as_slice(), instr) {
+            ([type_node @ Micheline::Prim { .. }], EmptyCollection::Set) => {
+                Ok((MType::from_micheline(type_node)?, None))
             }
-        }
+            ([arg], EmptyCollection::Set) => Err(MichelsonError::Message(format!(
+                "Expected a type as argument for {:?} instruction, got {}",
+                instruction, arg
+            ))),
+            (
+                [key_node @ Micheline::Prim { .. }, value_node @ Micheline::Prim { .. }],
+                EmptyCollection::Map | EmptyCollection::Bigmap,
+            ) => {
+                match (MType::from_micheline(key_node), MType::from_micheline(value_node)) {
+                    (Err(err), _) => Err(MichelsonError::Message(format!("Unexpected type for {:?} instruction in 'args' array: {}", instruction, err))),
+                    (_, Err(err)) => Err(MichelsonError::Message(format!("Unexpected type for {:?} instruction in 'args' array: {}", instruction, err))),
+                    (Ok(key_type), Ok(value_type)) => Ok((key_type, Some(value_type))),
+                }
+            }
+            ([_, _], EmptyCollection::Map | EmptyCollection::Bigmap) => Err(MichelsonError::Message(format!(
+                "Unexpected argument for {:?} instruction in 'args' array",
+                instruction
+            ))),
+            (_, EmptyCollection::Set) => Err(MichelsonError::UnexpectedArgsNumber((1, val.len()))),
+            (_, EmptyCollection::Map | EmptyCollection::Bigmap) => {
+                Err(MichelsonError::UnexpectedArgsNumber((2, val.len())))
+            }
+        },
     }
 }
 
+pub fn run(
+    stack: Stack,
+    element_type: &MType,
+    value_type: Option<&MType>,
+    options: &RunOptions,
+    mut stack_snapshots: StackSnapshots,
+    instr: EmptyCollection,
+) -> Result<(Stack, StackSnapshots), MichelsonError> {
+    // no need to check the stack, it can be empty
+    // gets the instruction
+    let instruction = get_instruction(&instr);
+    let new_collection = match (instr, value_type) {
+        (EmptyCollection::Set, None) => Ok(MValue::new_empty_set(element_type.clone())),
+        (EmptyCollection::Map, Some(value_type)) => {
+            Ok(MValue::new_empty_map(element_type.clone(), value_type.clone()))
+        }
+        (EmptyCollection::Bigmap, Some(value_type)) => {
+            Ok(MValue::new_empty_big_map(element_type.clone(), value_type.clone()))
+        }
+        _ => Err(MichelsonError::Message(format!("Unexpected collection type for {:?} instruction", instruction))),
+    }?;
+    // inserts the new element into the stack
+    let new_stack = stack.insert_at(
+        vec![StackElement::new(new_collection, instruction)],
+        options.pos,
+    );
+    // updates the stack snapshots
+    stack_snapshots.push(new_stack.clone());
+
+    Ok((new_stack, stack_snapshots))
+}
+
 /**
  * TESTS
  */
@@ -102,18 +98,18 @@ pub fn run(
 mod tests {
     use super::*;
     use crate::instructions::RunOptionsContext;
-    use crate::m_types::{CollectionValue, MapValue};
-    use serde_json::json;
-    use std::collections::HashMap;
+    use crate::m_types::nat;
+    use crate::m_types::{MapValue, SetValue};
+    use std::collections::BTreeMap;
 
     // PASSING
     #[test]
     fn empty_set_success() {
-        let arg_value: Value = json!({ "prim": "nat" });
+        let arg_value = Micheline::prim("nat", vec![]);
         let arg_vec = vec![arg_value];
-        let args: Option<&Vec<Value>> = Some(&arg_vec);
+        let args: Option<&Vec<Micheline>> = Some(&arg_vec);
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Int(5), Instruction::INIT),
+            StackElement::new(MValue::new_int(5), Instruction::INIT),
             StackElement::new(MValue::Mutez(6_000_000), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
@@ -124,23 +120,23 @@ mod tests {
 
         assert!(initial_stack.len() == 2);
 
-        match run(
-            initial_stack,
-            args,
-            &options,
-            stack_snapshots,
-            EmptyCollection::Set,
-        ) {
+        match parse_args(args, &EmptyCollection::Set).and_then(|(element_type, value_type)| {
+            run(
+                initial_stack,
+                &element_type,
+                value_type.as_ref(),
+                &options,
+                stack_snapshots,
+                EmptyCollection::Set,
+            )
+        }) {
             Ok((stack, _)) => {
-                let expected_set = CollectionValue {
-                    m_type: MType::Nat,
-                    value: Box::new(vec![]),
-                };
+                let expected_set = SetValue::new(vec![], MType::Nat);
                 assert!(stack.len() == 3);
                 match &stack[0].value {
                     MValue::Set(set) => {
                         assert_eq!(*set, expected_set);
-                        assert_eq!(set.size(), 0);
+                        assert_eq!(set.size(), nat::from(0u32));
                     }
                     _ => assert!(false),
                 };
@@ -152,11 +148,13 @@ mod tests {
 
     #[test]
     fn empty_map_success() {
-        let arg_value: Value = json!({ "args": [{"prim": "nat"}, {"prim": "string"}] });
-        let arg_vec = vec![arg_value];
-        let args: Option<&Vec<Value>> = Some(&arg_vec);
+        let arg_vec = vec![
+            Micheline::prim("nat", vec![]),
+            Micheline::prim("string", vec![]),
+        ];
+        let args: Option<&Vec<Micheline>> = Some(&arg_vec);
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Int(5), Instruction::INIT),
+            StackElement::new(MValue::new_int(5), Instruction::INIT),
             StackElement::new(MValue::Mutez(6_000_000), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
@@ -167,19 +165,22 @@ mod tests {
 
         assert!(initial_stack.len() == 2);
 
-        match run(
-            initial_stack,
-            args,
-            &options,
-            stack_snapshots,
-            EmptyCollection::Map,
-        ) {
+        match parse_args(args, &EmptyCollection::Map).and_then(|(element_type, value_type)| {
+            run(
+                initial_stack,
+                &element_type,
+                value_type.as_ref(),
+                &options,
+                stack_snapshots,
+                EmptyCollection::Map,
+            )
+        }) {
             Ok((stack, _)) => {
                 let expected_map = MapValue {
                     is_map: true,
                     key_type: MType::Nat,
                     value_type: MType::String,
-                    value: HashMap::new(),
+                    value: BTreeMap::new(),
                 };
                 assert!(stack.len() == 3);
                 match &stack[0].value {
@@ -197,11 +198,13 @@ mod tests {
 
     #[test]
     fn empty_big_map_success() {
-        let arg_value: Value = json!({ "args": [{"prim": "nat"}, {"prim": "string"}] });
-        let arg_vec = vec![arg_value];
-        let args: Option<&Vec<Value>> = Some(&arg_vec);
+        let arg_vec = vec![
+            Micheline::prim("nat", vec![]),
+            Micheline::prim("string", vec![]),
+        ];
+        let args: Option<&Vec<Micheline>> = Some(&arg_vec);
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Int(5), Instruction::INIT),
+            StackElement::new(MValue::new_int(5), Instruction::INIT),
             StackElement::new(MValue::Mutez(6_000_000), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
@@ -212,19 +215,22 @@ mod tests {
 
         assert!(initial_stack.len() == 2);
 
-        match run(
-            initial_stack,
-            args,
-            &options,
-            stack_snapshots,
-            EmptyCollection::Bigmap,
-        ) {
+        match parse_args(args, &EmptyCollection::Bigmap).and_then(|(element_type, value_type)| {
+            run(
+                initial_stack,
+                &element_type,
+                value_type.as_ref(),
+                &options,
+                stack_snapshots,
+                EmptyCollection::Bigmap,
+            )
+        }) {
             Ok((stack, _)) => {
                 let expected_big_map = MapValue {
                     is_map: false,
                     key_type: MType::Nat,
                     value_type: MType::String,
-                    value: HashMap::new(),
+                    value: BTreeMap::new(),
                 };
                 assert!(stack.len() == 3);
                 assert_eq!(stack[0].value, MValue::Big_map(expected_big_map));
@@ -238,9 +244,9 @@ mod tests {
     #[test]
     #[should_panic(expected = "Arguments for EMPTY_SET instruction cannot be empty")]
     fn empty_set_no_args() {
-        let args: Option<&Vec<Value>> = None;
+        let args: Option<&Vec<Micheline>> = None;
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Int(5), Instruction::INIT),
+            StackElement::new(MValue::new_int(5), Instruction::INIT),
             StackElement::new(MValue::Mutez(6_000_000), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
@@ -251,13 +257,16 @@ mod tests {
 
         assert!(initial_stack.len() == 2);
 
-        match run(
-            initial_stack,
-            args,
-            &options,
-            stack_snapshots,
-            EmptyCollection::Set,
-        ) {
+        match parse_args(args, &EmptyCollection::Set).and_then(|(element_type, value_type)| {
+            run(
+                initial_stack,
+                &element_type,
+                value_type.as_ref(),
+                &options,
+                stack_snapshots,
+                EmptyCollection::Set,
+            )
+        }) {
             Ok(_) => assert!(false),
             Err(err) => panic!("{}", err),
         }
@@ -265,12 +274,12 @@ mod tests {
 
     #[test]
     fn empty_set_wrong_args() {
-        // arg is not an object
-        let arg_value: Value = json!("test");
+        // arg is not a type
+        let arg_value = Micheline::Int(String::from("5"));
         let arg_vec = vec![arg_value];
-        let args: Option<&Vec<Value>> = Some(&arg_vec);
+        let args: Option<&Vec<Micheline>> = Some(&arg_vec);
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Int(5), Instruction::INIT),
+            StackElement::new(MValue::new_int(5), Instruction::INIT),
             StackElement::new(MValue::Mutez(6_000_000), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
@@ -281,56 +290,62 @@ mod tests {
 
         assert!(initial_stack.len() == 2);
 
-        match run(
-            initial_stack,
-            args,
-            &options,
-            stack_snapshots,
-            EmptyCollection::Set,
-        ) {
+        match parse_args(args, &EmptyCollection::Set).and_then(|(element_type, value_type)| {
+            run(
+                initial_stack,
+                &element_type,
+                value_type.as_ref(),
+                &options,
+                stack_snapshots,
+                EmptyCollection::Set,
+            )
+        }) {
             Ok(_) => assert!(false),
             Err(err) => assert_eq!(
-                err,
-                "Expected a 'serde_json::Value' of type object as argument for EMPTY_SET instruction"
+                err.to_string(),
+                "Expected a type as argument for EMPTY_SET instruction, got 5"
             ),
         };
 
-        // arg object is wrong
-        let arg_value: Value = json!({ "string": "nat" });
-        let arg_vec = vec![arg_value];
-        let args: Option<&Vec<Value>> = Some(&arg_vec);
+        // wrong number of arguments
+        let arg_vec = vec![
+            Micheline::prim("nat", vec![]),
+            Micheline::prim("string", vec![]),
+        ];
+        let args: Option<&Vec<Micheline>> = Some(&arg_vec);
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Int(5), Instruction::INIT),
+            StackElement::new(MValue::new_int(5), Instruction::INIT),
             StackElement::new(MValue::Mutez(6_000_000), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
 
         assert!(initial_stack.len() == 2);
 
-        match run(
-            initial_stack,
-            args,
-            &options,
-            stack_snapshots,
-            EmptyCollection::Set,
-        ) {
+        match parse_args(args, &EmptyCollection::Set).and_then(|(element_type, value_type)| {
+            run(
+                initial_stack,
+                &element_type,
+                value_type.as_ref(),
+                &options,
+                stack_snapshots,
+                EmptyCollection::Set,
+            )
+        }) {
             Ok(_) => assert!(false),
             Err(err) => assert_eq!(
-                err,
-                "Missing 'prim' field in argument for EMPTY_SET instruction"
+                err.to_string(),
+                "Unexpected number of arguments, expected `1`, got `2`"
             ),
         }
     }
 
     #[test]
     fn empty_map_wrong_args() {
-        // let arg_value: Value = json!({ "args": [{"prim": "nat"}, {"prim": "string"}] });
-        // wrong serde object
-        let arg_value: Value = json!({ "string": "nat" });
-        let arg_vec = vec![arg_value];
-        let args: Option<&Vec<Value>> = Some(&arg_vec);
+        // missing value type
+        let arg_vec = vec![Micheline::prim("nat", vec![])];
+        let args: Option<&Vec<Micheline>> = Some(&arg_vec);
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Int(5), Instruction::INIT),
+            StackElement::new(MValue::new_int(5), Instruction::INIT),
             StackElement::new(MValue::Mutez(6_000_000), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
@@ -341,69 +356,82 @@ mod tests {
 
         assert!(initial_stack.len() == 2);
 
-        match run(
-            initial_stack,
-            args,
-            &options,
-            stack_snapshots,
-            EmptyCollection::Map,
-        ) {
+        match parse_args(args, &EmptyCollection::Map).and_then(|(element_type, value_type)| {
+            run(
+                initial_stack,
+                &element_type,
+                value_type.as_ref(),
+                &options,
+                stack_snapshots,
+                EmptyCollection::Map,
+            )
+        }) {
             Ok(_) => assert!(false),
             Err(err) => assert_eq!(
-                err,
-                "Missing 'args' field in argument for EMPTY_MAP instruction"
+                err.to_string(),
+                "Unexpected number of arguments, expected `2`, got `1`"
             ),
         }
 
         // wrong args length
-        let arg_value: Value =
-            json!({ "args": [{"prim": "nat"}, {"prim": "string"}, {"prim": "string"}] });
-        let arg_vec = vec![arg_value];
-        let args: Option<&Vec<Value>> = Some(&arg_vec);
+        let arg_vec = vec![
+            Micheline::prim("nat", vec![]),
+            Micheline::prim("string", vec![]),
+            Micheline::prim("string", vec![]),
+        ];
+        let args: Option<&Vec<Micheline>> = Some(&arg_vec);
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Int(5), Instruction::INIT),
+            StackElement::new(MValue::new_int(5), Instruction::INIT),
             StackElement::new(MValue::Mutez(6_000_000), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
 
         assert!(initial_stack.len() == 2);
 
-        match run(
-            initial_stack,
-            args,
-            &options,
-            stack_snapshots,
-            EmptyCollection::Map,
-        ) {
+        match parse_args(args, &EmptyCollection::Map).and_then(|(element_type, value_type)| {
+            run(
+                initial_stack,
+                &element_type,
+                value_type.as_ref(),
+                &options,
+                stack_snapshots,
+                EmptyCollection::Map,
+            )
+        }) {
             Ok(_) => assert!(false),
             Err(err) => assert_eq!(
-                err,
-                "'args' field for EMPTY_MAP instruction must have 2 elements, got 3"
+                err.to_string(),
+                "Unexpected number of arguments, expected `2`, got `3`"
             ),
         }
 
         // wrong args values
-        let arg_value: Value = json!({ "args": [{"prim": "nat"}, {"test": "string"}] });
-        let arg_vec = vec![arg_value];
-        let args: Option<&Vec<Value>> = Some(&arg_vec);
+        let arg_vec = vec![
+            Micheline::prim("nat", vec![]),
+            Micheline::String(String::from("string")),
+        ];
+        let args: Option<&Vec<Micheline>> = Some(&arg_vec);
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Int(5), Instruction::INIT),
+            StackElement::new(MValue::new_int(5), Instruction::INIT),
             StackElement::new(MValue::Mutez(6_000_000), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
 
         assert!(initial_stack.len() == 2);
 
-        match run(
-            initial_stack,
-            args,
-            &options,
-            stack_snapshots,
-            EmptyCollection::Map,
-        ) {
+        match parse_args(args, &EmptyCollection::Map).and_then(|(element_type, value_type)| {
+            run(
+                initial_stack,
+                &element_type,
+                value_type.as_ref(),
+                &options,
+                stack_snapshots,
+                EmptyCollection::Map,
+            )
+        }) {
             Ok(_) => assert!(false),
             Err(err) => assert_eq!(
-                err,
+                err.to_string(),
                 "Unexpected argument for EMPTY_MAP instruction in 'args' array"
             ),
         }
@@ -0,0 +1,222 @@
+use crate::errors::MichelsonError;
+use crate::instructions::{Instruction, RunOptions};
+use crate::m_types::{MType, MValue};
+use crate::parser;
+use crate::stack::{Stack, StackElement, StackFuncs, StackSnapshots};
+
+// https://tezos.gitlab.io/michelson-reference/#instr-EXEC
+
+/// runs the code of the lambda on a new stack holding only the parameter
+/// the value left on the stack by the code replaces the parameter and the lambda
+pub fn run(
+    stack: Stack,
+    options: &RunOptions,
+    stack_snapshots: StackSnapshots,
+) -> Result<parser::RunResult, MichelsonError> {
+    let this_instruction = Instruction::EXEC;
+    // checks the stack
+    stack.check_depth(options.pos + 2, this_instruction)?;
+    let lambda = match stack[options.pos + 1].get_val() {
+        MValue::Lambda(lambda) => lambda,
+        val => {
+            return Err(MichelsonError::WrongType((
+                String::from("lambda"),
+                val.get_type().to_string(),
+                this_instruction,
+            )))
+        }
+    };
+    // checks the type of the parameter
+    let param = stack[options.pos].get_val();
+    let param_type = lambda.param_type()?;
+    if param.get_type() != param_type {
+        return Err(MichelsonError::InvalidStack((
+            options.pos,
+            param_type,
+            param.get_type(),
+            this_instruction,
+        )));
+    }
+    // the applied values are paired with the parameter
+    let param_element = StackElement::new(lambda.build_param(param), this_instruction);
+    let lambda_stack = if lambda.recursive {
+        // a recursive lambda receives itself, as it was before being partially applied
+        let mut self_lambda = lambda.clone();
+        self_lambda.applied = vec![];
+        vec![param_element, StackElement::new(MValue::Lambda(self_lambda), this_instruction)]
+    } else {
+        vec![param_element]
+    };
+    // runs the code, a failure is returned as is
+    let result = parser::run_block(lambda.body.block(), lambda_stack, stack_snapshots, options)?;
+    if result.has_failed() {
+        return Ok(result);
+    }
+    // the code must leave a single value of the return type
+    let return_type = lambda.m_type.1.clone();
+    let result_value = match result.stack.as_slice() {
+        [element] if element.value.get_type() == return_type => element.get_val(),
+        elements => {
+            return Err(MichelsonError::InvalidStackType((
+                return_type.to_string(),
+                elements.iter().map(|el| el.value.get_type()).collect::<Vec<MType>>(),
+                this_instruction,
+            )))
+        }
+    };
+    // replaces the parameter and the lambda with the result
+    let (_, new_stack) = stack.remove_at(options.pos);
+    let new_stack = new_stack.replace(
+        vec![StackElement::new(result_value, this_instruction)],
+        options.pos,
+    );
+    // updates the stack snapshots
+    let mut stack_snapshots = result.stack_snapshots;
+    stack_snapshots.push(new_stack.clone());
+
+    Ok(parser::RunResult {
+        stack: new_stack,
+        stack_snapshots,
+        failed: None,
+    })
+}
+
+/*
+    TESTS
+*/
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::m_types::{LambdaValue, PairValue};
+
+    fn lambda(param_type: MType, return_type: MType, code: &str, recursive: bool) -> MValue {
+        MValue::Lambda(LambdaValue::new(param_type, return_type, code.parse().unwrap(), recursive).unwrap())
+    }
+
+    // PASSING
+    #[test]
+    fn exec_success() {
+        let initial_stack: Stack = vec![
+            StackElement::new(MValue::new_nat(5), Instruction::INIT),
+            StackElement::new(
+                lambda(MType::Nat, MType::Nat, "{ PUSH nat 1 ; ADD }", false),
+                Instruction::INIT,
+            ),
+            StackElement::new(MValue::new_int(7), Instruction::INIT),
+        ];
+
+        match run(initial_stack, &RunOptions::mock(), vec![]) {
+            Ok(result) => {
+                assert_eq!(result.stack.len(), 2);
+                assert_eq!(result.stack[0].value, MValue::new_nat(6));
+                assert_eq!(result.stack[0].instruction, Instruction::EXEC);
+                assert_eq!(result.stack[1].value, MValue::new_int(7));
+            }
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    #[test]
+    fn exec_applied_lambda() {
+        let param_type = MType::pair(MType::Nat, MType::Nat);
+        let lambda = match lambda(param_type, MType::Nat, "{ UNPAIR ; SUB ; ABS }", false) {
+            MValue::Lambda(lambda) => lambda.apply(MValue::new_nat(10)).unwrap(),
+            _ => unreachable!(),
+        };
+        let initial_stack: Stack = vec![
+            StackElement::new(MValue::new_nat(3), Instruction::INIT),
+            StackElement::new(MValue::Lambda(lambda), Instruction::INIT),
+        ];
+
+        match run(initial_stack, &RunOptions::mock(), vec![]) {
+            Ok(result) => {
+                assert_eq!(result.stack.len(), 1);
+                assert_eq!(result.stack[0].value, MValue::new_nat(7));
+            }
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    #[test]
+    fn exec_recursive_lambda() {
+        // sums the numbers from n down to 0
+        let code = "{ DUP ; INT ; EQ ; IF { DROP 2 ; PUSH nat 0 } { DUP ; PUSH nat 1 ; SWAP ; SUB ; ABS ; DIG 2 ; SWAP ; EXEC ; ADD } }";
+        let initial_stack: Stack = vec![
+            StackElement::new(MValue::new_nat(4), Instruction::INIT),
+            StackElement::new(lambda(MType::Nat, MType::Nat, code, true), Instruction::INIT),
+        ];
+
+        match run(initial_stack, &RunOptions::mock(), vec![]) {
+            Ok(result) => {
+                assert_eq!(result.stack.len(), 1);
+                assert_eq!(result.stack[0].value, MValue::new_nat(10));
+            }
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    #[test]
+    fn exec_failwith() {
+        let initial_stack: Stack = vec![
+            StackElement::new(MValue::new_nat(5), Instruction::INIT),
+            StackElement::new(lambda(MType::Nat, MType::Nat, "{ FAILWITH }", false), Instruction::INIT),
+        ];
+
+        match run(initial_stack, &RunOptions::mock(), vec![]) {
+            Ok(result) => {
+                assert!(result.has_failed());
+                assert_eq!(result.failed.unwrap().value, MValue::new_nat(5));
+            }
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    // FAILING
+    #[test]
+    #[should_panic(
+        expected = "Expected element at position 0 to be of type nat, but got int for instruction EXEC"
+    )]
+    fn exec_wrong_param_type() {
+        let initial_stack: Stack = vec![
+            StackElement::new(MValue::new_int(5), Instruction::INIT),
+            StackElement::new(lambda(MType::Nat, MType::Nat, "{}", false), Instruction::INIT),
+        ];
+
+        match run(initial_stack, &RunOptions::mock(), vec![]) {
+            Ok(_) => assert!(false),
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid stack type for instruction EXEC, expected [nat], but got [nat : nat]")]
+    fn exec_wrong_result() {
+        let initial_stack: Stack = vec![
+            StackElement::new(MValue::new_nat(5), Instruction::INIT),
+            StackElement::new(lambda(MType::Nat, MType::Nat, "{ DUP }", false), Instruction::INIT),
+        ];
+
+        match run(initial_stack, &RunOptions::mock(), vec![]) {
+            Ok(_) => assert!(false),
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Wrong type, expected `lambda` for instruction EXEC, got `pair nat nat`")]
+    fn exec_not_a_lambda() {
+        let initial_stack: Stack = vec![
+            StackElement::new(MValue::new_nat(5), Instruction::INIT),
+            StackElement::new(
+                MValue::Pair(PairValue::new(MValue::new_nat(1), MValue::new_nat(2))),
+                Instruction::INIT,
+            ),
+        ];
+
+        match run(initial_stack, &RunOptions::mock(), vec![]) {
+            Ok(_) => assert!(false),
+            Err(err) => panic!("{}", err),
+        }
+    }
+}
@@ -1,20 +1,44 @@
-use crate::errors::{display_error, ErrorCode};
+use crate::errors::MichelsonError;
 use crate::instructions::{Instruction, RunOptions};
 use crate::m_types::{MValue, OptionValue};
 use crate::stack::{Stack, StackElement, StackFuncs, StackSnapshots};
-use serde_json::Value;
+use crate::micheline::Micheline;
 
 // https://tezos.gitlab.io/michelson-reference/#instr-GET
 // https://tezos.gitlab.io/michelson-reference/#instr-GETN
 
+/// decodes the optional position of the field to get in a right-combed pair
+pub fn parse_args(args: Option<&Vec<Micheline>>) -> Result<Option<usize>, MichelsonError> {
+    match args {
+        None => Ok(None),
+        Some(arg) => {
+            // verifies that the argument is correct
+            if arg.len() == 1 {
+                match &arg[0] {
+                    Micheline::Int(val) => match val.parse::<usize>() {
+                        Err(err) => Err(MichelsonError::Message(format!("Expected argument for GET instruction to be a number, but got \"{}\" instead ({:?})", val, err))),
+                        Ok(el_pos) => Ok(Some(el_pos)),
+                    },
+                    _ => Err(MichelsonError::Message(String::from("Expected argument for GET instruction to be an int"))),
+                }
+            } else {
+                Err(MichelsonError::Message(format!(
+                    "Unexpected length of arg vector for GET instruction, expected 1, but got {}",
+                    arg.len()
+                )))
+            }
+        }
+    }
+}
+
 pub fn run(
     stack: Stack,
-    args: Option<&Vec<Value>>,
+    el_pos: Option<usize>,
     options: &RunOptions,
     mut stack_snapshots: StackSnapshots,
-) -> Result<(Stack, StackSnapshots), String> {
+) -> Result<(Stack, StackSnapshots), MichelsonError> {
     // checking the stack depends on the presence of arguments for GET
-    let new_stack: Stack = match args {
+    let new_stack: Stack = match el_pos {
         None => {
             // this will get values from maps and bigmaps by key
             stack.check_depth(options.pos + 2, Instruction::GET)?;
@@ -40,102 +64,61 @@ pub fn run(
 
                         Ok(new_stack)
                     } else {
-                        Err(display_error(ErrorCode::WrongType((
+                        Err(MichelsonError::WrongType((
                             map.key_type.to_string(),
                             key.get_type().to_string(),
                             Instruction::GET
-                        ))))
+                        )))
                     }
                 }
-                _ => Err(format!(
+                _ => Err(MichelsonError::Message(format!(
                     "Invalid type for `GET` expected 'map' or 'big_map', but got {}",
                     stack[options.pos + 1].value.get_type().to_string()
-                )),
+                ))),
             }
         }
-        Some(arg) => {
+        Some(el_pos) => {
             // this will get values in nested pairs
             // checks the stack
             stack.check_depth(options.pos + 1, Instruction::GET)?;
             // verifies that the element on the stack is a pair
             match &stack[options.pos].value {
                 MValue::Pair(pair) => {
-                    // verifies that the argument is correct
-                    if arg.len() == 1 {
-                        if arg[0].is_object() {
-                            match arg[0]["int"].as_str() {
-                                None => Err(String::from("Expected argument for GET instruction to be an object with an 'int' property")),
-                                Some(val) => {
-                                    match val.parse::<usize>() {
-                                        Err(err) => Err(format!("Expected argument for GET instruction to be a number, but got \"{}\" instead ({:?})", val, err)),
-                                        Ok(el_pos) => {
-                                            // checks if the pair is right-combed with the right depth
-                                            let new_val: MValue = match &pair.check_right_comb_depth() {
-                                                None => if el_pos == 0 
-                                                    { 
-                                                        Ok(MValue::Pair(pair.clone())) 
-                                                    } else { 
-                                                        Err(format!("The pair for the instruction GET doesn't have the correct depth for the provided argument: {}", el_pos)) 
-                                                    },
-                                                Some(depth) => {
-                                                    // checks if the depth of the pair matches the requested depth
-                                                    let required_depth = if el_pos % 2 != 0 { (el_pos + 1) / 2 } else { el_pos / 2 };
-                                                    if required_depth > *depth {
-                                                        Err(format!("The pair is not deep enough for instruction GET, expected a depth of {}, but got {}", required_depth, depth))
-                                                    } else {
-                                                        match pair.unfold(required_depth) {
-                                                            Err(err) => Err(err),
-                                                            Ok(pair_val) => {
-                                                                if el_pos % 2 == 0 {
-                                                                    // right field
-                                                                    let m_type = &pair_val.value.1.get_type();
-                                                                    Ok(
-                                                                        MValue::Option(
-                                                                            OptionValue::new(
-                                                                                Some(pair_val.value.1), 
-                                                                                m_type.clone()
-                                                                            )
-                                                                        )
-                                                                    )
-                                                                } else {
-                                                                    // left field
-                                                                    let m_type = &pair_val.value.0.get_type();
-                                                                    Ok(
-                                                                        MValue::Option(
-                                                                            OptionValue::new(
-                                                                                Some(pair_val.value.0), 
-                                                                                m_type.clone()
-                                                                            )
-                                                                        )
-                                                                    )
-                                                                }
-                                                            }
-                                                        }
-                                                    }
-                                                }
-                                            }?;
-
-                                            Ok(stack.replace(vec![StackElement::new(new_val, Instruction::GET)], options.pos))
-                                        }                                        
+                    // checks if the pair is right-combed with the right depth
+                    let new_val: MValue = match &pair.check_right_comb_depth() {
+                        None => if el_pos == 0 
+                            { 
+                                Ok(MValue::Pair(pair.clone())) 
+                            } else { 
+                                Err(MichelsonError::Message(format!("The pair for the instruction GET doesn't have the correct depth for the provided argument: {}", el_pos))) 
+                            },
+                        Some(depth) => {
+                            // checks if the depth of the pair matches the requested depth
+                            let required_depth = if el_pos % 2 != 0 { (el_pos + 1) / 2 } else { el_pos / 2 };
+                            if required_depth > *depth {
+                                Err(MichelsonError::Message(format!("The pair is not deep enough for instruction GET, expected a depth of {}, but got {}", required_depth, depth)))
+                            } else {
+                                match pair.unfold(required_depth) {
+                                    Err(err) => Err(MichelsonError::from(err)),
+                                    Ok(pair_val) => {
+                                        let (left, right) = *pair_val.value;
+                                        if el_pos % 2 == 0 {
+                                            // right field
+                                            Ok(right)
+                                        } else {
+                                            // left field
+                                            Ok(left)
+                                        }
                                     }
                                 }
                             }
-                        } else {
-                            Err(String::from(
-                                "Expected a 'serde_json::Value' of type object for GET instruction",
-                            ))
                         }
-                    } else {
-                        Err(format!(
-                            "Unexpected length of arg vector for GET instruction, expected 1, but got {}",
-                            arg.len()
-                        ))
-                    }
+                    }?;
+
+                    Ok(stack.replace(vec![StackElement::new(new_val, Instruction::GET)], options.pos))
                 }
                 _ => Err(
-                    display_error(
-                        ErrorCode::WrongType((String::from("pair"), stack[options.pos].value.get_type().to_string(), Instruction::GET))
-                    )
+                    MichelsonError::WrongType((String::from("pair"), stack[options.pos].value.get_type().to_string(), Instruction::GET))
                 )
             }
         }
@@ -156,28 +139,27 @@ mod tests {
     use super::*;
     use crate::instructions::RunOptionsContext;
     use crate::m_types::{MType, PairValue};
-    use serde_json::json;
 
     // PASSING
     // get value out of a map
     #[test]
     fn get_map_success() {
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Int(6), Instruction::INIT),
+            StackElement::new(MValue::new_int(6), Instruction::INIT),
             StackElement::new(
                 MValue::new_map(
                     MType::Int, 
                     MType::String, 
                     vec![
-                        (MValue::Int(5), MValue::new_string("tezos")),
-                        (MValue::Int(6), MValue::new_string("taquito")),
-                        (MValue::Int(7), MValue::new_string("hello")),
-                        (MValue::Int(8), MValue::new_string("world")),
-                        (MValue::Int(9), MValue::new_string("blockchain")),
-                    ]), 
+                        (MValue::new_int(5), MValue::new_string("tezos")),
+                        (MValue::new_int(6), MValue::new_string("taquito")),
+                        (MValue::new_int(7), MValue::new_string("hello")),
+                        (MValue::new_int(8), MValue::new_string("world")),
+                        (MValue::new_int(9), MValue::new_string("blockchain")),
+                    ]).unwrap(), 
                 Instruction::INIT
             ),
-            StackElement::new(MValue::Int(8), Instruction::INIT),
+            StackElement::new(MValue::new_int(8), Instruction::INIT),
             StackElement::new(MValue::Mutez(6_000_000), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
@@ -185,17 +167,17 @@ mod tests {
             context: RunOptionsContext::mock(),
             pos: 0,
         };
-        let args: Option<&Vec<Value>> = None;
+        let args: Option<&Vec<Micheline>> = None;
 
         assert!(initial_stack.len() == 4);
 
-        match run(initial_stack, args, &options, stack_snapshots) {
+        match parse_args(args).and_then(|el_pos| run(initial_stack, el_pos, &options, stack_snapshots)) {
             Err(_) => assert!(false),
             Ok((stack, _)) => {
                 assert!(stack.len() == 3);
                 assert_eq!(stack[0].value, MValue::Option(OptionValue::new(Some(MValue::new_string("taquito")), MType::String)));
                 assert_eq!(stack[0].instruction, Instruction::GET);
-                assert_eq!(stack[1].value, MValue::Int(8));
+                assert_eq!(stack[1].value, MValue::new_int(8));
                 assert_eq!(stack[1].instruction, Instruction::INIT);
                 assert_eq!(stack[2].value, MValue::Mutez(6_000_000));
                 assert_eq!(stack[2].instruction, Instruction::INIT);
@@ -209,18 +191,18 @@ mod tests {
         let initial_stack: Stack = vec![
             StackElement::new(
                 MValue::Pair(PairValue::new(
-                    MValue::Nat(9),
+                    MValue::new_nat(9),
                     MValue::Pair(PairValue::new(
-                        MValue::Nat(11),
+                        MValue::new_nat(11),
                         MValue::Pair(PairValue::new(
-                            MValue::Nat(12),
+                            MValue::new_nat(12),
                             MValue::new_string("taquito")
                         ))
                     ))
                 )), 
                 Instruction::INIT
             ),
-            StackElement::new(MValue::Int(8), Instruction::INIT),
+            StackElement::new(MValue::new_int(8), Instruction::INIT),
             StackElement::new(MValue::Mutez(6_000_000), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
@@ -228,22 +210,22 @@ mod tests {
             context: RunOptionsContext::mock(),
             pos: 0,
         };
-        let arg_value: Value = json!({ "int": "5" });
+        let arg_value = Micheline::Int(String::from("5"));
         let arg_vec = vec![arg_value];
-        let args: Option<&Vec<Value>> = Some(&arg_vec);
+        let args: Option<&Vec<Micheline>> = Some(&arg_vec);
 
         assert!(initial_stack.len() == 3);
 
-        match run(initial_stack, args, &options, stack_snapshots) {
+        match parse_args(args).and_then(|el_pos| run(initial_stack, el_pos, &options, stack_snapshots)) {
             Err(err) => {
                 println!("{}", err);
                 assert!(false)
             },
             Ok((stack, _)) => {
                 assert!(stack.len() == 3);
-                assert_eq!(stack[0].value, MValue::Option(OptionValue::new(Some(MValue::Nat(12)), MType::Nat)));
+                assert_eq!(stack[0].value, MValue::new_nat(12));
                 assert_eq!(stack[0].instruction, Instruction::GET);
-                assert_eq!(stack[1].value, MValue::Int(8));
+                assert_eq!(stack[1].value, MValue::new_int(8));
                 assert_eq!(stack[1].instruction, Instruction::INIT);
                 assert_eq!(stack[2].value, MValue::Mutez(6_000_000));
                 assert_eq!(stack[2].instruction, Instruction::INIT);
@@ -254,21 +236,21 @@ mod tests {
     #[test]
     fn get_map_none_success() {
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Int(16), Instruction::INIT),
+            StackElement::new(MValue::new_int(16), Instruction::INIT),
             StackElement::new(
                 MValue::new_map(
                     MType::Int, 
                     MType::String, 
                     vec![
-                        (MValue::Int(5), MValue::new_string("tezos")),
-                        (MValue::Int(6), MValue::new_string("taquito")),
-                        (MValue::Int(7), MValue::new_string("hello")),
-                        (MValue::Int(8), MValue::new_string("world")),
-                        (MValue::Int(9), MValue::new_string("blockchain")),
-                    ]), 
+                        (MValue::new_int(5), MValue::new_string("tezos")),
+                        (MValue::new_int(6), MValue::new_string("taquito")),
+                        (MValue::new_int(7), MValue::new_string("hello")),
+                        (MValue::new_int(8), MValue::new_string("world")),
+                        (MValue::new_int(9), MValue::new_string("blockchain")),
+                    ]).unwrap(), 
                 Instruction::INIT
             ),
-            StackElement::new(MValue::Int(8), Instruction::INIT),
+            StackElement::new(MValue::new_int(8), Instruction::INIT),
             StackElement::new(MValue::Mutez(6_000_000), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
@@ -276,17 +258,17 @@ mod tests {
             context: RunOptionsContext::mock(),
             pos: 0,
         };
-        let args: Option<&Vec<Value>> = None;
+        let args: Option<&Vec<Micheline>> = None;
 
         assert!(initial_stack.len() == 4);
 
-        match run(initial_stack, args, &options, stack_snapshots) {
+        match parse_args(args).and_then(|el_pos| run(initial_stack, el_pos, &options, stack_snapshots)) {
             Err(_) => assert!(false),
             Ok((stack, _)) => {
                 assert!(stack.len() == 3);
                 assert_eq!(stack[0].value, MValue::Option(OptionValue::new(None, MType::String)));
                 assert_eq!(stack[0].instruction, Instruction::GET);
-                assert_eq!(stack[1].value, MValue::Int(8));
+                assert_eq!(stack[1].value, MValue::new_int(8));
                 assert_eq!(stack[1].instruction, Instruction::INIT);
                 assert_eq!(stack[2].value, MValue::Mutez(6_000_000));
                 assert_eq!(stack[2].instruction, Instruction::INIT);
@@ -304,12 +286,12 @@ mod tests {
                     MType::Int, 
                     MType::String, 
                     vec![
-                        (MValue::Int(5), MValue::new_string("tezos")),
-                        (MValue::Int(6), MValue::new_string("taquito")),
-                        (MValue::Int(7), MValue::new_string("hello")),
-                        (MValue::Int(8), MValue::new_string("world")),
-                        (MValue::Int(9), MValue::new_string("blockchain")),
-                    ]), 
+                        (MValue::new_int(5), MValue::new_string("tezos")),
+                        (MValue::new_int(6), MValue::new_string("taquito")),
+                        (MValue::new_int(7), MValue::new_string("hello")),
+                        (MValue::new_int(8), MValue::new_string("world")),
+                        (MValue::new_int(9), MValue::new_string("blockchain")),
+                    ]).unwrap(), 
                 Instruction::INIT
             )
         ];
@@ -318,27 +300,27 @@ mod tests {
             context: RunOptionsContext::mock(),
             pos: 0,
         };
-        let args: Option<&Vec<Value>> = None;
+        let args: Option<&Vec<Micheline>> = None;
 
         assert!(initial_stack.len() == 1);
 
-        match run(initial_stack, args, &options, stack_snapshots) {
-            Err(err) => assert_eq!(err, String::from("Unexpected stack length, expected a length of 2 for instruction GET, got 1")),
+        match parse_args(args).and_then(|el_pos| run(initial_stack, el_pos, &options, stack_snapshots)) {
+            Err(err) => assert_eq!(err.to_string(), String::from("Unexpected stack length, expected a length of 2 for instruction GET, got 1")),
             Ok(_) => assert!(false)
         }
 
         // wrong elements in the stack
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Int(8), Instruction::INIT),
+            StackElement::new(MValue::new_int(8), Instruction::INIT),
             StackElement::new(MValue::Mutez(6_000_000), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
-        let args: Option<&Vec<Value>> = None;
+        let args: Option<&Vec<Micheline>> = None;
 
         assert!(initial_stack.len() == 2);
 
-        match run(initial_stack, args, &options, stack_snapshots) {
-            Err(err) => assert_eq!(err, String::from("Invalid type for `GET` expected 'map' or 'big_map', but got mutez")),
+        match parse_args(args).and_then(|el_pos| run(initial_stack, el_pos, &options, stack_snapshots)) {
+            Err(err) => assert_eq!(err.to_string(), String::from("Invalid type for `GET` expected 'map' or 'big_map', but got mutez")),
             Ok(_) => assert!(false)
         }
     }
@@ -347,21 +329,21 @@ mod tests {
     #[test]
     fn get_map_wrong_key_type() {
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Nat(6), Instruction::INIT),
+            StackElement::new(MValue::new_nat(6), Instruction::INIT),
             StackElement::new(
                 MValue::new_map(
                     MType::Int, 
                     MType::String, 
                     vec![
-                        (MValue::Int(5), MValue::new_string("tezos")),
-                        (MValue::Int(6), MValue::new_string("taquito")),
-                        (MValue::Int(7), MValue::new_string("hello")),
-                        (MValue::Int(8), MValue::new_string("world")),
-                        (MValue::Int(9), MValue::new_string("blockchain")),
-                    ]), 
+                        (MValue::new_int(5), MValue::new_string("tezos")),
+                        (MValue::new_int(6), MValue::new_string("taquito")),
+                        (MValue::new_int(7), MValue::new_string("hello")),
+                        (MValue::new_int(8), MValue::new_string("world")),
+                        (MValue::new_int(9), MValue::new_string("blockchain")),
+                    ]).unwrap(), 
                 Instruction::INIT
             ),
-            StackElement::new(MValue::Int(8), Instruction::INIT),
+            StackElement::new(MValue::new_int(8), Instruction::INIT),
             StackElement::new(MValue::Mutez(6_000_000), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
@@ -369,12 +351,12 @@ mod tests {
             context: RunOptionsContext::mock(),
             pos: 0,
         };
-        let args: Option<&Vec<Value>> = None;
+        let args: Option<&Vec<Micheline>> = None;
 
         assert!(initial_stack.len() == 4);
 
-        match run(initial_stack, args, &options, stack_snapshots) {
-            Err(err) => assert_eq!(err, String::from("Wrong type, expected `int` for instruction GET, got `nat`")),
+        match parse_args(args).and_then(|el_pos| run(initial_stack, el_pos, &options, stack_snapshots)) {
+            Err(err) => assert_eq!(err.to_string(), String::from("Wrong type, expected `int` for instruction GET, got `nat`")),
             Ok(_) => assert!(false)
         }
     }
@@ -388,31 +370,31 @@ mod tests {
             context: RunOptionsContext::mock(),
             pos: 0,
         };
-        let arg_value: Value = json!({ "int": "5" });
+        let arg_value = Micheline::Int(String::from("5"));
         let arg_vec = vec![arg_value];
-        let args: Option<&Vec<Value>> = Some(&arg_vec);
+        let args: Option<&Vec<Micheline>> = Some(&arg_vec);
 
         assert!(initial_stack.len() == 0);
 
-        match run(initial_stack, args, &options, stack_snapshots) {
-            Err(err) => assert_eq!(err, String::from("Unexpected stack length, expected a length of 1 for instruction GET, got 0")),
+        match parse_args(args).and_then(|el_pos| run(initial_stack, el_pos, &options, stack_snapshots)) {
+            Err(err) => assert_eq!(err.to_string(), String::from("Unexpected stack length, expected a length of 1 for instruction GET, got 0")),
             Ok(_) => assert!(false)
         }
 
         // wrong elements in the stack
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Int(8), Instruction::INIT),
+            StackElement::new(MValue::new_int(8), Instruction::INIT),
             StackElement::new(MValue::Mutez(6_000_000), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
-        let arg_value: Value = json!({ "int": "5" });
+        let arg_value = Micheline::Int(String::from("5"));
         let arg_vec = vec![arg_value];
-        let args: Option<&Vec<Value>> = Some(&arg_vec);
+        let args: Option<&Vec<Micheline>> = Some(&arg_vec);
 
         assert!(initial_stack.len() == 2);
 
-        match run(initial_stack, args, &options, stack_snapshots) {
-            Err(err) => assert_eq!(err, String::from("Wrong type, expected `pair` for instruction GET, got `int`")),
+        match parse_args(args).and_then(|el_pos| run(initial_stack, el_pos, &options, stack_snapshots)) {
+            Err(err) => assert_eq!(err.to_string(), String::from("Wrong type, expected `pair` for instruction GET, got `int`")),
             Ok(_) => assert!(false)
         }
     }
@@ -422,12 +404,12 @@ mod tests {
         let initial_stack: Stack = vec![
             StackElement::new(
                 MValue::Pair(PairValue::new(
-                    MValue::Nat(9),
-                    MValue::Nat(12),
+                    MValue::new_nat(9),
+                    MValue::new_nat(12),
                 )), 
                 Instruction::INIT
             ),
-            StackElement::new(MValue::Int(8), Instruction::INIT),
+            StackElement::new(MValue::new_int(8), Instruction::INIT),
             StackElement::new(MValue::Mutez(6_000_000), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
@@ -435,14 +417,14 @@ mod tests {
             context: RunOptionsContext::mock(),
             pos: 0,
         };
-        let arg_value: Value = json!({ "int": "5" });
+        let arg_value = Micheline::Int(String::from("5"));
         let arg_vec = vec![arg_value];
-        let args: Option<&Vec<Value>> = Some(&arg_vec);
+        let args: Option<&Vec<Micheline>> = Some(&arg_vec);
 
         assert!(initial_stack.len() == 3);
 
-        match run(initial_stack, args, &options, stack_snapshots) {
-            Err(err) => assert_eq!(err, String::from("The pair is not deep enough for instruction GET, expected a depth of 3, but got 1")),
+        match parse_args(args).and_then(|el_pos| run(initial_stack, el_pos, &options, stack_snapshots)) {
+            Err(err) => assert_eq!(err.to_string(), String::from("The pair is not deep enough for instruction GET, expected a depth of 3, but got 1")),
             Ok(_) => assert!(false)
         }
     }
@@ -453,18 +435,18 @@ mod tests {
         let initial_stack: Stack = vec![
             StackElement::new(
                 MValue::Pair(PairValue::new(
-                    MValue::Nat(9),
+                    MValue::new_nat(9),
                     MValue::Pair(PairValue::new(
-                        MValue::Nat(11),
+                        MValue::new_nat(11),
                         MValue::Pair(PairValue::new(
-                            MValue::Nat(12),
+                            MValue::new_nat(12),
                             MValue::new_string("taquito")
                         ))
                     ))
                 )), 
                 Instruction::INIT
             ),
-            StackElement::new(MValue::Int(8), Instruction::INIT),
+            StackElement::new(MValue::new_int(8), Instruction::INIT),
             StackElement::new(MValue::Mutez(6_000_000), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
@@ -472,43 +454,43 @@ mod tests {
             context: RunOptionsContext::mock(),
             pos: 0,
         };
-        let arg_value: Value = json!({ "string": "5" });
+        let arg_value = Micheline::String(String::from("5"));
         let arg_vec = vec![arg_value];
-        let args: Option<&Vec<Value>> = Some(&arg_vec);
+        let args: Option<&Vec<Micheline>> = Some(&arg_vec);
 
         assert!(initial_stack.len() == 3);
 
-        match run(initial_stack, args, &options, stack_snapshots) {
-            Err(err) => assert_eq!(err, String::from("Expected argument for GET instruction to be an object with an 'int' property")),
+        match parse_args(args).and_then(|el_pos| run(initial_stack, el_pos, &options, stack_snapshots)) {
+            Err(err) => assert_eq!(err.to_string(), String::from("Expected argument for GET instruction to be an int")),
             Ok(_) => assert!(false)
         }
 
         let initial_stack: Stack = vec![
             StackElement::new(
                 MValue::Pair(PairValue::new(
-                    MValue::Nat(9),
+                    MValue::new_nat(9),
                     MValue::Pair(PairValue::new(
-                        MValue::Nat(11),
+                        MValue::new_nat(11),
                         MValue::Pair(PairValue::new(
-                            MValue::Nat(12),
+                            MValue::new_nat(12),
                             MValue::new_string("taquito")
                         ))
                     ))
                 )), 
                 Instruction::INIT
             ),
-            StackElement::new(MValue::Int(8), Instruction::INIT),
+            StackElement::new(MValue::new_int(8), Instruction::INIT),
             StackElement::new(MValue::Mutez(6_000_000), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
-        let arg_value: Value = json!({ "int": "test" });
+        let arg_value = Micheline::Int(String::from("test"));
         let arg_vec = vec![arg_value];
-        let args: Option<&Vec<Value>> = Some(&arg_vec);
+        let args: Option<&Vec<Micheline>> = Some(&arg_vec);
 
         assert!(initial_stack.len() == 3);
 
-        match run(initial_stack, args, &options, stack_snapshots) {
-            Err(err) => assert_eq!(err, String::from("Expected argument for GET instruction to be a number, but got \"test\" instead (ParseIntError { kind: InvalidDigit })")),
+        match parse_args(args).and_then(|el_pos| run(initial_stack, el_pos, &options, stack_snapshots)) {
+            Err(err) => assert_eq!(err.to_string(), String::from("Expected argument for GET instruction to be a number, but got \"test\" instead (ParseIntError { kind: InvalidDigit })")),
             Ok(_) => assert!(false)
         }
     }
@@ -1,58 +1,37 @@
-use crate::errors::{display_error, ErrorCode};
+use crate::compiler::Block;
+use crate::errors::MichelsonError;
 use crate::instructions::{Instruction, RunOptions};
 use crate::m_types::MValue;
 use crate::parser;
 use crate::stack::{Stack, StackFuncs, StackSnapshots};
-use serde_json::Value;
 
 // https://tezos.gitlab.io/michelson-reference/#instr-IF
 
 /// runs the instruction with the provided stack and options
 pub fn run(
     stack: Stack,
-    args: Option<&Vec<Value>>,
+    (branch_if, branch_else): (&Block, &Block),
     options: &RunOptions,
     mut stack_snapshots: StackSnapshots,
-) -> Result<parser::RunResult, String> {
+) -> Result<parser::RunResult, MichelsonError> {
     // checks the stack
-    match stack.check_depth(1, Instruction::IF) {
-        Ok(_) => (),
-        Err(err) => panic!("{}", err),
-    };
-    // checks the arguments
-    let args = match args {
-        None => panic!("No argument provided for IF instruction"),
-        Some(args_) => {
-            if args_.len() != 2 {
-                panic!(
-                    "{:?}",
-                    display_error(ErrorCode::UnexpectedArgsNumber((2, args_.len())))
-                )
-            } else {
-                args_
-            }
-        }
-    };
+    stack.check_depth(1, Instruction::IF)?;
     // unwraps the value and removes the element from the stack
     let (or_element, new_stack) = stack.remove_at(options.pos);
     // processes the stack element value
     match or_element.value {
         MValue::Bool(val) => {
             // gets the corresponding arguments
-            let new_args = if val == true {
-                args[0].to_string()
-            } else {
-                args[1].to_string()
-            };
+            let branch = if val == true { branch_if } else { branch_else };
             // updates the stack snapshots
             stack_snapshots.push(new_stack.clone());
             // runs the code inside the argument
-            parser::run(new_args.as_str(), new_stack, stack_snapshots)
+            parser::run_block(branch, new_stack, stack_snapshots, options)
         }
-        _ => Err(display_error(ErrorCode::WrongType((
+        _ => Err(MichelsonError::WrongType((
             String::from("bool"),
             stack[options.pos].value.to_string(),
             Instruction::IF,
-        )))),
+        ))),
     }
 }
@@ -0,0 +1,56 @@
+use crate::compiler::Block;
+use crate::errors::MichelsonError;
+use crate::instructions::{Instruction, RunOptions};
+use crate::m_types::MValue;
+use crate::parser;
+use crate::stack::{Stack, StackElement, StackFuncs, StackSnapshots};
+
+// https://tezos.gitlab.io/michelson-reference/#instr-IF_CONS
+
+/// runs the instruction with the provided stack and options
+pub fn run(
+    stack: Stack,
+    (branch_cons, branch_nil): (&Block, &Block),
+    options: &RunOptions,
+    mut stack_snapshots: StackSnapshots,
+) -> Result<parser::RunResult, MichelsonError> {
+    // checks the stack
+    stack.check_depth(options.pos + 1, Instruction::IF_CONS)?;
+    // unwraps the value
+    let (list_element, new_stack) = stack.remove_at(options.pos);
+    // processes the stack element value
+    match list_element.value {
+        MValue::List(list) => {
+            let mut elements = *list.value;
+            // gets the corresponding branch and the stack to run it on
+            let (branch, new_stack) = if elements.is_empty() {
+                (branch_nil, new_stack)
+            } else {
+                // splits the list into its head and its tail, the head goes on top of the tail
+                let head = elements.remove(0);
+                (
+                    branch_cons,
+                    new_stack.insert_at(
+                        vec![
+                            StackElement::new(head, Instruction::IF_CONS),
+                            StackElement::new(
+                                MValue::new_list(elements, list.m_type),
+                                Instruction::IF_CONS,
+                            ),
+                        ],
+                        options.pos,
+                    ),
+                )
+            };
+            // updates the stack snapshots
+            stack_snapshots.push(new_stack.clone());
+            // runs the code inside the argument
+            parser::run_block(branch, new_stack, stack_snapshots, options)
+        }
+        value => Err(MichelsonError::WrongType((
+            String::from("list"),
+            value.get_type().to_string(),
+            Instruction::IF_CONS,
+        ))),
+    }
+}
@@ -1,47 +1,30 @@
-use crate::errors::{display_error, ErrorCode};
+use crate::compiler::Block;
+use crate::errors::MichelsonError;
 use crate::instructions::{Instruction, RunOptions};
 use crate::m_types::{MValue, Or};
 use crate::parser;
 use crate::stack::{Stack, StackElement, StackFuncs, StackSnapshots};
-use serde_json::Value;
 
 // https://tezos.gitlab.io/michelson-reference/#instr-IF_LEFT
 
 /// runs the instruction with the provided stack and options
 pub fn run(
     stack: Stack,
-    args: Option<&Vec<Value>>,
+    (branch_if, branch_else): (&Block, &Block),
     options: &RunOptions,
     mut stack_snapshots: StackSnapshots,
-) -> Result<parser::RunResult, String> {
+) -> Result<parser::RunResult, MichelsonError> {
     // checks the stack
-    match stack.check_depth(1, Instruction::IF_LEFT) {
-        Ok(_) => (),
-        Err(err) => panic!("{}", err),
-    };
-    // checks the arguments
-    let args = match args {
-        None => panic!("No argument provided for IF_LEFT instruction"),
-        Some(args_) => {
-            if args_.len() != 2 {
-                panic!(
-                    "{:?}",
-                    display_error(ErrorCode::UnexpectedArgsNumber((2, args_.len())))
-                )
-            } else {
-                args_
-            }
-        }
-    };
+    stack.check_depth(1, Instruction::IF_LEFT)?;
     // unwraps the value
     let (or_element, stack) = stack.remove_at(options.pos);
     // processes the stack element value
     match or_element.value {
         MValue::Or(box_) => {
             // gets the corresponding arguments and m_value
-            let (new_args, m_val): (String, MValue) = match *box_.value {
-                Or::Left(left_val) => (args[0].to_string(), left_val),
-                Or::Right(right_val) => (args[1].to_string(), right_val),
+            let (branch, m_val): (&Block, MValue) = match *box_.value {
+                Or::Left(left_val) => (branch_if, left_val),
+                Or::Right(right_val) => (branch_else, right_val),
             };
             // Pushes unwrapped value to the stack
             let mut stack_head = vec![StackElement::new(m_val, Instruction::IF_LEFT)];
@@ -50,12 +33,12 @@ pub fn run(
             // updates the stack snapshots
             stack_snapshots.push(stack_head.clone());
             // runs the code inside the argument
-            parser::run(new_args.as_str(), stack_head, stack_snapshots)
+            parser::run_block(branch, stack_head, stack_snapshots, options)
         }
-        _ => Err(display_error(ErrorCode::WrongType((
+        _ => Err(MichelsonError::WrongType((
             String::from("or"),
             stack[options.pos].value.to_string(),
             Instruction::IF_LEFT,
-        )))),
+        ))),
     }
 }
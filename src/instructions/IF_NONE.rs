@@ -0,0 +1,47 @@
+use crate::compiler::Block;
+use crate::errors::MichelsonError;
+use crate::instructions::{Instruction, RunOptions};
+use crate::m_types::MValue;
+use crate::parser;
+use crate::stack::{Stack, StackElement, StackFuncs, StackSnapshots};
+
+// https://tezos.gitlab.io/michelson-reference/#instr-IF_NONE
+
+/// runs the instruction with the provided stack and options
+pub fn run(
+    stack: Stack,
+    (branch_none, branch_some): (&Block, &Block),
+    options: &RunOptions,
+    mut stack_snapshots: StackSnapshots,
+) -> Result<parser::RunResult, MichelsonError> {
+    // checks the stack
+    stack.check_depth(options.pos + 1, Instruction::IF_NONE)?;
+    // unwraps the value
+    let (option_element, new_stack) = stack.remove_at(options.pos);
+    // processes the stack element value
+    match option_element.value {
+        MValue::Option(option) => {
+            // gets the corresponding branch and the stack to run it on
+            let (branch, new_stack) = match *option.value {
+                None => (branch_none, new_stack),
+                // pushes the unwrapped value to the stack
+                Some(value) => (
+                    branch_some,
+                    new_stack.insert_at(
+                        vec![StackElement::new(value, Instruction::IF_NONE)],
+                        options.pos,
+                    ),
+                ),
+            };
+            // updates the stack snapshots
+            stack_snapshots.push(new_stack.clone());
+            // runs the code inside the argument
+            parser::run_block(branch, new_stack, stack_snapshots, options)
+        }
+        value => Err(MichelsonError::WrongType((
+            String::from("option"),
+            value.get_type().to_string(),
+            Instruction::IF_NONE,
+        ))),
+    }
+}
@@ -1,4 +1,4 @@
-use crate::errors::{display_error, ErrorCode};
+use crate::errors::MichelsonError;
 use crate::instructions::{Instruction, RunOptions};
 use crate::m_types::{int, MType, MValue};
 use crate::stack::{Stack, StackElement, StackFuncs, StackSnapshots};
@@ -9,20 +9,17 @@ pub fn run(
     stack: Stack,
     options: &RunOptions,
     mut stack_snapshots: StackSnapshots,
-) -> Result<(Stack, StackSnapshots), String> {
+) -> Result<(Stack, StackSnapshots), MichelsonError> {
     // checks the stack
-    match stack.check_depth(options.pos + 1, Instruction::INT) {
-        Ok(_) => (),
-        Err(err) => panic!("{}", err),
-    };
+    stack.check_depth(options.pos + 1, Instruction::INT)?;
     // verifies that the value at options.pos is a nat
-    let new_val_res: Result<MValue, String> = match stack[options.pos].value {
-        MValue::Nat(val) => Ok(MValue::Int(val as int)),
-        _ => Err(display_error(ErrorCode::InvalidType((
+    let new_val_res: Result<MValue, MichelsonError> = match stack[options.pos].get_val() {
+        MValue::Nat(val) => Ok(MValue::Int(int::from(val))),
+        _ => Err(MichelsonError::InvalidType((
             vec![MType::Nat],
             stack[options.pos].value.get_type(),
             Instruction::INT,
-        )))),
+        ))),
     };
 
     match new_val_res {
@@ -52,8 +49,8 @@ mod tests {
     #[test]
     fn int_success() {
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Nat(5), Instruction::INIT),
-            StackElement::new(MValue::Int(6), Instruction::INIT),
+            StackElement::new(MValue::new_nat(5), Instruction::INIT),
+            StackElement::new(MValue::new_int(6), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
         let options = RunOptions {
@@ -67,7 +64,7 @@ mod tests {
             Err(_) => assert!(false),
             Ok((stack, _)) => {
                 assert!(stack.len() == 2);
-                assert_eq!(stack[options.pos].value, MValue::Int(5));
+                assert_eq!(stack[options.pos].value, MValue::new_int(5));
             }
         }
     }
@@ -76,8 +73,8 @@ mod tests {
     #[test]
     fn int_success_pos() {
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Int(5), Instruction::INIT),
-            StackElement::new(MValue::Nat(6), Instruction::INIT),
+            StackElement::new(MValue::new_int(5), Instruction::INIT),
+            StackElement::new(MValue::new_nat(6), Instruction::INIT),
             StackElement::new(MValue::Mutez(7_000_000), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
@@ -92,8 +89,8 @@ mod tests {
             Err(_) => assert!(false),
             Ok((stack, _)) => {
                 assert!(stack.len() == 3);
-                assert_eq!(stack[0].value, MValue::Int(5));
-                assert_eq!(stack[1].value, MValue::Int(6));
+                assert_eq!(stack[0].value, MValue::new_int(5));
+                assert_eq!(stack[1].value, MValue::new_int(6));
                 assert_eq!(stack[1].instruction, Instruction::INT);
                 assert_eq!(stack[2].value, MValue::Mutez(7_000_000));
             }
@@ -1,6 +1,6 @@
-use crate::errors::{display_error, ErrorCode};
+use crate::errors::MichelsonError;
 use crate::instructions::{Instruction, RunOptions};
-use crate::m_types::{nat, MType, MValue, OptionValue};
+use crate::m_types::{MType, MValue, OptionValue};
 use crate::stack::{Stack, StackElement, StackFuncs, StackSnapshots};
 
 // https://tezos.gitlab.io/michelson-reference/#instr-ISNAT
@@ -9,17 +9,14 @@ pub fn run(
     stack: Stack,
     options: &RunOptions,
     mut stack_snapshots: StackSnapshots,
-) -> Result<(Stack, StackSnapshots), String> {
+) -> Result<(Stack, StackSnapshots), MichelsonError> {
     // checks the stack
     stack.check_depth(options.pos + 1, Instruction::ISNAT)?;
     // value on the stack must be an int
-    match stack[options.pos].value {
+    match &stack[options.pos].value {
         MValue::Int(val) => {
-            let new_val = if val < 0 {
-                MValue::Option(OptionValue::new(None, MType::Nat))
-            } else {
-                MValue::Option(OptionValue::new(Some(MValue::Nat(val as nat)), MType::Nat))
-            };
+            // the conversion fails for negative values
+            let new_val = MValue::Option(OptionValue::new(val.to_biguint().map(MValue::Nat), MType::Nat));
             // updates the stack
             let new_stack = stack.replace(
                 vec![StackElement::new(new_val, Instruction::ISNAT)],
@@ -30,11 +27,11 @@ pub fn run(
 
             Ok((new_stack, stack_snapshots))
         }
-        _ => Err(display_error(ErrorCode::InvalidType((
+        _ => Err(MichelsonError::InvalidType((
             vec![MType::Int],
             stack[options.pos].value.get_type(),
             Instruction::ISNAT,
-        )))),
+        ))),
     }
 }
 
@@ -53,8 +50,8 @@ mod tests {
     #[test]
     fn isnat_positive_int_success() {
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Int(5), Instruction::INIT),
-            StackElement::new(MValue::Int(6), Instruction::INIT),
+            StackElement::new(MValue::new_int(5), Instruction::INIT),
+            StackElement::new(MValue::new_int(6), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
         let options = RunOptions {
@@ -70,10 +67,10 @@ mod tests {
                 assert!(stack.len() == 2);
                 assert_eq!(
                     stack[0].value,
-                    MValue::Option(OptionValue::new(Some(MValue::Nat(5)), MType::Nat))
+                    MValue::Option(OptionValue::new(Some(MValue::new_nat(5)), MType::Nat))
                 );
                 assert_eq!(stack[0].instruction, Instruction::ISNAT);
-                assert_eq!(stack[1].value, MValue::Int(6));
+                assert_eq!(stack[1].value, MValue::new_int(6));
                 assert_eq!(stack[1].instruction, Instruction::INIT);
             }
         }
@@ -83,8 +80,8 @@ mod tests {
     #[test]
     fn isnat_negative_int_success() {
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Int(-5), Instruction::INIT),
-            StackElement::new(MValue::Int(6), Instruction::INIT),
+            StackElement::new(MValue::new_int(-5), Instruction::INIT),
+            StackElement::new(MValue::new_int(6), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
         let options = RunOptions {
@@ -103,7 +100,7 @@ mod tests {
                     MValue::Option(OptionValue::new(None, MType::Nat))
                 );
                 assert_eq!(stack[0].instruction, Instruction::ISNAT);
-                assert_eq!(stack[1].value, MValue::Int(6));
+                assert_eq!(stack[1].value, MValue::new_int(6));
                 assert_eq!(stack[1].instruction, Instruction::INIT);
             }
         }
@@ -115,7 +112,7 @@ mod tests {
     fn isnat_wrong_stack() {
         let initial_stack: Stack = vec![
             StackElement::new(MValue::new_string("test"), Instruction::INIT),
-            StackElement::new(MValue::Int(6), Instruction::INIT),
+            StackElement::new(MValue::new_int(6), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
         let options = RunOptions {
@@ -127,7 +124,7 @@ mod tests {
 
         match run(initial_stack, &options, stack_snapshots) {
             Err(err) => assert_eq!(
-                err,
+                err.to_string(),
                 String::from("Invalid type for `ISNAT` expected int, but got string")
             ),
             Ok(_) => assert!(false),
@@ -0,0 +1,178 @@
+use crate::compiler::Block;
+use crate::errors::MichelsonError;
+use crate::instructions::{Instruction, RunOptions};
+use crate::m_types::{MValue, PairValue};
+use crate::parser;
+use crate::stack::{Stack, StackElement, StackFuncs, StackSnapshots};
+
+// https://tezos.gitlab.io/michelson-reference/#instr-ITER
+
+/// runs the code for every element of a list, a set or a map
+/// the elements of sets and the entries of maps are visited in the order of their keys
+pub fn run(
+    stack: Stack,
+    body: &Block,
+    options: &RunOptions,
+    stack_snapshots: StackSnapshots,
+) -> Result<parser::RunResult, MichelsonError> {
+    let this_instruction = Instruction::ITER;
+    // checks the stack
+    stack.check_depth(options.pos + 1, this_instruction)?;
+    // gets the elements to iterate over
+    let elements: Vec<MValue> = match stack[options.pos].get_val() {
+        MValue::List(list) => *list.value,
+        MValue::Set(set) => set.value.into_iter().collect(),
+        MValue::Map(map) => {
+            // every entry is pushed to the stack as a pair of the key and the value
+            map.value
+                .into_iter()
+                .map(|(key, value)| MValue::Pair(PairValue::new(key, value)))
+                .collect()
+        }
+        val => {
+            return Err(MichelsonError::WrongType((
+                String::from("list, set or map"),
+                val.get_type().to_string(),
+                this_instruction,
+            )))
+        }
+    };
+    // removes the collection from the stack
+    let (_, mut new_stack) = stack.remove_at(options.pos);
+    let mut stack_snapshots = stack_snapshots;
+    // pushes the current element to the stack and applies instructions
+    for element in elements.into_iter() {
+        let stack_to_process = new_stack.insert_at(
+            vec![StackElement::new(element, this_instruction)],
+            options.pos,
+        );
+        let result = parser::run_block(body, stack_to_process, stack_snapshots, options)?;
+        if result.has_failed() {
+            // the failure aborts the whole execution
+            return Ok(result);
+        }
+        new_stack = result.stack;
+        stack_snapshots = result.stack_snapshots;
+    }
+
+    Ok(parser::RunResult {
+        stack: new_stack,
+        stack_snapshots,
+        failed: None,
+    })
+}
+
+/*
+    TESTS
+*/
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler;
+    use crate::m_types::MType;
+
+    // PASSING
+    #[test]
+    fn iter_list_success() {
+        let initial_stack: Stack = vec![
+            StackElement::new(
+                MValue::new_list(vec![MValue::new_nat(1), MValue::new_nat(2), MValue::new_nat(3)], MType::Nat),
+                Instruction::INIT,
+            ),
+            StackElement::new(MValue::new_nat(10), Instruction::INIT),
+        ];
+
+        match run(initial_stack, &compiler::compile_test_code("{ ADD }"), &RunOptions::mock(), vec![]) {
+            Ok(result) => {
+                assert_eq!(result.stack.len(), 1);
+                assert_eq!(result.stack[0].value, MValue::new_nat(16));
+            }
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    #[test]
+    fn iter_set_in_key_order() {
+        let initial_stack: Stack = vec![
+            StackElement::new(
+                MValue::new_set(
+                    vec![MValue::new_string("c"), MValue::new_string("a"), MValue::new_string("b")],
+                    MType::String,
+                ),
+                Instruction::INIT,
+            ),
+            StackElement::new(MValue::new_empty_list(MType::String), Instruction::INIT),
+        ];
+
+        match run(initial_stack, &compiler::compile_test_code("{ CONS }"), &RunOptions::mock(), vec![]) {
+            Ok(result) => {
+                assert_eq!(result.stack.len(), 1);
+                // the last element visited is the first element of the list
+                assert_eq!(
+                    result.stack[0].value,
+                    MValue::new_list(
+                        vec![MValue::new_string("c"), MValue::new_string("b"), MValue::new_string("a")],
+                        MType::String
+                    )
+                );
+            }
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    #[test]
+    fn iter_map_in_key_order() {
+        let initial_stack: Stack = vec![
+            StackElement::new(
+                MValue::new_map(
+                    MType::Nat,
+                    MType::String,
+                    vec![
+                        (MValue::new_nat(3), MValue::new_string("c")),
+                        (MValue::new_nat(1), MValue::new_string("a")),
+                        (MValue::new_nat(2), MValue::new_string("b")),
+                    ],
+                ).unwrap(),
+                Instruction::INIT,
+            ),
+            StackElement::new(MValue::new_string(""), Instruction::INIT),
+        ];
+
+        match run(initial_stack, &compiler::compile_test_code("{ CDR ; CONCAT }"), &RunOptions::mock(), vec![]) {
+            Ok(result) => {
+                assert_eq!(result.stack.len(), 1);
+                assert_eq!(result.stack[0].value, MValue::new_string("cba"));
+            }
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    #[test]
+    fn iter_empty_list() {
+        let initial_stack: Stack = vec![
+            StackElement::new(MValue::new_empty_list(MType::Nat), Instruction::INIT),
+            StackElement::new(MValue::new_nat(10), Instruction::INIT),
+        ];
+
+        match run(initial_stack, &compiler::compile_test_code("{ ADD }"), &RunOptions::mock(), vec![]) {
+            Ok(result) => {
+                assert_eq!(result.stack.len(), 1);
+                assert_eq!(result.stack[0].value, MValue::new_nat(10));
+            }
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    // FAILING
+    #[test]
+    #[should_panic(expected = "Wrong type, expected `list, set or map` for instruction ITER, got `nat`")]
+    fn iter_wrong_type() {
+        let initial_stack: Stack = vec![StackElement::new(MValue::new_nat(10), Instruction::INIT)];
+
+        match run(initial_stack, &compiler::compile_test_code("{ DROP }"), &RunOptions::mock(), vec![]) {
+            Ok(_) => assert!(false),
+            Err(err) => panic!("{}", err),
+        }
+    }
+}
@@ -1,3 +1,4 @@
+use crate::errors::MichelsonError;
 use crate::instructions::{Instruction, RunOptions};
 use crate::m_types::MValue;
 use crate::stack::{Stack, StackElement, StackFuncs, StackSnapshots};
@@ -9,21 +10,21 @@ pub fn run(
     stack: Stack,
     options: &RunOptions,
     mut stack_snapshots: StackSnapshots,
-) -> Result<(Stack, StackSnapshots), String> {
+) -> Result<(Stack, StackSnapshots), MichelsonError> {
     // checks the stack
     stack.check_depth(options.pos + 1, Instruction::KECCAK)?;
     // KECCAK can be used only with bytes
-    let keccak_res: Result<MValue, String> = match &stack[options.pos].value {
+    let keccak_res: Result<MValue, MichelsonError> = match &stack[options.pos].value {
         MValue::Bytes(val) => {
             let mut hasher = Keccak256::new();
-            hasher.update(val.as_bytes());
+            hasher.update(val.as_slice());
             let result = hasher.finalize();
-            Ok(MValue::Bytes(format!("{:x}", result)))
+            Ok(MValue::new_bytes(&result))
         }
-        _ => Err(format!(
+        _ => Err(MichelsonError::Message(format!(
             "Expected value of type bytes for KECCAK, but got {}",
             &stack[options.pos].value.to_string()
-        )),
+        ))),
     };
 
     match keccak_res {
@@ -51,6 +52,7 @@ pub fn run(
 mod tests {
     use super::*;
     use crate::instructions::RunOptionsContext;
+    use crate::m_types::BytesValue;
 
     // PASSING
     // test for keccak 256 hashing
@@ -58,11 +60,11 @@ mod tests {
     fn keccak_success() {
         let initial_stack: Stack = vec![
             StackElement::new(
-                MValue::Bytes(String::from("7461717569746f")),
+                MValue::new_bytes(b"taquito"),
                 Instruction::INIT,
             ),
-            StackElement::new(MValue::Int(5), Instruction::INIT),
-            StackElement::new(MValue::Nat(6), Instruction::INIT),
+            StackElement::new(MValue::new_int(5), Instruction::INIT),
+            StackElement::new(MValue::new_nat(6), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
         let options = RunOptions {
@@ -81,16 +83,37 @@ mod tests {
                 assert!(stack.len() == 3);
                 assert_eq!(
                     stack[0].value,
-                    MValue::Bytes(String::from(
-                        "62670a3744b0147d3b94d7f349b72df593039540e3612356d69a21c68d51ebce"
-                    ))
+                    MValue::Bytes(
+                        BytesValue::from_hex("0x46484141a6864648946997b2d11d8a6d71bd45d5eacf9ef355b02304eaac9485")
+                            .unwrap()
+                    )
                 );
                 assert_eq!(stack[0].instruction, Instruction::KECCAK);
-                assert_eq!(stack[1].value, MValue::Int(5));
+                assert_eq!(stack[1].value, MValue::new_int(5));
                 assert_eq!(stack[1].instruction, Instruction::INIT);
-                assert_eq!(stack[2].value, MValue::Nat(6));
+                assert_eq!(stack[2].value, MValue::new_nat(6));
                 assert_eq!(stack[2].instruction, Instruction::INIT);
             }
         }
     }
+
+    #[test]
+    fn keccak_empty_bytes() {
+        let initial_stack: Stack = vec![StackElement::new(MValue::new_bytes(b""), Instruction::INIT)];
+        let options = RunOptions {
+            context: RunOptionsContext::mock(),
+            pos: 0,
+        };
+
+        match run(initial_stack, &options, vec![]) {
+            Err(err) => panic!("{}", err),
+            Ok((stack, _)) => assert_eq!(
+                stack[0].value,
+                MValue::Bytes(
+                    BytesValue::from_hex("0xc5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470")
+                        .unwrap()
+                )
+            ),
+        }
+    }
 }
@@ -0,0 +1,133 @@
+use crate::errors::MichelsonError;
+use crate::instructions::{Instruction, RunOptions};
+use crate::m_types::{LambdaValue, MType, MValue};
+use crate::micheline::Micheline;
+use crate::stack::{Stack, StackElement, StackFuncs, StackSnapshots};
+
+// https://tezos.gitlab.io/michelson-reference/#instr-LAMBDA
+// https://tezos.gitlab.io/michelson-reference/#instr-LAMBDA_REC
+
+/// decodes the types of the parameter and of the result and the code of the lambda
+/// the code is compiled with the lambda to report invalid instructions before the lambda is executed
+pub fn parse_args(args: Option<&Vec<Micheline>>, instruction: Instruction) -> Result<MValue, MichelsonError> {
+    match args {
+        None => Err(MichelsonError::NoArgument(instruction)),
+        Some(arg) => match arg.as_slice() {
+            [param_type @ Micheline::Prim { .. }, return_type @ Micheline::Prim { .. }, code @ Micheline::Seq(_)] => {
+                Ok(MValue::Lambda(LambdaValue::new(
+                    MType::from_micheline(param_type)?,
+                    MType::from_micheline(return_type)?,
+                    code.clone(),
+                    instruction == Instruction::LAMBDA_REC,
+                )?))
+            }
+            [_, _, code] => Err(MichelsonError::InvalidArgument((
+                String::from("sequence"),
+                code.to_string(),
+            ))),
+            _ => Err(MichelsonError::UnexpectedArgsNumber((3, arg.len()))),
+        },
+    }
+}
+
+pub fn run(
+    stack: Stack,
+    value: &MValue,
+    instruction: Instruction,
+    options: &RunOptions,
+    mut stack_snapshots: StackSnapshots,
+) -> Result<(Stack, StackSnapshots), MichelsonError> {
+    // checking the stack is not required
+    // pushes the lambda to the stack
+    let new_stack = stack.insert_at(vec![StackElement::new(value.clone(), instruction)], options.pos);
+    // updates the stack snapshots
+    stack_snapshots.push(new_stack.clone());
+    // returns the new stack
+    Ok((new_stack, stack_snapshots))
+}
+
+/*
+    TESTS
+*/
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::instructions::RunOptionsContext;
+
+    fn lambda_args(code: &str) -> Vec<Micheline> {
+        match format!("{{ LAMBDA {} }}", code).parse::<Micheline>().unwrap() {
+            Micheline::Seq(mut nodes) => match nodes.remove(0) {
+                Micheline::Prim { args, .. } => args,
+                node => panic!("Unexpected node {}", node),
+            },
+            node => panic!("Unexpected node {}", node),
+        }
+    }
+
+    // PASSING
+    #[test]
+    fn lambda_success() {
+        let args = lambda_args("nat nat { PUSH nat 1 ; ADD }");
+        let initial_stack: Stack = vec![StackElement::new(MValue::new_int(5), Instruction::INIT)];
+        let options = RunOptions {
+            context: RunOptionsContext::mock(),
+            pos: 0,
+        };
+
+        match parse_args(Some(&args), Instruction::LAMBDA)
+            .and_then(|value| run(initial_stack, &value, Instruction::LAMBDA, &options, vec![]))
+        {
+            Ok((new_stack, _)) => {
+                assert!(new_stack.len() == 2);
+                assert_eq!(new_stack[0].instruction, Instruction::LAMBDA);
+                assert_eq!(
+                    new_stack[0].value.get_type(),
+                    MType::Lambda(Box::new((MType::Nat, MType::Nat)))
+                );
+                match &new_stack[0].value {
+                    MValue::Lambda(lambda) => assert!(!lambda.recursive),
+                    val => panic!("Expected a lambda, got {}", val.to_string()),
+                }
+            }
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    #[test]
+    fn lambda_rec_success() {
+        let args = lambda_args("nat nat { DROP 2 ; PUSH nat 1 }");
+
+        match parse_args(Some(&args), Instruction::LAMBDA_REC) {
+            Ok(MValue::Lambda(lambda)) => {
+                assert!(lambda.recursive);
+                assert!(lambda.applied.is_empty());
+            }
+            Ok(val) => panic!("Expected a lambda, got {}", val.to_string()),
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    // FAILING
+    #[test]
+    #[should_panic(expected = "Unknown instruction FOO")]
+    fn lambda_invalid_code() {
+        let args = lambda_args("nat nat { FOO }");
+
+        match parse_args(Some(&args), Instruction::LAMBDA) {
+            Ok(_) => assert!(false),
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Unexpected number of arguments, expected `3`, got `2`")]
+    fn lambda_missing_type() {
+        let args = lambda_args("nat { DROP }");
+
+        match parse_args(Some(&args), Instruction::LAMBDA) {
+            Ok(_) => assert!(false),
+            Err(err) => panic!("{}", err),
+        }
+    }
+}
@@ -1,6 +1,7 @@
-use crate::errors::{display_error, ErrorCode};
+use crate::errors::MichelsonError;
 use crate::instructions::{Instruction, RunOptions};
 use crate::m_types::{MType, MValue};
+use num_traits::Signed;
 use crate::stack::{Stack, StackElement, StackFuncs, StackSnapshots};
 
 // https://tezos.gitlab.io/michelson-reference/#instr-EQ
@@ -9,18 +10,15 @@ pub fn run(
     stack: Stack,
     options: &RunOptions,
     mut stack_snapshots: StackSnapshots,
-) -> Result<(Stack, StackSnapshots), String> {
+) -> Result<(Stack, StackSnapshots), MichelsonError> {
     // checks the stack
-    match stack.check_depth(options.pos + 1, Instruction::LE) {
-        Ok(_) => (),
-        Err(err) => panic!("{}", err),
-    };
+    stack.check_depth(options.pos + 1, Instruction::LE)?;
 
     // the element on the stack must be an int
-    match stack[options.pos].value {
+    match &stack[options.pos].value {
         MValue::Int(val) => {
             let new_val = {
-                if val <= 0 {
+                if !val.is_positive() {
                     MValue::Bool(true)
                 } else {
                     MValue::Bool(false)
@@ -35,11 +33,11 @@ pub fn run(
 
             Ok((new_stack, stack_snapshots))
         }
-        _ => Err(display_error(ErrorCode::InvalidType((
+        _ => Err(MichelsonError::InvalidType((
             vec![MType::Int],
             stack[options.pos].value.get_type(),
             Instruction::LE,
-        )))),
+        ))),
     }
 }
 
@@ -57,7 +55,7 @@ mod tests {
     fn eq_success() {
         // should output true
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Int(-22), Instruction::INIT),
+            StackElement::new(MValue::new_int(-22), Instruction::INIT),
             StackElement::new(MValue::Mutez(6_000_000), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
@@ -81,7 +79,7 @@ mod tests {
 
         // should output true
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Int(0), Instruction::INIT),
+            StackElement::new(MValue::new_int(0), Instruction::INIT),
             StackElement::new(MValue::Mutez(6_000_000), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
@@ -105,7 +103,7 @@ mod tests {
 
         // should output false
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Int(3), Instruction::INIT),
+            StackElement::new(MValue::new_int(3), Instruction::INIT),
             StackElement::new(MValue::Mutez(6_000_000), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
@@ -155,7 +153,7 @@ mod tests {
     #[should_panic(expected = "Invalid type for `LE` expected int, but got nat")]
     fn eq_invalid_type() {
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Nat(0), Instruction::INIT),
+            StackElement::new(MValue::new_nat(0), Instruction::INIT),
             StackElement::new(MValue::Mutez(6_000_000), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
@@ -1,79 +1,75 @@
+use crate::errors::MichelsonError;
 use crate::instructions::{Instruction, LeftOrRight, RunOptions};
 use crate::m_types::{MType, MValue, Or, OrValue};
 use crate::stack::{Stack, StackElement, StackFuncs, StackSnapshots};
-use serde_json::Value;
+use crate::micheline::Micheline;
 
 // https://tezos.gitlab.io/michelson-reference/#instr-LEFT
 
-pub fn run(
-    stack: Stack,
-    args: Option<&Vec<Value>>,
-    options: &RunOptions,
-    mut stack_snapshots: StackSnapshots,
+/// decodes the type of the other side of the union
+pub fn parse_args(
+    args: Option<&Vec<Micheline>>,
     left_or_right: LeftOrRight,
-) -> Result<(Stack, StackSnapshots), String> {
-    // checks the stack
-    match stack.check_depth(options.pos + 1, Instruction::LEFT) {
-        Ok(_) => (),
-        Err(err) => panic!("{}", err),
-    };
+) -> Result<MType, MichelsonError> {
     // picks the right instruction
     let instruction = match left_or_right {
         LeftOrRight::Left => Instruction::LEFT,
         LeftOrRight::Right => Instruction::RIGHT,
     };
-    // matches on the argument
     match args {
         Some(arg) => {
-            if arg[0].is_object() {
-                let new_union = match arg[0]["prim"].as_str() {
-                    None => Err(format!(
-                        "Missing 'prim' field for {:?} instruction",
-                        instruction
-                    )),
-                    Some(str) => match MType::from_string(str) {
-                        Err(err) => Err(err),
-                        Ok(second_type) => {
-                            let val_to_wrap = stack[options.pos].value.clone();
-                            // gets the type for the value
-                            let val_type = stack[options.pos].value.get_type();
-                            // creates the value and wraps the value at options.pos
-                            let new_val = match left_or_right {
-                                LeftOrRight::Left => MValue::Or(OrValue::new(
-                                    Or::Left(val_to_wrap),
-                                    (val_type, second_type),
-                                )),
-                                LeftOrRight::Right => MValue::Or(OrValue::new(
-                                    Or::Right(val_to_wrap),
-                                    (second_type, val_type),
-                                )),
-                            };
-                            // creates the new stack element
-                            Ok(new_val)
-                        }
-                    },
-                }?;
-                // updates the stack
-                let new_stack =
-                    stack.replace(vec![StackElement::new(new_union, instruction)], options.pos);
-                // updates the stack snapshots
-                stack_snapshots.push(new_stack.clone());
-
-                Ok((new_stack, stack_snapshots))
+            if let type_node @ Micheline::Prim { .. } = &arg[0] {
+                MType::from_micheline(type_node)
             } else {
-                Err(format!(
-                    "Expected a 'serde_json::Value' of type object for {:?} instruction",
-                    instruction
-                ))
+                Err(MichelsonError::Message(format!(
+                    "Expected a type for {:?} instruction, got {}",
+                    instruction, arg[0]
+                )))
             }
         }
-        None => Err(format!(
+        None => Err(MichelsonError::Message(format!(
             "Arguments for {:?} instruction cannot be empty",
             instruction
-        )),
+        ))),
     }
 }
 
+pub fn run(
+    stack: Stack,
+    second_type: &MType,
+    options: &RunOptions,
+    mut stack_snapshots: StackSnapshots,
+    left_or_right: LeftOrRight,
+) -> Result<(Stack, StackSnapshots), MichelsonError> {
+    // checks the stack
+    stack.check_depth(options.pos + 1, Instruction::LEFT)?;
+    // picks the right instruction
+    let instruction = match left_or_right {
+        LeftOrRight::Left => Instruction::LEFT,
+        LeftOrRight::Right => Instruction::RIGHT,
+    };
+    let val_to_wrap = stack[options.pos].value.clone();
+    // gets the type for the value
+    let val_type = stack[options.pos].value.get_type();
+    // creates the value and wraps the value at options.pos
+    let new_union = match left_or_right {
+        LeftOrRight::Left => MValue::Or(OrValue::new(
+            Or::Left(val_to_wrap),
+            (val_type, second_type.clone()),
+        )),
+        LeftOrRight::Right => MValue::Or(OrValue::new(
+            Or::Right(val_to_wrap),
+            (second_type.clone(), val_type),
+        )),
+    };
+    // updates the stack
+    let new_stack = stack.replace(vec![StackElement::new(new_union, instruction)], options.pos);
+    // updates the stack snapshots
+    stack_snapshots.push(new_stack.clone());
+
+    Ok((new_stack, stack_snapshots))
+}
+
 /**
  * TESTS
  */
@@ -83,17 +79,16 @@ mod tests {
     use super::*;
     use crate::instructions::RunOptionsContext;
     use crate::m_types::{MType, OrValue};
-    use serde_json::json;
 
     // PASSING TESTS
     // pushes a new empty list of nat to the stack
     #[test]
     fn left_success() {
-        let arg_value: Value = json!({ "prim": "nat" });
+        let arg_value = Micheline::prim("nat", vec![]);
         let arg_vec = vec![arg_value];
-        let args: Option<&Vec<Value>> = Some(&arg_vec);
+        let args: Option<&Vec<Micheline>> = Some(&arg_vec);
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Int(5), Instruction::INIT),
+            StackElement::new(MValue::new_int(5), Instruction::INIT),
             StackElement::new(MValue::Mutez(6_000_000), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
@@ -104,17 +99,19 @@ mod tests {
 
         assert!(initial_stack.len() == 2);
 
-        match run(
-            initial_stack,
-            args,
-            &options,
-            stack_snapshots,
-            LeftOrRight::Left,
-        ) {
+        match parse_args(args, LeftOrRight::Left).and_then(|second_type| {
+            run(
+                initial_stack,
+                &second_type,
+                &options,
+                stack_snapshots,
+                LeftOrRight::Left,
+            )
+        }) {
             Ok((stack, _)) => {
                 let expected_union = OrValue {
                     m_type: (MType::Int, MType::Nat),
-                    value: Box::new(Or::Left(MValue::Int(5))),
+                    value: Box::new(Or::Left(MValue::new_int(5))),
                 };
                 assert!(stack.len() == 2);
                 assert_eq!(stack[0].value, MValue::Or(expected_union));
@@ -126,11 +123,11 @@ mod tests {
 
     #[test]
     fn right_success() {
-        let arg_value: Value = json!({ "prim": "int" });
+        let arg_value = Micheline::prim("int", vec![]);
         let arg_vec = vec![arg_value];
-        let args: Option<&Vec<Value>> = Some(&arg_vec);
+        let args: Option<&Vec<Micheline>> = Some(&arg_vec);
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Nat(5), Instruction::INIT),
+            StackElement::new(MValue::new_nat(5), Instruction::INIT),
             StackElement::new(MValue::Mutez(6_000_000), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
@@ -141,17 +138,19 @@ mod tests {
 
         assert!(initial_stack.len() == 2);
 
-        match run(
-            initial_stack,
-            args,
-            &options,
-            stack_snapshots,
-            LeftOrRight::Right,
-        ) {
+        match parse_args(args, LeftOrRight::Right).and_then(|second_type| {
+            run(
+                initial_stack,
+                &second_type,
+                &options,
+                stack_snapshots,
+                LeftOrRight::Right,
+            )
+        }) {
             Ok((stack, _)) => {
                 let expected_union = OrValue {
                     m_type: (MType::Int, MType::Nat),
-                    value: Box::new(Or::Right(MValue::Nat(5))),
+                    value: Box::new(Or::Right(MValue::new_nat(5))),
                 };
                 assert!(stack.len() == 2);
                 assert_eq!(stack[0].value, MValue::Or(expected_union));
@@ -165,9 +164,9 @@ mod tests {
     #[test]
     #[should_panic(expected = "Arguments for LEFT instruction cannot be empty")]
     fn left_empty_arg() {
-        let args: Option<&Vec<Value>> = None;
+        let args: Option<&Vec<Micheline>> = None;
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Int(5), Instruction::INIT),
+            StackElement::new(MValue::new_int(5), Instruction::INIT),
             StackElement::new(MValue::Mutez(6_000_000), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
@@ -178,26 +177,28 @@ mod tests {
 
         assert!(initial_stack.len() == 2);
 
-        match run(
-            initial_stack,
-            args,
-            &options,
-            stack_snapshots,
-            LeftOrRight::Left,
-        ) {
+        match parse_args(args, LeftOrRight::Left).and_then(|second_type| {
+            run(
+                initial_stack,
+                &second_type,
+                &options,
+                stack_snapshots,
+                LeftOrRight::Left,
+            )
+        }) {
             Ok(_) => assert!(false),
             Err(err) => panic!("{}", err),
         }
     }
 
     #[test]
-    #[should_panic(expected = "Missing 'prim' field for RIGHT instruction")]
+    #[should_panic(expected = "Expected a type for RIGHT instruction, got \"int\"")]
     fn right_arg_wrong_format() {
-        let arg_value: Value = json!({ "string": "int" });
+        let arg_value = Micheline::String(String::from("int"));
         let arg_vec = vec![arg_value];
-        let args: Option<&Vec<Value>> = Some(&arg_vec);
+        let args: Option<&Vec<Micheline>> = Some(&arg_vec);
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Int(5), Instruction::INIT),
+            StackElement::new(MValue::new_int(5), Instruction::INIT),
             StackElement::new(MValue::Mutez(6_000_000), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
@@ -208,13 +209,15 @@ mod tests {
 
         assert!(initial_stack.len() == 2);
 
-        match run(
-            initial_stack,
-            args,
-            &options,
-            stack_snapshots,
-            LeftOrRight::Right,
-        ) {
+        match parse_args(args, LeftOrRight::Right).and_then(|second_type| {
+            run(
+                initial_stack,
+                &second_type,
+                &options,
+                stack_snapshots,
+                LeftOrRight::Right,
+            )
+        }) {
             Ok(_) => assert!(false),
             Err(err) => panic!("{}", err),
         }
@@ -225,9 +228,9 @@ mod tests {
         expected = "Unexpected stack length, expected a length of 1 for instruction LEFT, got 0"
     )]
     fn left_wrong_stack() {
-        let arg_value: Value = json!({ "prim": "int" });
+        let arg_value = Micheline::prim("int", vec![]);
         let arg_vec = vec![arg_value];
-        let args: Option<&Vec<Value>> = Some(&arg_vec);
+        let args: Option<&Vec<Micheline>> = Some(&arg_vec);
         let initial_stack: Stack = vec![];
         let stack_snapshots = vec![];
         let options = RunOptions {
@@ -237,13 +240,15 @@ mod tests {
 
         assert!(initial_stack.len() == 0);
 
-        match run(
-            initial_stack,
-            args,
-            &options,
-            stack_snapshots,
-            LeftOrRight::Left,
-        ) {
+        match parse_args(args, LeftOrRight::Left).and_then(|second_type| {
+            run(
+                initial_stack,
+                &second_type,
+                &options,
+                stack_snapshots,
+                LeftOrRight::Left,
+            )
+        }) {
             Ok(_) => assert!(false),
             Err(err) => panic!("{}", err),
         }
@@ -1,5 +1,6 @@
+use crate::errors::MichelsonError;
 use crate::instructions::{Instruction, RunOptions};
-use crate::m_types::MValue;
+use crate::m_types::{nat, MValue};
 use crate::stack::{Stack, StackElement, StackFuncs, StackSnapshots};
 
 // https://tezos.gitlab.io/michelson-reference/#instr-LEVEL
@@ -8,10 +9,10 @@ pub fn run(
     stack: Stack,
     options: &RunOptions,
     mut stack_snapshots: StackSnapshots,
-) -> Result<(Stack, StackSnapshots), String> {
+) -> Result<(Stack, StackSnapshots), MichelsonError> {
     // no stack check required
     // updates the stack
-    let new_el = StackElement::new(MValue::Nat(options.context.level + 1), Instruction::LEVEL);
+    let new_el = StackElement::new(MValue::Nat(nat::from(options.context.level + 1)), Instruction::LEVEL);
     let new_stack = stack.insert_at(vec![new_el], options.pos);
     // updates the stack snapshots
     stack_snapshots.push(new_stack.clone());
@@ -31,7 +32,7 @@ mod tests {
     fn level_success() {
         // should push the address to the stack
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Int(22), Instruction::INIT),
+            StackElement::new(MValue::new_int(22), Instruction::INIT),
             StackElement::new(MValue::Mutez(6_000_000), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
@@ -46,9 +47,9 @@ mod tests {
             Err(_) => assert!(false),
             Ok((stack, _)) => {
                 assert!(stack.len() == 3);
-                assert_eq!(stack[0].value, MValue::Nat(12));
+                assert_eq!(stack[0].value, MValue::new_nat(12));
                 assert_eq!(stack[0].instruction, Instruction::LEVEL);
-                assert_eq!(stack[1].value, MValue::Int(22));
+                assert_eq!(stack[1].value, MValue::new_int(22));
                 assert_eq!(stack[1].instruction, Instruction::INIT);
                 assert_eq!(stack[2].value, MValue::Mutez(6_000_000));
                 assert_eq!(stack[2].instruction, Instruction::INIT);
@@ -0,0 +1,151 @@
+use crate::compiler::Block;
+use crate::errors::MichelsonError;
+use crate::instructions::{Instruction, RunOptions};
+use crate::m_types::MValue;
+use crate::parser;
+use crate::stack::{Stack, StackFuncs, StackSnapshots};
+
+// https://tezos.gitlab.io/michelson-reference/#instr-LOOP
+
+/// runs the code as long as the boolean on top of the stack is true
+/// the loop fails after the maximum number of iterations set in the context
+pub fn run(
+    stack: Stack,
+    body: &Block,
+    options: &RunOptions,
+    mut stack_snapshots: StackSnapshots,
+) -> Result<parser::RunResult, MichelsonError> {
+    let this_instruction = Instruction::LOOP;
+    let mut stack = stack;
+    let mut iterations: usize = 0;
+    loop {
+        // checks the stack
+        stack.check_depth(options.pos + 1, this_instruction)?;
+        // removes the condition from the stack
+        let (condition, new_stack) = stack.remove_at(options.pos);
+        // updates the stack snapshots
+        stack_snapshots.push(new_stack.clone());
+        match condition.value {
+            MValue::Bool(false) => {
+                return Ok(parser::RunResult {
+                    stack: new_stack,
+                    stack_snapshots,
+                    failed: None,
+                })
+            }
+            MValue::Bool(true) => {
+                if iterations == options.context.max_loop_iterations {
+                    return Err(MichelsonError::TooManyIterations((
+                        iterations,
+                        this_instruction,
+                    )));
+                }
+                iterations += 1;
+                // runs the code, a failure is returned as is
+                let result = parser::run_block(body, new_stack, stack_snapshots, options)?;
+                if result.has_failed() {
+                    return Ok(result);
+                }
+                stack = result.stack;
+                stack_snapshots = result.stack_snapshots;
+            }
+            value => {
+                return Err(MichelsonError::WrongType((
+                    String::from("bool"),
+                    value.get_type().to_string(),
+                    this_instruction,
+                )))
+            }
+        }
+    }
+}
+
+/*
+    TESTS
+*/
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler;
+    use crate::instructions::RunOptionsContext;
+    use crate::stack::StackElement;
+
+    // PASSING
+    #[test]
+    fn loop_success() {
+        // counts down from 5 to 0
+        let initial_stack: Stack = vec![
+            StackElement::new(MValue::Bool(true), Instruction::INIT),
+            StackElement::new(MValue::new_int(5), Instruction::INIT),
+        ];
+        let options = RunOptions {
+            context: RunOptionsContext::mock(),
+            pos: 0,
+        };
+        let body = compiler::compile_test_code("{ PUSH int 1 ; SWAP ; SUB ; DUP ; GT }");
+
+        match run(initial_stack, &body, &options, vec![]) {
+            Ok(result) => {
+                assert_eq!(result.stack.len(), 1);
+                assert_eq!(result.stack[0].value, MValue::new_int(0));
+            }
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    #[test]
+    fn loop_false() {
+        let initial_stack: Stack = vec![
+            StackElement::new(MValue::Bool(false), Instruction::INIT),
+            StackElement::new(MValue::new_int(5), Instruction::INIT),
+        ];
+        let options = RunOptions {
+            context: RunOptionsContext::mock(),
+            pos: 0,
+        };
+
+        match run(initial_stack, &compiler::compile_test_code("{ FAILWITH }"), &options, vec![]) {
+            Ok(result) => {
+                assert!(!result.has_failed());
+                assert_eq!(result.stack.len(), 1);
+                assert_eq!(result.stack[0].value, MValue::new_int(5));
+            }
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    // FAILING
+    #[test]
+    #[should_panic(expected = "Instruction LOOP exceeded the maximum number of iterations (100)")]
+    fn loop_too_many_iterations() {
+        let initial_stack: Stack = vec![StackElement::new(MValue::Bool(true), Instruction::INIT)];
+        let options = RunOptions {
+            context: RunOptionsContext {
+                max_loop_iterations: 100,
+                ..RunOptionsContext::mock()
+            },
+            pos: 0,
+        };
+
+        match run(initial_stack, &compiler::compile_test_code("{ PUSH bool True }"), &options, vec![]) {
+            Ok(_) => assert!(false),
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Wrong type, expected `bool` for instruction LOOP, got `int`")]
+    fn loop_wrong_type() {
+        let initial_stack: Stack = vec![StackElement::new(MValue::new_int(1), Instruction::INIT)];
+        let options = RunOptions {
+            context: RunOptionsContext::mock(),
+            pos: 0,
+        };
+
+        match run(initial_stack, &compiler::compile_test_code("{ PUSH int 1 }"), &options, vec![]) {
+            Ok(_) => assert!(false),
+            Err(err) => panic!("{}", err),
+        }
+    }
+}
@@ -0,0 +1,152 @@
+use crate::compiler::Block;
+use crate::errors::MichelsonError;
+use crate::instructions::{Instruction, RunOptions};
+use crate::m_types::{MValue, Or};
+use crate::parser;
+use crate::stack::{Stack, StackElement, StackFuncs, StackSnapshots};
+
+// https://tezos.gitlab.io/michelson-reference/#instr-LOOP_LEFT
+
+/// runs the code with the value of `Left` until the value on top of the stack is `Right`,
+/// the value of `Right` is left on the stack
+/// the loop fails after the maximum number of iterations set in the context
+pub fn run(
+    stack: Stack,
+    body: &Block,
+    options: &RunOptions,
+    mut stack_snapshots: StackSnapshots,
+) -> Result<parser::RunResult, MichelsonError> {
+    let this_instruction = Instruction::LOOP_LEFT;
+    let mut stack = stack;
+    let mut iterations: usize = 0;
+    loop {
+        // checks the stack
+        stack.check_depth(options.pos + 1, this_instruction)?;
+        // unwraps the value
+        let (or_element, new_stack) = stack.remove_at(options.pos);
+        let or_value = match or_element.value {
+            MValue::Or(or_value) => *or_value.value,
+            value => {
+                return Err(MichelsonError::WrongType((
+                    String::from("or"),
+                    value.get_type().to_string(),
+                    this_instruction,
+                )))
+            }
+        };
+        match or_value {
+            Or::Right(value) => {
+                // the loop ends with the value of `Right` on the stack
+                let new_stack = new_stack.insert_at(
+                    vec![StackElement::new(value, this_instruction)],
+                    options.pos,
+                );
+                // updates the stack snapshots
+                stack_snapshots.push(new_stack.clone());
+                return Ok(parser::RunResult {
+                    stack: new_stack,
+                    stack_snapshots,
+                    failed: None,
+                });
+            }
+            Or::Left(value) => {
+                if iterations == options.context.max_loop_iterations {
+                    return Err(MichelsonError::TooManyIterations((
+                        iterations,
+                        this_instruction,
+                    )));
+                }
+                iterations += 1;
+                let new_stack = new_stack.insert_at(
+                    vec![StackElement::new(value, this_instruction)],
+                    options.pos,
+                );
+                // updates the stack snapshots
+                stack_snapshots.push(new_stack.clone());
+                // runs the code, a failure is returned as is
+                let result = parser::run_block(body, new_stack, stack_snapshots, options)?;
+                if result.has_failed() {
+                    return Ok(result);
+                }
+                stack = result.stack;
+                stack_snapshots = result.stack_snapshots;
+            }
+        }
+    }
+}
+
+/*
+    TESTS
+*/
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler;
+    use crate::instructions::RunOptionsContext;
+    use crate::m_types::{MType, OrValue};
+
+    // PASSING
+    #[test]
+    fn loop_left_success() {
+        // doubles the value until it is greater than 100
+        let initial_stack: Stack = vec![StackElement::new(
+            MValue::Or(OrValue::new(Or::Left(MValue::new_nat(3)), (MType::Nat, MType::Nat))),
+            Instruction::INIT,
+        )];
+        let options = RunOptions {
+            context: RunOptionsContext::mock(),
+            pos: 0,
+        };
+        let body = compiler::compile_test_code(
+            "{ PUSH nat 2 ; MUL ; DUP ; PUSH nat 100 ; COMPARE ; LT ; IF { RIGHT nat } { LEFT nat } }",
+        );
+
+        match run(initial_stack, &body, &options, vec![]) {
+            Ok(result) => {
+                assert_eq!(result.stack.len(), 1);
+                assert_eq!(result.stack[0].value, MValue::new_nat(192));
+            }
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    // FAILING
+    #[test]
+    #[should_panic(
+        expected = "Instruction LOOP_LEFT exceeded the maximum number of iterations (10)"
+    )]
+    fn loop_left_too_many_iterations() {
+        let initial_stack: Stack = vec![StackElement::new(
+            MValue::Or(OrValue::new(Or::Left(MValue::Unit), (MType::Unit, MType::Unit))),
+            Instruction::INIT,
+        )];
+        let options = RunOptions {
+            context: RunOptionsContext {
+                max_loop_iterations: 10,
+                ..RunOptionsContext::mock()
+            },
+            pos: 0,
+        };
+
+        match run(initial_stack, &compiler::compile_test_code("{ LEFT unit }"), &options, vec![]) {
+            Ok(_) => assert!(false),
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Wrong type, expected `or` for instruction LOOP_LEFT, got `nat`")]
+    fn loop_left_wrong_type() {
+        let initial_stack: Stack = vec![StackElement::new(MValue::new_nat(3), Instruction::INIT)];
+        let options = RunOptions {
+            context: RunOptionsContext::mock(),
+            pos: 0,
+        };
+
+        match run(initial_stack, &compiler::compile_test_code("{ LEFT nat }"), &options, vec![]) {
+            Ok(_) => assert!(false),
+            Err(err) => panic!("{}", err),
+        }
+    }
+}
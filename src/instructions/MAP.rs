@@ -1,17 +1,38 @@
+use crate::errors::MichelsonError;
+use crate::compiler::Block;
 use crate::instructions::{Instruction, RunOptions};
-use crate::m_types::{MValue, CollectionValue, MType, PairValue};
+use crate::m_types::{MValue, CollectionValue, MType, OptionValue, PairValue};
 use crate::stack::{Stack, StackFuncs, StackSnapshots};
 use crate::parser;
-use serde_json::Value;
+use crate::typechecker;
 
 // https://tezos.gitlab.io/michelson-reference/#instr-MAP
 
+/// checks that the body returns a value of the same type on every iteration
+/// the type returned by the first iteration is saved in `result_type`
+fn check_result_type(result_type: &mut Option<MType>, value: &MValue, pos: usize) -> Result<(), MichelsonError> {
+    let value_type = value.get_type();
+    match result_type {
+        Some(expected) if *expected != value_type => Err(MichelsonError::InvalidStack((
+            pos,
+            expected.clone(),
+            value_type,
+            Instruction::MAP,
+        ))),
+        Some(_) => Ok(()),
+        None => {
+            *result_type = Some(value_type);
+            Ok(())
+        }
+    }
+}
+
 pub fn run(
     stack: Stack,
-    args: Option<&Vec<Value>>,
+    body: &Block,
     options: &RunOptions,
     stack_snapshots: StackSnapshots,
-) -> Result<(Stack, StackSnapshots), String> {
+) -> Result<parser::RunResult, MichelsonError> {
     let this_instruction = Instruction::MAP;
     // checks the stack
     stack.check_depth(options.pos + 1, this_instruction)?;
@@ -24,210 +45,145 @@ pub fn run(
                 // removes the list from the stack
                 let (_, stack_without_list) = stack.remove_at(options.pos);
                 // loops through the list and applies instructions
-                match args {
-                    None => Ok((stack, stack_snapshots)), // an empty instruction block is possible, just returning the current stack
-                    Some (args_) => {
-                        if args_.len() == 1 {
-                            let list_len = list.value.len();
-                            // converts serde_json Value to string to run the code
-                            let code_block_json = serde_json::to_string(&args_[0]).unwrap();
-                            // iterates through the list, pushes the current element to the stack and applies instructions
-                            let mut new_list: Vec<MValue> = vec![];
-                            let (new_stack, stack_snapshots) = 
-                            list
-                            .value
-                            .into_iter()
-                            .try_fold(
-                                (stack_without_list, stack_snapshots), 
-                                |(stack, stack_snapshots), list_el| {
-                                    let stack_to_process = stack.push(list_el, this_instruction);
-                                    match parser::run(&code_block_json, stack_to_process, stack_snapshots) {
-                                        Ok(result) => {
-                                            if result.has_failed {
-                                                Err(String::from("Block code for instruction MAP could not be parsed"))
-                                            } else {
-                                                // removes the elements that was created from the stack
-                                                let (new_el, truncated_stack) = result.stack.remove_at(0);
-                                                // saves the new element in the list of new elements
-                                                new_list.push(new_el.value);
-                                                // returns the new stack and stack snapshots
-                                                Ok(
-                                                    (
-                                                        truncated_stack, 
-                                                        result.stack_snapshots                                                     
-                                                    )
-                                                )
-                                            }
-                                        },
-                                        Err(err) => Err(err)
-                                    }
-                                })?;                                
-                                // checks that the new list length is the same as the original list
-                                if list_len == new_list.len() {
-                                    // checks that all the elements in the new list are of the same type
-                                    // and figures out the type of the elements of the new list
-                                    let list_el_type = MType::check_vec_els_type(&new_list, this_instruction)?;
-                                    // creates the new list
-                                    let collection = CollectionValue { 
-                                        m_type: list_el_type, 
-                                        value: Box::new(new_list)
-                                    };
-                                    let new_list = MValue::List(collection);
+                let list_len = list.value.len();
+                // iterates through the list, pushes the current element to the stack and applies instructions
+                let mut new_list: Vec<MValue> = vec![];
+                let mut list_el_type: Option<MType> = None;
+                let (mut new_stack, mut stack_snapshots) = (stack_without_list, stack_snapshots);
+                for list_el in list.value.into_iter() {
+                    let stack_to_process = new_stack.push(list_el, this_instruction);
+                    let result = parser::run_block(body, stack_to_process, stack_snapshots, options)?;
+                    if result.has_failed() {
+                        // the failure aborts the whole execution
+                        return Ok(result);
+                    }
+                    // removes the elements that was created from the stack
+                    let (new_el, truncated_stack) = result.stack.remove_at(0);
+                    check_result_type(&mut list_el_type, &new_el.value, options.pos)?;
+                    // saves the new element in the list of new elements
+                    new_list.push(new_el.value);
+                    new_stack = truncated_stack;
+                    stack_snapshots = result.stack_snapshots;
+                }
+                // checks that the new list length is the same as the original list
+                if list_len == new_list.len() {
+                    // checks that all the elements in the new list are of the same type
+                    // and figures out the type of the elements of the new list
+                    let list_el_type = MType::check_vec_els_type(&new_list, this_instruction)?;
+                    // creates the new list
+                    let collection = CollectionValue { 
+                        m_type: list_el_type, 
+                        value: Box::new(new_list)
+                    };
+                    let new_list = MValue::List(collection);
+                    // pushes the new list onto the stack
+                    let new_stack = new_stack.push(new_list, this_instruction);
+                    
+                    Ok((new_stack, stack_snapshots))
+                } else {
+                    Err(MichelsonError::Message(
+                        format!("List generated by MAP instruction has a different length, expected a length of {}, but got {}", list_len, new_list.len())
+                    ))
+                }                        
+            } else {
+                // returns now if there are no element in the list
+                Ok((stack, stack_snapshots))
+            }
+        },
+        MValue::Map(map) => {
+            match map.size() {
+                Ok(map_size) => {
+                    if map_size != 0 {
+                        // removes the map from the stack
+                        let (_, stack_without_map) = stack.remove_at(options.pos);
+                        // iterates through the map, pushes the key and value as a pair to the stack and applies instructions
+                        let mut new_map_els: Vec<(MValue, MValue)> = vec![];
+                        let mut map_value_type: Option<MType> = None;
+                        let key_type = map.key_type.clone();
+                        let (mut new_stack, mut stack_snapshots) = (stack_without_map, stack_snapshots);
+                        // the entries are processed in the order of their keys
+                        for (key, value) in map.value.into_iter() {
+                            // creates the pair to be pushed to the stack
+                            let map_el = MValue::Pair(PairValue::new(key.clone(), value));
+                            let stack_to_process = new_stack.push(map_el, this_instruction);
+                            let result = parser::run_block(body, stack_to_process, stack_snapshots, options)?;
+                            if result.has_failed() {
+                                // the failure aborts the whole execution
+                                return Ok(result);
+                            }
+                            // removes the elements that was created from the stack
+                            let (new_el, truncated_stack) = result.stack.remove_at(0);
+                            check_result_type(&mut map_value_type, &new_el.value, options.pos)?;
+                            // saves the new element in the list of new elements
+                            new_map_els.push((key, new_el.value));
+                            new_stack = truncated_stack;
+                            stack_snapshots = result.stack_snapshots;
+                        }
+                        // checks that the length of the vector with the returned elements is the same as the original map
+                        if map_size == new_map_els.len() {
+                            match map_value_type {
+                                None => Err(MichelsonError::Message("No value type for the map created by MAP instruction was generated".to_string())),
+                                Some(map_value_type) => {
+                                    // creates the new map
+                                    let new_map = MValue::new_map(key_type, map_value_type, new_map_els)?;
                                     // pushes the new list onto the stack
-                                    let new_stack = new_stack.push(new_list, this_instruction);
+                                    let new_stack = new_stack.push(new_map, this_instruction);
                                     
                                     Ok((new_stack, stack_snapshots))
-                                } else {
-                                    Err(
-                                        format!("List generated by MAP instruction has a different length, expected a length of {}, but got {}", list_len, new_list.len())
-                                    )
-                                }                        
-                            } else {
-                                Err("Argument for MAP is an empty array, expected an array with 1 element".to_string())
+                                }
                             }
-                        }
-                    }
-                } else {
-                    // returns now if there are no element in the list
-                    Ok((stack, stack_snapshots))
-                }
-            },
-            MValue::Map(map) => {
-                match map.size() {
-                    Ok(map_size) => {
-                        if map_size != 0 {
-                            // removes the map from the stack
-                            let (_, stack_without_map) = stack.remove_at(options.pos);
-                            // loops through the list and applies instructions
-                            match args {
-                                None => Ok((stack, stack_snapshots)), // an empty instruction block is possible, just returning the current stack
-                                Some (args_) => {
-                                    if args_.len() == 1 {
-                                        // converts serde_json Value to string to run the code
-                                        let code_block_json = serde_json::to_string(&args_[0]).unwrap();
-                                        // iterates through the map, pushes the key and value as a pair to the stack and applies instructions
-                                        let mut new_map_els: Vec<(MValue, MValue)> = vec![];
-                                        let mut map_key_type: Option<MType> = None;
-                                        let (new_stack, stack_snapshots) = 
-                                            map
-                                            .value
-                                            .into_iter()
-                                            .try_fold(
-                                                (stack_without_map, stack_snapshots), 
-                                                |(stack, stack_snapshots), pair| {
-                                                    let (key, value) = pair;
-                                                    // checks that the type is the same
-                                                    // TODO: it might be wiser to check that the key type is consistent throughout the loop
-                                                    map_key_type = Some(key.get_type());
-                                                    // creates the pair to be pushed to the stack
-                                                    let map_el = MValue::Pair(PairValue::new(key.clone(), value));
-                                                    let stack_to_process = stack.push(map_el, this_instruction);
-                                                    match parser::run(&code_block_json, stack_to_process, stack_snapshots) {
-                                                        Ok(result) => {
-                                                            if result.has_failed {
-                                                                Err(String::from("Block code for instruction MAP could not be parsed"))
-                                                            } else {
-                                                                // removes the elements that was created from the stack
-                                                                let (new_el, truncated_stack) = result.stack.remove_at(0);
-                                                                // saves the new element in the list of new elements
-                                                                new_map_els.push((key, new_el.value));
-                                                                // returns the new stack and stack snapshots
-                                                                Ok(
-                                                                    (
-                                                                        truncated_stack, 
-                                                                        result.stack_snapshots                                                     
-                                                                    )
-                                                                )
-                                                            }
-                                                        },
-                                                        Err(err) => Err(err)
-                                                    }
-                                                })?;                                
-                                            // checks that the length of the vector with the returned elements is the same as the original map
-                                            if map_size == new_map_els.len() {
-                                                match map_key_type {
-                                                    None => Err("No key type for the map created by MAP instruction was generated".to_string()),
-                                                    Some(key_type) => {
-                                                        // checks that all the elements in the new list are of the same type
-                                                        // and figures out the type of the elements of the new list
-                                                        let map_value_type = MType::check_vec_els_type(
-                                                            &new_map_els.clone().into_iter().map(|el| el.1).collect(), 
-                                                            this_instruction
-                                                        )?;
-                                                        // creates the new map
-                                                        let new_map = MValue::new_map(key_type, map_value_type, new_map_els);
-                                                        // pushes the new list onto the stack
-                                                        let new_stack = new_stack.push(new_map, this_instruction);
-                                                        
-                                                        Ok((new_stack, stack_snapshots))
-                                                    }
-                                                }
-                                            } else {
-                                                Err(
-                                                    format!("Map generated by MAP instruction has a different length, expected a length of {}, but got {}", map_size, new_map_els.len())
-                                                )
-                                            }
-                                    } else {
-                                        Err("Argument for MAP is an empty array, expected an array with 1 element".to_string())
-                                    }
-                                }}
                         } else {
-                            // the map is empty
-                            Ok((stack, stack_snapshots))
+                            Err(MichelsonError::Message(
+                                format!("Map generated by MAP instruction has a different length, expected a length of {}, but got {}", map_size, new_map_els.len())
+                            ))
                         }
+                    } else {
+                        // the map is empty
+                        Ok((stack, stack_snapshots))
                     }
-                    Err(err) => Err(format!("Error while reading the size of a map at MAP instruction: {}", err))
                 }
-            },
-            MValue::Option(option) => {
-                // stack is left unchanged if option is None
-                match *option.value {
-                    None => Ok((stack, stack_snapshots)),
-                    Some(option) => {
-                        // removes the option from the stack
-                        let (_, stack_without_option) = stack.remove_at(options.pos);
-                        // loops through the list and applies instructions
-                        match args {
-                            None => Ok((stack_without_option, stack_snapshots)), // an empty instruction block is possible, just returning the current stack
-                            Some (args_) => {
-                                if args_.len() == 1 {
-                                    // converts serde_json Value to string to run the code
-                                    let code_block_json = serde_json::to_string(&args_[0]).unwrap();
-                                    // processes the code
-                                    let stack_to_process = stack_without_option.push(option, this_instruction);
-                                    match parser::run(&code_block_json, stack_to_process, stack_snapshots) {
-                                        Ok(result) => {
-                                            if result.has_failed {
-                                                Err(String::from("Block code for instruction MAP could not be parsed"))
-                                            } else {
-                                                // returns the new stack and stack snapshots
-                                                Ok(
-                                                    (
-                                                        result.stack, 
-                                                        result.stack_snapshots                                                     
-                                                    )
-                                                )
-                                            }
-                                        },
-                                        Err(err) => Err(err)
-                                    }
-                                } else {
-                                    Err("Argument for MAP is an empty array, expected an array with 1 element".to_string())
-                                }
-                            }
-                        }
+                Err(err) => Err(MichelsonError::Message(format!("Error while reading the size of a map at MAP instruction: {}", err)))
+            }
+        },
+        MValue::Option(option) => {
+            // removes the option from the stack
+            let (_, stack_without_option) = stack.remove_at(options.pos);
+            match *option.value {
+                None => {
+                    // the body is not run, the new option is typed with the type the body would return
+                    let rest: Vec<MType> = stack_without_option.iter().map(|el| el.value.get_type()).collect();
+                    let new_type = typechecker::map_body_type(body, option.m_type, &rest)?;
+                    let new_option = MValue::Option(OptionValue::new(None, new_type));
+                    Ok((stack_without_option.push(new_option, this_instruction), stack_snapshots))
+                }
+                Some(value) => {
+                    // processes the code, a failure is returned as is
+                    let stack_to_process = stack_without_option.push(value, this_instruction);
+                    let result = parser::run_block(body, stack_to_process, stack_snapshots, options)?;
+                    if result.has_failed() {
+                        return Ok(result);
                     }
+                    // wraps the element returned by the body into an option
+                    let (new_el, new_stack) = result.stack.remove_at(0);
+                    let new_type = new_el.value.get_type();
+                    let new_option = MValue::Option(OptionValue::new(Some(new_el.value), new_type));
+                    Ok((new_stack.push(new_option, this_instruction), result.stack_snapshots))
                 }
-            },
-            _ => Err(format!(
+            }
+        },
+        _ => Err(MichelsonError::Message(format!(
             "Invalid type on the stack at position {} for instruction `{:?}`, expected list or map, but got {:?}",
             options.pos,
             this_instruction,
             stack[options.pos].get_val().get_type()
-        )),
+        ))),
     }?;
 
-    Ok((new_stack, stack_snapshots))
+    Ok(parser::RunResult {
+        stack: new_stack,
+        stack_snapshots,
+        failed: None,
+    })
 }
 
 #[cfg(test)]
@@ -236,15 +192,15 @@ mod test {
     use crate::instructions::RunOptionsContext;
     use crate::m_types::{MType, PairValue, OptionValue};
     use crate::stack::StackElement;
-    use serde_json::json;
+    use crate::compiler;
 
     // PASSING
     #[test]
     fn map_success_list_of_nats() {
-        let initial_list = vec![MValue::Nat(2), MValue::Nat(3), MValue::Nat(4), MValue::Nat(5)];
+        let initial_list = vec![MValue::new_nat(2), MValue::new_nat(3), MValue::new_nat(4), MValue::new_nat(5)];
         let initial_stack: Stack = vec![
             StackElement::new(MValue::List(CollectionValue { m_type: MType::Nat, value: Box::new(initial_list) }), Instruction::INIT),
-            StackElement::new(MValue::Int(-22), Instruction::INIT),
+            StackElement::new(MValue::new_int(-22), Instruction::INIT),
             StackElement::new(MValue::Mutez(6_000_000), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
@@ -253,24 +209,22 @@ mod test {
             pos: 0,
         };
 
-        let args = vec![
-            json!([{ "prim": "PUSH", "args": [{"prim":"nat"}, {"int": "3"}] }, { "prim": "MUL" }])
-        ];
+        let body = compiler::compile_test_code(r#"[{ "prim": "PUSH", "args": [{"prim":"nat"}, {"int": "3"}] }, { "prim": "MUL" }]"#);
 
         assert!(initial_stack.len() == 3);
 
-        match run(initial_stack, Some(&args), &options, stack_snapshots) {
+        match run(initial_stack, &body, &options, stack_snapshots) {
             Err(_) => assert!(false),
-            Ok((stack, _)) => {
+            Ok(parser::RunResult { stack, .. }) => {
                 assert!(stack.len() == 3);
                 assert_eq!(stack[0].value, MValue::List(
                     CollectionValue { 
                         m_type: MType::Nat, 
-                        value: Box::new(vec![MValue::Nat(6), MValue::Nat(9), MValue::Nat(12), MValue::Nat(15)]) 
+                        value: Box::new(vec![MValue::new_nat(6), MValue::new_nat(9), MValue::new_nat(12), MValue::new_nat(15)]) 
                     }
                 ));
                 assert_eq!(stack[0].instruction, Instruction::MAP);
-                assert_eq!(stack[1].value, MValue::Int(-22));
+                assert_eq!(stack[1].value, MValue::new_int(-22));
                 assert_eq!(stack[1].instruction, Instruction::INIT);
                 assert_eq!(stack[2].value, MValue::Mutez(6_000_000));
                 assert_eq!(stack[2].instruction, Instruction::INIT);
@@ -281,17 +235,17 @@ mod test {
     #[test]
     fn map_success_list_of_pairs() {
         let initial_list = vec![
-            MValue::Pair(PairValue::new(MValue::Nat(3), MValue::String(String::from("pair_1")))),
-            MValue::Pair(PairValue::new(MValue::Nat(5), MValue::String(String::from("pair_2")))),
-            MValue::Pair(PairValue::new(MValue::Nat(7), MValue::String(String::from("pair_3")))),
-            MValue::Pair(PairValue::new(MValue::Nat(9), MValue::String(String::from("pair_4"))))
+            MValue::Pair(PairValue::new(MValue::new_nat(3), MValue::String(String::from("pair_1")))),
+            MValue::Pair(PairValue::new(MValue::new_nat(5), MValue::String(String::from("pair_2")))),
+            MValue::Pair(PairValue::new(MValue::new_nat(7), MValue::String(String::from("pair_3")))),
+            MValue::Pair(PairValue::new(MValue::new_nat(9), MValue::String(String::from("pair_4"))))
         ];
         let initial_stack: Stack = vec![
             StackElement::new(MValue::List(CollectionValue { 
-                m_type: MType::Pair(Box::new((MType::Nat, MType::String))), 
+                m_type: MType::pair(MType::Nat, MType::String), 
                 value: Box::new(initial_list) }), 
                 Instruction::INIT),
-            StackElement::new(MValue::Int(-22), Instruction::INIT),
+            StackElement::new(MValue::new_int(-22), Instruction::INIT),
             StackElement::new(MValue::Mutez(6_000_000), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
@@ -300,8 +254,7 @@ mod test {
             pos: 0,
         };
 
-        let args = vec![
-            json!([
+        let body = compiler::compile_test_code(r#"[
                 { "prim": "UNPAIR" },
                 { "prim": "PUSH", "args": [{"prim": "nat"}, {"int": "2"}] },
                 { "prim": "ADD" },
@@ -310,28 +263,27 @@ mod test {
                 { "prim": "SWAP" },
                 { "prim": "CONCAT" },
                 { "prim": "PAIR" }
-            ])
-        ];
+            ]"#);
 
         assert!(initial_stack.len() == 3);
 
-        match run(initial_stack, Some(&args), &options, stack_snapshots) {
+        match run(initial_stack, &body, &options, stack_snapshots) {
             Err(_) => assert!(false),
-            Ok((stack, _)) => {
+            Ok(parser::RunResult { stack, .. }) => {
                 assert!(stack.len() == 3);
                 assert_eq!(stack[0].value, MValue::List(
                     CollectionValue { 
-                        m_type: MType::Pair(Box::new((MType::String, MType::Nat))), 
+                        m_type: MType::pair(MType::String, MType::Nat), 
                         value: Box::new(vec![
-                            MValue::Pair(PairValue::new(MValue::String(String::from("pair_1_good")), MValue::Nat(5))),
-                            MValue::Pair(PairValue::new(MValue::String(String::from("pair_2_good")), MValue::Nat(7))),
-                            MValue::Pair(PairValue::new(MValue::String(String::from("pair_3_good")), MValue::Nat(9))),
-                            MValue::Pair(PairValue::new(MValue::String(String::from("pair_4_good")), MValue::Nat(11)))
+                            MValue::Pair(PairValue::new(MValue::String(String::from("pair_1_good")), MValue::new_nat(5))),
+                            MValue::Pair(PairValue::new(MValue::String(String::from("pair_2_good")), MValue::new_nat(7))),
+                            MValue::Pair(PairValue::new(MValue::String(String::from("pair_3_good")), MValue::new_nat(9))),
+                            MValue::Pair(PairValue::new(MValue::String(String::from("pair_4_good")), MValue::new_nat(11)))
                         ]) 
                     }
                 ));
                 assert_eq!(stack[0].instruction, Instruction::MAP);
-                assert_eq!(stack[1].value, MValue::Int(-22));
+                assert_eq!(stack[1].value, MValue::new_int(-22));
                 assert_eq!(stack[1].instruction, Instruction::INIT);
                 assert_eq!(stack[2].value, MValue::Mutez(6_000_000));
                 assert_eq!(stack[2].instruction, Instruction::INIT);
@@ -345,15 +297,15 @@ mod test {
             MType::String, 
             MType::Nat, 
             vec![
-                (MValue::String(String::from("tezos")), MValue::Nat(3)),
-                (MValue::String(String::from("taquito")), MValue::Nat(4)),
-                (MValue::String(String::from("tacos")), MValue::Nat(5)),
-                (MValue::String(String::from("cardano_lol")), MValue::Nat(6))
+                (MValue::String(String::from("tezos")), MValue::new_nat(3)),
+                (MValue::String(String::from("taquito")), MValue::new_nat(4)),
+                (MValue::String(String::from("tacos")), MValue::new_nat(5)),
+                (MValue::String(String::from("cardano_lol")), MValue::new_nat(6))
             ]
-        );
+        ).unwrap();
         let initial_stack: Stack = vec![
             StackElement::new(initial_map, Instruction::INIT),
-            StackElement::new(MValue::Int(33), Instruction::INIT),
+            StackElement::new(MValue::new_int(33), Instruction::INIT),
             StackElement::new(MValue::Mutez(6_000_000), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
@@ -362,29 +314,27 @@ mod test {
             pos: 0,
         };
 
-        let args = vec![
-            json!([{ "prim": "CDR" }, { "prim": "PUSH", "args": [{"prim":"nat"}, {"int": "3"}] }, { "prim": "MUL" }])
-        ];
+        let body = compiler::compile_test_code(r#"[{ "prim": "CDR" }, { "prim": "PUSH", "args": [{"prim":"nat"}, {"int": "3"}] }, { "prim": "MUL" }]"#);
 
         assert!(initial_stack.len() == 3);
 
-        match run(initial_stack, Some(&args), &options, stack_snapshots) {
+        match run(initial_stack, &body, &options, stack_snapshots) {
             Err(_) => assert!(false),
-            Ok((stack, _)) => {
+            Ok(parser::RunResult { stack, .. }) => {
                 let output_map = MValue::new_map(
                     MType::String, 
                     MType::Nat, 
                     vec![
-                        (MValue::String(String::from("tezos")), MValue::Nat(9)),
-                        (MValue::String(String::from("taquito")), MValue::Nat(12)),
-                        (MValue::String(String::from("tacos")), MValue::Nat(15)),
-                        (MValue::String(String::from("cardano_lol")), MValue::Nat(18))
+                        (MValue::String(String::from("tezos")), MValue::new_nat(9)),
+                        (MValue::String(String::from("taquito")), MValue::new_nat(12)),
+                        (MValue::String(String::from("tacos")), MValue::new_nat(15)),
+                        (MValue::String(String::from("cardano_lol")), MValue::new_nat(18))
                     ]
-                );
+                ).unwrap();
                 assert!(stack.len() == 3);
                 assert_eq!(stack[0].value, output_map);
                 assert_eq!(stack[0].instruction, Instruction::MAP);
-                assert_eq!(stack[1].value, MValue::Int(33));
+                assert_eq!(stack[1].value, MValue::new_int(33));
                 assert_eq!(stack[1].instruction, Instruction::INIT);
                 assert_eq!(stack[2].value, MValue::Mutez(6_000_000));
                 assert_eq!(stack[2].instruction, Instruction::INIT);
@@ -396,17 +346,17 @@ mod test {
     fn map_success_with_complex_map() {
         let initial_map = MValue::new_map(
             MType::String, 
-            MType::Pair(Box::new((MType::Int, MType::Int))), 
+            MType::pair(MType::Int, MType::Int), 
             vec![
-                (MValue::String(String::from("tezos")), MValue::Pair(PairValue::new(MValue::Int(5), MValue::Int(6)))),
-                (MValue::String(String::from("taquito")), MValue::Pair(PairValue::new(MValue::Int(7), MValue::Int(8)))),
-                (MValue::String(String::from("tacos")), MValue::Pair(PairValue::new(MValue::Int(9), MValue::Int(10)))),
-                (MValue::String(String::from("cardano_lol")), MValue::Pair(PairValue::new(MValue::Int(11), MValue::Int(12))))
+                (MValue::String(String::from("tezos")), MValue::Pair(PairValue::new(MValue::new_int(5), MValue::new_int(6)))),
+                (MValue::String(String::from("taquito")), MValue::Pair(PairValue::new(MValue::new_int(7), MValue::new_int(8)))),
+                (MValue::String(String::from("tacos")), MValue::Pair(PairValue::new(MValue::new_int(9), MValue::new_int(10)))),
+                (MValue::String(String::from("cardano_lol")), MValue::Pair(PairValue::new(MValue::new_int(11), MValue::new_int(12))))
             ]
-        );
+        ).unwrap();
         let initial_stack: Stack = vec![
             StackElement::new(initial_map, Instruction::INIT),
-            StackElement::new(MValue::Int(33), Instruction::INIT),
+            StackElement::new(MValue::new_int(33), Instruction::INIT),
             StackElement::new(MValue::Mutez(6_000_000), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
@@ -415,36 +365,34 @@ mod test {
             pos: 0,
         };
 
-        let args = vec![
-            json!([
+        let body = compiler::compile_test_code(r#"[
                 { "prim": "CDR" }, 
                 { "prim": "UNPAIR" }, 
                 { "prim": "ADD" },
                 { "prim": "ABS" },
                 { "prim": "PUSH", "args": [{"prim":"string"}, {"string": "tillwebezos"}] },
                 { "prim": "PAIR" }
-            ])
-        ];
+            ]"#);
 
         assert!(initial_stack.len() == 3);
 
-        match run(initial_stack, Some(&args), &options, stack_snapshots) {
+        match run(initial_stack, &body, &options, stack_snapshots) {
             Err(_) => assert!(false),
-            Ok((stack, _)) => {
+            Ok(parser::RunResult { stack, .. }) => {
                 let output_map = MValue::new_map(
                     MType::String, 
-                    MType::Pair(Box::new((MType::String, MType::Nat))), 
+                    MType::pair(MType::String, MType::Nat), 
                     vec![
-                        (MValue::String(String::from("tezos")), MValue::Pair(PairValue::new(MValue::String(String::from("tillwebezos")), MValue::Nat(11)))),
-                        (MValue::String(String::from("taquito")), MValue::Pair(PairValue::new(MValue::String(String::from("tillwebezos")), MValue::Nat(15)))),
-                        (MValue::String(String::from("tacos")), MValue::Pair(PairValue::new(MValue::String(String::from("tillwebezos")), MValue::Nat(19)))),
-                        (MValue::String(String::from("cardano_lol")), MValue::Pair(PairValue::new(MValue::String(String::from("tillwebezos")), MValue::Nat(23))))
+                        (MValue::String(String::from("tezos")), MValue::Pair(PairValue::new(MValue::String(String::from("tillwebezos")), MValue::new_nat(11)))),
+                        (MValue::String(String::from("taquito")), MValue::Pair(PairValue::new(MValue::String(String::from("tillwebezos")), MValue::new_nat(15)))),
+                        (MValue::String(String::from("tacos")), MValue::Pair(PairValue::new(MValue::String(String::from("tillwebezos")), MValue::new_nat(19)))),
+                        (MValue::String(String::from("cardano_lol")), MValue::Pair(PairValue::new(MValue::String(String::from("tillwebezos")), MValue::new_nat(23))))
                     ]
-                );
+                ).unwrap();
                 assert!(stack.len() == 3);
                 assert_eq!(stack[0].value, output_map);
                 assert_eq!(stack[0].instruction, Instruction::MAP);
-                assert_eq!(stack[1].value, MValue::Int(33));
+                assert_eq!(stack[1].value, MValue::new_int(33));
                 assert_eq!(stack[1].instruction, Instruction::INIT);
                 assert_eq!(stack[2].value, MValue::Mutez(6_000_000));
                 assert_eq!(stack[2].instruction, Instruction::INIT);
@@ -455,8 +403,8 @@ mod test {
     #[test]
     fn map_success_with_option() {
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Option(OptionValue::new(Some(MValue::Nat(6)), MType::Nat)), Instruction::INIT),
-            StackElement::new(MValue::Int(45), Instruction::INIT),
+            StackElement::new(MValue::Option(OptionValue::new(Some(MValue::new_nat(6)), MType::Nat)), Instruction::INIT),
+            StackElement::new(MValue::new_int(45), Instruction::INIT),
             StackElement::new(MValue::Mutez(6_000_000), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
@@ -465,23 +413,106 @@ mod test {
             pos: 0,
         };
 
-        let args = vec![
-            json!([{ "prim": "PUSH", "args": [{"prim":"nat"}, {"int": "3"}] }, { "prim": "MUL" }])
-        ];
+        let body = compiler::compile_test_code(r#"[{ "prim": "PUSH", "args": [{"prim":"nat"}, {"int": "3"}] }, { "prim": "MUL" }]"#);
 
         assert!(initial_stack.len() == 3);
 
-        match run(initial_stack, Some(&args), &options, stack_snapshots) {
+        match run(initial_stack, &body, &options, stack_snapshots) {
             Err(_) => assert!(false),
-            Ok((stack, _)) => {
+            Ok(parser::RunResult { stack, .. }) => {
                 assert!(stack.len() == 3);
-                assert_eq!(stack[0].value, MValue::Nat(18));
-                assert_eq!(stack[0].instruction, Instruction::MUL);
-                assert_eq!(stack[1].value, MValue::Int(45));
+                assert_eq!(stack[0].value, MValue::Option(OptionValue::new(Some(MValue::new_nat(18)), MType::Nat)));
+                assert_eq!(stack[0].instruction, Instruction::MAP);
+                assert_eq!(stack[1].value, MValue::new_int(45));
                 assert_eq!(stack[1].instruction, Instruction::INIT);
                 assert_eq!(stack[2].value, MValue::Mutez(6_000_000));
                 assert_eq!(stack[2].instruction, Instruction::INIT);
             }
         }
     }
+
+    #[test]
+    fn map_success_with_none() {
+        let initial_stack: Stack = vec![
+            StackElement::new(MValue::Option(OptionValue::new(None, MType::Int)), Instruction::INIT),
+            StackElement::new(MValue::new_nat(45), Instruction::INIT),
+        ];
+        let options = RunOptions {
+            context: RunOptionsContext::mock(),
+            pos: 0,
+        };
+
+        let body = compiler::compile_test_code(r#"[{ "prim": "ABS" }, { "prim": "PUSH", "args": [{"prim":"string"}, {"string": "abs"}] }, { "prim": "PAIR" }]"#);
+
+        match run(initial_stack, &body, &options, vec![]) {
+            Err(err) => panic!("{}", err),
+            Ok(parser::RunResult { stack, .. }) => {
+                assert!(stack.len() == 2);
+                assert_eq!(
+                    stack[0].value,
+                    MValue::Option(OptionValue::new(None, MType::pair(MType::String, MType::Nat)))
+                );
+                assert_eq!(stack[0].instruction, Instruction::MAP);
+                assert_eq!(stack[1].value, MValue::new_nat(45));
+            }
+        }
+    }
+
+    #[test]
+    fn map_option_agrees_with_typechecker() {
+        let code = compiler::compile_test_code(
+            r#"[{ "prim": "MAP", "args": [[{ "prim": "DUP", "args": [{ "int": "2" }] }, { "prim": "ADD" }, { "prim": "ISNAT" }]] }]"#,
+        );
+        let options = RunOptions {
+            context: RunOptionsContext::mock(),
+            pos: 0,
+        };
+        let input = vec![MType::Option(Box::new(MType::Int)), MType::Nat];
+        let expected = match typechecker::typecheck(&code, input).unwrap().stack {
+            typechecker::Typed::Stack(stack) => stack,
+            typechecker::Typed::Failed => panic!("MAP is not expected to fail"),
+        };
+        assert_eq!(
+            expected,
+            vec![MType::Option(Box::new(MType::Option(Box::new(MType::Nat)))), MType::Nat]
+        );
+
+        for option in [Some(MValue::new_int(-3)), None] {
+            let initial_stack: Stack = vec![
+                StackElement::new(MValue::Option(OptionValue::new(option, MType::Int)), Instruction::INIT),
+                StackElement::new(MValue::new_nat(5), Instruction::INIT),
+            ];
+            match parser::run_block(&code, initial_stack, vec![], &options) {
+                Err(err) => panic!("{}", err),
+                Ok(result) => {
+                    let types: Vec<MType> = result.stack.iter().map(|el| el.value.get_type()).collect();
+                    assert_eq!(types, expected);
+                }
+            }
+        }
+    }
+
+    // FAILING
+    // the body returns a nat for the first element and a string for the second one
+    #[test]
+    #[should_panic(expected = "Expected element at position 0 to be of type nat, but got string for instruction MAP")]
+    fn map_mixed_result_types() {
+        let initial_stack: Stack = vec![StackElement::new(
+            MValue::new_list(vec![MValue::new_int(1), MValue::new_int(-1)], MType::Int),
+            Instruction::INIT,
+        )];
+        let options = RunOptions {
+            context: RunOptionsContext::mock(),
+            pos: 0,
+        };
+
+        let body = compiler::compile_test_code(
+            r#"[{ "prim": "GT" }, { "prim": "IF", "args": [[{ "prim": "PUSH", "args": [{ "prim": "nat" }, { "int": "1" }] }], [{ "prim": "PUSH", "args": [{ "prim": "string" }, { "string": "negative" }] }]] }]"#,
+        );
+
+        match run(initial_stack, &body, &options, vec![]) {
+            Ok(_) => assert!(false),
+            Err(err) => panic!("{}", err),
+        }
+    }
 }
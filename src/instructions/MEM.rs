@@ -1,4 +1,4 @@
-use crate::errors::{display_error, ErrorCode};
+use crate::errors::MichelsonError;
 use crate::instructions::{Instruction, RunOptions};
 use crate::m_types::MValue;
 use crate::stack::{Stack, StackElement, StackFuncs, StackSnapshots};
@@ -9,7 +9,7 @@ pub fn run(
     stack: Stack,
     options: &RunOptions,
     mut stack_snapshots: StackSnapshots,
-) -> Result<(Stack, StackSnapshots), String> {
+) -> Result<(Stack, StackSnapshots), MichelsonError> {
     // checks the stack
     stack.check_depth(options.pos + 2, Instruction::MEM)?;
 
@@ -22,16 +22,13 @@ pub fn run(
             // checks if the value to find is of the right type
             if set.m_type == el_to_find.get_type() {
                 // finds if value is in the set
-                match set.value.iter().find(|&x| x == el_to_find) {
-                    None => Ok(false),
-                    Some(_) => Ok(true),
-                }
+                Ok(set.value.contains(el_to_find))
             } else {
-                Err(display_error(ErrorCode::InvalidType((
+                Err(MichelsonError::InvalidType((
                     vec![set.m_type.clone()],
                     el_to_find.get_type(),
                     Instruction::MEM,
-                ))))
+                )))
             }
         }
         MValue::Big_map(map) | MValue::Map(map) => {
@@ -42,17 +39,17 @@ pub fn run(
                     Some(_) => Ok(true),
                 }
             } else {
-                Err(display_error(ErrorCode::InvalidType((
+                Err(MichelsonError::InvalidType((
                     vec![map.key_type.clone()],
                     el_to_find.get_type(),
                     Instruction::MEM,
-                ))))
+                )))
             }
         }
-        _ => Err(format!(
+        _ => Err(MichelsonError::Message(format!(
             "Invalid type for `MEM` expected set, map or big_map, but got {:?}",
             stack[options.pos].value.get_type()
-        )),
+        ))),
     }?;
     // creates the new element to insert
     let new_el = StackElement::new(MValue::Bool(result), Instruction::MEM);
@@ -82,16 +79,16 @@ mod tests {
     fn mem_set_success() {
         // if the element is in the set
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Nat(8), Instruction::INIT),
+            StackElement::new(MValue::new_nat(8), Instruction::INIT),
             StackElement::new(
                 MValue::new_set(
-                    vec![MValue::Nat(7), MValue::Nat(6), MValue::Nat(8)],
+                    vec![MValue::new_nat(7), MValue::new_nat(6), MValue::new_nat(8)],
                     MType::Nat,
                 ),
                 Instruction::INIT,
             ),
-            StackElement::new(MValue::Nat(5), Instruction::INIT),
-            StackElement::new(MValue::Int(6), Instruction::INIT),
+            StackElement::new(MValue::new_nat(5), Instruction::INIT),
+            StackElement::new(MValue::new_int(6), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
         let options = RunOptions {
@@ -112,16 +109,16 @@ mod tests {
 
         // if the element is not in the set
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Nat(9), Instruction::INIT),
+            StackElement::new(MValue::new_nat(9), Instruction::INIT),
             StackElement::new(
                 MValue::new_set(
-                    vec![MValue::Nat(7), MValue::Nat(6), MValue::Nat(8)],
+                    vec![MValue::new_nat(7), MValue::new_nat(6), MValue::new_nat(8)],
                     MType::Nat,
                 ),
                 Instruction::INIT,
             ),
-            StackElement::new(MValue::Nat(5), Instruction::INIT),
-            StackElement::new(MValue::Int(6), Instruction::INIT),
+            StackElement::new(MValue::new_nat(5), Instruction::INIT),
+            StackElement::new(MValue::new_int(6), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
 
@@ -141,21 +138,21 @@ mod tests {
     fn mem_map_success() {
         // if the element is in the set
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Nat(8), Instruction::INIT),
+            StackElement::new(MValue::new_nat(8), Instruction::INIT),
             StackElement::new(
                 MValue::new_map(
                     MType::Nat,
                     MType::Int,
                     vec![
-                        (MValue::Nat(7), MValue::Int(7)),
-                        (MValue::Nat(6), MValue::Int(6)),
-                        (MValue::Nat(8), MValue::Int(8)),
+                        (MValue::new_nat(7), MValue::new_int(7)),
+                        (MValue::new_nat(6), MValue::new_int(6)),
+                        (MValue::new_nat(8), MValue::new_int(8)),
                     ],
-                ),
+                ).unwrap(),
                 Instruction::INIT,
             ),
-            StackElement::new(MValue::Nat(5), Instruction::INIT),
-            StackElement::new(MValue::Int(6), Instruction::INIT),
+            StackElement::new(MValue::new_nat(5), Instruction::INIT),
+            StackElement::new(MValue::new_int(6), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
         let options = RunOptions {
@@ -176,21 +173,21 @@ mod tests {
 
         // if the element is not in the set
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Nat(9), Instruction::INIT),
+            StackElement::new(MValue::new_nat(9), Instruction::INIT),
             StackElement::new(
                 MValue::new_map(
                     MType::Nat,
                     MType::Int,
                     vec![
-                        (MValue::Nat(7), MValue::Int(7)),
-                        (MValue::Nat(6), MValue::Int(6)),
-                        (MValue::Nat(8), MValue::Int(8)),
+                        (MValue::new_nat(7), MValue::new_int(7)),
+                        (MValue::new_nat(6), MValue::new_int(6)),
+                        (MValue::new_nat(8), MValue::new_int(8)),
                     ],
-                ),
+                ).unwrap(),
                 Instruction::INIT,
             ),
-            StackElement::new(MValue::Nat(5), Instruction::INIT),
-            StackElement::new(MValue::Int(6), Instruction::INIT),
+            StackElement::new(MValue::new_nat(5), Instruction::INIT),
+            StackElement::new(MValue::new_int(6), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
 
@@ -211,22 +208,22 @@ mod tests {
         // if the element is in the set
         let initial_stack: Stack = vec![
             StackElement::new(
-                MValue::Pair(PairValue::new(MValue::Nat(7), MValue::Int(11))),
+                MValue::Pair(PairValue::new(MValue::new_nat(7), MValue::new_int(11))),
                 Instruction::INIT,
             ),
             StackElement::new(
                 MValue::new_set(
                     vec![
-                        MValue::Pair(PairValue::new(MValue::Nat(8), MValue::Int(11))),
-                        MValue::Pair(PairValue::new(MValue::Nat(7), MValue::Int(11))),
-                        MValue::Pair(PairValue::new(MValue::Nat(7), MValue::Int(12))),
+                        MValue::Pair(PairValue::new(MValue::new_nat(8), MValue::new_int(11))),
+                        MValue::Pair(PairValue::new(MValue::new_nat(7), MValue::new_int(11))),
+                        MValue::Pair(PairValue::new(MValue::new_nat(7), MValue::new_int(12))),
                     ],
-                    MType::Pair(Box::new((MType::Nat, MType::Int))),
+                    MType::pair(MType::Nat, MType::Int),
                 ),
                 Instruction::INIT,
             ),
-            StackElement::new(MValue::Nat(5), Instruction::INIT),
-            StackElement::new(MValue::Int(6), Instruction::INIT),
+            StackElement::new(MValue::new_nat(5), Instruction::INIT),
+            StackElement::new(MValue::new_int(6), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
         let options = RunOptions {
@@ -261,7 +258,7 @@ mod tests {
 
         match run(initial_stack, &options, stack_snapshots) {
             Err(err) => assert_eq!(
-                err,
+                err.to_string(),
                 String::from(
                     "Unexpected stack length, expected a length of 2 for instruction MEM, got 0"
                 )
@@ -270,7 +267,7 @@ mod tests {
         }
 
         // 1 element in the stack
-        let initial_stack: Stack = vec![StackElement::new(MValue::Nat(9), Instruction::INIT)];
+        let initial_stack: Stack = vec![StackElement::new(MValue::new_nat(9), Instruction::INIT)];
         let stack_snapshots = vec![];
         let options = RunOptions {
             context: RunOptionsContext::mock(),
@@ -281,7 +278,7 @@ mod tests {
 
         match run(initial_stack, &options, stack_snapshots) {
             Err(err) => assert_eq!(
-                err,
+                err.to_string(),
                 String::from(
                     "Unexpected stack length, expected a length of 2 for instruction MEM, got 1"
                 )
@@ -294,16 +291,16 @@ mod tests {
     fn mem_set_wrong_stack_types() {
         // empty stack
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Int(8), Instruction::INIT),
+            StackElement::new(MValue::new_int(8), Instruction::INIT),
             StackElement::new(
                 MValue::new_set(
-                    vec![MValue::Nat(7), MValue::Nat(6), MValue::Nat(8)],
+                    vec![MValue::new_nat(7), MValue::new_nat(6), MValue::new_nat(8)],
                     MType::Nat,
                 ),
                 Instruction::INIT,
             ),
-            StackElement::new(MValue::Nat(5), Instruction::INIT),
-            StackElement::new(MValue::Int(6), Instruction::INIT),
+            StackElement::new(MValue::new_nat(5), Instruction::INIT),
+            StackElement::new(MValue::new_int(6), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
         let options = RunOptions {
@@ -315,7 +312,7 @@ mod tests {
 
         match run(initial_stack, &options, stack_snapshots) {
             Err(err) => assert_eq!(
-                err,
+                err.to_string(),
                 String::from("Invalid type for `MEM` expected nat, but got int")
             ),
             Ok(_) => assert!(false),
@@ -326,21 +323,21 @@ mod tests {
     fn mem_map_wrong_type() {
         // if the element is in the set
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Int(8), Instruction::INIT),
+            StackElement::new(MValue::new_int(8), Instruction::INIT),
             StackElement::new(
                 MValue::new_map(
                     MType::Nat,
                     MType::Int,
                     vec![
-                        (MValue::Nat(7), MValue::Int(7)),
-                        (MValue::Nat(6), MValue::Int(6)),
-                        (MValue::Nat(8), MValue::Int(8)),
+                        (MValue::new_nat(7), MValue::new_int(7)),
+                        (MValue::new_nat(6), MValue::new_int(6)),
+                        (MValue::new_nat(8), MValue::new_int(8)),
                     ],
-                ),
+                ).unwrap(),
                 Instruction::INIT,
             ),
-            StackElement::new(MValue::Nat(5), Instruction::INIT),
-            StackElement::new(MValue::Int(6), Instruction::INIT),
+            StackElement::new(MValue::new_nat(5), Instruction::INIT),
+            StackElement::new(MValue::new_int(6), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
         let options = RunOptions {
@@ -352,7 +349,7 @@ mod tests {
 
         match run(initial_stack, &options, stack_snapshots) {
             Err(err) => assert_eq!(
-                err,
+                err.to_string(),
                 String::from("Invalid type for `MEM` expected nat, but got int")
             ),
             Ok(_) => assert!(false),
@@ -1,6 +1,7 @@
-use crate::errors::{display_error, ErrorCode};
+use crate::errors::MichelsonError;
 use crate::instructions::{Instruction, RunOptions};
-use crate::m_types::{int, MValue};
+use crate::m_types::{int, nat, MValue, MAX_MUTEZ};
+use num_traits::ToPrimitive;
 use crate::stack::{Stack, StackElement, StackFuncs, StackSnapshots};
 
 // https://tezos.gitlab.io/michelson-reference/#instr-MUL
@@ -9,64 +10,35 @@ pub fn run(
     stack: Stack,
     options: &RunOptions,
     mut stack_snapshots: StackSnapshots,
-) -> Result<(Stack, StackSnapshots), String> {
+) -> Result<(Stack, StackSnapshots), MichelsonError> {
     // checks the stack
-    match stack.check_depth(options.pos + 2, Instruction::MUL) {
-        Ok(_) => (),
-        Err(err) => panic!("{}", err),
-    };
+    stack.check_depth(options.pos + 2, Instruction::MUL)?;
     // pattern matches the different numeric types
     let new_val: MValue = match (
         stack[options.pos].get_val(),
         stack[options.pos + 1].get_val(),
     ) {
         (MValue::Int(left), MValue::Int(right)) => MValue::Int(left * right),
-        (MValue::Int(left), MValue::Nat(right)) => {
-            if MValue::Nat(right).check_nat() {
-                MValue::Int(left * right as int)
-            } else {
-                panic!("{}", display_error(ErrorCode::InvalidNat(right)))
-            }
-        } // int
-        (MValue::Nat(left), MValue::Int(right)) => {
-            if MValue::Nat(left).check_nat() {
-                MValue::Int(left as int * right)
-            } else {
-                panic!("{}", display_error(ErrorCode::InvalidNat(left)))
-            }
-        } // int
-        (MValue::Nat(left), MValue::Nat(right)) => {
-            if MValue::Nat(left).check_nat() == false {
-                panic!("{}", display_error(ErrorCode::InvalidNat(left)))
-            } else if MValue::Nat(right).check_nat() == false {
-                panic!("{}", display_error(ErrorCode::InvalidNat(right)))
-            } else {
-                MValue::Nat(left * right)
-            }
-        } // nat
-        (MValue::Mutez(left), MValue::Nat(right)) => {
-            if MValue::Mutez(left).check_mutez() == false {
-                panic!("{}", display_error(ErrorCode::InvalidMutez(left)))
-            } else if MValue::Nat(right).check_nat() == false {
-                panic!("{}", display_error(ErrorCode::InvalidNat(right)))
-            } else {
-                MValue::Mutez(left * right)
+        (MValue::Int(left), MValue::Nat(right)) => MValue::Int(left * int::from(right)), // int
+        (MValue::Nat(left), MValue::Int(right)) => MValue::Int(int::from(left) * right), // int
+        (MValue::Nat(left), MValue::Nat(right)) => MValue::Nat(left * right), // nat
+        (MValue::Mutez(amount), MValue::Nat(factor)) | (MValue::Nat(factor), MValue::Mutez(amount)) => {
+            // the product is computed on big numbers, it must stay in the range of mutez values
+            match (nat::from(amount) * factor)
+                .to_u64()
+                .filter(|product| *product <= MAX_MUTEZ)
+            {
+                Some(product) => MValue::Mutez(product),
+                None => return Err(MichelsonError::MutezOverflow(Instruction::MUL)),
             }
         } // mutez
-        (MValue::Nat(left), MValue::Mutez(right)) => {
-            if MValue::Mutez(right).check_mutez() == false {
-                panic!("{}", display_error(ErrorCode::InvalidMutez(right)))
-            } else if MValue::Nat(left).check_nat() == false {
-                panic!("{}", display_error(ErrorCode::InvalidNat(left)))
-            } else {
-                MValue::Mutez(left * right)
-            }
-        } // mutez
-        (m_val_left, m_val_right) => panic!(
-            "Cannot multiply together values of type {} and {}",
-            m_val_left.to_string(),
-            m_val_right.to_string()
-        ),
+        (m_val_left, m_val_right) => {
+            return Err(MichelsonError::Message(format!(
+                "Cannot multiply together values of type {} and {}",
+                m_val_left.to_string(),
+                m_val_right.to_string()
+            )))
+        }
     };
     // updates the stack by removing the 2 elements
     let (_, new_stack) = stack.remove_at(options.pos);
@@ -89,15 +61,15 @@ pub fn run(
 mod tests {
     use super::*;
     use crate::instructions::RunOptionsContext;
-    use crate::m_types::MValue;
+    use crate::m_types::{nat, MValue};
 
     // PASSING TESTS
     // Tests MUL with 2 ints
     #[test]
     fn mul_int_int() -> () {
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Int(5), Instruction::INIT),
-            StackElement::new(MValue::Int(6), Instruction::INIT),
+            StackElement::new(MValue::new_int(5), Instruction::INIT),
+            StackElement::new(MValue::new_int(6), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
         let options = RunOptions {
@@ -109,7 +81,7 @@ mod tests {
             Err(_) => assert!(false),
             Ok((stack, _)) => {
                 assert!(stack.len() == 1);
-                assert_eq!(stack[0].value, MValue::Int(30));
+                assert_eq!(stack[0].value, MValue::new_int(30));
             }
         }
     }
@@ -118,8 +90,8 @@ mod tests {
     #[test]
     fn mul_int_nat() -> () {
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Int(5), Instruction::INIT),
-            StackElement::new(MValue::Nat(6), Instruction::INIT),
+            StackElement::new(MValue::new_int(5), Instruction::INIT),
+            StackElement::new(MValue::new_nat(6), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
         let options = RunOptions {
@@ -131,7 +103,7 @@ mod tests {
             Err(_) => assert!(false),
             Ok((stack, _)) => {
                 assert!(stack.len() == 1);
-                assert_eq!(stack[0].value, MValue::Int(30));
+                assert_eq!(stack[0].value, MValue::new_int(30));
             }
         }
     }
@@ -140,8 +112,31 @@ mod tests {
     #[test]
     fn mul_nat_nat() -> () {
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Nat(5), Instruction::INIT),
-            StackElement::new(MValue::Nat(6), Instruction::INIT),
+            StackElement::new(MValue::new_nat(5), Instruction::INIT),
+            StackElement::new(MValue::new_nat(6), Instruction::INIT),
+        ];
+        let stack_snapshots = vec![];
+        let options = RunOptions {
+            context: RunOptionsContext::mock(),
+            pos: 0,
+        };
+
+        match run(initial_stack, &options, stack_snapshots) {
+            Err(_) => assert!(false),
+            Ok((stack, _)) => {
+                assert!(stack.len() == 1);
+                assert_eq!(stack[0].value, MValue::new_nat(30));
+            }
+        }
+    }
+
+    // Tests MUL with 2 nats whose product doesn't fit in 128 bits
+    #[test]
+    fn mul_big_nat_nat() -> () {
+        let big: nat = "1000000000000000000000000000000".parse().unwrap();
+        let initial_stack: Stack = vec![
+            StackElement::new(MValue::Nat(big.clone()), Instruction::INIT),
+            StackElement::new(MValue::Nat(big), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
         let options = RunOptions {
@@ -153,7 +148,10 @@ mod tests {
             Err(_) => assert!(false),
             Ok((stack, _)) => {
                 assert!(stack.len() == 1);
-                assert_eq!(stack[0].value, MValue::Nat(30));
+                assert_eq!(
+                    stack[0].value,
+                    MValue::Nat(format!("1{}", "0".repeat(60)).parse().unwrap())
+                );
             }
         }
     }
@@ -163,7 +161,7 @@ mod tests {
     fn mul_mutez_nat() -> () {
         let initial_stack: Stack = vec![
             StackElement::new(MValue::Mutez(5_000_000), Instruction::INIT),
-            StackElement::new(MValue::Nat(6), Instruction::INIT),
+            StackElement::new(MValue::new_nat(6), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
         let options = RunOptions {
@@ -184,7 +182,7 @@ mod tests {
     #[test]
     fn mul_nat_mutez() -> () {
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Nat(6), Instruction::INIT),
+            StackElement::new(MValue::new_nat(6), Instruction::INIT),
             StackElement::new(MValue::Mutez(5_000_000), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
@@ -202,6 +200,28 @@ mod tests {
         }
     }
 
+    // Tests MUL with 0 mutez and a nat that doesn't fit in 64 bits
+    #[test]
+    fn mul_zero_mutez_big_nat() {
+        let initial_stack: Stack = vec![
+            StackElement::new(MValue::Mutez(0), Instruction::INIT),
+            StackElement::new(MValue::new_nat(1 << 70), Instruction::INIT),
+        ];
+        let stack_snapshots = vec![];
+        let options = RunOptions {
+            context: RunOptionsContext::mock(),
+            pos: 0,
+        };
+
+        match run(initial_stack, &options, stack_snapshots) {
+            Err(err) => panic!("{}", err),
+            Ok((stack, _)) => {
+                assert!(stack.len() == 1);
+                assert_eq!(stack[0].value, MValue::Mutez(0));
+            }
+        }
+    }
+
     // FAILING TESTS
     // MUL with strings
     #[test]
@@ -209,7 +229,7 @@ mod tests {
     fn mul_string_nat() {
         let initial_stack: Stack = vec![
             StackElement::new(MValue::String(String::from("5")), Instruction::INIT),
-            StackElement::new(MValue::Nat(6), Instruction::INIT),
+            StackElement::new(MValue::new_nat(6), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
         let options = RunOptions {
@@ -241,4 +261,42 @@ mod tests {
             Ok(_) => assert!(false),
         }
     }
+
+    #[test]
+    #[should_panic(expected = "Overflow of mutez value for instruction MUL, the maximum is 9223372036854775807")]
+    fn mul_mutez_nat_overflow() {
+        let initial_stack: Stack = vec![
+            StackElement::new(MValue::Mutez(MAX_MUTEZ / 2 + 1), Instruction::INIT),
+            StackElement::new(MValue::new_nat(2), Instruction::INIT),
+        ];
+        let stack_snapshots = vec![];
+        let options = RunOptions {
+            context: RunOptionsContext::mock(),
+            pos: 0,
+        };
+
+        match run(initial_stack, &options, stack_snapshots) {
+            Err(err) => panic!("{}", err),
+            Ok(_) => assert!(false),
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Overflow of mutez value for instruction MUL, the maximum is 9223372036854775807")]
+    fn mul_mutez_big_nat_overflow() {
+        let initial_stack: Stack = vec![
+            StackElement::new(MValue::Mutez(1), Instruction::INIT),
+            StackElement::new(MValue::new_nat(1 << 70), Instruction::INIT),
+        ];
+        let stack_snapshots = vec![];
+        let options = RunOptions {
+            context: RunOptionsContext::mock(),
+            pos: 0,
+        };
+
+        match run(initial_stack, &options, stack_snapshots) {
+            Err(err) => panic!("{}", err),
+            Ok(_) => assert!(false),
+        }
+    }
 }
@@ -1,4 +1,4 @@
-use crate::errors::{display_error, ErrorCode};
+use crate::errors::MichelsonError;
 use crate::instructions::{Instruction, RunOptions};
 use crate::m_types::{int, MType, MValue};
 use crate::stack::{Stack, StackElement, StackFuncs, StackSnapshots};
@@ -9,27 +9,24 @@ pub fn run(
     stack: Stack,
     options: &RunOptions,
     mut stack_snapshots: StackSnapshots,
-) -> Result<(Stack, StackSnapshots), String> {
+) -> Result<(Stack, StackSnapshots), MichelsonError> {
     // checks the stack
-    match stack.check_depth(options.pos + 1, Instruction::NEG) {
-        Ok(_) => (),
-        Err(err) => panic!("{}", err),
-    };
+    stack.check_depth(options.pos + 1, Instruction::NEG)?;
     // verifies that the value at options.pos is an int or a nat
-    let new_val_res: Result<MValue, String> = match stack[options.pos].value {
+    let new_val_res: Result<MValue, MichelsonError> = match stack[options.pos].get_val() {
         MValue::Int(val) => {
-            let new_int = val * -1 as int;
+            let new_int = -val;
             Ok(MValue::Int(new_int))
         }
         MValue::Nat(val) => {
-            let new_int = (val as int) * -1;
+            let new_int = -int::from(val);
             Ok(MValue::Int(new_int))
         }
-        _ => Err(display_error(ErrorCode::InvalidType((
+        _ => Err(MichelsonError::InvalidType((
             vec![MType::Int, MType::Nat],
             stack[options.pos].value.get_type(),
             Instruction::NEG,
-        )))),
+        ))),
     };
 
     match new_val_res {
@@ -59,8 +56,8 @@ mod tests {
     #[test]
     fn neg_int_success() {
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Int(5), Instruction::INIT),
-            StackElement::new(MValue::Int(6), Instruction::INIT),
+            StackElement::new(MValue::new_int(5), Instruction::INIT),
+            StackElement::new(MValue::new_int(6), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
         let options = RunOptions {
@@ -74,9 +71,9 @@ mod tests {
             Err(_) => assert!(false),
             Ok((stack, _)) => {
                 assert!(stack.len() == 2);
-                assert_eq!(stack[0].value, MValue::Int(-5));
+                assert_eq!(stack[0].value, MValue::new_int(-5));
                 assert_eq!(stack[0].instruction, Instruction::NEG);
-                assert_eq!(stack[1].value, MValue::Int(6));
+                assert_eq!(stack[1].value, MValue::new_int(6));
                 assert_eq!(stack[1].instruction, Instruction::INIT);
             }
         }
@@ -86,8 +83,8 @@ mod tests {
     #[test]
     fn neg_nat_success() {
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Nat(5), Instruction::INIT),
-            StackElement::new(MValue::Int(6), Instruction::INIT),
+            StackElement::new(MValue::new_nat(5), Instruction::INIT),
+            StackElement::new(MValue::new_int(6), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
         let options = RunOptions {
@@ -101,9 +98,9 @@ mod tests {
             Err(_) => assert!(false),
             Ok((stack, _)) => {
                 assert!(stack.len() == 2);
-                assert_eq!(stack[0].value, MValue::Int(-5));
+                assert_eq!(stack[0].value, MValue::new_int(-5));
                 assert_eq!(stack[0].instruction, Instruction::NEG);
-                assert_eq!(stack[1].value, MValue::Int(6));
+                assert_eq!(stack[1].value, MValue::new_int(6));
                 assert_eq!(stack[1].instruction, Instruction::INIT);
             }
         }
@@ -113,8 +110,8 @@ mod tests {
     #[test]
     fn neg_success_pos() {
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Int(5), Instruction::INIT),
-            StackElement::new(MValue::Int(6), Instruction::INIT),
+            StackElement::new(MValue::new_int(5), Instruction::INIT),
+            StackElement::new(MValue::new_int(6), Instruction::INIT),
             StackElement::new(MValue::Mutez(7_000_000), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
@@ -129,9 +126,9 @@ mod tests {
             Err(_) => assert!(false),
             Ok((stack, _)) => {
                 assert!(stack.len() == 3);
-                assert_eq!(stack[0].value, MValue::Int(5));
+                assert_eq!(stack[0].value, MValue::new_int(5));
                 assert_eq!(stack[0].instruction, Instruction::INIT);
-                assert_eq!(stack[1].value, MValue::Int(-6));
+                assert_eq!(stack[1].value, MValue::new_int(-6));
                 assert_eq!(stack[1].instruction, Instruction::NEG);
                 assert_eq!(stack[2].value, MValue::Mutez(7_000_000));
                 assert_eq!(stack[2].instruction, Instruction::INIT);
@@ -1,3 +1,4 @@
+use crate::errors::MichelsonError;
 use crate::instructions::{Instruction, RunOptions};
 use crate::m_types::MValue;
 use crate::stack::{Stack, StackElement, StackFuncs, StackSnapshots};
@@ -8,7 +9,7 @@ pub fn run(
     stack: Stack,
     options: &RunOptions,
     mut stack_snapshots: StackSnapshots,
-) -> Result<(Stack, StackSnapshots), String> {
+) -> Result<(Stack, StackSnapshots), MichelsonError> {
     // checks the stack
     stack.check_depth(options.pos, Instruction::NEVER)?;
     // updates the stack
@@ -32,7 +33,7 @@ mod tests {
     fn never_success() {
         // should push the address to the stack
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Int(22), Instruction::INIT),
+            StackElement::new(MValue::new_int(22), Instruction::INIT),
             StackElement::new(MValue::Mutez(6_000_000), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
@@ -49,7 +50,7 @@ mod tests {
                 assert!(stack.len() == 3);
                 assert_eq!(stack[0].value, MValue::Never);
                 assert_eq!(stack[0].instruction, Instruction::NEVER);
-                assert_eq!(stack[1].value, MValue::Int(22));
+                assert_eq!(stack[1].value, MValue::new_int(22));
                 assert_eq!(stack[1].instruction, Instruction::INIT);
                 assert_eq!(stack[2].value, MValue::Mutez(6_000_000));
                 assert_eq!(stack[2].instruction, Instruction::INIT);
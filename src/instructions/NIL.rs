@@ -1,59 +1,43 @@
+use crate::errors::MichelsonError;
 use crate::instructions::{Instruction, RunOptions};
 use crate::m_types::{CollectionValue, MType, MValue};
 use crate::stack::{Stack, StackElement, StackFuncs, StackSnapshots};
-use serde_json::Value;
+use crate::micheline::Micheline;
 
 // https://tezos.gitlab.io/michelson-reference/#instr-NIL
 
+/// decodes the type of the elements of the list
+pub fn parse_args(args: Option<&Vec<Micheline>>) -> Result<MType, MichelsonError> {
+    match args {
+        None => Err(MichelsonError::Message(String::from(
+            "Arguments for NIL instruction cannot be empty",
+        ))),
+        Some(val) => match &val[0] {
+            type_node @ Micheline::Prim { .. } => MType::from_micheline(type_node),
+            _ => Err(MichelsonError::Message(String::from("Expected string for the list element type"))),
+        },
+    }
+}
+
 pub fn run(
     stack: Stack,
-    args: Option<&Vec<Value>>,
+    list_type: &MType,
     options: &RunOptions,
     mut stack_snapshots: StackSnapshots,
-) -> Result<(Stack, StackSnapshots), String> {
+) -> Result<(Stack, StackSnapshots), MichelsonError> {
     // no need to check the stack, it can be empty
-    let new_stack_res: Result<Stack, String> = match args {
-        None => Err(String::from(
-            "Arguments for NIL instruction cannot be empty",
-        )),
-        Some(val) => {
-            if val[0].is_object() {
-                // TODO: refactor that with ?
-                let new_list_res: Result<StackElement, String> = match val[0]["prim"].as_str() {
-                    None => Err(String::from("Expected string for the list element type")),
-                    Some(str) => match MType::from_string(str) {
-                        Err(err) => Err(err),
-                        Ok(list_type) => Ok(StackElement::new(
-                            MValue::List(CollectionValue {
-                                m_type: list_type,
-                                value: Box::new(vec![]),
-                            }),
-                            Instruction::NIL,
-                        )),
-                    },
-                };
-                match new_list_res {
-                    Err(err) => Err(err),
-                    Ok(new_list) => {
-                        let new_stack = stack.insert_at(vec![new_list], options.pos);
-                        Ok(new_stack)
-                    }
-                }
-            } else {
-                // TODO: refactor this part so the function can output an Err and not panic (cf LEFT)
-                panic!("Expected a 'serde_json::Value' of type object for NIL instruction")
-            }
-        }
-    };
-    match new_stack_res {
-        Err(err) => Err(err),
-        Ok(new_stack) => {
-            // updates the stack snapshots
-            stack_snapshots.push(new_stack.clone());
-
-            Ok((new_stack, stack_snapshots))
-        }
-    }
+    let new_list = StackElement::new(
+        MValue::List(CollectionValue {
+            m_type: list_type.clone(),
+            value: Box::new(vec![]),
+        }),
+        Instruction::NIL,
+    );
+    let new_stack = stack.insert_at(vec![new_list], options.pos);
+    // updates the stack snapshots
+    stack_snapshots.push(new_stack.clone());
+
+    Ok((new_stack, stack_snapshots))
 }
 
 /**
@@ -65,17 +49,16 @@ mod tests {
     use super::*;
     use crate::instructions::RunOptionsContext;
     use crate::m_types::{CollectionValue, MType};
-    use serde_json::json;
 
     // PASSING TESTS
     // pushes a new empty list of nat to the stack
     #[test]
     fn nil_list_of_nat() {
-        let arg_value: Value = json!({ "prim": "nat" });
+        let arg_value = Micheline::prim("nat", vec![]);
         let arg_vec = vec![arg_value];
-        let args: Option<&Vec<Value>> = Some(&arg_vec);
+        let args: Option<&Vec<Micheline>> = Some(&arg_vec);
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Int(5), Instruction::INIT),
+            StackElement::new(MValue::new_int(5), Instruction::INIT),
             StackElement::new(MValue::Mutez(6_000_000), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
@@ -86,7 +69,9 @@ mod tests {
 
         assert!(initial_stack.len() == 2);
 
-        match run(initial_stack, args, &options, stack_snapshots) {
+        match parse_args(args)
+            .and_then(|list_type| run(initial_stack, &list_type, &options, stack_snapshots))
+        {
             Ok((stack, _)) => {
                 let expected_list = CollectionValue {
                     m_type: MType::Nat,
@@ -104,9 +89,9 @@ mod tests {
     #[test]
     #[should_panic(expected = "Arguments for NIL instruction cannot be empty")]
     fn nil_empty_args() {
-        let args: Option<&Vec<Value>> = None;
+        let args: Option<&Vec<Micheline>> = None;
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Int(5), Instruction::INIT),
+            StackElement::new(MValue::new_int(5), Instruction::INIT),
             StackElement::new(MValue::Mutez(6_000_000), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
@@ -117,7 +102,9 @@ mod tests {
 
         assert!(initial_stack.len() == 2);
 
-        match run(initial_stack, args, &options, stack_snapshots) {
+        match parse_args(args)
+            .and_then(|list_type| run(initial_stack, &list_type, &options, stack_snapshots))
+        {
             Ok(_) => assert!(false),
             Err(err) => panic!("{}", err),
         }
@@ -127,11 +114,11 @@ mod tests {
     #[test]
     #[should_panic(expected = "Expected string for the list element type")]
     fn nil_wrong_args() {
-        let arg_value: Value = json!({ "int": "3" });
+        let arg_value = Micheline::Int(String::from("3"));
         let arg_vec = vec![arg_value];
-        let args: Option<&Vec<Value>> = Some(&arg_vec);
+        let args: Option<&Vec<Micheline>> = Some(&arg_vec);
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Int(5), Instruction::INIT),
+            StackElement::new(MValue::new_int(5), Instruction::INIT),
             StackElement::new(MValue::Mutez(6_000_000), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
@@ -142,7 +129,9 @@ mod tests {
 
         assert!(initial_stack.len() == 2);
 
-        match run(initial_stack, args, &options, stack_snapshots) {
+        match parse_args(args)
+            .and_then(|list_type| run(initial_stack, &list_type, &options, stack_snapshots))
+        {
             Ok(_) => assert!(false),
             Err(err) => panic!("{}", err),
         }
@@ -152,11 +141,11 @@ mod tests {
     #[test]
     #[should_panic(expected = "Unknown type 'test'")]
     fn nil_wrong_arg_type() {
-        let arg_value: Value = json!({ "prim": "test" });
+        let arg_value = Micheline::prim("test", vec![]);
         let arg_vec = vec![arg_value];
-        let args: Option<&Vec<Value>> = Some(&arg_vec);
+        let args: Option<&Vec<Micheline>> = Some(&arg_vec);
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Int(5), Instruction::INIT),
+            StackElement::new(MValue::new_int(5), Instruction::INIT),
             StackElement::new(MValue::Mutez(6_000_000), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
@@ -167,7 +156,9 @@ mod tests {
 
         assert!(initial_stack.len() == 2);
 
-        match run(initial_stack, args, &options, stack_snapshots) {
+        match parse_args(args)
+            .and_then(|list_type| run(initial_stack, &list_type, &options, stack_snapshots))
+        {
             Ok(_) => assert!(false),
             Err(err) => panic!("{}", err),
         }
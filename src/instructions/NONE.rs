@@ -1,57 +1,43 @@
+use crate::errors::MichelsonError;
 use crate::instructions::{Instruction, RunOptions};
 use crate::m_types::{MType, MValue, OptionValue};
 use crate::stack::{Stack, StackElement, StackFuncs, StackSnapshots};
-use serde_json::Value;
+use crate::micheline::Micheline;
 
 // https://tezos.gitlab.io/michelson-reference/#instr-NONE
 
+/// decodes the type of the value of the option
+pub fn parse_args(args: Option<&Vec<Micheline>>) -> Result<MType, MichelsonError> {
+    match args {
+        None => Err(MichelsonError::Message(String::from(
+            "Arguments for NONE instruction cannot be empty",
+        ))),
+        Some(val) => match &val[0] {
+            type_node @ Micheline::Prim { .. } => MType::from_micheline(type_node),
+            _ => Err(MichelsonError::Message(String::from("Expected string for the option element type"))),
+        },
+    }
+}
+
 pub fn run(
     stack: Stack,
-    args: Option<&Vec<Value>>,
+    option_type: &MType,
     options: &RunOptions,
     mut stack_snapshots: StackSnapshots,
-) -> Result<(Stack, StackSnapshots), String> {
+) -> Result<(Stack, StackSnapshots), MichelsonError> {
     // no need to check the stack, it can be empty
-    let new_stack_res: Result<Stack, String> = match args {
-        None => Err(String::from(
-            "Arguments for NONE instruction cannot be empty",
-        )),
-        Some(val) => {
-            if val[0].is_object() {
-                let new_opt_res: Result<StackElement, String> = match val[0]["prim"].as_str() {
-                    None => Err(String::from("Expected string for the option element type")),
-                    Some(str) => match MType::from_string(str) {
-                        Err(err) => Err(err),
-                        Ok(option_type) => Ok(StackElement::new(
-                            MValue::Option(OptionValue {
-                                m_type: option_type,
-                                value: Box::new(None),
-                            }),
-                            Instruction::NONE,
-                        )),
-                    },
-                };
-                match new_opt_res {
-                    Err(err) => Err(err),
-                    Ok(new_option) => {
-                        let new_stack = stack.insert_at(vec![new_option], options.pos);
-                        Ok(new_stack)
-                    }
-                }
-            } else {
-                panic!("Expected a 'serde_json::Value' of type object")
-            }
-        }
-    };
-    match new_stack_res {
-        Err(err) => Err(err),
-        Ok(new_stack) => {
-            // updates the stack snapshots
-            stack_snapshots.push(new_stack.clone());
-
-            Ok((new_stack, stack_snapshots))
-        }
-    }
+    let new_option = StackElement::new(
+        MValue::Option(OptionValue {
+            m_type: option_type.clone(),
+            value: Box::new(None),
+        }),
+        Instruction::NONE,
+    );
+    let new_stack = stack.insert_at(vec![new_option], options.pos);
+    // updates the stack snapshots
+    stack_snapshots.push(new_stack.clone());
+
+    Ok((new_stack, stack_snapshots))
 }
 
 /**
@@ -63,17 +49,16 @@ mod tests {
     use super::*;
     use crate::instructions::RunOptionsContext;
     use crate::m_types::{MType, OptionValue};
-    use serde_json::json;
 
     // PASSING TESTS
     // pushes a new option of type nat to the stack
     #[test]
     fn option_of_nat() {
-        let arg_value: Value = json!({ "prim": "nat" });
+        let arg_value = Micheline::prim("nat", vec![]);
         let arg_vec = vec![arg_value];
-        let args: Option<&Vec<Value>> = Some(&arg_vec);
+        let args: Option<&Vec<Micheline>> = Some(&arg_vec);
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Int(5), Instruction::INIT),
+            StackElement::new(MValue::new_int(5), Instruction::INIT),
             StackElement::new(MValue::Mutez(6_000_000), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
@@ -84,7 +69,9 @@ mod tests {
 
         assert!(initial_stack.len() == 2);
 
-        match run(initial_stack, args, &options, stack_snapshots) {
+        match parse_args(args)
+            .and_then(|option_type| run(initial_stack, &option_type, &options, stack_snapshots))
+        {
             Ok((stack, _)) => {
                 let expected_option = OptionValue {
                     m_type: MType::Nat,
@@ -102,9 +89,9 @@ mod tests {
     #[test]
     #[should_panic(expected = "Arguments for NONE instruction cannot be empty")]
     fn nil_empty_args() {
-        let args: Option<&Vec<Value>> = None;
+        let args: Option<&Vec<Micheline>> = None;
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Int(5), Instruction::INIT),
+            StackElement::new(MValue::new_int(5), Instruction::INIT),
             StackElement::new(MValue::Mutez(6_000_000), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
@@ -115,7 +102,9 @@ mod tests {
 
         assert!(initial_stack.len() == 2);
 
-        match run(initial_stack, args, &options, stack_snapshots) {
+        match parse_args(args)
+            .and_then(|option_type| run(initial_stack, &option_type, &options, stack_snapshots))
+        {
             Ok(_) => assert!(false),
             Err(err) => panic!("{}", err),
         }
@@ -125,11 +114,11 @@ mod tests {
     #[test]
     #[should_panic(expected = "Expected string for the option element type")]
     fn nil_wrong_args() {
-        let arg_value: Value = json!({ "int": "3" });
+        let arg_value = Micheline::Int(String::from("3"));
         let arg_vec = vec![arg_value];
-        let args: Option<&Vec<Value>> = Some(&arg_vec);
+        let args: Option<&Vec<Micheline>> = Some(&arg_vec);
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Int(5), Instruction::INIT),
+            StackElement::new(MValue::new_int(5), Instruction::INIT),
             StackElement::new(MValue::Mutez(6_000_000), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
@@ -140,7 +129,9 @@ mod tests {
 
         assert!(initial_stack.len() == 2);
 
-        match run(initial_stack, args, &options, stack_snapshots) {
+        match parse_args(args)
+            .and_then(|option_type| run(initial_stack, &option_type, &options, stack_snapshots))
+        {
             Ok(_) => assert!(false),
             Err(err) => panic!("{}", err),
         }
@@ -150,11 +141,11 @@ mod tests {
     #[test]
     #[should_panic(expected = "Unknown type 'test'")]
     fn nil_wrong_arg_type() {
-        let arg_value: Value = json!({ "prim": "test" });
+        let arg_value = Micheline::prim("test", vec![]);
         let arg_vec = vec![arg_value];
-        let args: Option<&Vec<Value>> = Some(&arg_vec);
+        let args: Option<&Vec<Micheline>> = Some(&arg_vec);
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Int(5), Instruction::INIT),
+            StackElement::new(MValue::new_int(5), Instruction::INIT),
             StackElement::new(MValue::Mutez(6_000_000), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
@@ -165,7 +156,9 @@ mod tests {
 
         assert!(initial_stack.len() == 2);
 
-        match run(initial_stack, args, &options, stack_snapshots) {
+        match parse_args(args)
+            .and_then(|option_type| run(initial_stack, &option_type, &options, stack_snapshots))
+        {
             Ok(_) => assert!(false),
             Err(err) => panic!("{}", err),
         }
@@ -1,3 +1,4 @@
+use crate::errors::MichelsonError;
 use crate::instructions::{Instruction, RunOptions};
 use crate::m_types::{int, MValue};
 use crate::stack::{Stack, StackElement, StackFuncs, StackSnapshots};
@@ -8,23 +9,20 @@ pub fn run(
     stack: Stack,
     options: &RunOptions,
     mut stack_snapshots: StackSnapshots,
-) -> Result<(Stack, StackSnapshots), String> {
+) -> Result<(Stack, StackSnapshots), MichelsonError> {
     // checks the stack
-    match stack.check_depth(options.pos + 1, Instruction::NOT) {
-        Ok(_) => (),
-        Err(err) => panic!("{}", err),
-    };
+    stack.check_depth(options.pos + 1, Instruction::NOT)?;
     // pattern matches the different numeric types
     let new_val: MValue = match stack[options.pos].get_val() {
         // boolean NOT
         MValue::Bool(val) => Ok(MValue::Bool(!val)),
         // bitwise NOT
-        MValue::Nat(val) => Ok(MValue::Int(!val as int)),
+        MValue::Nat(val) => Ok(MValue::Int(!int::from(val))),
         MValue::Int(val) => Ok(MValue::Int(!val)),
-        _ => Err(format!(
+        _ => Err(MichelsonError::Message(format!(
             "Invalid types for `NOT` expected `bool`, `int` or `nat`, but got `{}`",
             stack[options.pos].value.get_type().to_string()
-        )),
+        ))),
     }?;
     // pushes the new element to the stack
     let new_stack = stack.replace(
@@ -53,7 +51,7 @@ mod tests {
     fn not_bool() -> () {
         let initial_stack: Stack = vec![
             StackElement::new(MValue::Bool(true), Instruction::INIT),
-            StackElement::new(MValue::Int(2), Instruction::INIT),
+            StackElement::new(MValue::new_int(2), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
         let options = RunOptions {
@@ -69,7 +67,7 @@ mod tests {
                 assert!(stack.len() == 2);
                 assert_eq!(stack[0].value, MValue::Bool(false));
                 assert_eq!(stack[0].instruction, Instruction::NOT);
-                assert_eq!(stack[1].value, MValue::Int(2));
+                assert_eq!(stack[1].value, MValue::new_int(2));
                 assert_eq!(stack[1].instruction, Instruction::INIT);
             }
         }
@@ -79,8 +77,8 @@ mod tests {
     #[test]
     fn not_nat() -> () {
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Nat(3), Instruction::INIT),
-            StackElement::new(MValue::Nat(44), Instruction::INIT),
+            StackElement::new(MValue::new_nat(3), Instruction::INIT),
+            StackElement::new(MValue::new_nat(44), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
         let options = RunOptions {
@@ -94,17 +92,17 @@ mod tests {
             Err(_) => assert!(false),
             Ok((stack, _)) => {
                 assert!(stack.len() == 2);
-                assert_eq!(stack[0].value, MValue::Int(-4));
+                assert_eq!(stack[0].value, MValue::new_int(-4));
                 assert_eq!(stack[0].instruction, Instruction::NOT);
-                assert_eq!(stack[1].value, MValue::Nat(44));
+                assert_eq!(stack[1].value, MValue::new_nat(44));
                 assert_eq!(stack[1].instruction, Instruction::INIT);
             }
         }
 
         // example from the Michelson reference
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Nat(0), Instruction::INIT),
-            StackElement::new(MValue::Nat(44), Instruction::INIT),
+            StackElement::new(MValue::new_nat(0), Instruction::INIT),
+            StackElement::new(MValue::new_nat(44), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
 
@@ -114,9 +112,9 @@ mod tests {
             Err(_) => assert!(false),
             Ok((stack, _)) => {
                 assert!(stack.len() == 2);
-                assert_eq!(stack[0].value, MValue::Int(-1));
+                assert_eq!(stack[0].value, MValue::new_int(-1));
                 assert_eq!(stack[0].instruction, Instruction::NOT);
-                assert_eq!(stack[1].value, MValue::Nat(44));
+                assert_eq!(stack[1].value, MValue::new_nat(44));
                 assert_eq!(stack[1].instruction, Instruction::INIT);
             }
         }
@@ -131,8 +129,8 @@ mod tests {
     fn not_wrong_types() -> () {
         let initial_stack: Stack = vec![
             StackElement::new(MValue::String(String::from("test")), Instruction::INIT),
-            StackElement::new(MValue::Nat(3), Instruction::INIT),
-            StackElement::new(MValue::Nat(44), Instruction::INIT),
+            StackElement::new(MValue::new_nat(3), Instruction::INIT),
+            StackElement::new(MValue::new_nat(44), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
         let options = RunOptions {
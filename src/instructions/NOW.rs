@@ -1,7 +1,7 @@
+use crate::errors::MichelsonError;
 use crate::instructions::{Instruction, RunOptions};
 use crate::m_types::MValue;
 use crate::stack::{Stack, StackElement, StackFuncs, StackSnapshots};
-use std::time::{SystemTime, UNIX_EPOCH};
 
 // https://tezos.gitlab.io/michelson-reference/#instr-NOW
 
@@ -9,14 +9,10 @@ pub fn run(
     stack: Stack,
     options: &RunOptions,
     mut stack_snapshots: StackSnapshots,
-) -> Result<(Stack, StackSnapshots), String> {
+) -> Result<(Stack, StackSnapshots), MichelsonError> {
     // no stack check required
-    // updates the stack
-    let duration = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
-    let new_el = StackElement::new(
-        MValue::Timestamp(duration.as_secs() as usize),
-        Instruction::NOW,
-    );
+    // updates the stack with the timestamp provided in the context
+    let new_el = StackElement::new(MValue::Timestamp(options.context.now), Instruction::NOW);
     let new_stack = stack.insert_at(vec![new_el], options.pos);
     // updates the stack snapshots
     stack_snapshots.push(new_stack.clone());
@@ -36,7 +32,7 @@ mod tests {
     fn now_success() {
         // should push the address to the stack
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Int(22), Instruction::INIT),
+            StackElement::new(MValue::new_int(22), Instruction::INIT),
             StackElement::new(MValue::Mutez(6_000_000), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
@@ -50,19 +46,33 @@ mod tests {
         match run(initial_stack, &options, stack_snapshots) {
             Err(_) => assert!(false),
             Ok((stack, _)) => {
-                let duration = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
-
                 assert!(stack.len() == 3);
-                assert_eq!(
-                    stack[0].value,
-                    MValue::Timestamp(duration.as_secs() as usize)
-                );
+                assert_eq!(stack[0].value, MValue::Timestamp(1_640_995_200));
                 assert_eq!(stack[0].instruction, Instruction::NOW);
-                assert_eq!(stack[1].value, MValue::Int(22));
+                assert_eq!(stack[1].value, MValue::new_int(22));
                 assert_eq!(stack[1].instruction, Instruction::INIT);
                 assert_eq!(stack[2].value, MValue::Mutez(6_000_000));
                 assert_eq!(stack[2].instruction, Instruction::INIT);
             }
         }
     }
+
+    #[test]
+    fn now_advances_with_context() {
+        let mut options = RunOptions {
+            context: RunOptionsContext::mock(),
+            pos: 0,
+        };
+        options.context.advance_time(30);
+        options.context.advance_blocks(2, 15);
+
+        match run(vec![], &options, vec![]) {
+            Err(_) => assert!(false),
+            Ok((stack, _)) => {
+                assert!(stack.len() == 1);
+                assert_eq!(stack[0].value, MValue::Timestamp(1_640_995_260));
+                assert_eq!(options.context.level, 13);
+            }
+        }
+    }
 }
@@ -1,3 +1,4 @@
+use crate::errors::MichelsonError;
 use crate::instructions::{Instruction, RunOptions};
 use crate::m_types::MValue;
 use crate::stack::{Stack, StackElement, StackFuncs, StackSnapshots};
@@ -8,12 +9,9 @@ pub fn run(
     stack: Stack,
     options: &RunOptions,
     mut stack_snapshots: StackSnapshots,
-) -> Result<(Stack, StackSnapshots), String> {
+) -> Result<(Stack, StackSnapshots), MichelsonError> {
     // checks the stack
-    match stack.check_depth(options.pos + 2, Instruction::OR) {
-        Ok(_) => (),
-        Err(err) => panic!("{}", err),
-    };
+    stack.check_depth(options.pos + 2, Instruction::OR)?;
     // pattern matches the different numeric types
     let new_val: MValue = match (
         stack[options.pos].get_val(),
@@ -23,11 +21,11 @@ pub fn run(
         (MValue::Bool(left), MValue::Bool(right)) => Ok(MValue::Bool(left || right)),
         // bitwise OR
         (MValue::Nat(left), MValue::Nat(right)) => Ok(MValue::Nat(left | right)),
-        _ => Err(format!(
+        _ => Err(MichelsonError::Message(format!(
             "Invalid types for `OR` expected `bool/bool` or `nat/nat`, but got `{}/{}`",
             stack[options.pos].value.get_type().to_string(),
             stack[options.pos + 1].value.get_type().to_string()
-        )),
+        ))),
     }?;
     // removes the 2 elements being compared from the stack
     let (_, new_stack) = stack.remove_at(options.pos);
@@ -85,9 +83,9 @@ mod tests {
     #[test]
     fn or_nat_nat() -> () {
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Nat(2), Instruction::INIT),
-            StackElement::new(MValue::Nat(3), Instruction::INIT),
-            StackElement::new(MValue::Nat(44), Instruction::INIT),
+            StackElement::new(MValue::new_nat(2), Instruction::INIT),
+            StackElement::new(MValue::new_nat(3), Instruction::INIT),
+            StackElement::new(MValue::new_nat(44), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
         let options = RunOptions {
@@ -101,9 +99,9 @@ mod tests {
             Err(_) => assert!(false),
             Ok((stack, _)) => {
                 assert!(stack.len() == 2);
-                assert_eq!(stack[0].value, MValue::Nat(3));
+                assert_eq!(stack[0].value, MValue::new_nat(3));
                 assert_eq!(stack[0].instruction, Instruction::OR);
-                assert_eq!(stack[1].value, MValue::Nat(44));
+                assert_eq!(stack[1].value, MValue::new_nat(44));
                 assert_eq!(stack[1].instruction, Instruction::INIT);
             }
         }
@@ -118,8 +116,8 @@ mod tests {
     fn or_wrong_types() -> () {
         let initial_stack: Stack = vec![
             StackElement::new(MValue::String(String::from("test")), Instruction::INIT),
-            StackElement::new(MValue::Nat(3), Instruction::INIT),
-            StackElement::new(MValue::Nat(44), Instruction::INIT),
+            StackElement::new(MValue::new_nat(3), Instruction::INIT),
+            StackElement::new(MValue::new_nat(44), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
         let options = RunOptions {
@@ -141,7 +139,7 @@ mod tests {
         expected = "Unexpected stack length, expected a length of 2 for instruction OR, got 1"
     )]
     fn or_wrong_stack_depth() -> () {
-        let initial_stack: Stack = vec![StackElement::new(MValue::Nat(3), Instruction::INIT)];
+        let initial_stack: Stack = vec![StackElement::new(MValue::new_nat(3), Instruction::INIT)];
         let stack_snapshots = vec![];
         let options = RunOptions {
             context: RunOptionsContext::mock(),
@@ -1,5 +1,6 @@
+use crate::errors::MichelsonError;
 use crate::instructions::{Instruction, RunOptions};
-use crate::m_types::{MValue, PairValue};
+use crate::m_types::{MType, MValue, PairValue};
 use crate::stack::{Stack, StackElement, StackFuncs, StackSnapshots};
 
 // https://tezos.gitlab.io/michelson-reference/#instr-PAIR
@@ -8,7 +9,7 @@ pub fn run(
     stack: Stack,
     options: &RunOptions,
     mut stack_snapshots: StackSnapshots,
-) -> Result<(Stack, StackSnapshots), String> {
+) -> Result<(Stack, StackSnapshots), MichelsonError> {
     // checks the stack
     match stack.check_depth(2, Instruction::PAIR) {
         Ok(_) => {
@@ -21,8 +22,17 @@ pub fn run(
             // drops the 2 elements from the stack
             let (_, new_stack) = stack.remove_at(options.pos);
             let (_, new_stack) = new_stack.remove_at(options.pos);
+            // the fields of the pair keep the annotations of the elements
+            let (left, right) = (&stack[options.pos], &stack[options.pos + 1]);
+            let mut pair_el = StackElement::new(new_pair, Instruction::PAIR);
+            if left.has_type_annots() || right.has_type_annots() {
+                pair_el = pair_el.with_type(MType::Pair(
+                    Box::new((left.annotated_type(), right.annotated_type())),
+                    Box::new((left.type_annots.clone(), right.type_annots.clone())),
+                ));
+            }
             // pushes the new pair to the stack
-            let mut stack_with_pair: Stack = vec![StackElement::new(new_pair, Instruction::PAIR)];
+            let mut stack_with_pair: Stack = vec![pair_el];
             let mut old_stack = new_stack.clone();
             stack_with_pair.append(&mut old_stack);
             // updates the stack snapshots
@@ -49,8 +59,8 @@ mod tests {
     #[test]
     fn pair_success() {
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Int(5), Instruction::INIT),
-            StackElement::new(MValue::Nat(6), Instruction::INIT),
+            StackElement::new(MValue::new_int(5), Instruction::INIT),
+            StackElement::new(MValue::new_nat(6), Instruction::INIT),
             StackElement::new(MValue::Mutez(6_000_000), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
@@ -69,7 +79,7 @@ mod tests {
                     new_stack[0].value
                         == MValue::Pair(PairValue {
                             m_type: (MType::Int, MType::Nat),
-                            value: Box::new((MValue::Int(5), MValue::Nat(6)))
+                            value: Box::new((MValue::new_int(5), MValue::new_nat(6)))
                         })
                 )
             }
@@ -83,7 +93,7 @@ mod tests {
         expected = "Unexpected stack length, expected a length of 2 for instruction PAIR, got 1"
     )]
     fn pair_wrong_stack() {
-        let initial_stack: Stack = vec![StackElement::new(MValue::Int(5), Instruction::INIT)];
+        let initial_stack: Stack = vec![StackElement::new(MValue::new_int(5), Instruction::INIT)];
         let stack_snapshots = vec![];
         let options = RunOptions {
             context: RunOptionsContext::mock(),
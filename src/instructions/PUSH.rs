@@ -1,201 +1,46 @@
-use crate::errors::{display_error, ErrorCode};
+use crate::errors::MichelsonError;
 use crate::instructions::{Instruction, RunOptions};
-use crate::m_types::{int, mutez, nat, timestamp, MType, MValue};
+use crate::m_types::{MType, MValue};
 use crate::stack::{Stack, StackElement, StackFuncs, StackSnapshots};
-use serde_json::Value;
+use crate::micheline::Micheline;
 
 // https://tezos.gitlab.io/michelson-reference/#instr-PUSH
 
+/// decodes the value to push from its type and its Micheline representation
+pub fn parse_args(args: Option<&Vec<Micheline>>) -> Result<MValue, MichelsonError> {
+    match args {
+        None => Err(MichelsonError::NoArgument(Instruction::PUSH)),
+        Some(arg) => match arg.as_slice() {
+            // checks that the value matches the type
+            [type_node @ Micheline::Prim { .. }, value_node] => {
+                let element_type = MType::from_micheline(type_node)?;
+                MValue::from_micheline(value_node, &element_type)
+            }
+            [type_node, _] => Err(MichelsonError::Message(format!(
+                "Expected a type as first argument for PUSH, got {}",
+                type_node
+            ))),
+            _ => Err(MichelsonError::UnexpectedArgsNumber((2, arg.len()))),
+        },
+    }
+}
+
 pub fn run(
     stack: Stack,
-    args: Option<&Vec<Value>>,
+    value: &MValue,
     options: &RunOptions,
     mut stack_snapshots: StackSnapshots,
-) -> Result<(Stack, StackSnapshots), String> {
+) -> Result<(Stack, StackSnapshots), MichelsonError> {
     // checking the stack is not required
-    // checks that the arguments are correct
-    let new_stack_element: Result<StackElement, String> = match args {
-        None => Err(display_error(ErrorCode::NoArgument(Instruction::PUSH))),
-        Some(arg) => {
-            // argument must be a vector of 2 elements
-            if arg.len() == 2 {
-                // extracts the first argument
-                let first_arg = &arg[0];
-                let element_type_res: Result<MType, String> =
-                    if first_arg.is_object() && first_arg.get("prim").is_some() {
-                        // checks if the value is a string
-                        match first_arg["prim"].as_str() {
-                            None => Err(format!(
-                                "JSON value for PUSH type argument is not a valid string: {}",
-                                first_arg
-                            )),
-                            Some(str) => {
-                                // checks if the type is a valid Michelson type
-                                MType::from_string(str)
-                            }
-                        }
-                    } else {
-                        Err(format!(
-                        "Expected an object with a \"prim\" field in JSON value for PUSH, got {:?}",
-                        first_arg
-                    ))
-                    };
-                let element_type = match element_type_res {
-                    Ok(el_type) => el_type,
-                    Err(err) => panic!("{}", err),
-                };
-                // extracts the second argument
-                let second_arg = &arg[1];
-                let element_value_res: Result<(String, String), String> = if second_arg.is_object()
-                {
-                    if second_arg.get("int").is_some() {
-                        // int value
-                        match second_arg.get("int").unwrap().as_str() {
-                            None => Err(String::from("Expected value for \"int\" property to be a string (at PUSH instruction)")),
-                            Some (str) => Ok((String::from("int"), String::from(str)))
-                        }
-                    } else if second_arg.get("string").is_some() {
-                        // string value
-                        match second_arg.get("string").unwrap().as_str() {
-                            None => Err(String::from("Expected value for \"string\" property to be a string (at PUSH instruction)")),
-                            Some (str) => Ok((String::from("string"), String::from(str)))
-                        }
-                    } else {
-                        //TODO: the property can also be "prim"
-                        Err(format!(
-                            "JSON value for PUSH value argument is not valid: expected \"int\" or \"string\", but got {}",
-                            second_arg
-                        ))
-                    }
-                } else {
-                    Err(format!(
-                        "Expected an object in JSON value for PUSH, got {:?}",
-                        second_arg
-                    ))
-                };
-                let element_value = match element_value_res {
-                    Ok(el_value) => el_value,
-                    Err(err) => panic!("{}", err),
-                };
-                // checks that the value matches the type
-                match element_type {
-                    // numeric types
-                    MType::Int => {
-                        let (val_type, value) = element_value;
-                        if val_type == "int" {
-                            match value.parse::<int>() {
-                                Ok(val) => {
-                                    // creates the new stack element
-                                    Ok(StackElement::new(MValue::Int(val), Instruction::PUSH))
-                                }
-                                Err(_) => Err(display_error(ErrorCode::InvalidArgument((
-                                    String::from("numeric value"),
-                                    value,
-                                )))),
-                            }
-                        } else {
-                            Err(display_error(ErrorCode::InvalidArgument((
-                                String::from("int"),
-                                val_type,
-                            ))))
-                        }
-                    }
-                    MType::Nat => {
-                        let (val_type, value) = element_value;
-                        if val_type == "int" {
-                            match value.parse::<nat>() {
-                                Ok(val) => {
-                                    // creates the new stack element
-                                    Ok(StackElement::new(MValue::Nat(val), Instruction::PUSH))
-                                }
-                                Err(_) => Err(display_error(ErrorCode::InvalidArgument((
-                                    String::from("numeric value"),
-                                    value,
-                                )))),
-                            }
-                        } else {
-                            Err(display_error(ErrorCode::InvalidArgument((
-                                String::from("int"),
-                                val_type,
-                            ))))
-                        }
-                    }
-                    MType::Mutez => {
-                        let (val_type, value) = element_value;
-                        if val_type == "int" {
-                            match value.parse::<mutez>() {
-                                Ok(val) => {
-                                    // creates the new stack element
-                                    Ok(StackElement::new(MValue::Mutez(val), Instruction::PUSH))
-                                }
-                                Err(_) => Err(display_error(ErrorCode::InvalidArgument((
-                                    String::from("numeric value"),
-                                    value,
-                                )))),
-                            }
-                        } else {
-                            Err(display_error(ErrorCode::InvalidArgument((
-                                String::from("int"),
-                                val_type,
-                            ))))
-                        }
-                    }
-                    MType::Timestamp => {
-                        let (val_type, value) = element_value;
-                        if val_type == "int" {
-                            match value.parse::<timestamp>() {
-                                Ok(val) => {
-                                    // creates the new stack element
-                                    Ok(StackElement::new(MValue::Timestamp(val), Instruction::PUSH))
-                                }
-                                Err(_) => Err(display_error(ErrorCode::InvalidArgument((
-                                    String::from("numeric value"),
-                                    value,
-                                )))),
-                            }
-                        } else {
-                            Err(display_error(ErrorCode::InvalidArgument((
-                                String::from("int"),
-                                val_type,
-                            ))))
-                        }
-                    }
-                    // other types
-                    MType::String => {
-                        let (val_type, value) = element_value;
-                        if val_type == "string" {
-                            Ok(StackElement::new(MValue::String(value), Instruction::PUSH))
-                        } else {
-                            Err(display_error(ErrorCode::InvalidArgument((
-                                String::from("string"),
-                                val_type,
-                            ))))
-                        }
-                    }
-                    // TODO: handle all the possible cases
-                    _ => Err(String::from(
-                        "Unhandled patterns to check type/value in PUSH instruction",
-                    )),
-                }
-            } else {
-                Err(display_error(ErrorCode::UnexpectedArgsNumber((
-                    2,
-                    arg.len(),
-                ))))
-            }
-        }
-    };
     // pushes the element to the stack
-    match new_stack_element {
-        Err(err) => Err(err),
-        Ok(stack_el) => {
-            let new_stack = stack.insert_at(vec![stack_el], options.pos);
-            // updates the stack snapshots
-            stack_snapshots.push(new_stack.clone());
-            // returns the new stack
-            Ok((new_stack, stack_snapshots))
-        }
-    }
+    let new_stack = stack.insert_at(
+        vec![StackElement::new(value.clone(), Instruction::PUSH)],
+        options.pos,
+    );
+    // updates the stack snapshots
+    stack_snapshots.push(new_stack.clone());
+    // returns the new stack
+    Ok((new_stack, stack_snapshots))
 }
 
 /**
@@ -206,18 +51,18 @@ pub fn run(
 mod tests {
     use super::*;
     use crate::instructions::RunOptionsContext;
-    use serde_json::{json, Value};
+    use crate::m_types::{OptionValue, PairValue};
 
     // PASSING
     #[test]
     fn push_success() {
-        let arg_type: Value = json!({"prim": "string"});
-        let arg_value: Value = json!({"string": "FA2_NOT_OPERATOR"});
+        let arg_type = Micheline::prim("string", vec![]);
+        let arg_value = Micheline::String(String::from("FA2_NOT_OPERATOR"));
         let arg_vec = vec![arg_type, arg_value];
-        let args: Option<&Vec<Value>> = Some(&arg_vec);
+        let args: Option<&Vec<Micheline>> = Some(&arg_vec);
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Int(5), Instruction::INIT),
-            StackElement::new(MValue::Nat(6), Instruction::INIT),
+            StackElement::new(MValue::new_int(5), Instruction::INIT),
+            StackElement::new(MValue::new_nat(6), Instruction::INIT),
             StackElement::new(MValue::Mutez(6_000_000), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
@@ -228,7 +73,7 @@ mod tests {
 
         assert!(initial_stack.len() == 3);
 
-        match run(initial_stack, args, &options, stack_snapshots) {
+        match parse_args(args).and_then(|value| run(initial_stack, &value, &options, stack_snapshots)) {
             Ok((new_stack, _)) => {
                 assert!(new_stack.len() == 4);
                 assert!(new_stack[0].value == MValue::String(String::from("FA2_NOT_OPERATOR")));
@@ -237,16 +82,68 @@ mod tests {
         }
     }
 
+    #[test]
+    fn push_complex_type() {
+        let arg_type: Micheline = "pair (option nat) (list string)".parse().unwrap();
+        let arg_value: Micheline = "Pair None { \"tezos\" ; \"taquito\" }".parse().unwrap();
+        let arg_vec = vec![arg_type, arg_value];
+        let args: Option<&Vec<Micheline>> = Some(&arg_vec);
+        let initial_stack: Stack = vec![StackElement::new(MValue::new_int(5), Instruction::INIT)];
+        let stack_snapshots = vec![];
+        let options = RunOptions {
+            context: RunOptionsContext::mock(),
+            pos: 0,
+        };
+
+        match parse_args(args).and_then(|value| run(initial_stack, &value, &options, stack_snapshots)) {
+            Ok((new_stack, _)) => {
+                assert!(new_stack.len() == 2);
+                assert_eq!(
+                    new_stack[0].value.get_type().to_string(),
+                    "pair (option nat) (list string)"
+                );
+                assert_eq!(
+                    new_stack[0].value,
+                    MValue::Pair(PairValue::new(
+                        MValue::Option(OptionValue::new(None, MType::Nat)),
+                        MValue::new_list(
+                            vec![MValue::new_string("tezos"), MValue::new_string("taquito")],
+                            MType::String
+                        )
+                    ))
+                );
+            }
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    #[test]
+    fn push_big_int() {
+        let literal = "-123456789012345678901234567890123456789012345678901234567890";
+        let arg_vec = vec![Micheline::prim("int", vec![]), Micheline::Int(String::from(literal))];
+        let options = RunOptions {
+            context: RunOptionsContext::mock(),
+            pos: 0,
+        };
+
+        match parse_args(Some(&arg_vec)).and_then(|value| run(vec![], &value, &options, vec![])) {
+            Ok((new_stack, _)) => {
+                assert_eq!(new_stack[0].value, MValue::Int(literal.parse().unwrap()));
+            }
+            Err(err) => panic!("{}", err),
+        }
+    }
+
     // FAILING
     #[test]
     #[should_panic(expected = "Unexpected number of arguments, expected `2`, got `1`")]
     fn pair_wrong_args_number() {
-        let arg_value: Value = json!({"string": "FA2_NOT_OPERATOR"});
+        let arg_value = Micheline::String(String::from("FA2_NOT_OPERATOR"));
         let arg_vec = vec![arg_value];
-        let args: Option<&Vec<Value>> = Some(&arg_vec);
+        let args: Option<&Vec<Micheline>> = Some(&arg_vec);
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Int(5), Instruction::INIT),
-            StackElement::new(MValue::Nat(6), Instruction::INIT),
+            StackElement::new(MValue::new_int(5), Instruction::INIT),
+            StackElement::new(MValue::new_nat(6), Instruction::INIT),
             StackElement::new(MValue::Mutez(6_000_000), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
@@ -257,7 +154,7 @@ mod tests {
 
         assert!(initial_stack.len() == 3);
 
-        match run(initial_stack, args, &options, stack_snapshots) {
+        match parse_args(args).and_then(|value| run(initial_stack, &value, &options, stack_snapshots)) {
             Ok(_) => assert!(false),
             Err(err) => panic!("{}", err),
         }
@@ -266,16 +163,16 @@ mod tests {
     // wrong argument type
     #[test]
     #[should_panic(
-        expected = "Expected an object with a \"prim\" field in JSON value for PUSH, got Object({\"int\": String(\"0\")}"
+        expected = "Expected a type as first argument for PUSH, got 0"
     )]
     fn pair_wrong_args_type() {
-        let arg_type: Value = json!({"int": "0"});
-        let arg_value: Value = json!({"string": "FA2_NOT_OPERATOR"});
+        let arg_type = Micheline::Int(String::from("0"));
+        let arg_value = Micheline::String(String::from("FA2_NOT_OPERATOR"));
         let arg_vec = vec![arg_type, arg_value];
-        let args: Option<&Vec<Value>> = Some(&arg_vec);
+        let args: Option<&Vec<Micheline>> = Some(&arg_vec);
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Int(5), Instruction::INIT),
-            StackElement::new(MValue::Nat(6), Instruction::INIT),
+            StackElement::new(MValue::new_int(5), Instruction::INIT),
+            StackElement::new(MValue::new_nat(6), Instruction::INIT),
             StackElement::new(MValue::Mutez(6_000_000), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
@@ -286,7 +183,7 @@ mod tests {
 
         assert!(initial_stack.len() == 3);
 
-        match run(initial_stack, args, &options, stack_snapshots) {
+        match parse_args(args).and_then(|value| run(initial_stack, &value, &options, stack_snapshots)) {
             Ok(_) => assert!(false),
             Err(err) => panic!("{}", err),
         }
@@ -296,13 +193,13 @@ mod tests {
     #[test]
     #[should_panic(expected = "Unknown type 'test'")]
     fn pair_wrong_mich_type() {
-        let arg_type: Value = json!({"prim": "test"});
-        let arg_value: Value = json!({"string": "FA2_NOT_OPERATOR"});
+        let arg_type = Micheline::prim("test", vec![]);
+        let arg_value = Micheline::String(String::from("FA2_NOT_OPERATOR"));
         let arg_vec = vec![arg_type, arg_value];
-        let args: Option<&Vec<Value>> = Some(&arg_vec);
+        let args: Option<&Vec<Micheline>> = Some(&arg_vec);
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Int(5), Instruction::INIT),
-            StackElement::new(MValue::Nat(6), Instruction::INIT),
+            StackElement::new(MValue::new_int(5), Instruction::INIT),
+            StackElement::new(MValue::new_nat(6), Instruction::INIT),
             StackElement::new(MValue::Mutez(6_000_000), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
@@ -313,7 +210,7 @@ mod tests {
 
         assert!(initial_stack.len() == 3);
 
-        match run(initial_stack, args, &options, stack_snapshots) {
+        match parse_args(args).and_then(|value| run(initial_stack, &value, &options, stack_snapshots)) {
             Ok(_) => assert!(false),
             Err(err) => panic!("{}", err),
         }
@@ -322,16 +219,16 @@ mod tests {
     // wrong argument value
     #[test]
     #[should_panic(
-        expected = "Expected value for \"string\" property to be a string (at PUSH instruction)"
+        expected = "Invalid argument provided, expected `string`, but got `Unit`"
     )]
     fn pair_wrong_args_value() {
-        let arg_type: Value = json!({"prim": "string"});
-        let arg_value: Value = json!({"string": ["FA2_NOT_OPERATOR"]});
+        let arg_type = Micheline::prim("string", vec![]);
+        let arg_value = Micheline::prim("Unit", vec![]);
         let arg_vec = vec![arg_type, arg_value];
-        let args: Option<&Vec<Value>> = Some(&arg_vec);
+        let args: Option<&Vec<Micheline>> = Some(&arg_vec);
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Int(5), Instruction::INIT),
-            StackElement::new(MValue::Nat(6), Instruction::INIT),
+            StackElement::new(MValue::new_int(5), Instruction::INIT),
+            StackElement::new(MValue::new_nat(6), Instruction::INIT),
             StackElement::new(MValue::Mutez(6_000_000), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
@@ -342,7 +239,7 @@ mod tests {
 
         assert!(initial_stack.len() == 3);
 
-        match run(initial_stack, args, &options, stack_snapshots) {
+        match parse_args(args).and_then(|value| run(initial_stack, &value, &options, stack_snapshots)) {
             Ok(_) => assert!(false),
             Err(err) => panic!("{}", err),
         }
@@ -352,13 +249,13 @@ mod tests {
     #[test]
     #[should_panic(expected = "Invalid argument provided, expected `string`, but got `int`")]
     fn pair_wrong_type_value() {
-        let arg_type: Value = json!({"prim": "string"});
-        let arg_value: Value = json!({"int": "5"});
+        let arg_type = Micheline::prim("string", vec![]);
+        let arg_value = Micheline::Int(String::from("5"));
         let arg_vec = vec![arg_type, arg_value];
-        let args: Option<&Vec<Value>> = Some(&arg_vec);
+        let args: Option<&Vec<Micheline>> = Some(&arg_vec);
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Int(5), Instruction::INIT),
-            StackElement::new(MValue::Nat(6), Instruction::INIT),
+            StackElement::new(MValue::new_int(5), Instruction::INIT),
+            StackElement::new(MValue::new_nat(6), Instruction::INIT),
             StackElement::new(MValue::Mutez(6_000_000), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
@@ -369,7 +266,25 @@ mod tests {
 
         assert!(initial_stack.len() == 3);
 
-        match run(initial_stack, args, &options, stack_snapshots) {
+        match parse_args(args).and_then(|value| run(initial_stack, &value, &options, stack_snapshots)) {
+            Ok(_) => assert!(false),
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    // mutez values are bounded by 2^63 - 1
+    #[test]
+    #[should_panic(
+        expected = "Invalid argument provided, expected `mutez value between 0 and 9223372036854775807`, but got `9223372036854775808`"
+    )]
+    fn push_mutez_out_of_range() {
+        let arg_vec = vec![Micheline::prim("mutez", vec![]), Micheline::Int(String::from("9223372036854775808"))];
+        let options = RunOptions {
+            context: RunOptionsContext::mock(),
+            pos: 0,
+        };
+
+        match parse_args(Some(&arg_vec)).and_then(|value| run(vec![], &value, &options, vec![])) {
             Ok(_) => assert!(false),
             Err(err) => panic!("{}", err),
         }
@@ -1,3 +1,4 @@
+use crate::errors::MichelsonError;
 use crate::instructions::{Instruction, RunOptions};
 use crate::m_types::MValue;
 use crate::stack::{Stack, StackElement, StackFuncs, StackSnapshots};
@@ -8,18 +9,15 @@ pub fn run(
     stack: Stack,
     options: &RunOptions,
     mut stack_snapshots: StackSnapshots,
-) -> Result<(Stack, StackSnapshots), String> {
+) -> Result<(Stack, StackSnapshots), MichelsonError> {
     // checks the stack
-    match stack.check_depth(options.pos, Instruction::SENDER) {
-        Ok(_) => (),
-        Err(err) => panic!("{}", err),
-    };
+    stack.check_depth(options.pos, Instruction::SENDER)?;
     // checks if the provided address is valid
     match MValue::new_address(options.context.sender.clone()) {
-        None => Err(format!(
+        None => Err(MichelsonError::Message(format!(
             "Provided address for SENDER is not a valid address: {:?}",
             options.context.sender
-        )),
+        ))),
         Some(addr) => {
             // updates the stack
             let new_el = StackElement::new(addr, Instruction::SENDER);
@@ -38,13 +36,13 @@ pub fn run(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::instructions::RunOptionsContext;
+    use crate::instructions::{RunOptionsContext, MAX_LOOP_ITERATIONS};
 
     #[test]
     fn sender_success() {
         // should push the address to the stack
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Int(22), Instruction::INIT),
+            StackElement::new(MValue::new_int(22), Instruction::INIT),
             StackElement::new(MValue::Mutez(6_000_000), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
@@ -56,7 +54,9 @@ mod tests {
                 self_address: String::from("KT1L7GvUxZH5tfa6cgZKnH6vpp2uVxnFVHKu"),
                 balance: 50_000_000,
                 level: 11,
+                now: 1_640_995_200,
                 chain_id: String::from("chain_id"),
+                max_loop_iterations: MAX_LOOP_ITERATIONS,
             },
             pos: 0,
         };
@@ -72,7 +72,7 @@ mod tests {
                     MValue::Address(String::from("tz1Me1MGhK7taay748h4gPnX2cXvbgL6xsYL"))
                 );
                 assert_eq!(stack[0].instruction, Instruction::SENDER);
-                assert_eq!(stack[1].value, MValue::Int(22));
+                assert_eq!(stack[1].value, MValue::new_int(22));
                 assert_eq!(stack[1].instruction, Instruction::INIT);
                 assert_eq!(stack[2].value, MValue::Mutez(6_000_000));
                 assert_eq!(stack[2].instruction, Instruction::INIT);
@@ -87,7 +87,7 @@ mod tests {
     fn sender_invalid_address() {
         // should push the address to the stack
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Int(22), Instruction::INIT),
+            StackElement::new(MValue::new_int(22), Instruction::INIT),
             StackElement::new(MValue::Mutez(6_000_000), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
@@ -1,3 +1,4 @@
+use crate::errors::MichelsonError;
 use crate::instructions::{Instruction, RunOptions};
 use crate::m_types::{nat, MValue};
 use crate::stack::{Stack, StackElement, StackFuncs, StackSnapshots};
@@ -8,23 +9,20 @@ pub fn run(
     stack: Stack,
     options: &RunOptions,
     mut stack_snapshots: StackSnapshots,
-) -> Result<(Stack, StackSnapshots), String> {
+) -> Result<(Stack, StackSnapshots), MichelsonError> {
     // checks the stack
-    match stack.check_depth(options.pos + 1, Instruction::SIZE) {
-        Ok(_) => (),
-        Err(err) => panic!("{}", err),
-    };
+    stack.check_depth(options.pos + 1, Instruction::SIZE)?;
     // SIZE can be used only with string, list, set, map or bytes
-    let size_res: Result<MValue, String> = match &stack[options.pos].value {
-        MValue::String(val) => Ok(MValue::Nat(val.len() as nat)),
-        MValue::Bytes(val) => Ok(MValue::Nat(val.len() as nat)),
-        MValue::List(list) => Ok(MValue::Nat(list.value.len() as nat)),
-        MValue::Set(set) => Ok(MValue::Nat(set.value.len() as nat)),
-        MValue::Map(map) => Ok(MValue::Nat(map.size()? as nat)),
-        _ => Err(format!(
+    let size_res: Result<MValue, MichelsonError> = match &stack[options.pos].value {
+        MValue::String(val) => Ok(MValue::Nat(nat::from(val.len()))),
+        MValue::Bytes(val) => Ok(MValue::Nat(nat::from(val.len()))),
+        MValue::List(list) => Ok(MValue::Nat(nat::from(list.value.len()))),
+        MValue::Set(set) => Ok(MValue::Nat(nat::from(set.value.len()))),
+        MValue::Map(map) => Ok(MValue::Nat(nat::from(map.size()?))),
+        _ => Err(MichelsonError::Message(format!(
             "Expected string, bytes, list, set or map for SIZE, but got {}",
             &stack[options.pos].value.to_string()
-        )),
+        ))),
     };
 
     match size_res {
@@ -60,8 +58,8 @@ mod tests {
     fn size_string_success() {
         let initial_stack: Stack = vec![
             StackElement::new(MValue::String(String::from("test")), Instruction::INIT),
-            StackElement::new(MValue::Int(5), Instruction::INIT),
-            StackElement::new(MValue::Nat(6), Instruction::INIT),
+            StackElement::new(MValue::new_int(5), Instruction::INIT),
+            StackElement::new(MValue::new_nat(6), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
         let options = RunOptions {
@@ -75,11 +73,11 @@ mod tests {
             Err(_) => assert!(false),
             Ok((stack, _)) => {
                 assert!(stack.len() == 3);
-                assert_eq!(stack[0].value, MValue::Nat(4));
+                assert_eq!(stack[0].value, MValue::new_nat(4));
                 assert_eq!(stack[0].instruction, Instruction::SIZE);
-                assert_eq!(stack[1].value, MValue::Int(5));
+                assert_eq!(stack[1].value, MValue::new_int(5));
                 assert_eq!(stack[1].instruction, Instruction::INIT);
-                assert_eq!(stack[2].value, MValue::Nat(6));
+                assert_eq!(stack[2].value, MValue::new_nat(6));
                 assert_eq!(stack[2].instruction, Instruction::INIT);
             }
         }
@@ -89,9 +87,9 @@ mod tests {
     #[test]
     fn size_bytes_success() {
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Bytes(String::from("74657374")), Instruction::INIT),
-            StackElement::new(MValue::Int(5), Instruction::INIT),
-            StackElement::new(MValue::Nat(6), Instruction::INIT),
+            StackElement::new(MValue::new_bytes(b"test"), Instruction::INIT),
+            StackElement::new(MValue::new_int(5), Instruction::INIT),
+            StackElement::new(MValue::new_nat(6), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
         let options = RunOptions {
@@ -105,11 +103,11 @@ mod tests {
             Err(_) => assert!(false),
             Ok((stack, _)) => {
                 assert!(stack.len() == 3);
-                assert_eq!(stack[0].value, MValue::Nat(8));
+                assert_eq!(stack[0].value, MValue::new_nat(4));
                 assert_eq!(stack[0].instruction, Instruction::SIZE);
-                assert_eq!(stack[1].value, MValue::Int(5));
+                assert_eq!(stack[1].value, MValue::new_int(5));
                 assert_eq!(stack[1].instruction, Instruction::INIT);
-                assert_eq!(stack[2].value, MValue::Nat(6));
+                assert_eq!(stack[2].value, MValue::new_nat(6));
                 assert_eq!(stack[2].instruction, Instruction::INIT);
             }
         }
@@ -122,13 +120,13 @@ mod tests {
         let initial_stack: Stack = vec![
             StackElement::new(
                 MValue::new_list(
-                    vec![MValue::Nat(3), MValue::Nat(4), MValue::Nat(5)],
+                    vec![MValue::new_nat(3), MValue::new_nat(4), MValue::new_nat(5)],
                     MType::Nat,
                 ),
                 Instruction::INIT,
             ),
-            StackElement::new(MValue::Int(5), Instruction::INIT),
-            StackElement::new(MValue::Nat(6), Instruction::INIT),
+            StackElement::new(MValue::new_int(5), Instruction::INIT),
+            StackElement::new(MValue::new_nat(6), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
         let options = RunOptions {
@@ -142,11 +140,11 @@ mod tests {
             Err(_) => assert!(false),
             Ok((stack, _)) => {
                 assert!(stack.len() == 3);
-                assert_eq!(stack[0].value, MValue::Nat(3));
+                assert_eq!(stack[0].value, MValue::new_nat(3));
                 assert_eq!(stack[0].instruction, Instruction::SIZE);
-                assert_eq!(stack[1].value, MValue::Int(5));
+                assert_eq!(stack[1].value, MValue::new_int(5));
                 assert_eq!(stack[1].instruction, Instruction::INIT);
-                assert_eq!(stack[2].value, MValue::Nat(6));
+                assert_eq!(stack[2].value, MValue::new_nat(6));
                 assert_eq!(stack[2].instruction, Instruction::INIT);
             }
         }
@@ -154,8 +152,8 @@ mod tests {
         // empty list
         let initial_stack: Stack = vec![
             StackElement::new(MValue::new_empty_list(MType::Nat), Instruction::INIT),
-            StackElement::new(MValue::Int(5), Instruction::INIT),
-            StackElement::new(MValue::Nat(6), Instruction::INIT),
+            StackElement::new(MValue::new_int(5), Instruction::INIT),
+            StackElement::new(MValue::new_nat(6), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
 
@@ -165,11 +163,11 @@ mod tests {
             Err(_) => assert!(false),
             Ok((stack, _)) => {
                 assert!(stack.len() == 3);
-                assert_eq!(stack[0].value, MValue::Nat(0));
+                assert_eq!(stack[0].value, MValue::new_nat(0));
                 assert_eq!(stack[0].instruction, Instruction::SIZE);
-                assert_eq!(stack[1].value, MValue::Int(5));
+                assert_eq!(stack[1].value, MValue::new_int(5));
                 assert_eq!(stack[1].instruction, Instruction::INIT);
-                assert_eq!(stack[2].value, MValue::Nat(6));
+                assert_eq!(stack[2].value, MValue::new_nat(6));
                 assert_eq!(stack[2].instruction, Instruction::INIT);
             }
         }
@@ -182,13 +180,13 @@ mod tests {
         let initial_stack: Stack = vec![
             StackElement::new(
                 MValue::new_set(
-                    vec![MValue::Nat(3), MValue::Nat(4), MValue::Nat(5)],
+                    vec![MValue::new_nat(3), MValue::new_nat(4), MValue::new_nat(5)],
                     MType::Nat,
                 ),
                 Instruction::INIT,
             ),
-            StackElement::new(MValue::Int(5), Instruction::INIT),
-            StackElement::new(MValue::Nat(6), Instruction::INIT),
+            StackElement::new(MValue::new_int(5), Instruction::INIT),
+            StackElement::new(MValue::new_nat(6), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
         let options = RunOptions {
@@ -202,11 +200,11 @@ mod tests {
             Err(_) => assert!(false),
             Ok((stack, _)) => {
                 assert!(stack.len() == 3);
-                assert_eq!(stack[0].value, MValue::Nat(3));
+                assert_eq!(stack[0].value, MValue::new_nat(3));
                 assert_eq!(stack[0].instruction, Instruction::SIZE);
-                assert_eq!(stack[1].value, MValue::Int(5));
+                assert_eq!(stack[1].value, MValue::new_int(5));
                 assert_eq!(stack[1].instruction, Instruction::INIT);
-                assert_eq!(stack[2].value, MValue::Nat(6));
+                assert_eq!(stack[2].value, MValue::new_nat(6));
                 assert_eq!(stack[2].instruction, Instruction::INIT);
             }
         }
@@ -214,8 +212,8 @@ mod tests {
         // empty set
         let initial_stack: Stack = vec![
             StackElement::new(MValue::new_empty_set(MType::Nat), Instruction::INIT),
-            StackElement::new(MValue::Int(5), Instruction::INIT),
-            StackElement::new(MValue::Nat(6), Instruction::INIT),
+            StackElement::new(MValue::new_int(5), Instruction::INIT),
+            StackElement::new(MValue::new_nat(6), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
 
@@ -225,11 +223,11 @@ mod tests {
             Err(_) => assert!(false),
             Ok((stack, _)) => {
                 assert!(stack.len() == 3);
-                assert_eq!(stack[0].value, MValue::Nat(0));
+                assert_eq!(stack[0].value, MValue::new_nat(0));
                 assert_eq!(stack[0].instruction, Instruction::SIZE);
-                assert_eq!(stack[1].value, MValue::Int(5));
+                assert_eq!(stack[1].value, MValue::new_int(5));
                 assert_eq!(stack[1].instruction, Instruction::INIT);
-                assert_eq!(stack[2].value, MValue::Nat(6));
+                assert_eq!(stack[2].value, MValue::new_nat(6));
                 assert_eq!(stack[2].instruction, Instruction::INIT);
             }
         }
@@ -243,17 +241,17 @@ mod tests {
             StackElement::new(
                 MValue::new_map(
                     MType::Nat,
-                    MType::Int,
+                    MType::Nat,
                     vec![
-                        (MValue::Nat(1), MValue::Nat(2)),
-                        (MValue::Nat(3), MValue::Nat(4)),
-                        (MValue::Nat(5), MValue::Nat(6)),
+                        (MValue::new_nat(1), MValue::new_nat(2)),
+                        (MValue::new_nat(3), MValue::new_nat(4)),
+                        (MValue::new_nat(5), MValue::new_nat(6)),
                     ],
-                ),
+                ).unwrap(),
                 Instruction::INIT,
             ),
-            StackElement::new(MValue::Int(5), Instruction::INIT),
-            StackElement::new(MValue::Nat(6), Instruction::INIT),
+            StackElement::new(MValue::new_int(5), Instruction::INIT),
+            StackElement::new(MValue::new_nat(6), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
         let options = RunOptions {
@@ -267,11 +265,11 @@ mod tests {
             Err(_) => assert!(false),
             Ok((stack, _)) => {
                 assert!(stack.len() == 3);
-                assert_eq!(stack[0].value, MValue::Nat(3));
+                assert_eq!(stack[0].value, MValue::new_nat(3));
                 assert_eq!(stack[0].instruction, Instruction::SIZE);
-                assert_eq!(stack[1].value, MValue::Int(5));
+                assert_eq!(stack[1].value, MValue::new_int(5));
                 assert_eq!(stack[1].instruction, Instruction::INIT);
-                assert_eq!(stack[2].value, MValue::Nat(6));
+                assert_eq!(stack[2].value, MValue::new_nat(6));
                 assert_eq!(stack[2].instruction, Instruction::INIT);
             }
         }
@@ -282,8 +280,8 @@ mod tests {
                 MValue::new_empty_map(MType::Nat, MType::Nat),
                 Instruction::INIT,
             ),
-            StackElement::new(MValue::Int(5), Instruction::INIT),
-            StackElement::new(MValue::Nat(6), Instruction::INIT),
+            StackElement::new(MValue::new_int(5), Instruction::INIT),
+            StackElement::new(MValue::new_nat(6), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
 
@@ -293,11 +291,11 @@ mod tests {
             Err(_) => assert!(false),
             Ok((stack, _)) => {
                 assert!(stack.len() == 3);
-                assert_eq!(stack[0].value, MValue::Nat(0));
+                assert_eq!(stack[0].value, MValue::new_nat(0));
                 assert_eq!(stack[0].instruction, Instruction::SIZE);
-                assert_eq!(stack[1].value, MValue::Int(5));
+                assert_eq!(stack[1].value, MValue::new_int(5));
                 assert_eq!(stack[1].instruction, Instruction::INIT);
-                assert_eq!(stack[2].value, MValue::Nat(6));
+                assert_eq!(stack[2].value, MValue::new_nat(6));
                 assert_eq!(stack[2].instruction, Instruction::INIT);
             }
         }
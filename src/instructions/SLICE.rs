@@ -1,5 +1,7 @@
+use crate::errors::MichelsonError;
 use crate::instructions::{Instruction, RunOptions};
-use crate::m_types::{MValue, OptionValue, MType};
+use crate::m_types::{nat, MValue, OptionValue, MType};
+use num_traits::ToPrimitive;
 use crate::stack::{Stack, StackElement, StackFuncs, StackSnapshots};
 
 // https://tezos.gitlab.io/michelson-reference/#instr-SLICE
@@ -8,47 +10,50 @@ pub fn run(
     stack: Stack,
     options: &RunOptions,
     mut stack_snapshots: StackSnapshots,
-) -> Result<(Stack, StackSnapshots), String> {
+) -> Result<(Stack, StackSnapshots), MichelsonError> {
     // checks the stack
     stack.check_depth(options.pos + 3, Instruction::SLICE)?;
+    // bounds of the slice, offsets that don't fit in memory are out of the boundaries anyway
+    let bounds = |offset: &nat, length: &nat, size: usize| match (offset.to_usize(), length.to_usize()) {
+        (Some(offset), Some(length)) if offset < size && offset + length <= size => Some((offset, length)),
+        _ => None,
+    };
     // the values on the stack must be nat : nat : string/bytes
     let new_val = match (&stack[options.pos].value, &stack[options.pos + 1].value, &stack[options.pos + 2].value) {
         (MValue::Nat(offset), MValue::Nat(length), MValue::String(str_to_slice)) => {
             // checks if offset and length stay in the string boundaries
-            if *offset >= str_to_slice.len() as u128 || offset + length > str_to_slice.len() as u128 {
-                Ok(MValue::Option(OptionValue::new(None, MType::String)))
-            } else {
-                Ok(
+            match bounds(offset, length, str_to_slice.len()) {
+                None => Ok(MValue::Option(OptionValue::new(None, MType::String))),
+                Some((offset, length)) => Ok(
                     MValue::Option(
                         OptionValue::new(
-                            Some(MValue::String(str_to_slice.chars().skip(*offset as usize).take(*length as usize).collect())), 
+                            Some(MValue::String(str_to_slice.chars().skip(offset).take(length).collect())), 
                             MType::String
                         )
                     )
-                )
-            }            
+                ),
+            }
         }
         (MValue::Nat(offset), MValue::Nat(length), MValue::Bytes(bytes_to_slice)) => {
             // checks if offset and length stay in the bytes boundaries
-            if *offset * 2 >= bytes_to_slice.len() as u128 || offset * 2 + length > bytes_to_slice.len() as u128 {
-                Ok(MValue::Option(OptionValue::new(None, MType::Bytes)))
-            } else {
-                Ok(
+            match bounds(offset, length, bytes_to_slice.len()) {
+                None => Ok(MValue::Option(OptionValue::new(None, MType::Bytes))),
+                Some((offset, length)) => Ok(
                     MValue::Option(
                         OptionValue::new(
-                            Some(MValue::Bytes(bytes_to_slice.chars().skip(*offset as usize * 2).take(*length as usize * 2).collect())), 
+                            Some(MValue::new_bytes(&bytes_to_slice.as_slice()[offset..offset + length])), 
                             MType::Bytes
                         )
                     )
-                )
+                ),
             }
         }
-        _ => Err(format!(
+        _ => Err(MichelsonError::Message(format!(
             "Expected a stack of the following types: `nat : nat : string` or `nat : nat : bytes` for instruction SLICE, but got `{} : {} : {}`", 
             stack[options.pos].value.get_type().to_string(), 
             stack[options.pos + 1].value.get_type().to_string(), 
             stack[options.pos + 2].value.get_type().to_string()
-        ))
+        )))
     }?;
 
     // removes the current elements from the stack
@@ -75,10 +80,10 @@ pub fn run(
     #[test]
     fn slice_string_success() {
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Nat(0), Instruction::INIT),
-            StackElement::new(MValue::Nat(3), Instruction::INIT),
+            StackElement::new(MValue::new_nat(0), Instruction::INIT),
+            StackElement::new(MValue::new_nat(3), Instruction::INIT),
             StackElement::new(MValue::new_string("taquito"), Instruction::INIT),
-            StackElement::new(MValue::Int(22), Instruction::INIT),
+            StackElement::new(MValue::new_int(22), Instruction::INIT),
             StackElement::new(MValue::Mutez(6_000_000), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
@@ -98,7 +103,7 @@ pub fn run(
                     MValue::Option(OptionValue::new(Some(MValue::String(String::from("taq"))), MType::String))
                 );
                 assert_eq!(stack[0].instruction, Instruction::SLICE);
-                assert_eq!(stack[1].value, MValue::Int(22));
+                assert_eq!(stack[1].value, MValue::new_int(22));
                 assert_eq!(stack[1].instruction, Instruction::INIT);
                 assert_eq!(stack[2].value, MValue::Mutez(6_000_000));
                 assert_eq!(stack[2].instruction, Instruction::INIT);
@@ -106,10 +111,10 @@ pub fn run(
         }
 
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Nat(4), Instruction::INIT),
-            StackElement::new(MValue::Nat(3), Instruction::INIT),
+            StackElement::new(MValue::new_nat(4), Instruction::INIT),
+            StackElement::new(MValue::new_nat(3), Instruction::INIT),
             StackElement::new(MValue::new_string("taquito"), Instruction::INIT),
-            StackElement::new(MValue::Int(22), Instruction::INIT),
+            StackElement::new(MValue::new_int(22), Instruction::INIT),
             StackElement::new(MValue::Mutez(6_000_000), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
@@ -125,7 +130,7 @@ pub fn run(
                     MValue::Option(OptionValue::new(Some(MValue::String(String::from("ito"))), MType::String))
                 );
                 assert_eq!(stack[0].instruction, Instruction::SLICE);
-                assert_eq!(stack[1].value, MValue::Int(22));
+                assert_eq!(stack[1].value, MValue::new_int(22));
                 assert_eq!(stack[1].instruction, Instruction::INIT);
                 assert_eq!(stack[2].value, MValue::Mutez(6_000_000));
                 assert_eq!(stack[2].instruction, Instruction::INIT);
@@ -136,10 +141,10 @@ pub fn run(
     #[test]
     fn slice_bytes_success() {
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Nat(0), Instruction::INIT),
-            StackElement::new(MValue::Nat(3), Instruction::INIT),
-            StackElement::new(MValue::new_bytes("7461717569746f"), Instruction::INIT),
-            StackElement::new(MValue::Int(22), Instruction::INIT),
+            StackElement::new(MValue::new_nat(0), Instruction::INIT),
+            StackElement::new(MValue::new_nat(3), Instruction::INIT),
+            StackElement::new(MValue::new_bytes(b"taquito"), Instruction::INIT),
+            StackElement::new(MValue::new_int(22), Instruction::INIT),
             StackElement::new(MValue::Mutez(6_000_000), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
@@ -156,10 +161,10 @@ pub fn run(
                 assert!(stack.len() == 3);
                 assert_eq!(
                     stack[0].value,
-                    MValue::Option(OptionValue::new(Some(MValue::Bytes(String::from("746171"))), MType::Bytes))
+                    MValue::Option(OptionValue::new(Some(MValue::new_bytes(b"taq")), MType::Bytes))
                 );
                 assert_eq!(stack[0].instruction, Instruction::SLICE);
-                assert_eq!(stack[1].value, MValue::Int(22));
+                assert_eq!(stack[1].value, MValue::new_int(22));
                 assert_eq!(stack[1].instruction, Instruction::INIT);
                 assert_eq!(stack[2].value, MValue::Mutez(6_000_000));
                 assert_eq!(stack[2].instruction, Instruction::INIT);
@@ -167,10 +172,10 @@ pub fn run(
         }
 
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Nat(4), Instruction::INIT),
-            StackElement::new(MValue::Nat(3), Instruction::INIT),
-            StackElement::new(MValue::new_bytes("7461717569746f"), Instruction::INIT),
-            StackElement::new(MValue::Int(22), Instruction::INIT),
+            StackElement::new(MValue::new_nat(4), Instruction::INIT),
+            StackElement::new(MValue::new_nat(3), Instruction::INIT),
+            StackElement::new(MValue::new_bytes(b"taquito"), Instruction::INIT),
+            StackElement::new(MValue::new_int(22), Instruction::INIT),
             StackElement::new(MValue::Mutez(6_000_000), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
@@ -183,10 +188,10 @@ pub fn run(
                 assert!(stack.len() == 3);
                 assert_eq!(
                     stack[0].value,
-                    MValue::Option(OptionValue::new(Some(MValue::Bytes(String::from("69746f"))), MType::Bytes))
+                    MValue::Option(OptionValue::new(Some(MValue::new_bytes(b"ito")), MType::Bytes))
                 );
                 assert_eq!(stack[0].instruction, Instruction::SLICE);
-                assert_eq!(stack[1].value, MValue::Int(22));
+                assert_eq!(stack[1].value, MValue::new_int(22));
                 assert_eq!(stack[1].instruction, Instruction::INIT);
                 assert_eq!(stack[2].value, MValue::Mutez(6_000_000));
                 assert_eq!(stack[2].instruction, Instruction::INIT);
@@ -197,10 +202,10 @@ pub fn run(
     #[test]
     fn slice_outofbound_offset() {
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Nat(7), Instruction::INIT),
-            StackElement::new(MValue::Nat(3), Instruction::INIT),
+            StackElement::new(MValue::new_nat(7), Instruction::INIT),
+            StackElement::new(MValue::new_nat(3), Instruction::INIT),
             StackElement::new(MValue::new_string("taquito"), Instruction::INIT),
-            StackElement::new(MValue::Int(22), Instruction::INIT),
+            StackElement::new(MValue::new_int(22), Instruction::INIT),
             StackElement::new(MValue::Mutez(6_000_000), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
@@ -220,7 +225,7 @@ pub fn run(
                     MValue::Option(OptionValue::new(None, MType::String))
                 );
                 assert_eq!(stack[0].instruction, Instruction::SLICE);
-                assert_eq!(stack[1].value, MValue::Int(22));
+                assert_eq!(stack[1].value, MValue::new_int(22));
                 assert_eq!(stack[1].instruction, Instruction::INIT);
                 assert_eq!(stack[2].value, MValue::Mutez(6_000_000));
                 assert_eq!(stack[2].instruction, Instruction::INIT);
@@ -231,10 +236,10 @@ pub fn run(
     #[test]
     fn slice_outofbound_length() {
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Nat(5), Instruction::INIT),
-            StackElement::new(MValue::Nat(3), Instruction::INIT),
+            StackElement::new(MValue::new_nat(5), Instruction::INIT),
+            StackElement::new(MValue::new_nat(3), Instruction::INIT),
             StackElement::new(MValue::new_string("taquito"), Instruction::INIT),
-            StackElement::new(MValue::Int(22), Instruction::INIT),
+            StackElement::new(MValue::new_int(22), Instruction::INIT),
             StackElement::new(MValue::Mutez(6_000_000), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
@@ -254,7 +259,7 @@ pub fn run(
                     MValue::Option(OptionValue::new(None, MType::String))
                 );
                 assert_eq!(stack[0].instruction, Instruction::SLICE);
-                assert_eq!(stack[1].value, MValue::Int(22));
+                assert_eq!(stack[1].value, MValue::new_int(22));
                 assert_eq!(stack[1].instruction, Instruction::INIT);
                 assert_eq!(stack[2].value, MValue::Mutez(6_000_000));
                 assert_eq!(stack[2].instruction, Instruction::INIT);
@@ -266,8 +271,8 @@ pub fn run(
     #[test]
     fn slice_wrong_stack_depth() {
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Nat(3), Instruction::INIT),
-            StackElement::new(MValue::new_bytes("7461717569746f"), Instruction::INIT),
+            StackElement::new(MValue::new_nat(3), Instruction::INIT),
+            StackElement::new(MValue::new_bytes(b"taquito"), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
         let options = RunOptions {
@@ -278,7 +283,7 @@ pub fn run(
         assert!(initial_stack.len() == 2);
 
         match run(initial_stack, &options, stack_snapshots) {
-            Err(err) => assert_eq!(err, String::from("Unexpected stack length, expected a length of 3 for instruction SLICE, got 2")),
+            Err(err) => assert_eq!(err.to_string(), String::from("Unexpected stack length, expected a length of 3 for instruction SLICE, got 2")),
             Ok(_) => assert!(false),
         }
     }
@@ -286,10 +291,10 @@ pub fn run(
     #[test]
     fn slice_wrong_stack_types() {
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Int(0), Instruction::INIT),
-            StackElement::new(MValue::Nat(3), Instruction::INIT),
+            StackElement::new(MValue::new_int(0), Instruction::INIT),
+            StackElement::new(MValue::new_nat(3), Instruction::INIT),
             StackElement::new(MValue::new_string("taquito"), Instruction::INIT),
-            StackElement::new(MValue::Int(22), Instruction::INIT),
+            StackElement::new(MValue::new_int(22), Instruction::INIT),
             StackElement::new(MValue::Mutez(6_000_000), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
@@ -302,7 +307,7 @@ pub fn run(
 
         match run(initial_stack, &options, stack_snapshots) {
             Err(err) => assert_eq!(
-                err, 
+                err.to_string(), 
                 String::from("Expected a stack of the following types: `nat : nat : string` or `nat : nat : bytes` for instruction SLICE, but got `int : nat : string`")
             ),
             Ok(_) => assert!(false),
@@ -1,3 +1,4 @@
+use crate::errors::MichelsonError;
 use crate::instructions::{Instruction, RunOptions};
 use crate::m_types::{MValue, OptionValue};
 use crate::stack::{Stack, StackElement, StackFuncs, StackSnapshots};
@@ -8,12 +9,9 @@ pub fn run(
     stack: Stack,
     options: &RunOptions,
     mut stack_snapshots: StackSnapshots,
-) -> Result<(Stack, StackSnapshots), String> {
+) -> Result<(Stack, StackSnapshots), MichelsonError> {
     // checks the stack
-    match stack.check_depth(options.pos + 1, Instruction::SOME) {
-        Ok(_) => (),
-        Err(err) => panic!("{}", err),
-    };
+    stack.check_depth(options.pos + 1, Instruction::SOME)?;
     // wraps the value at options.pos
     let val_to_wrap = stack[options.pos].value.clone();
     let new_val: MValue = MValue::Option(OptionValue {
@@ -45,8 +43,8 @@ mod tests {
     #[test]
     fn some_success() {
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Int(5), Instruction::INIT),
-            StackElement::new(MValue::Nat(6), Instruction::INIT),
+            StackElement::new(MValue::new_int(5), Instruction::INIT),
+            StackElement::new(MValue::new_nat(6), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
         let options = RunOptions {
@@ -64,11 +62,11 @@ mod tests {
                     stack[0].value,
                     MValue::Option(OptionValue {
                         m_type: MType::Int,
-                        value: Box::new(Option::Some(MValue::Int(5)))
+                        value: Box::new(Option::Some(MValue::new_int(5)))
                     })
                 );
                 assert_eq!(stack[0].instruction, Instruction::SOME);
-                assert_eq!(stack[1].value, MValue::Nat(6));
+                assert_eq!(stack[1].value, MValue::new_nat(6));
                 assert_eq!(stack[1].instruction, Instruction::INIT);
             }
         }
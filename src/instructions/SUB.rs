@@ -1,5 +1,7 @@
+use crate::errors::MichelsonError;
 use crate::instructions::{Instruction, RunOptions};
-use crate::m_types::{int, timestamp, MValue};
+use crate::m_types::{int, MValue};
+use num_traits::ToPrimitive;
 use crate::stack::{Stack, StackElement, StackFuncs, StackSnapshots};
 
 // https://tezos.gitlab.io/michelson-reference/#instr-SUB
@@ -8,35 +10,35 @@ pub fn run(
     stack: Stack,
     options: &RunOptions,
     mut stack_snapshots: StackSnapshots,
-) -> Result<(Stack, StackSnapshots), String> {
+) -> Result<(Stack, StackSnapshots), MichelsonError> {
     // checks the stack
-    match stack.check_depth(2, Instruction::SUB) {
-        Ok(_) => (),
-        Err(err) => panic!("{}", err),
-    };
+    stack.check_depth(2, Instruction::SUB)?;
 
-    let new_val_res: Result<MValue, String> = match (
+    let new_val_res: Result<MValue, MichelsonError> = match (
         stack[options.pos].get_val(),
         stack[options.pos + 1].get_val(),
     ) {
         (MValue::Int(left), MValue::Int(right)) => Ok(MValue::Int(left - right)),
-        (MValue::Int(left), MValue::Nat(right)) => Ok(MValue::Int(left - (right as int))),
-        (MValue::Nat(left), MValue::Int(right)) => Ok(MValue::Int((left as int) - right)),
-        (MValue::Nat(left), MValue::Nat(right)) => Ok(MValue::Int((left as int) - (right as int))),
-        (MValue::Timestamp(left), MValue::Int(right)) => {
-            Ok(MValue::Timestamp(left - (right as timestamp)))
-        }
+        (MValue::Int(left), MValue::Nat(right)) => Ok(MValue::Int(left - int::from(right))),
+        (MValue::Nat(left), MValue::Int(right)) => Ok(MValue::Int(int::from(left) - right)),
+        (MValue::Nat(left), MValue::Nat(right)) => Ok(MValue::Int(int::from(left) - int::from(right))),
+        (MValue::Timestamp(left), MValue::Int(right)) => match (int::from(left) - right).to_i64() {
+            Some(new_timestamp) => Ok(MValue::Timestamp(new_timestamp)),
+            None => Err(MichelsonError::Message(String::from(
+                "The result of SUB is out of the range of timestamps",
+            ))),
+        },
         (MValue::Timestamp(left), MValue::Timestamp(right)) => {
-            Ok(MValue::Int((left - right) as int))
+            Ok(MValue::Int(int::from(left) - int::from(right)))
         }
-        (MValue::Mutez(_), MValue::Mutez(_)) => Err(String::from(
+        (MValue::Mutez(_), MValue::Mutez(_)) => Err(MichelsonError::Message(String::from(
             "Use the SUB_MUTEZ instruction to subtract mutez values",
-        )),
-        (m_val_left, m_val_right) => Err(format!(
+        ))),
+        (m_val_left, m_val_right) => Err(MichelsonError::Message(format!(
             "Cannot subtract values of type {} and {} with the SUB instruction",
             m_val_left.to_string(),
             m_val_right.to_string()
-        )),
+        ))),
     };
     match new_val_res {
         Err(err) => Err(err),
@@ -70,8 +72,8 @@ mod tests {
     #[test]
     fn sub_int_int() {
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Int(15), Instruction::INIT),
-            StackElement::new(MValue::Int(6), Instruction::INIT),
+            StackElement::new(MValue::new_int(15), Instruction::INIT),
+            StackElement::new(MValue::new_int(6), Instruction::INIT),
             StackElement::new(MValue::Mutez(6_000_000), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
@@ -85,7 +87,7 @@ mod tests {
         match run(initial_stack, &options, stack_snapshots) {
             Ok((new_stack, _)) => {
                 assert!(new_stack.len() == 2);
-                assert!(new_stack[0].value == MValue::Int(9));
+                assert!(new_stack[0].value == MValue::new_int(9));
             }
             Err(_) => assert!(false),
         }
@@ -95,8 +97,8 @@ mod tests {
     #[test]
     fn sub_int_nat() {
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Int(15), Instruction::INIT),
-            StackElement::new(MValue::Nat(6), Instruction::INIT),
+            StackElement::new(MValue::new_int(15), Instruction::INIT),
+            StackElement::new(MValue::new_nat(6), Instruction::INIT),
             StackElement::new(MValue::Mutez(6_000_000), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
@@ -110,7 +112,7 @@ mod tests {
         match run(initial_stack, &options, stack_snapshots) {
             Ok((new_stack, _)) => {
                 assert!(new_stack.len() == 2);
-                assert!(new_stack[0].value == MValue::Int(9));
+                assert!(new_stack[0].value == MValue::new_int(9));
             }
             Err(_) => assert!(false),
         }
@@ -120,8 +122,8 @@ mod tests {
     #[test]
     fn sub_nat_nat() {
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Nat(5), Instruction::INIT),
-            StackElement::new(MValue::Nat(6), Instruction::INIT),
+            StackElement::new(MValue::new_nat(5), Instruction::INIT),
+            StackElement::new(MValue::new_nat(6), Instruction::INIT),
             StackElement::new(MValue::Mutez(6_000_000), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
@@ -135,12 +137,54 @@ mod tests {
         match run(initial_stack, &options, stack_snapshots) {
             Ok((new_stack, _)) => {
                 assert!(new_stack.len() == 2);
-                assert!(new_stack[0].value == MValue::Int(-1));
+                assert!(new_stack[0].value == MValue::new_int(-1));
             }
             Err(_) => assert!(false),
         }
     }
 
+    // SUB timestamp - int = timestamp, before the epoch
+    #[test]
+    fn sub_timestamp_int() {
+        let initial_stack: Stack = vec![
+            StackElement::new(MValue::Timestamp(100), Instruction::INIT),
+            StackElement::new(MValue::new_int(3_600), Instruction::INIT),
+        ];
+        let options = RunOptions {
+            context: RunOptionsContext::mock(),
+            pos: 0,
+        };
+
+        match run(initial_stack, &options, vec![]) {
+            Err(err) => panic!("{}", err),
+            Ok((stack, _)) => {
+                assert!(stack.len() == 1);
+                assert_eq!(stack[0].value, MValue::Timestamp(-3_500));
+            }
+        }
+    }
+
+    // SUB timestamp - timestamp = int
+    #[test]
+    fn sub_timestamp_timestamp() {
+        let initial_stack: Stack = vec![
+            StackElement::new(MValue::Timestamp(1_640_995_200), Instruction::INIT),
+            StackElement::new(MValue::Timestamp(1_640_995_260), Instruction::INIT),
+        ];
+        let options = RunOptions {
+            context: RunOptionsContext::mock(),
+            pos: 0,
+        };
+
+        match run(initial_stack, &options, vec![]) {
+            Err(err) => panic!("{}", err),
+            Ok((stack, _)) => {
+                assert!(stack.len() == 1);
+                assert_eq!(stack[0].value, MValue::new_int(-60));
+            }
+        }
+    }
+
     // FAILING
     // SUB string - nat
     #[test]
@@ -150,7 +194,7 @@ mod tests {
     fn sub_string_nat() {
         let initial_stack: Stack = vec![
             StackElement::new(MValue::String(String::from("5")), Instruction::INIT),
-            StackElement::new(MValue::Nat(6), Instruction::INIT),
+            StackElement::new(MValue::new_nat(6), Instruction::INIT),
             StackElement::new(MValue::Mutez(6_000_000), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
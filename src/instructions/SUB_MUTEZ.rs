@@ -1,4 +1,4 @@
-use crate::errors::{display_error, ErrorCode};
+use crate::errors::MichelsonError;
 use crate::instructions::{Instruction, RunOptions};
 use crate::m_types::{MType, MValue, OptionValue};
 use crate::stack::{Stack, StackElement, StackFuncs, StackSnapshots};
@@ -9,7 +9,7 @@ pub fn run(
     stack: Stack,
     options: &RunOptions,
     mut stack_snapshots: StackSnapshots,
-) -> Result<(Stack, StackSnapshots), String> {
+) -> Result<(Stack, StackSnapshots), MichelsonError> {
     let this_instruction = Instruction::SUB_MUTEZ;
     // checks the stack
     stack.check_depth(options.pos + 2, this_instruction)?;
@@ -19,12 +19,8 @@ pub fn run(
         stack[options.pos + 1].get_val(),
     ) {
         (MValue::Mutez(minuend), MValue::Mutez(subtrahend)) => {
-            // gets the result of the subtraction
-            let result = if minuend < subtrahend {
-                None
-            } else {
-                Some(MValue::Mutez(minuend - subtrahend))
-            };
+            // gets the result of the subtraction, none if it would be negative
+            let result = minuend.checked_sub(subtrahend).map(MValue::Mutez);
             // creates an optional value
             let new_val = MValue::Option(OptionValue {
                 m_type: MType::Mutez,
@@ -34,15 +30,15 @@ pub fn run(
             Ok(StackElement::new(new_val, this_instruction))
         }
         (MValue::Mutez(_), val) | (val, MValue::Mutez(_)) => {
-            Err(display_error(ErrorCode::WrongType((
+            Err(MichelsonError::WrongType((
                 String::from("mutez"),
                 val.get_type().to_string(),
                 this_instruction,
-            ))))
+            )))
         }
-        _ => Err(String::from(
+        _ => Err(MichelsonError::Message(String::from(
             "SUB_MUTEZ instruction requires 2 mutez values on the stack",
-        )),
+        ))),
     }?;
 
     let (_, new_stack) = stack.remove_at(options.pos);
@@ -64,7 +60,7 @@ mod tests {
         let initial_stack: Stack = vec![
             StackElement::new(MValue::Mutez(15), Instruction::INIT),
             StackElement::new(MValue::Mutez(6), Instruction::INIT),
-            StackElement::new(MValue::Int(6_000_000), Instruction::INIT),
+            StackElement::new(MValue::new_int(6_000_000), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
         let options = RunOptions {
@@ -82,7 +78,7 @@ mod tests {
                         == MValue::Option(OptionValue::new(Some(MValue::Mutez(9)), MType::Mutez))
                 );
                 assert!(new_stack[0].instruction == Instruction::SUB_MUTEZ);
-                assert!(new_stack[1].value == MValue::Int(6_000_000));
+                assert!(new_stack[1].value == MValue::new_int(6_000_000));
                 assert!(new_stack[1].instruction == Instruction::INIT)
             }
             Err(_) => assert!(false),
@@ -94,7 +90,7 @@ mod tests {
         let initial_stack: Stack = vec![
             StackElement::new(MValue::Mutez(5), Instruction::INIT),
             StackElement::new(MValue::Mutez(16), Instruction::INIT),
-            StackElement::new(MValue::Int(6_000_000), Instruction::INIT),
+            StackElement::new(MValue::new_int(6_000_000), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
         let options = RunOptions {
@@ -109,7 +105,7 @@ mod tests {
                 assert!(new_stack.len() == 2);
                 assert!(new_stack[0].value == MValue::Option(OptionValue::new(None, MType::Mutez)));
                 assert!(new_stack[0].instruction == Instruction::SUB_MUTEZ);
-                assert!(new_stack[1].value == MValue::Int(6_000_000));
+                assert!(new_stack[1].value == MValue::new_int(6_000_000));
                 assert!(new_stack[1].instruction == Instruction::INIT)
             }
             Err(_) => assert!(false),
@@ -121,8 +117,8 @@ mod tests {
     fn sub_mutez_fail_1() {
         let initial_stack: Stack = vec![
             StackElement::new(MValue::Mutez(5), Instruction::INIT),
-            StackElement::new(MValue::Nat(16), Instruction::INIT),
-            StackElement::new(MValue::Int(6_000_000), Instruction::INIT),
+            StackElement::new(MValue::new_nat(16), Instruction::INIT),
+            StackElement::new(MValue::new_int(6_000_000), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
         let options = RunOptions {
@@ -135,7 +131,7 @@ mod tests {
         match run(initial_stack, &options, stack_snapshots) {
             Ok(_) => assert!(false),
             Err(err) => assert!(
-                err == String::from(
+                err.to_string() == String::from(
                     "Wrong type, expected `mutez` for instruction SUB_MUTEZ, got `nat`"
                 )
             ),
@@ -145,9 +141,9 @@ mod tests {
     #[test]
     fn sub_mutez_fail_2() {
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Int(5), Instruction::INIT),
+            StackElement::new(MValue::new_int(5), Instruction::INIT),
             StackElement::new(MValue::Mutez(16), Instruction::INIT),
-            StackElement::new(MValue::Int(6_000_000), Instruction::INIT),
+            StackElement::new(MValue::new_int(6_000_000), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
         let options = RunOptions {
@@ -160,7 +156,7 @@ mod tests {
         match run(initial_stack, &options, stack_snapshots) {
             Ok(_) => assert!(false),
             Err(err) => assert!(
-                err == String::from(
+                err.to_string() == String::from(
                     "Wrong type, expected `mutez` for instruction SUB_MUTEZ, got `int`"
                 )
             ),
@@ -170,9 +166,9 @@ mod tests {
     #[test]
     fn sub_mutez_fail_3() {
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Int(5), Instruction::INIT),
-            StackElement::new(MValue::Nat(16), Instruction::INIT),
-            StackElement::new(MValue::Int(6_000_000), Instruction::INIT),
+            StackElement::new(MValue::new_int(5), Instruction::INIT),
+            StackElement::new(MValue::new_nat(16), Instruction::INIT),
+            StackElement::new(MValue::new_int(6_000_000), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
         let options = RunOptions {
@@ -185,14 +181,14 @@ mod tests {
         match run(initial_stack, &options, stack_snapshots) {
             Ok(_) => assert!(false),
             Err(err) => assert!(
-                err == String::from("SUB_MUTEZ instruction requires 2 mutez values on the stack")
+                err.to_string() == String::from("SUB_MUTEZ instruction requires 2 mutez values on the stack")
             ),
         }
     }
 
     #[test]
     fn sub_mutez_fail_4() {
-        let initial_stack: Stack = vec![StackElement::new(MValue::Int(5), Instruction::INIT)];
+        let initial_stack: Stack = vec![StackElement::new(MValue::new_int(5), Instruction::INIT)];
         let stack_snapshots = vec![];
         let options = RunOptions {
             context: RunOptionsContext::mock(),
@@ -204,7 +200,7 @@ mod tests {
         match run(initial_stack, &options, stack_snapshots) {
             Ok(_) => assert!(false),
             Err(err) => assert!(
-                err == String::from("Unexpected stack length, expected a length of 2 for instruction SUB_MUTEZ, got 1")
+                err.to_string() == String::from("Unexpected stack length, expected a length of 2 for instruction SUB_MUTEZ, got 1")
             ),
         }
     }
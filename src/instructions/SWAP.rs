@@ -1,3 +1,4 @@
+use crate::errors::MichelsonError;
 use crate::instructions::{Instruction, RunOptions};
 use crate::stack::{Stack, StackFuncs, StackSnapshots};
 
@@ -7,7 +8,7 @@ pub fn run(
     stack: Stack,
     options: &RunOptions,
     mut stack_snapshots: StackSnapshots,
-) -> Result<(Stack, StackSnapshots), String> {
+) -> Result<(Stack, StackSnapshots), MichelsonError> {
     // checks the stack
     match stack.check_depth(2, Instruction::SWAP) {
         Ok(_) => {
@@ -38,7 +39,7 @@ mod tests {
     fn swap_success() {
         let initial_stack: Stack = vec![
             StackElement::new(MValue::String(String::from("test")), Instruction::INIT),
-            StackElement::new(MValue::Int(6), Instruction::INIT),
+            StackElement::new(MValue::new_int(6), Instruction::INIT),
             StackElement::new(MValue::Mutez(6_000_000), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
@@ -52,7 +53,7 @@ mod tests {
         match run(initial_stack, &options, stack_snapshots) {
             Ok((new_stack, _)) => {
                 assert!(new_stack.len() == 3);
-                assert!(new_stack[0].value == MValue::Int(6));
+                assert!(new_stack[0].value == MValue::new_int(6));
                 assert!(new_stack[1].value == MValue::String(String::from("test")));
                 assert!(new_stack[2].value == MValue::Mutez(6_000_000));
             }
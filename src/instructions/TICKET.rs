@@ -1,5 +1,7 @@
+use crate::errors::MichelsonError;
 use crate::instructions::{Instruction, RunOptions};
 use crate::m_types::{MValue, OptionValue, MType};
+use num_traits::Zero;
 use crate::stack::{Stack, StackElement, StackFuncs, StackSnapshots};
 
 // https://tezos.gitlab.io/michelson-reference/#instr-TICKET
@@ -8,7 +10,7 @@ pub fn run(
     stack: Stack,
     options: &RunOptions,
     mut stack_snapshots: StackSnapshots,
-) -> Result<(Stack, StackSnapshots), String> {
+) -> Result<(Stack, StackSnapshots), MichelsonError> {
     let this_instruction = Instruction::TICKET;
     // checks the stack
     stack.check_depth(options.pos + 2, this_instruction)?;
@@ -19,7 +21,7 @@ pub fn run(
                 let value_type = value.clone().get_type();
                 // creates the new ticket
                 let new_ticket = 
-                    if amount == 0 {
+                    if amount.is_zero() {
                         Ok(None)
                     } else {
                         match MValue::new_ticket(value, amount, options.context.self_address.clone()) {
@@ -65,15 +67,15 @@ pub fn run(
 mod test {
     use super::*;
     use crate::instructions::RunOptionsContext;
-    use crate::m_types::{Ticket, PairValue};
+    use crate::m_types::{nat, Ticket, PairValue};
 
     // PASSING
     #[test]
     fn create_ticket_success_1() {
         let initial_stack: Stack = vec![
             StackElement::new(MValue::String(String::from("tezos")), Instruction::INIT),
-            StackElement::new(MValue::Nat(10), Instruction::INIT),
-            StackElement::new(MValue::Int(-22), Instruction::INIT),
+            StackElement::new(MValue::new_nat(10), Instruction::INIT),
+            StackElement::new(MValue::new_int(-22), Instruction::INIT),
             StackElement::new(MValue::Mutez(6_000_000), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
@@ -94,7 +96,7 @@ mod test {
                         Some(MValue::Ticket(Box::new(
                             Ticket { 
                                 value: MValue::String(String::from("tezos")),
-                                amount: 10,
+                                amount: nat::from(10u32),
                                 ticketer: options.context.self_address.clone()
                             }
                         ))), 
@@ -102,7 +104,7 @@ mod test {
                     ))
                 );
                 assert_eq!(stack[0].instruction, Instruction::TICKET);
-                assert_eq!(stack[1].value, MValue::Int(-22));
+                assert_eq!(stack[1].value, MValue::new_int(-22));
                 assert_eq!(stack[1].instruction, Instruction::INIT);
                 assert_eq!(stack[2].value, MValue::Mutez(6_000_000));
                 assert_eq!(stack[2].instruction, Instruction::INIT);
@@ -113,9 +115,9 @@ mod test {
     #[test]
     fn create_ticket_success_2() {
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Pair(PairValue::new(MValue::Nat(55), MValue::String(String::from("taquito")))), Instruction::INIT),
-            StackElement::new(MValue::Nat(22), Instruction::INIT),
-            StackElement::new(MValue::Int(-22), Instruction::INIT),
+            StackElement::new(MValue::Pair(PairValue::new(MValue::new_nat(55), MValue::String(String::from("taquito")))), Instruction::INIT),
+            StackElement::new(MValue::new_nat(22), Instruction::INIT),
+            StackElement::new(MValue::new_int(-22), Instruction::INIT),
             StackElement::new(MValue::Mutez(6_000_000), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
@@ -137,17 +139,17 @@ mod test {
                             Ticket { 
                                 value: MValue::Pair(PairValue { 
                                     m_type: (MType::Nat, MType::String),
-                                    value: Box::new((MValue::Nat(55), MValue::String(String::from("taquito"))))
+                                    value: Box::new((MValue::new_nat(55), MValue::String(String::from("taquito"))))
                                 }),
-                                amount: 22,
+                                amount: nat::from(22u32),
                                 ticketer: options.context.self_address.clone()
                             }
                         ))), 
-                        MType::Ticket(Box::new((MType::Pair(Box::new((MType::Nat, MType::String))), MType::Nat, MType::Address)))
+                        MType::Ticket(Box::new((MType::pair(MType::Nat, MType::String), MType::Nat, MType::Address)))
                     ))
                 );
                 assert_eq!(stack[0].instruction, Instruction::TICKET);
-                assert_eq!(stack[1].value, MValue::Int(-22));
+                assert_eq!(stack[1].value, MValue::new_int(-22));
                 assert_eq!(stack[1].instruction, Instruction::INIT);
                 assert_eq!(stack[2].value, MValue::Mutez(6_000_000));
                 assert_eq!(stack[2].instruction, Instruction::INIT);
@@ -159,8 +161,8 @@ mod test {
     fn create_ticket_success_3() {
         let initial_stack: Stack = vec![
             StackElement::new(MValue::String(String::from("tezos")), Instruction::INIT),
-            StackElement::new(MValue::Nat(0), Instruction::INIT),
-            StackElement::new(MValue::Int(-22), Instruction::INIT),
+            StackElement::new(MValue::new_nat(0), Instruction::INIT),
+            StackElement::new(MValue::new_int(-22), Instruction::INIT),
             StackElement::new(MValue::Mutez(6_000_000), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
@@ -183,7 +185,7 @@ mod test {
                     ))
                 );
                 assert_eq!(stack[0].instruction, Instruction::TICKET);
-                assert_eq!(stack[1].value, MValue::Int(-22));
+                assert_eq!(stack[1].value, MValue::new_int(-22));
                 assert_eq!(stack[1].instruction, Instruction::INIT);
                 assert_eq!(stack[2].value, MValue::Mutez(6_000_000));
                 assert_eq!(stack[2].instruction, Instruction::INIT);
@@ -195,7 +197,7 @@ mod test {
     #[test]
     fn create_ticket_fail_1() {
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Nat(22), Instruction::INIT),
+            StackElement::new(MValue::new_nat(22), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
         let options = RunOptions {
@@ -207,7 +209,7 @@ mod test {
 
         match run(initial_stack, &options, stack_snapshots) {
             Err(err) => assert_eq!(
-                err, 
+                err.to_string(), 
                 "Unexpected stack length, expected a length of 2 for instruction TICKET, got 1"
             ),
             Ok(_) => assert!(false)
@@ -217,9 +219,9 @@ mod test {
     #[test]
     fn create_ticket_fail_2() {
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Nat(22), Instruction::INIT),
+            StackElement::new(MValue::new_nat(22), Instruction::INIT),
             StackElement::new(MValue::String(String::from("tezos")), Instruction::INIT),
-            StackElement::new(MValue::Int(-22), Instruction::INIT),
+            StackElement::new(MValue::new_int(-22), Instruction::INIT),
             StackElement::new(MValue::Mutez(6_000_000), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
@@ -232,7 +234,7 @@ mod test {
 
         match run(initial_stack, &options, stack_snapshots) {
             Err(err) => assert_eq!(
-                err, 
+                err.to_string(), 
                 "Wrong type for instruction TICKET, expected a stack of type ['a : nat : S'] but got [nat : string : S']"
             ),
             Ok(_) => assert!(false)
@@ -1,3 +1,4 @@
+use crate::errors::MichelsonError;
 use crate::instructions::{Instruction, RunOptions};
 use crate::m_types::MValue;
 use crate::stack::{Stack, StackElement, StackFuncs, StackSnapshots};
@@ -8,22 +9,22 @@ pub fn run(
     stack: Stack,
     options: &RunOptions,
     mut stack_snapshots: StackSnapshots,
-) -> Result<(Stack, StackSnapshots), String> {
+) -> Result<(Stack, StackSnapshots), MichelsonError> {
     let this_instruction = Instruction::UNPAIR;
     // checks the stack
     stack.check_depth(options.pos + 1, this_instruction)?;
     // unpairs the value
-    let unpair_res: Result<(MValue, MValue), String> = match stack[options.pos].value.clone() {
+    let unpair_res: Result<(MValue, MValue), MichelsonError> = match stack[options.pos].value.clone() {
         MValue::Pair(pair) => Ok(pair.unpair()),
-        _ => Err(format!(
+        _ => Err(MichelsonError::Message(format!(
             "Invalid pair found at UNPAIR instruction: {:?}",
             stack[options.pos].value
-        )),
+        ))),
     };
     let (el1, el2) = unpair_res?;
-    // creates the new stack elements
-    let stack_el1 = StackElement::new(el1, this_instruction);
-    let stack_el2 = StackElement::new(el2, this_instruction);
+    // creates the new stack elements, the fields keep their annotations
+    let stack_el1 = stack[options.pos].annotate_field(0, StackElement::new(el1, this_instruction));
+    let stack_el2 = stack[options.pos].annotate_field(1, StackElement::new(el2, this_instruction));
     let els_to_insert = vec![stack_el1, stack_el2];
     let new_stack = stack.clone().replace(els_to_insert, options.pos);
     // updates the stack snapshots
@@ -40,7 +41,6 @@ mod tests {
     use super::*;
     use crate::instructions::RunOptionsContext;
     use crate::m_types::{MType, PairValue};
-    use serde_json::Value;
 
     // PASSING
     #[test]
@@ -49,11 +49,11 @@ mod tests {
             StackElement::new(
                 MValue::Pair(PairValue {
                     m_type: (MType::Int, MType::Nat),
-                    value: Box::new((MValue::Int(6), MValue::Nat(11))),
+                    value: Box::new((MValue::new_int(6), MValue::new_nat(11))),
                 }),
                 Instruction::INIT,
             ),
-            StackElement::new(MValue::Int(6), Instruction::INIT),
+            StackElement::new(MValue::new_int(6), Instruction::INIT),
             StackElement::new(MValue::Mutez(6_000_000), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
@@ -67,8 +67,8 @@ mod tests {
         match run(initial_stack, &options, stack_snapshots) {
             Ok((new_stack, _)) => {
                 assert!(new_stack.len() == 4);
-                assert!(new_stack[0].value == MValue::Int(6));
-                assert!(new_stack[1].value == MValue::Nat(11));
+                assert!(new_stack[0].value == MValue::new_int(6));
+                assert!(new_stack[1].value == MValue::new_nat(11));
             }
             Err(_) => assert!(false),
         }
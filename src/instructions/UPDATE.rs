@@ -1,19 +1,35 @@
+use crate::errors::MichelsonError;
 use crate::instructions::{Instruction, RunOptions};
 use crate::m_types::{CollectionValue, MType, MValue, PairValue};
 use crate::stack::{Stack, StackElement, StackFuncs, StackSnapshots};
-use serde_json::Value;
+use crate::micheline::Micheline;
 
 // https://tezos.gitlab.io/michelson-reference/#instr-MAP
 
+/// decodes the optional position of the field to update in a right-combed pair
+pub fn parse_args(args: Option<&Vec<Micheline>>) -> Result<Option<usize>, MichelsonError> {
+    match args {
+        None => Ok(None),
+        Some(args) => match args.as_slice() {
+            [Micheline::Int(val)] => match val.parse::<usize>() {
+                Err(err) => Err(MichelsonError::Message(format!("Expected argument for UPDATE instruction to be a number, but got \"{}\" instead ({:?})", val, err))),
+                Ok(el_pos) => Ok(Some(el_pos)),
+            },
+            [arg] => Err(MichelsonError::Message(format!("Unexpected format for UPDATE argument: {}", arg))),
+            _ => Err(MichelsonError::UnexpectedArgsNumber((1, args.len()))),
+        },
+    }
+}
+
 pub fn run(
     mut stack: Stack,
-    args: Option<&Vec<Value>>,
+    el_pos: Option<usize>,
     options: &RunOptions,
     mut stack_snapshots: StackSnapshots,
-) -> Result<(Stack, StackSnapshots), String> {
+) -> Result<(Stack, StackSnapshots), MichelsonError> {
     let this_instruction = Instruction::UPDATE;
     // arguments are only present if UPDATE is used top update a right combed pair
-    match args {
+    match el_pos {
         None => {
             // updates a map, a big_map or a set
             // checks the stack
@@ -24,28 +40,28 @@ pub fn run(
                 stack[options.pos + 1].get_val(),
                 stack[options.pos + 2].get_val(),
             ) {
-                (key, value, MValue::Big_map(big_map)) => todo!(),
-                (key, value, MValue::Map(map)) => todo!(),
+                (_, _, MValue::Big_map(_)) => Err(MichelsonError::Unsupported(String::from(
+                    "UPDATE instruction on big maps",
+                ))),
+                (_, _, MValue::Map(_)) => Err(MichelsonError::Unsupported(String::from(
+                    "UPDATE instruction on maps",
+                ))),
                 (element, MValue::Bool(flag), MValue::Set(set)) => {
                     let new_set = if element.get_type() == set.m_type {
-                        // the set doesn't include the element
-                        if flag && !set.value.contains(&element) {
-                            // adds the element to the set
+                        if flag {
+                            // adds the element to the set, nothing happens if it is already there
                             Ok(set.update(element))
-                        } else if !flag && set.value.contains(&element) {
-                            // removes the element from the set
-                            Ok(set.remove(element))
                         } else {
-                            // nothing happens
-                            Ok(set)
+                            // removes the element from the set
+                            Ok(set.remove(&element))
                         }
                     } else {
-                        Err(format!(
+                        Err(MichelsonError::Message(format!(
                                     "Invalid type for instruction `{:?}` expected {} to update the set, but got {}",
                                     this_instruction,
                                     set.m_type.to_string(),
                                     element.get_type().to_string()
-                                ))
+                                )))
                     }?;
                     // updates the stack
                     let _ = stack
@@ -59,17 +75,20 @@ pub fn run(
                     // returns the unchanged stack
                     Ok((stack, stack_snapshots))
                 }
-                _ => Err(format!(
+                _ => Err(MichelsonError::Message(format!(
                     "Invalid stack for instruction UPDATE => 0- {} / 1- {} / 2- {}",
                     stack[options.pos].get_val().get_type().to_string(),
                     stack[options.pos + 1].get_val().get_type().to_string(),
                     stack[options.pos + 2].get_val().get_type().to_string()
-                )),
+                ))),
             }
         }
-        Some(args) => {
+        Some(el_pos) => {
             // updates a pair
-            todo!()
+            Err(MichelsonError::Unsupported(format!(
+                "UPDATE instruction on field {} of a pair",
+                el_pos
+            )))
         }
     }
 }
@@ -83,18 +102,18 @@ mod test {
     fn update_success_add_simple_set() {
         let initial_set = MValue::new_set(
             vec![
-                MValue::Nat(2),
-                MValue::Nat(3),
-                MValue::Nat(4),
-                MValue::Nat(5),
+                MValue::new_nat(2),
+                MValue::new_nat(3),
+                MValue::new_nat(4),
+                MValue::new_nat(5),
             ],
             MType::Nat,
         );
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Nat(9), Instruction::INIT),
+            StackElement::new(MValue::new_nat(9), Instruction::INIT),
             StackElement::new(MValue::Bool(true), Instruction::INIT),
             StackElement::new(initial_set, Instruction::INIT),
-            StackElement::new(MValue::Int(22), Instruction::INIT),
+            StackElement::new(MValue::new_int(22), Instruction::INIT),
             StackElement::new(MValue::Mutez(6_000_000), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
@@ -113,17 +132,31 @@ mod test {
                     stack[0].value,
                     MValue::new_set(
                         vec![
-                            MValue::Nat(9),
-                            MValue::Nat(2),
-                            MValue::Nat(3),
-                            MValue::Nat(4),
-                            MValue::Nat(5)
+                            MValue::new_nat(9),
+                            MValue::new_nat(2),
+                            MValue::new_nat(3),
+                            MValue::new_nat(4),
+                            MValue::new_nat(5)
                         ],
                         MType::Nat
                     )
                 );
+                // the new element is kept in order
+                match &stack[0].value {
+                    MValue::Set(set) => assert_eq!(
+                        set.value.iter().cloned().collect::<Vec<MValue>>(),
+                        vec![
+                            MValue::new_nat(2),
+                            MValue::new_nat(3),
+                            MValue::new_nat(4),
+                            MValue::new_nat(5),
+                            MValue::new_nat(9)
+                        ]
+                    ),
+                    _ => assert!(false),
+                }
                 assert_eq!(stack[0].instruction, Instruction::UPDATE);
-                assert_eq!(stack[1].value, MValue::Int(22));
+                assert_eq!(stack[1].value, MValue::new_int(22));
                 assert_eq!(stack[1].instruction, Instruction::INIT);
                 assert_eq!(stack[2].value, MValue::Mutez(6_000_000));
                 assert_eq!(stack[2].instruction, Instruction::INIT);
@@ -135,18 +168,18 @@ mod test {
     fn update_success_add_simple_set_no_change() {
         let initial_set = MValue::new_set(
             vec![
-                MValue::Nat(2),
-                MValue::Nat(3),
-                MValue::Nat(4),
-                MValue::Nat(5),
+                MValue::new_nat(2),
+                MValue::new_nat(3),
+                MValue::new_nat(4),
+                MValue::new_nat(5),
             ],
             MType::Nat,
         );
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Nat(4), Instruction::INIT),
+            StackElement::new(MValue::new_nat(4), Instruction::INIT),
             StackElement::new(MValue::Bool(true), Instruction::INIT),
             StackElement::new(initial_set, Instruction::INIT),
-            StackElement::new(MValue::Int(22), Instruction::INIT),
+            StackElement::new(MValue::new_int(22), Instruction::INIT),
             StackElement::new(MValue::Mutez(6_000_000), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
@@ -165,16 +198,16 @@ mod test {
                     stack[0].value,
                     MValue::new_set(
                         vec![
-                            MValue::Nat(2),
-                            MValue::Nat(3),
-                            MValue::Nat(4),
-                            MValue::Nat(5)
+                            MValue::new_nat(2),
+                            MValue::new_nat(3),
+                            MValue::new_nat(4),
+                            MValue::new_nat(5)
                         ],
                         MType::Nat
                     )
                 );
                 assert_eq!(stack[0].instruction, Instruction::UPDATE);
-                assert_eq!(stack[1].value, MValue::Int(22));
+                assert_eq!(stack[1].value, MValue::new_int(22));
                 assert_eq!(stack[1].instruction, Instruction::INIT);
                 assert_eq!(stack[2].value, MValue::Mutez(6_000_000));
                 assert_eq!(stack[2].instruction, Instruction::INIT);
@@ -186,18 +219,18 @@ mod test {
     fn update_success_remove_simple_set() {
         let initial_set = MValue::new_set(
             vec![
-                MValue::Nat(2),
-                MValue::Nat(3),
-                MValue::Nat(4),
-                MValue::Nat(5),
+                MValue::new_nat(2),
+                MValue::new_nat(3),
+                MValue::new_nat(4),
+                MValue::new_nat(5),
             ],
             MType::Nat,
         );
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Nat(3), Instruction::INIT),
+            StackElement::new(MValue::new_nat(3), Instruction::INIT),
             StackElement::new(MValue::Bool(false), Instruction::INIT),
             StackElement::new(initial_set, Instruction::INIT),
-            StackElement::new(MValue::Int(22), Instruction::INIT),
+            StackElement::new(MValue::new_int(22), Instruction::INIT),
             StackElement::new(MValue::Mutez(6_000_000), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
@@ -215,12 +248,12 @@ mod test {
                 assert_eq!(
                     stack[0].value,
                     MValue::new_set(
-                        vec![MValue::Nat(2), MValue::Nat(4), MValue::Nat(5)],
+                        vec![MValue::new_nat(2), MValue::new_nat(4), MValue::new_nat(5)],
                         MType::Nat
                     )
                 );
                 assert_eq!(stack[0].instruction, Instruction::UPDATE);
-                assert_eq!(stack[1].value, MValue::Int(22));
+                assert_eq!(stack[1].value, MValue::new_int(22));
                 assert_eq!(stack[1].instruction, Instruction::INIT);
                 assert_eq!(stack[2].value, MValue::Mutez(6_000_000));
                 assert_eq!(stack[2].instruction, Instruction::INIT);
@@ -232,18 +265,18 @@ mod test {
     fn update_success_remove_simple_set_no_change() {
         let initial_set = MValue::new_set(
             vec![
-                MValue::Nat(2),
-                MValue::Nat(3),
-                MValue::Nat(4),
-                MValue::Nat(5),
+                MValue::new_nat(2),
+                MValue::new_nat(3),
+                MValue::new_nat(4),
+                MValue::new_nat(5),
             ],
             MType::Nat,
         );
         let initial_stack: Stack = vec![
-            StackElement::new(MValue::Nat(9), Instruction::INIT),
+            StackElement::new(MValue::new_nat(9), Instruction::INIT),
             StackElement::new(MValue::Bool(false), Instruction::INIT),
             StackElement::new(initial_set, Instruction::INIT),
-            StackElement::new(MValue::Int(22), Instruction::INIT),
+            StackElement::new(MValue::new_int(22), Instruction::INIT),
             StackElement::new(MValue::Mutez(6_000_000), Instruction::INIT),
         ];
         let stack_snapshots = vec![];
@@ -262,16 +295,16 @@ mod test {
                     stack[0].value,
                     MValue::new_set(
                         vec![
-                            MValue::Nat(2),
-                            MValue::Nat(3),
-                            MValue::Nat(4),
-                            MValue::Nat(5)
+                            MValue::new_nat(2),
+                            MValue::new_nat(3),
+                            MValue::new_nat(4),
+                            MValue::new_nat(5)
                         ],
                         MType::Nat
                     )
                 );
                 assert_eq!(stack[0].instruction, Instruction::UPDATE);
-                assert_eq!(stack[1].value, MValue::Int(22));
+                assert_eq!(stack[1].value, MValue::new_int(22));
                 assert_eq!(stack[1].instruction, Instruction::INIT);
                 assert_eq!(stack[2].value, MValue::Mutez(6_000_000));
                 assert_eq!(stack[2].instruction, Instruction::INIT);
@@ -2,17 +2,25 @@
 #![allow(non_camel_case_types)]
 #![allow(dead_code)]
 
+mod compiler;
+mod contract;
 mod errors;
 mod instructions;
 mod m_types;
+mod macros;
+mod micheline;
 mod parser;
 mod stack;
+mod typechecker;
 mod utils;
 
 #[cfg(test)]
 mod test {
     use super::*;
-    use instructions::Instruction;
+    use contract::Contract;
+    use errors::MichelsonError;
+    use instructions::{Instruction, RunOptions, RunOptionsContext};
+    use micheline::Micheline;
     use m_types::{or, CollectionValue, MType, MValue, Or, OrValue, PairValue};
     use parser::RunResult;
     use stack::{Stack, StackElement, StackSnapshots};
@@ -33,33 +41,41 @@ mod test {
 
         let parsed_michelson = parser::parse(String::from(michelson_code));
         let parsed_json = match parsed_michelson {
-            Ok(mich) => parser::to_json(&mich),
+            Ok(mich) => Ok(Micheline::Seq(mich).to_json()),
             Err((err, _)) => panic!("{}", err),
         };
 
-        let run_result: Result<RunResult, String> = match parsed_json {
+        let run_result: Result<RunResult, MichelsonError> = match parsed_json {
             Ok(json) => {
                 // (or (or (int %decrement) (int %increment)) (unit %reset))
                 // reset params
                 let param_type: or<MType, MType> =
-                    (MType::Or(Box::new((MType::Int, MType::Int))), MType::Unit);
+                    (MType::or(MType::Int, MType::Int), MType::Unit);
                 let param = MValue::Or(OrValue {
                     m_type: param_type.clone(),
                     value: Box::new(Or::Right(MValue::Unit)),
                 });
 
-                let storage = MValue::Int(5);
+                let storage = MValue::new_int(5);
                 let storage_type = MType::Int;
                 // creates the initial stack
                 let stack: Stack = vec![StackElement::new(
                     MValue::Pair(PairValue {
-                        m_type: (MType::Or(Box::new(param_type)), storage_type),
+                        m_type: (MType::or(param_type.0, param_type.1), storage_type),
                         value: Box::new((param, storage)),
                     }),
                     Instruction::INIT,
                 )];
                 let stack_snapshots: StackSnapshots = vec![stack.clone()];
-                parser::run(&json, stack, stack_snapshots)
+                parser::run(
+                    &json,
+                    stack,
+                    stack_snapshots,
+                    &RunOptions {
+                        context: RunOptionsContext::mock(),
+                        pos: 0,
+                    },
+                )
             }
             Err(err) => Err(err),
         };
@@ -68,13 +84,13 @@ mod test {
             Err(_) => assert!(false),
             Ok(result) => {
                 assert_eq!(result.stack.len(), 1);
-                assert_eq!(result.has_failed, false);
+                assert_eq!(result.has_failed(), false);
                 assert_eq!(
                     result.stack[0].get_val().get_type(),
-                    MType::Pair(Box::new((
+                    MType::pair(
                         MType::List(Box::new(MType::Operation)),
                         MType::Int
-                    )))
+                    )
                 );
                 assert_eq!(
                     result.stack[0].get_val(),
@@ -85,7 +101,7 @@ mod test {
                                 m_type: MType::Operation,
                                 value: Box::new(vec![])
                             }),
-                            MValue::Int(0)
+                            MValue::new_int(0)
                         ))
                     })
                 )
@@ -112,21 +128,21 @@ mod test {
         assert!(parsed_michelson.is_ok());
 
         let parsed_json = match parsed_michelson {
-            Ok(mich) => parser::to_json(&mich),
+            Ok(mich) => Ok(Micheline::Seq(mich).to_json()),
             Err((err, _)) => Err(err),
         };
         assert!(parsed_json.is_ok());
 
-        let run_result: Result<RunResult, String> = match parsed_json {
+        let run_result: Result<RunResult, MichelsonError> = match parsed_json {
             Ok(json) => {
                 let param_type: MType = MType::List(Box::new(MType::Int));
                 let param = MValue::List(CollectionValue {
                     m_type: MType::Int,
-                    value: Box::new(vec![MValue::Int(3), MValue::Int(6), MValue::Int(11)]),
+                    value: Box::new(vec![MValue::new_int(3), MValue::new_int(6), MValue::new_int(11)]),
                 });
                 let storage = MValue::List(CollectionValue {
                     m_type: MType::Int,
-                    value: Box::new(vec![MValue::Int(5), MValue::Int(6)]),
+                    value: Box::new(vec![MValue::new_int(5), MValue::new_int(6)]),
                 });
                 let storage_type = MType::List(Box::new(MType::Int));
 
@@ -139,16 +155,24 @@ mod test {
                     Instruction::INIT,
                 )];
                 let stack_snapshots: StackSnapshots = vec![stack.clone()];
-                parser::run(&json, stack, stack_snapshots)
+                parser::run(
+                    &json,
+                    stack,
+                    stack_snapshots,
+                    &RunOptions {
+                        context: RunOptionsContext::mock(),
+                        pos: 0,
+                    },
+                )
             }
-            Err(err) => Err(err),
+            Err(err) => Err(MichelsonError::from(err)),
         };
 
         match run_result {
             Err(_) => assert!(false),
             Ok(result) => {
                 assert_eq!(result.stack.len(), 1);
-                assert_eq!(result.has_failed, false);
+                assert_eq!(result.has_failed(), false);
                 assert_eq!(
                     result.stack[0].get_val(),
                     MValue::Pair(PairValue::new(
@@ -158,17 +182,127 @@ mod test {
                         }),
                         MValue::List(CollectionValue {
                             m_type: MType::Int,
-                            value: Box::new(vec![MValue::Int(6), MValue::Int(12), MValue::Int(22)])
+                            value: Box::new(vec![MValue::new_int(9), MValue::new_int(18), MValue::new_int(33)])
                         })
                     ))
                 );
                 assert_eq!(
                     result.stack[0].get_val().get_type(),
-                    MType::Pair(Box::new((
+                    MType::pair(
                         MType::List(Box::new(MType::Operation)),
                         MType::List(Box::new(MType::Int))
-                    )))
+                    )
+                );
+            }
+        }
+    }
+
+    /*
+        CONTRACT LOADED FROM A SCRIPT
+    */
+    #[test]
+    fn contract_from_script() {
+        let script = r#"
+            parameter (or (or (int %decrement) (int %increment)) (unit %reset)) ;
+            storage int ;
+            code { UNPAIR ;
+                   IF_LEFT { IF_LEFT { SWAP ; SUB } { ADD } } { DROP 2 ; PUSH int 0 } ;
+                   NIL operation ;
+                   PAIR }
+        "#;
+        let contract = match Contract::from_str(script) {
+            Ok(contract) => contract,
+            Err(err) => panic!("{}", err),
+        };
+        // increment params
+        let param_type = match &contract.parameter {
+            MType::Or(types, _) => *types.clone(),
+            _ => panic!("Expected an `or` type for the parameter"),
+        };
+        let param = MValue::Or(OrValue {
+            m_type: param_type,
+            value: Box::new(Or::Left(MValue::Or(OrValue {
+                m_type: (MType::Int, MType::Int),
+                value: Box::new(Or::Right(MValue::new_int(5))),
+            }))),
+        });
+        let storage = MValue::new_int(3);
+        assert_eq!(storage.get_type(), contract.storage);
+        // creates the initial stack
+        let stack: Stack = vec![StackElement::new(
+            MValue::Pair(PairValue::new(param, storage)),
+            Instruction::INIT,
+        )];
+        let stack_snapshots: StackSnapshots = vec![stack.clone()];
+        let json = Micheline::Seq(contract.code).to_json();
+
+        let options = RunOptions {
+            context: RunOptionsContext::mock(),
+            pos: 0,
+        };
+        match parser::run(&json, stack, stack_snapshots, &options) {
+            Err(err) => panic!("{}", err),
+            Ok(result) => {
+                assert_eq!(result.stack.len(), 1);
+                assert_eq!(result.has_failed(), false);
+                assert_eq!(
+                    result.stack[0].get_val(),
+                    MValue::Pair(PairValue::new(
+                        MValue::new_empty_list(MType::Operation),
+                        MValue::new_int(8)
+                    ))
+                );
+            }
+        }
+    }
+
+    /*
+        CALL CONTEXT
+    */
+    #[test]
+    fn context_in_nested_blocks() {
+        let michelson_code = r#"
+            DROP ;
+            PUSH int 1 ;
+            GT ;
+            IF { SENDER ;
+                 AMOUNT ;
+                 NIL nat ;
+                 PUSH nat 1 ;
+                 CONS ;
+                 MAP { DROP ; LEVEL } }
+               { UNIT ; FAILWITH }"#;
+        let code = match parser::parse(String::from(michelson_code)) {
+            Ok(code) => code,
+            Err((err, _)) => panic!("{}", err),
+        };
+        let json = Micheline::Seq(code).to_json();
+        let sender = String::from("tz1VSUr8wwNhLAzempoch5d6hLRiTh8Cjcjb");
+        let options = RunOptions {
+            context: RunOptionsContext {
+                amount: 2_500_000,
+                sender: sender.clone(),
+                level: 1_000,
+                ..RunOptionsContext::mock()
+            },
+            pos: 0,
+        };
+        let stack: Stack = vec![StackElement::new(MValue::Unit, Instruction::INIT)];
+        let stack_snapshots: StackSnapshots = vec![stack.clone()];
+
+        match parser::run(&json, stack, stack_snapshots, &options) {
+            Err(err) => panic!("{}", err),
+            Ok(result) => {
+                assert_eq!(result.stack.len(), 3);
+                assert_eq!(
+                    result.stack[0].get_val(),
+                    MValue::List(CollectionValue {
+                        m_type: MType::Nat,
+                        value: Box::new(vec![MValue::new_nat(1_001)])
+                    })
                 );
+                assert_eq!(result.stack[1].get_val(), MValue::Mutez(2_500_000));
+                assert_eq!(result.stack[2].get_val(), MValue::Address(sender));
             }
         }
     }
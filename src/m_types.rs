@@ -1,9 +1,17 @@
+use crate::compiler::{self, Block};
+use crate::errors::MichelsonError;
 use crate::instructions::Instruction;
+use crate::micheline::Micheline;
 use bs58;
-use std::collections::HashMap;
-use std::hash::{Hash, Hasher};
-
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+use chrono::{DateTime, SecondsFormat};
+use num_bigint::{BigInt, BigUint};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::hash::Hash;
+use std::rc::Rc;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub enum Or {
     Left(MValue),
     Right(MValue),
@@ -15,12 +23,86 @@ pub enum OrType<A, B> {
     Right(B),
 }
 
+/// Field and type annotations of a type, e.g. `%owner` and `:address`
+/// annotations don't change the type of a value, so they are ignored when types are compared
+#[derive(Debug, Clone, Default)]
+pub struct TypeAnnots {
+    pub field: Option<String>,
+    pub type_name: Option<String>,
+}
+
+impl TypeAnnots {
+    /// reads the field and type annotations from the annotations of a Micheline node
+    /// variable annotations are ignored as they don't belong to types
+    pub fn from_annots(annots: &[String]) -> TypeAnnots {
+        let mut type_annots = TypeAnnots::default();
+        for annot in annots {
+            if let Some(field) = annot.strip_prefix('%') {
+                type_annots.field = Some(String::from(field));
+            } else if let Some(type_name) = annot.strip_prefix(':') {
+                type_annots.type_name = Some(String::from(type_name));
+            }
+        }
+        type_annots
+    }
+
+    /// returns the annotations in the Micheline format, e.g. `[":address", "%owner"]`
+    pub fn to_annots(&self) -> Vec<String> {
+        let mut annots = vec![];
+        if let Some(type_name) = &self.type_name {
+            annots.push(format!(":{}", type_name));
+        }
+        if let Some(field) = &self.field {
+            annots.push(format!("%{}", field));
+        }
+        annots
+    }
+
+    /// checks if there is no field and no type annotation
+    pub fn is_empty(&self) -> bool {
+        self.field.is_none() && self.type_name.is_none()
+    }
+
+    /// replaces the field annotation
+    pub fn with_field(self, field: Option<String>) -> TypeAnnots {
+        TypeAnnots { field, ..self }
+    }
+}
+
+// types that only differ by their annotations are the same type
+impl PartialEq for TypeAnnots {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for TypeAnnots {}
+
+impl Hash for TypeAnnots {
+    fn hash<H: std::hash::Hasher>(&self, _: &mut H) {}
+}
+
+impl PartialOrd for TypeAnnots {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for TypeAnnots {
+    fn cmp(&self, _: &Self) -> Ordering {
+        Ordering::Equal
+    }
+}
+
+/// Annotations of the two branches of an `or` or a `pair` type, e.g. `%left` and `%right`
+pub type BranchAnnots = Box<(TypeAnnots, TypeAnnots)>;
+
 pub enum AddressType {
     ImplicitAccount,
     Contract,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct Ticket {
     pub value: MValue,
     pub amount: nat,
@@ -29,7 +111,7 @@ pub struct Ticket {
 
 impl Ticket {
     pub fn new(value: MValue, amount: nat, ticketer: address) -> Result<Ticket, String> {
-        if amount > 0 {
+        if amount > nat::ZERO {
             Ok(Ticket {
                 amount,
                 value,
@@ -47,28 +129,32 @@ pub enum Never {}
 
 pub type unit = ();
 pub type never = Never;
-pub type int = i128;
-pub type nat = u128;
+pub type int = BigInt;
+pub type nat = BigUint;
 pub type string = String;
 pub type chain_id = String;
-pub type bytes = String;
-pub type mutez = u128;
+pub type bytes = BytesValue;
+pub type mutez = u64;
 pub type key_hash = String;
 pub type key = String;
 pub type signature = String;
-pub type timestamp = usize;
+// number of seconds since the epoch, negative before 1970
+pub type timestamp = i64;
 pub type address = String;
 pub type operation = String;
+
+/// largest amount of mutez, amounts are signed 64-bit integers in the protocol
+pub const MAX_MUTEZ: mutez = i64::MAX as mutez;
 pub type option<T> = Option<T>;
 pub type or<A, B> = (A, B);
 pub type pair<A, B> = (A, B);
 pub type list<T> = Vec<T>;
-pub type set<T> = Vec<T>;
-pub type map<K, V> = HashMap<K, V>;
-pub type big_map<K, V> = HashMap<K, V>;
+pub type set<T> = BTreeSet<T>;
+pub type map<K, V> = BTreeMap<K, V>;
+pub type big_map<K, V> = BTreeMap<K, V>;
 pub type ticket<A> = (A, nat, address);
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub enum MType {
     Unit,
     Never,
@@ -88,37 +174,171 @@ pub enum MType {
     Ticket(Box<(MType, MType, MType)>),
     Contract(Box<(MType, MType)>),
     Option(Box<MType>),
-    Or(Box<(MType, MType)>),
-    Pair(Box<(MType, MType)>),
+    Or(Box<(MType, MType)>, BranchAnnots),
+    Pair(Box<(MType, MType)>, BranchAnnots),
     List(Box<MType>),
     Set(Box<MType>),
     Map(Box<(MType, MType)>),
     Big_map(Box<(MType, MType)>),
+    Lambda(Box<(MType, MType)>),
 }
 
 impl MType {
-    pub fn from_string(str: &str) -> Result<MType, String> {
-        match str {
-            "unit" => Ok(MType::Unit),
-            "never" => Ok(MType::Never),
-            "bool" => Ok(MType::Bool),
-            "int" => Ok(MType::Int),
-            "nat" => Ok(MType::Nat),
-            "string" => Ok(MType::String),
-            "chain_id" => Ok(MType::ChainId),
-            "bytes" => Ok(MType::Bytes),
-            "mutez" => Ok(MType::Mutez),
-            "key_hash" => Ok(MType::KeyHash),
-            "key" => Ok(MType::Key),
-            "signature" => Ok(MType::Signature),
-            "timestamp" => Ok(MType::Timestamp),
-            "address" => Ok(MType::Address),
-            "operation" => Ok(MType::Operation),
-            _ => Err(String::from(format!("Unknown type '{}'", str))),
+    /// parses a type from the Michelson syntax, e.g. `pair (option nat) (map string (list int))`
+    pub fn from_string(str: &str) -> Result<MType, MichelsonError> {
+        let node = str.parse::<Micheline>().map_err(MichelsonError::Message)?;
+        MType::from_micheline(&node)
+    }
+
+    /// parses a type from its Micheline JSON representation
+    pub fn from_json(json: &str) -> Result<MType, MichelsonError> {
+        let node = Micheline::from_json(json).map_err(MichelsonError::InvalidJson)?;
+        MType::from_micheline(&node)
+    }
+
+    /// builds a type from a Micheline node
+    /// n-ary pairs are turned into right-combed pairs, the annotations of the root of the type are ignored
+    pub fn from_micheline(node: &Micheline) -> Result<MType, MichelsonError> {
+        MType::from_micheline_annotated(node).map(|(m_type, _)| m_type)
+    }
+
+    /// builds a type from a Micheline node along with the field and type annotations of its root,
+    /// the branches of `or` and `pair` types keep their own annotations
+    /// n-ary pairs are turned into right-combed pairs
+    pub fn from_micheline_annotated(
+        node: &Micheline,
+    ) -> Result<(MType, TypeAnnots), MichelsonError> {
+        let (name, args, annots) = match node {
+            Micheline::Prim { name, args, annots } => {
+                (name.as_str(), args.as_slice(), annots.as_slice())
+            }
+            _ => {
+                return Err(MichelsonError::Message(format!(
+                    "Expected a type, but got `{}`",
+                    node
+                )))
+            }
+        };
+        // parses the arguments of the type along with their annotations
+        let parse_args = |args: &[Micheline]| -> Result<Vec<(MType, TypeAnnots)>, MichelsonError> {
+            args.iter().map(MType::from_micheline_annotated).collect()
+        };
+        let parse_arg = |arg: &Micheline| MType::from_micheline(arg);
+        let m_type = match (name, args) {
+            ("unit", []) => MType::Unit,
+            ("never", []) => MType::Never,
+            ("bool", []) => MType::Bool,
+            ("int", []) => MType::Int,
+            ("nat", []) => MType::Nat,
+            ("string", []) => MType::String,
+            ("chain_id", []) => MType::ChainId,
+            ("bytes", []) => MType::Bytes,
+            ("mutez", []) => MType::Mutez,
+            ("key_hash", []) => MType::KeyHash,
+            ("key", []) => MType::Key,
+            ("signature", []) => MType::Signature,
+            ("timestamp", []) => MType::Timestamp,
+            ("address", []) => MType::Address,
+            ("operation", []) => MType::Operation,
+            ("option", [arg]) => MType::Option(Box::new(parse_arg(arg)?)),
+            ("list", [arg]) => MType::List(Box::new(parse_arg(arg)?)),
+            ("set", [arg]) => MType::Set(Box::new(parse_arg(arg)?)),
+            ("contract", [arg]) => MType::Contract(Box::new((MType::Address, parse_arg(arg)?))),
+            ("ticket", [arg]) => {
+                MType::Ticket(Box::new((parse_arg(arg)?, MType::Nat, MType::Address)))
+            }
+            ("map" | "big_map" | "lambda", [first, second]) => {
+                let types = Box::new((parse_arg(first)?, parse_arg(second)?));
+                match name {
+                    "map" => MType::Map(types),
+                    "big_map" => MType::Big_map(types),
+                    _ => MType::Lambda(types),
+                }
+            }
+            ("or", [_, _]) => {
+                let mut branches = parse_args(args)?;
+                let (left, left_annots) = branches.remove(0);
+                let (right, right_annots) = branches.remove(0);
+                MType::Or(Box::new((left, right)), Box::new((left_annots, right_annots)))
+            }
+            // n-ary pairs are right-combed
+            ("pair", [first, rest @ ..]) if !rest.is_empty() => {
+                let mut branches = match rest {
+                    [_] => parse_args(args)?,
+                    _ => parse_args(&[first.clone(), Micheline::prim("pair", rest.to_vec())])?,
+                };
+                let (left, left_annots) = branches.remove(0);
+                let (right, right_annots) = branches.remove(0);
+                MType::Pair(Box::new((left, right)), Box::new((left_annots, right_annots)))
+            }
+            (
+                "unit" | "never" | "bool" | "int" | "nat" | "string" | "chain_id" | "bytes"
+                | "mutez" | "key_hash" | "key" | "signature" | "timestamp" | "address"
+                | "operation" | "option" | "list" | "set" | "contract" | "ticket" | "or" | "map"
+                | "big_map" | "lambda" | "pair",
+                _,
+            ) => {
+                return Err(MichelsonError::Message(format!(
+                    "Unexpected number of arguments ({}) for type `{}`",
+                    args.len(),
+                    name
+                )))
+            }
+            _ => return Err(MichelsonError::Message(format!("Unknown type '{}'", name))),
+        };
+
+        Ok((m_type, TypeAnnots::from_annots(annots)))
+    }
+
+    /// builds a `pair` type whose branches have no annotations
+    pub fn pair(left: MType, right: MType) -> MType {
+        MType::Pair(Box::new((left, right)), BranchAnnots::default())
+    }
+
+    /// builds an `or` type whose branches have no annotations
+    pub fn or(left: MType, right: MType) -> MType {
+        MType::Or(Box::new((left, right)), BranchAnnots::default())
+    }
+
+    /// returns the annotations of the branches of `or` and `pair` types, `None` for other types
+    pub fn branch_annots(&self) -> Option<&(TypeAnnots, TypeAnnots)> {
+        match self {
+            MType::Or(_, annots) | MType::Pair(_, annots) => Some(annots),
+            _ => None,
         }
     }
 
-    pub fn to_string(&self) -> String {
+    /// turns the type into a Micheline node
+    pub fn to_micheline(&self) -> Micheline {
+        self.to_micheline_annotated(&TypeAnnots::default())
+    }
+
+    /// turns the type into a Micheline node whose root carries the provided annotations
+    pub fn to_micheline_annotated(&self, type_annots: &TypeAnnots) -> Micheline {
+        let args = match self {
+            MType::Ticket(types) => vec![types.0.to_micheline()],
+            MType::Contract(types) => vec![types.1.to_micheline()],
+            MType::Option(m_type) | MType::List(m_type) | MType::Set(m_type) => {
+                vec![m_type.to_micheline()]
+            }
+            MType::Or(types, annots) | MType::Pair(types, annots) => vec![
+                types.0.to_micheline_annotated(&annots.0),
+                types.1.to_micheline_annotated(&annots.1),
+            ],
+            MType::Map(types) | MType::Big_map(types) | MType::Lambda(types) => {
+                vec![types.0.to_micheline(), types.1.to_micheline()]
+            }
+            _ => vec![],
+        };
+        Micheline::Prim {
+            name: self.name(),
+            args,
+            annots: type_annots.to_annots(),
+        }
+    }
+
+    /// returns the name of the type constructor, e.g. `pair` for `pair int nat`
+    pub fn name(&self) -> String {
         match self {
             MType::Unit => String::from("unit"),
             MType::Never => String::from("never"),
@@ -138,12 +358,41 @@ impl MType {
             MType::Ticket(_) => String::from("ticket"),
             MType::Contract(_) => String::from("contract"),
             MType::Option(_) => String::from("option"),
-            MType::Or(_) => String::from("or"),
-            MType::Pair(_) => String::from("pair"),
+            MType::Or(..) => String::from("or"),
+            MType::Pair(..) => String::from("pair"),
             MType::List(_) => String::from("list"),
             MType::Set(_) => String::from("set"),
             MType::Map(_) => String::from("map"),
             MType::Big_map(_) => String::from("big_map"),
+            MType::Lambda(_) => String::from("lambda"),
+        }
+    }
+
+    /// returns the type in the Michelson syntax on a single line, e.g. `pair (option nat) (list int)`
+    pub fn to_string(&self) -> String {
+        self.to_micheline().to_flat_string(false)
+    }
+
+    /// checks if values of the type can be compared with each other (e.g. with COMPARE)
+    pub fn is_comparable(&self) -> bool {
+        match self {
+            MType::Unit
+            | MType::Never
+            | MType::Bool
+            | MType::Int
+            | MType::Nat
+            | MType::String
+            | MType::ChainId
+            | MType::Bytes
+            | MType::Mutez
+            | MType::KeyHash
+            | MType::Key
+            | MType::Signature
+            | MType::Timestamp
+            | MType::Address => true,
+            MType::Option(m_type) => m_type.is_comparable(),
+            MType::Or(types, _) | MType::Pair(types, _) => types.0.is_comparable() && types.1.is_comparable(),
+            _ => false,
         }
     }
 
@@ -178,7 +427,71 @@ impl MType {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// Sequence of bytes, written in hexadecimal with the `0x` prefix in Michelson, e.g. `0x05ab`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct BytesValue(pub Vec<u8>);
+
+impl BytesValue {
+    pub fn new(val: &[u8]) -> BytesValue {
+        BytesValue(val.to_vec())
+    }
+
+    /// parses the hexadecimal representation of the bytes, with or without the `0x` prefix
+    pub fn from_hex(hex: &str) -> Result<BytesValue, String> {
+        let digits = hex.strip_prefix("0x").unwrap_or(hex);
+        if !digits.len().is_multiple_of(2) || !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(format!("Invalid bytes value 0x{}", digits));
+        }
+        Ok(BytesValue(
+            (0..digits.len())
+                .step_by(2)
+                .map(|i| u8::from_str_radix(&digits[i..i + 2], 16).unwrap())
+                .collect(),
+        ))
+    }
+
+    /// returns the hexadecimal representation of the bytes, without the `0x` prefix
+    pub fn to_hex(&self) -> String {
+        self.0.iter().map(|byte| format!("{:02x}", byte)).collect()
+    }
+
+    pub fn as_slice(&self) -> &[u8] {
+        &self.0
+    }
+
+    /// returns the number of bytes
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl fmt::Display for BytesValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "0x{}", self.to_hex())
+    }
+}
+
+/// parses a timestamp from its RFC3339 representation, e.g. `2022-01-01T00:00:00Z`
+pub fn timestamp_from_rfc3339(val: &str) -> Result<timestamp, String> {
+    DateTime::parse_from_rfc3339(val)
+        .map(|date| date.timestamp())
+        .map_err(|_| format!("Invalid timestamp {}", val))
+}
+
+/// returns the RFC3339 representation of a timestamp, e.g. `2022-01-01T00:00:00Z`
+/// timestamps out of the range of dates are returned as a number of seconds
+pub fn timestamp_to_string(val: timestamp) -> String {
+    match DateTime::from_timestamp(val, 0) {
+        Some(date) => date.to_rfc3339_opts(SecondsFormat::Secs, true),
+        None => val.to_string(),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct OptionValue {
     pub m_type: MType,
     pub value: Box<Option<MValue>>,
@@ -193,7 +506,7 @@ impl OptionValue {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct OrValue {
     pub m_type: (MType, MType),
     pub value: Box<Or>,
@@ -228,7 +541,7 @@ impl OrValue {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct PairValue {
     pub m_type: (MType, MType),
     pub value: Box<(MValue, MValue)>,
@@ -301,7 +614,7 @@ impl PairValue {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct CollectionValue {
     pub m_type: MType,
     pub value: Box<Vec<MValue>>,
@@ -356,19 +669,60 @@ impl CollectionValue {
         }
     }
 
-    /// Alias for `cons` to use with MValue of type set
-    /// `.update` should be used with MValue of type list to match Michelson UPDATE
-    pub fn update(&self, element: MValue) -> CollectionValue {
-        self.cons(element)
+    /// Returns the size of the collection
+    pub fn size(&self) -> nat {
+        nat::from(self.value.len())
+    }
+}
+
+/// Set value, the elements are kept in the order of the comparable values and without duplicates
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct SetValue {
+    pub m_type: MType,
+    pub value: set<MValue>,
+}
+
+impl SetValue {
+    pub fn new(elements: Vec<MValue>, m_type: MType) -> SetValue {
+        SetValue {
+            m_type,
+            value: elements.into_iter().collect(),
+        }
+    }
+
+    /// checks if all the elements in the set are of the expected type
+    pub fn check_elements_type(&self, current_instruction: Instruction) -> Result<(), String> {
+        match self.value.iter().find(|val| val.get_type() != self.m_type) {
+            None => Ok(()),
+            Some(val) => Err(format!(
+                "Expected values of type {} in set for `{:?}`, but got a value of type {}",
+                self.m_type.to_string(),
+                current_instruction,
+                val.get_type().to_string()
+            )),
+        }
     }
 
-    /// Returns the size of the collection
+    /// Adds an element to the set, the set is unchanged if it already contains the element
+    /// `.update` should be used with MValue of type set to match Michelson UPDATE
+    pub fn update(mut self, element: MValue) -> SetValue {
+        self.value.insert(element);
+        self
+    }
+
+    /// Removes an element from the set
+    pub fn remove(mut self, element: &MValue) -> SetValue {
+        self.value.remove(element);
+        self
+    }
+
+    /// Returns the size of the set
     pub fn size(&self) -> nat {
-        self.value.len() as nat
+        nat::from(self.value.len())
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct MapValue {
     pub is_map: bool, // MapValue is used to represent both maps and big_maps
     pub key_type: MType,
@@ -376,15 +730,58 @@ pub struct MapValue {
     pub value: map<MValue, MValue>,
 }
 
-impl Hash for MapValue {
-    fn hash<H>(&self, _: &mut H)
-    where
-        H: Hasher,
-    {
+impl MapValue {
+    /// creates a map or a big_map from its entries, the entries are sorted by key
+    /// the keys must be comparable and unique, the entries must be of the types of the map
+    pub fn new(
+        is_map: bool,
+        key_type: MType,
+        value_type: MType,
+        elements: Vec<(MValue, MValue)>,
+    ) -> Result<MapValue, MichelsonError> {
+        let name = if is_map { "map" } else { "big_map" };
+        if !key_type.is_comparable() {
+            return Err(MichelsonError::Message(format!(
+                "Keys of a {} must be comparable, but got {}",
+                name,
+                key_type.to_string()
+            )));
+        }
+        let mut value = BTreeMap::new();
+        for (key, val) in elements {
+            if key.get_type() != key_type {
+                return Err(MichelsonError::Message(format!(
+                    "Expected keys of type {} in {}, but got a key of type {}",
+                    key_type.to_string(),
+                    name,
+                    key.get_type().to_string()
+                )));
+            }
+            if val.get_type() != value_type {
+                return Err(MichelsonError::Message(format!(
+                    "Expected values of type {} in {}, but got a value of type {}",
+                    value_type.to_string(),
+                    name,
+                    val.get_type().to_string()
+                )));
+            }
+            if value.contains_key(&key) {
+                return Err(MichelsonError::Message(format!(
+                    "Duplicate key {} in {}",
+                    key.to_micheline().to_flat_string(false),
+                    name
+                )));
+            }
+            value.insert(key, val);
+        }
+        Ok(MapValue {
+            is_map,
+            key_type,
+            value_type,
+            value,
+        })
     }
-}
 
-impl MapValue {
     /// returns the size of the map
     pub fn size(&self) -> Result<usize, String> {
         if self.is_map {
@@ -407,7 +804,123 @@ impl MapValue {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// Code of a lambda, compiled once when the lambda is built
+/// it follows from the Micheline code of the lambda, so it's ignored when lambdas are compared
+#[derive(Debug, Clone)]
+pub struct LambdaBody(Rc<Block>);
+
+impl LambdaBody {
+    pub fn block(&self) -> &Block {
+        &self.0
+    }
+}
+
+impl PartialEq for LambdaBody {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for LambdaBody {}
+
+impl Hash for LambdaBody {
+    fn hash<H: std::hash::Hasher>(&self, _: &mut H) {}
+}
+
+impl PartialOrd for LambdaBody {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for LambdaBody {
+    fn cmp(&self, _: &Self) -> Ordering {
+        Ordering::Equal
+    }
+}
+
+/// Lambda value, the code is compiled when the lambda is built
+/// the Micheline code is kept to compare and print the lambda
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct LambdaValue {
+    // types of the parameter and of the result of the code
+    pub m_type: (MType, MType),
+    pub code: Micheline,
+    pub body: LambdaBody,
+    // lambdas built with LAMBDA_REC receive themselves on the stack
+    pub recursive: bool,
+    // values partially applied with APPLY, in the order they were applied
+    pub applied: Vec<MValue>,
+}
+
+impl LambdaValue {
+    /// builds a lambda from its code, fails if the code doesn't compile
+    pub fn new(
+        param_type: MType,
+        return_type: MType,
+        code: Micheline,
+        recursive: bool,
+    ) -> Result<LambdaValue, MichelsonError> {
+        let body = match &code {
+            Micheline::Seq(nodes) => compiler::compile(nodes)?,
+            node => compiler::compile(std::slice::from_ref(node))?,
+        };
+        Ok(LambdaValue {
+            m_type: (param_type, return_type),
+            code,
+            body: LambdaBody(Rc::new(body)),
+            recursive,
+            applied: vec![],
+        })
+    }
+
+    /// returns the type of the parameter that is still expected by the lambda
+    /// every applied value removes the left side of the parameter pair
+    pub fn param_type(&self) -> Result<MType, MichelsonError> {
+        let mut param_type = self.m_type.0.clone();
+        for _ in self.applied.iter() {
+            param_type = match param_type {
+                MType::Pair(types, _) => types.1,
+                // APPLY only accepts lambdas taking a pair
+                m_type => {
+                    return Err(MichelsonError::WrongType((
+                        String::from("pair"),
+                        m_type.to_string(),
+                        Instruction::APPLY,
+                    )))
+                }
+            };
+        }
+        Ok(param_type)
+    }
+
+    /// partially applies the lambda to the provided value
+    /// the value becomes the left side of the pair passed to the code
+    pub fn apply(&self, value: MValue) -> Result<LambdaValue, MichelsonError> {
+        match self.param_type()? {
+            MType::Pair(types, _) if types.0 == value.get_type() => {
+                let mut lambda = self.clone();
+                lambda.applied.push(value);
+                Ok(lambda)
+            }
+            param_type => Err(MichelsonError::Message(format!(
+                "Cannot apply a value of type {} to a lambda taking {}",
+                value.get_type().to_string(),
+                param_type.to_string()
+            ))),
+        }
+    }
+
+    /// builds the parameter passed to the code from the applied values and the provided value
+    pub fn build_param(&self, value: MValue) -> MValue {
+        self.applied
+            .iter()
+            .rev()
+            .fold(value, |acc, applied| MValue::Pair(PairValue::new(applied.clone(), acc)))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct ContractValue {
     address: address,
     parameter: MType,
@@ -450,9 +963,78 @@ pub enum MValue {
     Or(OrValue),
     Pair(PairValue),
     List(CollectionValue),
-    Set(CollectionValue),
+    Set(SetValue),
     Map(MapValue),
     Big_map(MapValue),
+    Lambda(LambdaValue),
+}
+
+/// Keys and signatures are ordered by curve like in the protocol, then by their bytes:
+/// ed25519 (`edpk`, `edsig`) < secp256k1 (`sppk`, `spsig1`) < p256 (`p2pk`, `p2sig`) < bls12_381 (`BLpk`, `BLsig`)
+/// the base58 string breaks the ties of values that can't be decoded
+fn curve_sort_key(val: &str) -> (u8, Vec<u8>, &str) {
+    let curve = match val.get(..2) {
+        Some("ed") => 0,
+        Some("sp") => 1,
+        Some("p2") => 2,
+        Some("BL") => 3,
+        _ => 4,
+    };
+    (curve, bs58::decode(val).into_vec().unwrap_or_default(), val)
+}
+
+/// Values of comparable types follow the ordering of the protocol, e.g. `None < Some _`,
+/// `Left _ < Right _`, pairs are ordered field by field and implicit accounts come before contracts.
+/// Other values are ordered by structure, so that any value can be stored in a set or used as a key
+impl Ord for MValue {
+    fn cmp(&self, other: &MValue) -> Ordering {
+        match (self, other) {
+            (MValue::Unit, MValue::Unit) | (MValue::Never, MValue::Never) => Ordering::Equal,
+            (MValue::Bool(left), MValue::Bool(right)) => left.cmp(right),
+            (MValue::Int(left), MValue::Int(right)) => left.cmp(right),
+            (MValue::Nat(left), MValue::Nat(right)) => left.cmp(right),
+            (MValue::Mutez(left), MValue::Mutez(right)) => left.cmp(right),
+            (MValue::Timestamp(left), MValue::Timestamp(right)) => left.cmp(right),
+            (MValue::Bytes(left), MValue::Bytes(right)) => left.cmp(right),
+            (MValue::String(left), MValue::String(right))
+            | (MValue::ChainId(left), MValue::ChainId(right))
+            | (MValue::KeyHash(left), MValue::KeyHash(right))
+            | (MValue::Operation(left), MValue::Operation(right)) => left.cmp(right),
+            (MValue::Key(left), MValue::Key(right))
+            | (MValue::Signature(left), MValue::Signature(right)) => {
+                curve_sort_key(left).cmp(&curve_sort_key(right))
+            }
+            (MValue::Address(left), MValue::Address(right)) => {
+                (left.starts_with("KT1"), left).cmp(&(right.starts_with("KT1"), right))
+            }
+            // the types only break ties between values of different types, e.g. `None` of different types
+            (MValue::Option(left), MValue::Option(right)) => {
+                (&left.value, &left.m_type).cmp(&(&right.value, &right.m_type))
+            }
+            (MValue::Or(left), MValue::Or(right)) => {
+                (&left.value, &left.m_type).cmp(&(&right.value, &right.m_type))
+            }
+            (MValue::Pair(left), MValue::Pair(right)) => {
+                (&left.value, &left.m_type).cmp(&(&right.value, &right.m_type))
+            }
+            (MValue::Contract(left), MValue::Contract(right)) => left.cmp(right),
+            (MValue::Ticket(left), MValue::Ticket(right)) => left.cmp(right),
+            (MValue::List(left), MValue::List(right)) => left.cmp(right),
+            (MValue::Set(left), MValue::Set(right)) => left.cmp(right),
+            (MValue::Map(left), MValue::Map(right)) | (MValue::Big_map(left), MValue::Big_map(right)) => {
+                left.cmp(right)
+            }
+            (MValue::Lambda(left), MValue::Lambda(right)) => left.cmp(right),
+            // values of different types are ordered by type
+            (left, right) => left.get_type().cmp(&right.get_type()),
+        }
+    }
+}
+
+impl PartialOrd for MValue {
+    fn partial_cmp(&self, other: &MValue) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
 }
 
 impl MValue {
@@ -482,6 +1064,7 @@ impl MValue {
             MValue::Set(_) => String::from("set"),
             MValue::Map(_) => String::from("map"),
             MValue::Big_map(_) => String::from("big_map"),
+            MValue::Lambda(_) => String::from("lambda"),
         }
     }
 
@@ -509,8 +1092,8 @@ impl MValue {
                 MType::Contract(Box::new((MType::Address, val.parameter.clone())))
             }
             MValue::Option(val) => MType::Option(Box::new(val.m_type.clone())),
-            MValue::Or(val) => MType::Or(Box::new(val.m_type.clone())),
-            MValue::Pair(val) => MType::Pair(Box::new(val.m_type.clone())),
+            MValue::Or(val) => MType::or(val.m_type.0.clone(), val.m_type.1.clone()),
+            MValue::Pair(val) => MType::pair(val.m_type.0.clone(), val.m_type.1.clone()),
             MValue::List(val) => MType::List(Box::new(val.m_type.clone())),
             MValue::Set(val) => MType::Set(Box::new(val.m_type.clone())),
             MValue::Map(val) => {
@@ -519,6 +1102,11 @@ impl MValue {
             MValue::Big_map(val) => {
                 MType::Big_map(Box::new((val.key_type.clone(), val.value_type.clone())))
             }
+            // lambdas are only applied to the values expected by their parameter, see `LambdaValue::apply`
+            MValue::Lambda(val) => MType::Lambda(Box::new((
+                val.param_type().unwrap_or_else(|_| val.m_type.0.clone()),
+                val.m_type.1.clone(),
+            ))),
         }
     }
 
@@ -603,13 +1191,8 @@ impl MValue {
     /// checks if value is a valid nat
     pub fn check_nat(&self) -> bool {
         match &self {
-            MValue::Nat(m_val) => {
-                if m_val >= &0 {
-                    true
-                } else {
-                    false
-                }
-            }
+            // nat values can't be negative
+            MValue::Nat(_) => true,
             _ => false,
         }
     }
@@ -618,13 +1201,7 @@ impl MValue {
     /// checks if value is a valid mutez
     pub fn check_mutez(&self) -> bool {
         match &self {
-            MValue::Mutez(m_val) => {
-                if m_val >= &0 {
-                    true
-                } else {
-                    false
-                }
-            }
+            MValue::Mutez(m_val) => *m_val <= MAX_MUTEZ,
             _ => false,
         }
     }
@@ -643,6 +1220,215 @@ impl MValue {
         }
     }
 
+    /// builds a value of the provided type from its Micheline representation
+    /// e.g. `Pair (Some 5) { Elt "a" { 1 ; 2 } }` for `pair (option nat) (map string (list int))`
+    pub fn from_micheline(node: &Micheline, m_type: &MType) -> Result<MValue, MichelsonError> {
+        // the kind of node, used to report invalid values
+        let node_kind = || match node {
+            Micheline::Int(_) => String::from("int"),
+            Micheline::String(_) => String::from("string"),
+            Micheline::Bytes(_) => String::from("bytes"),
+            Micheline::Prim { name, .. } => name.clone(),
+            Micheline::Seq(_) => String::from("sequence"),
+        };
+        let invalid = || MichelsonError::InvalidArgument((m_type.to_string(), node_kind()));
+        let numeric = |val: &str| MichelsonError::InvalidArgument((String::from("numeric value"), String::from(val)));
+        let prim_args = |expected_name: &str| match node {
+            Micheline::Prim { name, args, .. } if name == expected_name => Some(args.as_slice()),
+            _ => None,
+        };
+
+        match (m_type, node) {
+            (MType::Unit, _) if prim_args("Unit") == Some(&[]) => Ok(MValue::Unit),
+            (MType::Bool, _) if prim_args("True") == Some(&[]) => Ok(MValue::Bool(true)),
+            (MType::Bool, _) if prim_args("False") == Some(&[]) => Ok(MValue::Bool(false)),
+            (MType::Int, Micheline::Int(val)) => val.parse::<int>().map(MValue::Int).map_err(|_| numeric(val)),
+            (MType::Nat, Micheline::Int(val)) => val.parse::<nat>().map(MValue::Nat).map_err(|_| numeric(val)),
+            (MType::Mutez, Micheline::Int(val)) => match val.parse::<mutez>() {
+                Ok(amount) if amount <= MAX_MUTEZ => Ok(MValue::Mutez(amount)),
+                _ => Err(MichelsonError::InvalidArgument((
+                    format!("mutez value between 0 and {}", MAX_MUTEZ),
+                    val.clone(),
+                ))),
+            },
+            (MType::Timestamp, Micheline::Int(val)) => {
+                val.parse::<timestamp>().map(MValue::Timestamp).map_err(|_| numeric(val))
+            }
+            (MType::Timestamp, Micheline::String(val)) => timestamp_from_rfc3339(val)
+                .map(MValue::Timestamp)
+                .map_err(|_| MichelsonError::InvalidArgument((String::from("RFC3339 timestamp"), val.clone()))),
+            (MType::String, Micheline::String(val)) => Ok(MValue::String(val.clone())),
+            (MType::ChainId, Micheline::String(val)) => Ok(MValue::ChainId(val.clone())),
+            (MType::KeyHash, Micheline::String(val)) => Ok(MValue::KeyHash(val.clone())),
+            (MType::Key, Micheline::String(val)) => Ok(MValue::Key(val.clone())),
+            (MType::Signature, Micheline::String(val)) => Ok(MValue::Signature(val.clone())),
+            (MType::Address, Micheline::String(val)) => MValue::new_address(val.clone())
+                .ok_or_else(|| MichelsonError::InvalidArgument((String::from("address"), val.clone()))),
+            (MType::Bytes, Micheline::Bytes(val)) => Ok(MValue::Bytes(
+                BytesValue::from_hex(val).map_err(|_| MichelsonError::InvalidArgument((m_type.to_string(), format!("0x{}", val))))?,
+            )),
+            (MType::Option(el_type), _) if prim_args("None") == Some(&[]) => {
+                Ok(MValue::Option(OptionValue::new(None, *el_type.clone())))
+            }
+            (MType::Option(el_type), _) => match prim_args("Some") {
+                Some([val]) => Ok(MValue::Option(OptionValue::new(
+                    Some(MValue::from_micheline(val, el_type)?),
+                    *el_type.clone(),
+                ))),
+                _ => Err(invalid()),
+            },
+            (MType::Or(types, _), _) => match (prim_args("Left"), prim_args("Right")) {
+                (Some([val]), _) => Ok(MValue::Or(OrValue::new(
+                    Or::Left(MValue::from_micheline(val, &types.0)?),
+                    *types.clone(),
+                ))),
+                (_, Some([val])) => Ok(MValue::Or(OrValue::new(
+                    Or::Right(MValue::from_micheline(val, &types.1)?),
+                    *types.clone(),
+                ))),
+                _ => Err(invalid()),
+            },
+            // n-ary pairs and sequences of values are right-combed
+            (MType::Pair(types, _), _) => {
+                let els = match (prim_args("Pair"), node) {
+                    (Some(els), _) => els,
+                    (None, Micheline::Seq(els)) => els.as_slice(),
+                    _ => return Err(invalid()),
+                };
+                if els.len() < 2 {
+                    return Err(invalid());
+                }
+                let right = match &els[1..] {
+                    [right] => MValue::from_micheline(right, &types.1)?,
+                    rest => MValue::from_micheline(&Micheline::prim("Pair", rest.to_vec()), &types.1)?,
+                };
+                Ok(MValue::Pair(PairValue::new(
+                    MValue::from_micheline(&els[0], &types.0)?,
+                    right,
+                )))
+            }
+            (MType::List(el_type), Micheline::Seq(els)) => Ok(MValue::new_list(
+                els.iter()
+                    .map(|el| MValue::from_micheline(el, el_type))
+                    .collect::<Result<Vec<MValue>, MichelsonError>>()?,
+                *el_type.clone(),
+            )),
+            (MType::Set(el_type), Micheline::Seq(els)) => Ok(MValue::new_set(
+                els.iter()
+                    .map(|el| MValue::from_micheline(el, el_type))
+                    .collect::<Result<Vec<MValue>, MichelsonError>>()?,
+                *el_type.clone(),
+            )),
+            (MType::Map(types) | MType::Big_map(types), Micheline::Seq(els)) => {
+                let elements = els
+                    .iter()
+                    .map(|el| match el {
+                        Micheline::Prim { name, args, .. } if name == "Elt" && args.len() == 2 => Ok((
+                            MValue::from_micheline(&args[0], &types.0)?,
+                            MValue::from_micheline(&args[1], &types.1)?,
+                        )),
+                        _ => Err(MichelsonError::InvalidArgument((String::from("Elt"), el.to_string()))),
+                    })
+                    .collect::<Result<Vec<(MValue, MValue)>, MichelsonError>>()?;
+                let (key_type, value_type) = *types.clone();
+                match m_type {
+                    MType::Map(_) => MValue::new_map(key_type, value_type, elements),
+                    _ => MValue::new_big_map(key_type, value_type, elements),
+                }
+            }
+            (MType::Lambda(types), Micheline::Seq(_)) => Ok(MValue::Lambda(LambdaValue::new(
+                types.0.clone(),
+                types.1.clone(),
+                node.clone(),
+                false,
+            )?)),
+            (MType::Lambda(types), _) => match prim_args("Lambda_rec") {
+                Some([code @ Micheline::Seq(_)]) => Ok(MValue::Lambda(LambdaValue::new(
+                    types.0.clone(),
+                    types.1.clone(),
+                    code.clone(),
+                    true,
+                )?)),
+                _ => Err(invalid()),
+            },
+            (MType::Never | MType::Operation | MType::Contract(_) | MType::Ticket(_), _) => Err(
+                MichelsonError::Message(format!("Values of type {} cannot be built from Micheline", m_type.to_string())),
+            ),
+            _ => Err(invalid()),
+        }
+    }
+
+    /// turns the value into its Micheline representation, the reverse of `from_micheline`
+    /// timestamps are printed in the RFC3339 format, e.g. `"2022-01-01T00:00:00Z"`
+    pub fn to_micheline(&self) -> Micheline {
+        let prim = |name: &str, args: Vec<Micheline>| Micheline::prim(name, args);
+        match self {
+            MValue::Unit => prim("Unit", vec![]),
+            // `never` has no values, the placeholder is printed as is
+            MValue::Never => prim("Never", vec![]),
+            MValue::Bool(true) => prim("True", vec![]),
+            MValue::Bool(false) => prim("False", vec![]),
+            MValue::Int(val) => Micheline::Int(val.to_string()),
+            MValue::Nat(val) => Micheline::Int(val.to_string()),
+            MValue::Mutez(val) => Micheline::Int(val.to_string()),
+            // timestamps out of the range of dates are printed as numbers of seconds
+            MValue::Timestamp(val) => match DateTime::from_timestamp(*val, 0) {
+                Some(_) => Micheline::String(timestamp_to_string(*val)),
+                None => Micheline::Int(val.to_string()),
+            },
+            MValue::String(val)
+            | MValue::ChainId(val)
+            | MValue::KeyHash(val)
+            | MValue::Key(val)
+            | MValue::Signature(val)
+            | MValue::Address(val)
+            | MValue::Operation(val) => Micheline::String(val.clone()),
+            MValue::Bytes(val) => Micheline::Bytes(val.to_hex()),
+            MValue::Contract(val) => Micheline::String(val.get_address()),
+            MValue::Ticket(ticket) => prim(
+                "Pair",
+                vec![
+                    Micheline::String(ticket.ticketer.clone()),
+                    ticket.value.to_micheline(),
+                    Micheline::Int(ticket.amount.to_string()),
+                ],
+            ),
+            MValue::Option(option) => match &*option.value {
+                Some(val) => prim("Some", vec![val.to_micheline()]),
+                None => prim("None", vec![]),
+            },
+            MValue::Or(or) => match &*or.value {
+                Or::Left(val) => prim("Left", vec![val.to_micheline()]),
+                Or::Right(val) => prim("Right", vec![val.to_micheline()]),
+            },
+            MValue::Pair(pair) => prim("Pair", vec![pair.value.0.to_micheline(), pair.value.1.to_micheline()]),
+            MValue::List(list) => Micheline::Seq(list.value.iter().map(MValue::to_micheline).collect()),
+            MValue::Set(set) => Micheline::Seq(set.value.iter().map(MValue::to_micheline).collect()),
+            MValue::Map(map) | MValue::Big_map(map) => Micheline::Seq(
+                map.value
+                    .iter()
+                    .map(|(key, val)| prim("Elt", vec![key.to_micheline(), val.to_micheline()]))
+                    .collect(),
+            ),
+            MValue::Lambda(lambda) => {
+                // the applied values are pushed and paired with the parameter before the code
+                let mut code = vec![];
+                for applied in lambda.applied.iter().rev() {
+                    code.push(prim("PUSH", vec![applied.get_type().to_micheline(), applied.to_micheline()]));
+                    code.push(prim("PAIR", vec![]));
+                }
+                match &lambda.code {
+                    Micheline::Seq(nodes) => code.extend(nodes.iter().cloned()),
+                    node => code.push(node.clone()),
+                }
+                match lambda.recursive {
+                    true => prim("Lambda_rec", vec![Micheline::Seq(code)]),
+                    false => Micheline::Seq(code),
+                }
+            }
+        }
+    }
+
     /// safeguard method
     /// creates a new address value and checks that the provided value is a valid address
     pub fn new_address(val: String) -> Option<MValue> {
@@ -673,19 +1459,13 @@ impl MValue {
 
     /// creates a new empty set
     pub fn new_empty_set(t: MType) -> MValue {
-        MValue::Set(CollectionValue {
-            m_type: t,
-            value: Box::new(vec![]),
-        })
+        MValue::Set(SetValue::new(vec![], t))
     }
 
     /// creates a new set with the elements passed as arguments
+    /// the elements are sorted and the duplicates are removed
     pub fn new_set(elements: Vec<MValue>, t: MType) -> MValue {
-        // TODO: verify that the element in the set are of the same MType and no duplicate
-        MValue::Set(CollectionValue {
-            m_type: t,
-            value: Box::new(elements),
-        })
+        MValue::Set(SetValue::new(elements, t))
     }
 
     /// creates a new empty map
@@ -694,20 +1474,18 @@ impl MValue {
             is_map: true,
             key_type,
             value_type,
-            value: HashMap::new(),
+            value: BTreeMap::new(),
         })
     }
 
-    /// creates a new map with the elements passed as arguments
-    pub fn new_map(key_type: MType, value_type: MType, elements: Vec<(MValue, MValue)>) -> MValue {
-        // TODO: verify that the element in the map are of the same MType
-        // TODO: verify that the elements can be map keys
-        MValue::Map(MapValue {
-            is_map: true,
-            key_type,
-            value_type,
-            value: elements.into_iter().collect(),
-        })
+    /// creates a new map with the elements passed as arguments, the entries are sorted by key
+    /// fails if the keys are not comparable, if an entry is not of the map types or if a key is duplicated
+    pub fn new_map(
+        key_type: MType,
+        value_type: MType,
+        elements: Vec<(MValue, MValue)>,
+    ) -> Result<MValue, MichelsonError> {
+        MapValue::new(true, key_type, value_type, elements).map(MValue::Map)
     }
 
     /// creates a new empty big_map
@@ -716,24 +1494,28 @@ impl MValue {
             is_map: false,
             key_type,
             value_type,
-            value: HashMap::new(),
+            value: BTreeMap::new(),
         })
     }
 
-    /// creates a new map with the elements passed as arguments
+    /// creates a new big_map with the elements passed as arguments, the entries are sorted by key
+    /// fails if the keys are not comparable, if an entry is not of the map types or if a key is duplicated
     pub fn new_big_map(
         key_type: MType,
         value_type: MType,
         elements: Vec<(MValue, MValue)>,
-    ) -> MValue {
-        // TODO: verify that the element in the map are of the same MType
-        // TODO: verify that the elements can be map keys
-        MValue::Big_map(MapValue {
-            is_map: false,
-            key_type,
-            value_type,
-            value: elements.into_iter().collect(),
-        })
+    ) -> Result<MValue, MichelsonError> {
+        MapValue::new(false, key_type, value_type, elements).map(MValue::Big_map)
+    }
+
+    /// creates a new int value
+    pub fn new_int(val: i128) -> MValue {
+        MValue::Int(int::from(val))
+    }
+
+    /// creates a new nat value
+    pub fn new_nat(val: u128) -> MValue {
+        MValue::Nat(nat::from(val))
     }
 
     /// creates a new string value
@@ -742,8 +1524,8 @@ impl MValue {
     }
 
     /// creates a new bytes value
-    pub fn new_bytes(val: &str) -> MValue {
-        MValue::Bytes(val.to_string())
+    pub fn new_bytes(val: &[u8]) -> MValue {
+        MValue::Bytes(BytesValue::new(val))
     }
 
     /// creates a new ticket value
@@ -772,9 +1554,338 @@ mod tests {
         }
 
         // pair type
-        // match MType::from_string("pair nat int") {
-        //     Ok(res) => assert_eq!(res, MType::Pair(Box::new((MType::Nat, MType::Int)))),
-        //     Err(_) => assert!(false),
-        // }
+        match MType::from_string("pair nat int") {
+            Ok(res) => assert_eq!(res, MType::pair(MType::Nat, MType::Int)),
+            Err(_) => assert!(false),
+        }
+
+        // nested types with annotations
+        match MType::from_string("(pair %pair (option :opt nat) (map string (list int)))") {
+            Ok(res) => assert_eq!(
+                res,
+                MType::pair(
+                    MType::Option(Box::new(MType::Nat)),
+                    MType::Map(Box::new((MType::String, MType::List(Box::new(MType::Int))))),
+                )
+            ),
+            Err(_) => assert!(false),
+        }
+    }
+
+    #[test]
+    fn mtype_right_combed_pairs() {
+        let expected = MType::pair(
+            MType::Nat,
+            MType::pair(MType::String, MType::pair(MType::Bool, MType::Unit)),
+        );
+        assert_eq!(MType::from_string("pair nat string bool unit"), Ok(expected.clone()));
+        assert_eq!(MType::from_string("pair nat (pair string (pair bool unit))"), Ok(expected));
+    }
+
+    #[test]
+    fn mtype_from_json() {
+        let json = r#"{"prim":"or","args":[{"prim":"ticket","args":[{"prim":"bytes"}]},{"prim":"contract","args":[{"prim":"unit"}],"annots":["%default"]}]}"#;
+        assert_eq!(
+            MType::from_json(json),
+            Ok(MType::or(
+                MType::Ticket(Box::new((MType::Bytes, MType::Nat, MType::Address))),
+                MType::Contract(Box::new((MType::Address, MType::Unit))),
+            ))
+        );
+    }
+
+    #[test]
+    fn mtype_to_string_round_trip() {
+        let types = vec![
+            "nat",
+            "pair (option nat) (map string (list int))",
+            "or (big_map address (set key_hash)) (pair timestamp (pair mutez (contract unit)))",
+            "list (ticket (pair chain_id bytes))",
+        ];
+        for type_str in types {
+            let m_type = MType::from_string(type_str).unwrap();
+            assert_eq!(m_type.to_string(), type_str);
+            assert_eq!(MType::from_string(&m_type.to_string()), Ok(m_type));
+        }
+    }
+
+    #[test]
+    fn mtype_annotations() {
+        let node: Micheline = "pair %storage (address :owner %owner) (or (nat %add) (nat %sub))"
+            .parse()
+            .unwrap();
+        let (m_type, annots) = MType::from_micheline_annotated(&node).unwrap();
+        // annotations don't change the type
+        assert_eq!(
+            m_type,
+            MType::from_string("pair address (or nat nat)").unwrap()
+        );
+        assert_eq!(annots.field, Some(String::from("storage")));
+        let (fields, branches) = match &m_type {
+            MType::Pair(types, annots) => (annots, &types.1),
+            _ => panic!("Expected a pair type"),
+        };
+        assert_eq!(fields.0.field, Some(String::from("owner")));
+        assert_eq!(fields.0.type_name, Some(String::from("owner")));
+        let branches = branches.branch_annots().unwrap();
+        assert_eq!(branches.0.field, Some(String::from("add")));
+        assert_eq!(branches.1.field, Some(String::from("sub")));
+        assert!(MType::Nat.branch_annots().is_none());
+        assert_eq!(
+            m_type.to_micheline_annotated(&annots).to_flat_string(false),
+            "pair %storage (address :owner %owner) (or (nat %add) (nat %sub))"
+        );
+        // types built from a string keep the annotations of their branches
+        assert_eq!(
+            MType::from_string("option (or (nat %add) (nat %sub))").unwrap().to_string(),
+            "option (or (nat %add) (nat %sub))"
+        );
+    }
+
+    #[test]
+    fn mtype_annotations_right_combed_pairs() {
+        let node: Micheline = "pair (int %a) (nat %b) (string %c)".parse().unwrap();
+        let m_type = MType::from_micheline(&node).unwrap();
+        assert_eq!(m_type, MType::from_string("pair int (pair nat string)").unwrap());
+        let (fields, rest) = match &m_type {
+            MType::Pair(types, annots) => (annots, &types.1),
+            _ => panic!("Expected a pair type"),
+        };
+        assert_eq!(fields.0.field, Some(String::from("a")));
+        assert_eq!(fields.1.field, None);
+        let rest = rest.branch_annots().unwrap();
+        assert_eq!(rest.0.field, Some(String::from("b")));
+        assert_eq!(rest.1.field, Some(String::from("c")));
+    }
+
+    #[test]
+    fn mvalue_from_micheline() {
+        let m_type = MType::from_string("pair (option nat) (map string (list int)) bool").unwrap();
+        let node: Micheline = "Pair (Some 5) { Elt \"a\" { 1 ; -2 } } True".parse().unwrap();
+        assert_eq!(
+            MValue::from_micheline(&node, &m_type),
+            Ok(MValue::Pair(PairValue::new(
+                MValue::Option(OptionValue::new(Some(MValue::new_nat(5)), MType::Nat)),
+                MValue::Pair(PairValue::new(
+                    MValue::new_map(
+                        MType::String,
+                        MType::List(Box::new(MType::Int)),
+                        vec![(
+                            MValue::new_string("a"),
+                            MValue::new_list(vec![MValue::new_int(1), MValue::new_int(-2)], MType::Int)
+                        )]
+                    ).unwrap(),
+                    MValue::Bool(true)
+                ))
+            )))
+        );
+    }
+
+    #[test]
+    fn bytes_hex_round_trip() {
+        let bytes = BytesValue::from_hex("0x05ab00").unwrap();
+        assert_eq!(bytes, BytesValue::new(&[0x05, 0xab, 0x00]));
+        assert_eq!(bytes.len(), 3);
+        assert_eq!(bytes.to_string(), "0x05ab00");
+        assert_eq!(BytesValue::from_hex("05AB00"), Ok(bytes));
+        assert_eq!(
+            MValue::from_micheline(&"0x7461".parse().unwrap(), &MType::Bytes),
+            Ok(MValue::new_bytes(b"ta"))
+        );
+    }
+
+    #[test]
+    fn mvalue_comparable_ordering() {
+        let none = MValue::Option(OptionValue::new(None, MType::Nat));
+        let some = MValue::Option(OptionValue::new(Some(MValue::new_nat(0)), MType::Nat));
+        assert!(none < some);
+        let or_type = (MType::Nat, MType::Nat);
+        let left = MValue::Or(OrValue::new(Or::Left(MValue::new_nat(9)), or_type.clone()));
+        let right = MValue::Or(OrValue::new(Or::Right(MValue::new_nat(0)), or_type));
+        assert!(left < right);
+        // pairs are compared field by field
+        assert!(
+            MValue::Pair(PairValue::new(MValue::new_int(-1), MValue::new_string("b")))
+                < MValue::Pair(PairValue::new(MValue::new_int(0), MValue::new_string("a")))
+        );
+        // implicit accounts come before contracts
+        assert!(
+            MValue::Address(String::from("tz1Me1MGhK7taay748h4gPnX2cXvbgL6xsYL"))
+                < MValue::Address(String::from("KT1DrZokUnBg35YANi5sQxGfyWgDSAJRfJqY"))
+        );
+        assert!(MValue::new_bytes(&[0x01]) < MValue::new_bytes(&[0x01, 0x00]));
+        assert!(MValue::Bool(false) < MValue::Bool(true));
+        // keys and signatures are ordered by curve: ed25519 < secp256k1 < p256
+        let ed25519 = MValue::Key(String::from("edpkuBknW28nW72KG6RoHtYW7p12T6GKc7nAbwYX5m8Wd9sDVC9yav"));
+        let secp256k1 = MValue::Key(String::from("sppk7a2WEfU54qAURxD3MmLnWXaSpnodYXsGUudnrPQHX3ySfVUxQrH"));
+        let p256 = MValue::Key(String::from("p2pk66tjkEYPkyahuU8zNrFWHX2aFcRbrs1ia2Y3rp4SQ7EhcAcjkuq"));
+        assert!(ed25519 < secp256k1 && secp256k1 < p256);
+        assert!(
+            MValue::Signature(String::from("spsig1PPUFZucuAQybs5wsqsNQ68QNgFaBnVKMFaoZZfi1BtNnuCAWnmL9wVy5HfHkR6AeodjVGxpBVVSYcJKyMURn6K1yknYLm"))
+                < MValue::Signature(String::from("p2sigRmXDp38VNVaEQH28LYukfLPn8QB5hPEberhvQrrUpRscDZJrrApbRh2u46PTVTwKXjxTLKNN9dyLhPQU6U6jWPGxe4d9v"))
+        );
+        // None values of different types are neither equal nor ordered as equal
+        let none_int = MValue::Option(OptionValue::new(None, MType::Int));
+        assert_ne!(none, none_int);
+        assert_ne!(none.cmp(&none_int), Ordering::Equal);
+        assert_eq!(none.cmp(&none.clone()), Ordering::Equal);
+    }
+
+    #[test]
+    fn set_and_map_are_ordered() {
+        let set = MValue::new_set(
+            vec![MValue::new_int(3), MValue::new_int(-1), MValue::new_int(3)],
+            MType::Int,
+        );
+        match &set {
+            MValue::Set(set) => assert_eq!(
+                set.value.iter().cloned().collect::<Vec<MValue>>(),
+                vec![MValue::new_int(-1), MValue::new_int(3)]
+            ),
+            _ => assert!(false),
+        }
+        // the order of construction doesn't change the value
+        assert_eq!(set, MValue::new_set(vec![MValue::new_int(-1), MValue::new_int(3)], MType::Int));
+
+        let entries = vec![
+            (MValue::new_string("b"), MValue::new_nat(2)),
+            (MValue::new_string("a"), MValue::new_nat(1)),
+        ];
+        let map = MValue::new_map(MType::String, MType::Nat, entries.clone()).unwrap();
+        match &map {
+            MValue::Map(map) => assert_eq!(
+                map.value.keys().cloned().collect::<Vec<MValue>>(),
+                vec![MValue::new_string("a"), MValue::new_string("b")]
+            ),
+            _ => assert!(false),
+        }
+        assert_eq!(
+            map,
+            MValue::new_map(MType::String, MType::Nat, entries.into_iter().rev().collect()).unwrap()
+        );
+    }
+
+    #[test]
+    fn timestamp_rfc3339_round_trip() {
+        assert_eq!(timestamp_from_rfc3339("2022-01-01T00:00:00Z"), Ok(1_640_995_200));
+        assert_eq!(timestamp_from_rfc3339("2022-01-01T02:00:00+02:00"), Ok(1_640_995_200));
+        assert_eq!(timestamp_from_rfc3339("1969-12-31T23:59:00Z"), Ok(-60));
+        assert_eq!(timestamp_to_string(1_640_995_200), "2022-01-01T00:00:00Z");
+        assert_eq!(timestamp_to_string(-60), "1969-12-31T23:59:00Z");
+        assert_eq!(timestamp_to_string(i64::MAX), i64::MAX.to_string());
+        // timestamps can be written as strings or as numbers of seconds
+        assert_eq!(
+            MValue::from_micheline(&"\"2022-01-01T00:00:00Z\"".parse().unwrap(), &MType::Timestamp),
+            Ok(MValue::Timestamp(1_640_995_200))
+        );
+        assert_eq!(
+            MValue::from_micheline(&"-60".parse().unwrap(), &MType::Timestamp),
+            Ok(MValue::Timestamp(-60))
+        );
+    }
+
+    #[test]
+    fn mvalue_to_micheline() {
+        let m_type = MType::from_string("pair (list timestamp) (map string (option mutez)) (or unit bytes)").unwrap();
+        let node: Micheline = "Pair { 1640995200 ; \"1969-12-31T23:59:00Z\" ; 9223372036854775807 } { Elt \"a\" (Some 5) ; Elt \"b\" None } (Right 0x00ff)"
+            .parse()
+            .unwrap();
+        let value = MValue::from_micheline(&node, &m_type).unwrap();
+        // timestamps are printed as dates, unless they are out of the range of dates
+        assert_eq!(
+            value.to_micheline().to_flat_string(false),
+            "Pair { \"2022-01-01T00:00:00Z\" ; \"1969-12-31T23:59:00Z\" ; 9223372036854775807 } (Pair { Elt \"a\" (Some 5) ; Elt \"b\" None } (Right 0x00ff))"
+        );
+        assert_eq!(MValue::from_micheline(&value.to_micheline(), &m_type), Ok(value));
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid argument provided, expected `RFC3339 timestamp`, but got `2022-13-01`")]
+    fn mvalue_invalid_timestamp() {
+        match MValue::from_micheline(&"\"2022-13-01\"".parse().unwrap(), &MType::Timestamp) {
+            Ok(_) => assert!(false),
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Duplicate key \"a\" in map")]
+    fn map_duplicate_key() {
+        let entries = vec![
+            (MValue::new_string("a"), MValue::new_nat(1)),
+            (MValue::new_string("a"), MValue::new_nat(2)),
+        ];
+        match MValue::new_map(MType::String, MType::Nat, entries) {
+            Ok(_) => assert!(false),
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Expected keys of type string in big_map, but got a key of type nat")]
+    fn big_map_wrong_key_type() {
+        let entries = vec![(MValue::new_nat(1), MValue::new_nat(1))];
+        match MValue::new_big_map(MType::String, MType::Nat, entries) {
+            Ok(_) => assert!(false),
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Keys of a map must be comparable, but got list nat")]
+    fn map_non_comparable_keys() {
+        match MValue::new_map(MType::List(Box::new(MType::Nat)), MType::Nat, vec![]) {
+            Ok(_) => assert!(false),
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Unknown instruction FOO")]
+    fn mvalue_lambda_invalid_code() {
+        let m_type = MType::from_string("lambda nat nat").unwrap();
+        match MValue::from_micheline(&"{ DROP ; FOO }".parse().unwrap(), &m_type) {
+            Ok(_) => assert!(false),
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Wrong type, expected `pair` for instruction APPLY, got `nat`")]
+    fn lambda_param_type_not_a_pair() {
+        let mut lambda = LambdaValue::new(MType::Nat, MType::Nat, "{}".parse().unwrap(), false).unwrap();
+        lambda.applied.push(MValue::new_nat(1));
+        match lambda.param_type() {
+            Ok(_) => assert!(false),
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Unexpected number of arguments (1) for type `map`")]
+    fn mtype_wrong_args_number() {
+        match MType::from_string("map nat") {
+            Ok(_) => assert!(false),
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid argument provided, expected `bytes`, but got `0x123`")]
+    fn mvalue_odd_bytes() {
+        match MValue::from_micheline(&"0x123".parse().unwrap(), &MType::Bytes) {
+            Ok(_) => assert!(false),
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid argument provided, expected `nat`, but got `string`")]
+    fn mvalue_wrong_type() {
+        let m_type = MType::from_string("list nat").unwrap();
+        match MValue::from_micheline(&"{ 1 ; \"2\" }".parse().unwrap(), &m_type) {
+            Ok(_) => assert!(false),
+            Err(err) => panic!("{}", err),
+        }
     }
 }
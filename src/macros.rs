@@ -0,0 +1,651 @@
+use crate::errors::MichelsonError;
+use crate::micheline::Micheline;
+use crate::parser::SourceMap;
+
+// https://tezos.gitlab.io/active/michelson.html#macros
+
+const COMPARISONS: [&str; 6] = ["EQ", "NEQ", "LT", "GT", "LE", "GE"];
+
+/// Location in the original code of a node of the expanded code
+/// and name of the macro that generated it, if any
+#[derive(Debug, Clone)]
+struct Origin {
+    location: usize,
+    macro_name: Option<String>,
+}
+
+/// Original location and macro name of the expanded nodes, in pre-order
+type Origins = Vec<(usize, Option<String>)>;
+
+/// Node of the code being expanded, along with its origin
+#[derive(Debug, Clone)]
+struct Node {
+    origin: Origin,
+    kind: NodeKind,
+}
+
+#[derive(Debug, Clone)]
+enum NodeKind {
+    Literal(Micheline),
+    Prim {
+        name: String,
+        args: Vec<Node>,
+        annots: Vec<String>,
+    },
+    Seq(Vec<Node>),
+}
+
+/// Creates the nodes generated by a macro
+struct Generator {
+    origin: Origin,
+}
+
+impl Generator {
+    fn prim(&self, name: &str, args: Vec<Node>) -> Node {
+        Node {
+            origin: self.origin.clone(),
+            kind: NodeKind::Prim {
+                name: String::from(name),
+                args,
+                annots: vec![],
+            },
+        }
+    }
+
+    fn seq(&self, nodes: Vec<Node>) -> Node {
+        Node {
+            origin: self.origin.clone(),
+            kind: NodeKind::Seq(nodes),
+        }
+    }
+
+    fn int(&self, val: usize) -> Node {
+        Node {
+            origin: self.origin.clone(),
+            kind: NodeKind::Literal(Micheline::Int(val.to_string())),
+        }
+    }
+}
+
+/// Structure of the pairs built by the `PA*AI*R` and `UNPA*AI*R` macros
+enum PairTree {
+    Leaf,
+    Pair(Box<(PairTree, PairTree)>),
+}
+
+/// Parses the `P`, `A` and `I` letters of a pair macro
+/// left := A | pair, right := I | pair, pair := P left right
+fn parse_pair_tree(letters: &[u8], pos: &mut usize) -> Option<PairTree> {
+    if letters.get(*pos) != Some(&b'P') {
+        return None;
+    }
+    *pos += 1;
+    let left = match letters.get(*pos) {
+        Some(b'A') => {
+            *pos += 1;
+            PairTree::Leaf
+        }
+        Some(b'P') => parse_pair_tree(letters, pos)?,
+        _ => return None,
+    };
+    let right = match letters.get(*pos) {
+        Some(b'I') => {
+            *pos += 1;
+            PairTree::Leaf
+        }
+        Some(b'P') => parse_pair_tree(letters, pos)?,
+        _ => return None,
+    };
+    Some(PairTree::Pair(Box::new((left, right))))
+}
+
+/// Returns the structure of a `PA*AI*R` macro, without the final `R`
+fn pair_macro_tree(letters: &str) -> Option<PairTree> {
+    let mut pos = 0;
+    match parse_pair_tree(letters.as_bytes(), &mut pos) {
+        Some(tree) if pos == letters.len() => Some(tree),
+        _ => None,
+    }
+}
+
+/// Returns the `A` and `D` letters of a `C[AD]+R` macro
+fn cadr_letters(name: &str, prefix: &str) -> Option<String> {
+    let letters = name.strip_prefix(prefix)?.strip_suffix('R')?;
+    if !letters.is_empty() && letters.chars().all(|c| c == 'A' || c == 'D') {
+        Some(String::from(letters))
+    } else {
+        None
+    }
+}
+
+/// Returns the number of repeated letters in macros like `DUUP` or `DIIP`
+fn repeated_letters(name: &str, letter: char) -> Option<usize> {
+    let letters = name.strip_prefix('D')?.strip_suffix('P')?;
+    if letters.len() > 1 && letters.chars().all(|c| c == letter) {
+        Some(letters.len())
+    } else {
+        None
+    }
+}
+
+/// Checks the number of arguments provided to a macro
+fn check_args(name: &str, args: &[Node], expected: usize) -> Result<(), String> {
+    if args.len() == expected {
+        Ok(())
+    } else {
+        Err(format!(
+            "Unexpected number of arguments for macro {}, expected {}, got {}",
+            name,
+            expected,
+            args.len()
+        ))
+    }
+}
+
+fn pair_expansion(tree: &PairTree, gen: &Generator) -> Vec<Node> {
+    match tree {
+        PairTree::Leaf => vec![],
+        PairTree::Pair(pair) => {
+            let mut nodes = pair_expansion(&pair.0, gen);
+            if let PairTree::Pair(_) = pair.1 {
+                nodes.push(gen.prim("DIP", vec![gen.seq(pair_expansion(&pair.1, gen))]));
+            }
+            nodes.push(gen.prim("PAIR", vec![]));
+            nodes
+        }
+    }
+}
+
+fn unpair_expansion(tree: &PairTree, gen: &Generator) -> Vec<Node> {
+    match tree {
+        PairTree::Leaf => vec![],
+        PairTree::Pair(pair) => {
+            let mut nodes = vec![gen.prim("UNPAIR", vec![])];
+            if let PairTree::Pair(_) = pair.1 {
+                nodes.push(gen.prim("DIP", vec![gen.seq(unpair_expansion(&pair.1, gen))]));
+            }
+            nodes.extend(unpair_expansion(&pair.0, gen));
+            nodes
+        }
+    }
+}
+
+fn set_cadr_expansion(letters: &str, gen: &Generator) -> Vec<Node> {
+    match (letters, &letters[1..]) {
+        ("A", _) => vec![
+            gen.prim("CDR", vec![]),
+            gen.prim("SWAP", vec![]),
+            gen.prim("PAIR", vec![]),
+        ],
+        ("D", _) => vec![gen.prim("CAR", vec![]), gen.prim("PAIR", vec![])],
+        (_, rest) => {
+            let (access, other) = if letters.starts_with('A') {
+                ("CAR", "CDR")
+            } else {
+                ("CDR", "CAR")
+            };
+            let mut inner = vec![gen.prim(access, vec![])];
+            inner.extend(set_cadr_expansion(rest, gen));
+            let mut nodes = vec![
+                gen.prim("DUP", vec![]),
+                gen.prim("DIP", vec![gen.seq(inner)]),
+                gen.prim(other, vec![]),
+            ];
+            if letters.starts_with('A') {
+                nodes.push(gen.prim("SWAP", vec![]));
+            }
+            nodes.push(gen.prim("PAIR", vec![]));
+            nodes
+        }
+    }
+}
+
+fn map_cadr_expansion(letters: &str, code: Node, gen: &Generator) -> Vec<Node> {
+    match (letters, &letters[1..]) {
+        ("A", _) => vec![
+            gen.prim("DUP", vec![]),
+            gen.prim("CDR", vec![]),
+            gen.prim("DIP", vec![gen.seq(vec![gen.prim("CAR", vec![]), code])]),
+            gen.prim("SWAP", vec![]),
+            gen.prim("PAIR", vec![]),
+        ],
+        ("D", _) => vec![
+            gen.prim("DUP", vec![]),
+            gen.prim("CDR", vec![]),
+            code,
+            gen.prim("SWAP", vec![]),
+            gen.prim("CAR", vec![]),
+            gen.prim("PAIR", vec![]),
+        ],
+        (_, rest) => {
+            let (access, other) = if letters.starts_with('A') {
+                ("CAR", "CDR")
+            } else {
+                ("CDR", "CAR")
+            };
+            let mut inner = vec![gen.prim(access, vec![])];
+            inner.extend(map_cadr_expansion(rest, code, gen));
+            let mut nodes = vec![
+                gen.prim("DUP", vec![]),
+                gen.prim("DIP", vec![gen.seq(inner)]),
+                gen.prim(other, vec![]),
+            ];
+            if letters.starts_with('A') {
+                nodes.push(gen.prim("SWAP", vec![]));
+            }
+            nodes.push(gen.prim("PAIR", vec![]));
+            nodes
+        }
+    }
+}
+
+/// Expands the macro with the provided name
+/// Returns `None` if the name is not a macro
+fn expand_macro(
+    name: &str,
+    args: Vec<Node>,
+    gen: &Generator,
+) -> Option<Result<Vec<Node>, String>> {
+    let fail = || gen.prim("FAIL", vec![]);
+    let branches = |args: Vec<Node>| -> Result<(Node, Node), String> {
+        check_args(name, &args, 2)?;
+        let mut args = args.into_iter();
+        match (args.next(), args.next()) {
+            (Some(left), Some(right)) => Ok((left, right)),
+            _ => Err(format!("Missing branches for macro {}", name)),
+        }
+    };
+
+    let expansion = if name == "FAIL" {
+        check_args(name, &args, 0)
+            .map(|_| vec![gen.prim("UNIT", vec![]), gen.prim("FAILWITH", vec![])])
+    } else if name == "ASSERT" {
+        check_args(name, &args, 0).map(|_| {
+            vec![gen.prim("IF", vec![gen.seq(vec![]), gen.seq(vec![fail()])])]
+        })
+    } else if let Some(op) = name.strip_prefix("CMP").filter(|op| COMPARISONS.contains(op)) {
+        check_args(name, &args, 0)
+            .map(|_| vec![gen.prim("COMPARE", vec![]), gen.prim(op, vec![])])
+    } else if let Some(op) = name.strip_prefix("IFCMP").filter(|op| COMPARISONS.contains(op)) {
+        branches(args).map(|(left, right)| {
+            vec![
+                gen.prim("COMPARE", vec![]),
+                gen.prim(op, vec![]),
+                gen.prim("IF", vec![left, right]),
+            ]
+        })
+    } else if let Some(op) = name.strip_prefix("IF").filter(|op| COMPARISONS.contains(op)) {
+        branches(args)
+            .map(|(left, right)| vec![gen.prim(op, vec![]), gen.prim("IF", vec![left, right])])
+    } else if let Some(op) = name.strip_prefix("ASSERT_CMP").filter(|op| COMPARISONS.contains(op)) {
+        check_args(name, &args, 0).map(|_| {
+            vec![gen.prim(
+                &format!("IFCMP{}", op),
+                vec![gen.seq(vec![]), gen.seq(vec![fail()])],
+            )]
+        })
+    } else if let Some(op) = name.strip_prefix("ASSERT_").filter(|op| COMPARISONS.contains(op)) {
+        check_args(name, &args, 0).map(|_| {
+            vec![gen.prim(
+                &format!("IF{}", op),
+                vec![gen.seq(vec![]), gen.seq(vec![fail()])],
+            )]
+        })
+    } else if name == "ASSERT_NONE" || name == "ASSERT_SOME" {
+        check_args(name, &args, 0).map(|_| {
+            let branches = if name == "ASSERT_NONE" {
+                vec![gen.seq(vec![]), gen.seq(vec![fail()])]
+            } else {
+                vec![gen.seq(vec![fail()]), gen.seq(vec![])]
+            };
+            vec![gen.prim("IF_NONE", branches)]
+        })
+    } else if name == "ASSERT_LEFT" || name == "ASSERT_RIGHT" {
+        check_args(name, &args, 0).map(|_| {
+            let branches = if name == "ASSERT_LEFT" {
+                vec![gen.seq(vec![]), gen.seq(vec![fail()])]
+            } else {
+                vec![gen.seq(vec![fail()]), gen.seq(vec![])]
+            };
+            vec![gen.prim("IF_LEFT", branches)]
+        })
+    } else if name == "IF_SOME" {
+        branches(args).map(|(left, right)| vec![gen.prim("IF_NONE", vec![right, left])])
+    } else if name == "IF_RIGHT" {
+        branches(args).map(|(left, right)| vec![gen.prim("IF_LEFT", vec![right, left])])
+    } else if let Some(n) = repeated_letters(name, 'U') {
+        check_args(name, &args, 0).map(|_| vec![gen.prim("DUP", vec![gen.int(n)])])
+    } else if let Some(n) = repeated_letters(name, 'I') {
+        check_args(name, &args, 1).map(|_| {
+            let mut args = args;
+            vec![gen.prim("DIP", vec![gen.int(n), args.remove(0)])]
+        })
+    } else if let Some(letters) = cadr_letters(name, "C").filter(|letters| letters.len() > 1) {
+        check_args(name, &args, 0).map(|_| {
+            letters
+                .chars()
+                .map(|c| gen.prim(if c == 'A' { "CAR" } else { "CDR" }, vec![]))
+                .collect()
+        })
+    } else if let Some(letters) = cadr_letters(name, "SET_C") {
+        check_args(name, &args, 0).map(|_| set_cadr_expansion(&letters, gen))
+    } else if let Some(letters) = cadr_letters(name, "MAP_C") {
+        check_args(name, &args, 1).map(|_| {
+            let mut args = args;
+            map_cadr_expansion(&letters, args.remove(0), gen)
+        })
+    } else if let Some(tree) = name
+        .strip_prefix("UN")
+        .filter(|_| name != "UNPAIR")
+        .and_then(|letters| letters.strip_suffix('R'))
+        .and_then(pair_macro_tree)
+    {
+        check_args(name, &args, 0).map(|_| unpair_expansion(&tree, gen))
+    } else if let Some(tree) = Some(name)
+        .filter(|name| *name != "PAIR")
+        .and_then(|name| name.strip_suffix('R'))
+        .and_then(pair_macro_tree)
+    {
+        check_args(name, &args, 0).map(|_| pair_expansion(&tree, gen))
+    } else {
+        return None;
+    };
+
+    Some(expansion)
+}
+
+/// Moves the annotations of a macro to the last instruction of its expansion
+fn move_annots(mut nodes: Vec<Node>, annots: Vec<String>) -> Vec<Node> {
+    if let Some(Node {
+        kind: NodeKind::Prim {
+            annots: last_annots,
+            ..
+        },
+        ..
+    }) = nodes.last_mut()
+    {
+        last_annots.extend(annots);
+    }
+    nodes
+}
+
+/// Expands the macros found in the arguments of a node
+fn expand_args(node: Node) -> Result<Node, (String, usize)> {
+    let Node { origin, kind } = node;
+    let kind = match kind {
+        NodeKind::Seq(nodes) => NodeKind::Seq(expand_seq(nodes)?),
+        NodeKind::Prim { name, args, annots } => NodeKind::Prim {
+            name,
+            args: args
+                .into_iter()
+                .map(expand_args)
+                .collect::<Result<Vec<Node>, (String, usize)>>()?,
+            annots,
+        },
+        literal => literal,
+    };
+    Ok(Node { origin, kind })
+}
+
+/// Expands the macros in a sequence of instructions
+/// The expansion of a macro is spliced into the sequence
+fn expand_seq(nodes: Vec<Node>) -> Result<Vec<Node>, (String, usize)> {
+    let mut expanded = vec![];
+    for node in nodes {
+        match node {
+            Node {
+                origin,
+                kind: NodeKind::Prim { name, args, annots },
+            } => {
+                // nodes generated by a macro are attributed to the macro written in the code
+                let gen = Generator {
+                    origin: match origin.macro_name {
+                        Some(_) => origin.clone(),
+                        None => Origin {
+                            location: origin.location,
+                            macro_name: Some(name.clone()),
+                        },
+                    },
+                };
+                match expand_macro(&name, args.clone(), &gen) {
+                    None => expanded.push(expand_args(Node {
+                        origin,
+                        kind: NodeKind::Prim { name, args, annots },
+                    })?),
+                    Some(Err(err)) => return Err((err, origin.location)),
+                    // the expansion may include other macros
+                    Some(Ok(nodes)) => expanded.extend(expand_seq(move_annots(nodes, annots))?),
+                }
+            }
+            node => expanded.push(expand_args(node)?),
+        }
+    }
+    Ok(expanded)
+}
+
+/// Attaches its location to every node of the parsed code, in pre-order
+fn locate(code: &Micheline, location: &mut usize) -> Node {
+    let origin = Origin {
+        location: *location,
+        macro_name: None,
+    };
+    *location += 1;
+    let kind = match code {
+        Micheline::Prim { name, args, annots } => NodeKind::Prim {
+            name: name.clone(),
+            args: args.iter().map(|arg| locate(arg, location)).collect(),
+            annots: annots.clone(),
+        },
+        Micheline::Seq(nodes) => {
+            NodeKind::Seq(nodes.iter().map(|node| locate(node, location)).collect())
+        }
+        literal => NodeKind::Literal(literal.clone()),
+    };
+    Node { origin, kind }
+}
+
+/// Turns the expanded nodes back into parsed code, collecting their origins in pre-order
+fn unlocate(node: Node, origins: &mut Origins) -> Micheline {
+    origins.push((node.origin.location, node.origin.macro_name));
+    match node.kind {
+        NodeKind::Prim { name, args, annots } => Micheline::Prim {
+            name,
+            args: args.into_iter().map(|arg| unlocate(arg, origins)).collect(),
+            annots,
+        },
+        NodeKind::Seq(nodes) => {
+            Micheline::Seq(nodes.into_iter().map(|node| unlocate(node, origins)).collect())
+        }
+        NodeKind::Literal(literal) => literal,
+    }
+}
+
+/// Expands the macros of the code
+/// Returns the expanded code and the original location and macro name of every node
+fn expand_code(
+    code: &[Micheline],
+) -> Result<(Vec<Micheline>, Origins), (String, usize)> {
+    // location 0 is the root sequence
+    let mut location = 1;
+    let nodes: Vec<Node> = code.iter().map(|node| locate(node, &mut location)).collect();
+    let mut origins = vec![(0, None)];
+    let expanded = expand_seq(nodes)?
+        .into_iter()
+        .map(|node| unlocate(node, &mut origins))
+        .collect();
+    Ok((expanded, origins))
+}
+
+/// Expands the macros of the code into core instructions
+pub fn expand(code: &[Micheline]) -> Result<Vec<Micheline>, MichelsonError> {
+    match expand_code(code) {
+        Ok((expanded, _)) => Ok(expanded),
+        Err((err, _)) => Err(MichelsonError::Message(err)),
+    }
+}
+
+/// Expands the macros of the code into core instructions
+/// The returned source map links every expanded node to the code it comes from
+pub fn expand_with_locations(
+    code: &[Micheline],
+    source_map: &SourceMap,
+) -> Result<(Vec<Micheline>, SourceMap), MichelsonError> {
+    match expand_code(code) {
+        Ok((expanded, origins)) => Ok((expanded, source_map.remap(origins))),
+        Err((err, location)) => Err(source_map.locate_error(MichelsonError::Message(err), location)),
+    }
+}
+
+/**
+ * TESTS
+ */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser;
+
+    fn expand_str(code: &str) -> Result<String, String> {
+        let code = parser::parse(String::from(code)).map_err(|(err, _)| err)?;
+        let expanded = expand(&code).map_err(|err| err.to_string())?;
+        Ok(Micheline::Seq(expanded).to_json())
+    }
+
+    fn parse_str(code: &str) -> String {
+        let code = parser::parse(String::from(code)).unwrap();
+        Micheline::Seq(code).to_json()
+    }
+
+    // PASSING
+    #[test]
+    fn expand_comparisons() {
+        assert_eq!(expand_str("CMPEQ"), Ok(parse_str("COMPARE ; EQ")));
+        assert_eq!(
+            expand_str("IFCMPLT { DROP } { SWAP }"),
+            Ok(parse_str("COMPARE ; LT ; IF { DROP } { SWAP }"))
+        );
+        assert_eq!(
+            expand_str("IFNEQ { DROP } { SWAP }"),
+            Ok(parse_str("NEQ ; IF { DROP } { SWAP }"))
+        );
+    }
+
+    #[test]
+    fn expand_assertions() {
+        assert_eq!(expand_str("FAIL"), Ok(parse_str("UNIT ; FAILWITH")));
+        assert_eq!(
+            expand_str("ASSERT"),
+            Ok(parse_str("IF {} { UNIT ; FAILWITH }"))
+        );
+        assert_eq!(
+            expand_str("ASSERT_GE"),
+            Ok(parse_str("GE ; IF {} { UNIT ; FAILWITH }"))
+        );
+        assert_eq!(
+            expand_str("ASSERT_CMPEQ"),
+            Ok(parse_str("COMPARE ; EQ ; IF {} { UNIT ; FAILWITH }"))
+        );
+        assert_eq!(
+            expand_str("ASSERT_SOME"),
+            Ok(parse_str("IF_NONE { UNIT ; FAILWITH } {}"))
+        );
+        assert_eq!(
+            expand_str("ASSERT_NONE"),
+            Ok(parse_str("IF_NONE {} { UNIT ; FAILWITH }"))
+        );
+        assert_eq!(
+            expand_str("ASSERT_RIGHT"),
+            Ok(parse_str("IF_LEFT { UNIT ; FAILWITH } {}"))
+        );
+    }
+
+    #[test]
+    fn expand_stack_macros() {
+        assert_eq!(expand_str("DUUUP"), Ok(parse_str("DUP 3")));
+        assert_eq!(expand_str("DIIP { DROP }"), Ok(parse_str("DIP 2 { DROP }")));
+        assert_eq!(
+            expand_str("IF_SOME { DROP } { UNIT }"),
+            Ok(parse_str("IF_NONE { UNIT } { DROP }"))
+        );
+    }
+
+    #[test]
+    fn expand_pair_access_macros() {
+        assert_eq!(expand_str("CADDR"), Ok(parse_str("CAR ; CDR ; CDR")));
+        assert_eq!(expand_str("SET_CAR"), Ok(parse_str("CDR ; SWAP ; PAIR")));
+        assert_eq!(expand_str("SET_CDR"), Ok(parse_str("CAR ; PAIR")));
+        assert_eq!(
+            expand_str("SET_CADR"),
+            Ok(parse_str("DUP ; DIP { CAR ; CAR ; PAIR } ; CDR ; SWAP ; PAIR"))
+        );
+        assert_eq!(
+            expand_str("MAP_CAR { PUSH nat 1 ; ADD }"),
+            Ok(parse_str(
+                "DUP ; CDR ; DIP { CAR ; { PUSH nat 1 ; ADD } } ; SWAP ; PAIR"
+            ))
+        );
+        assert_eq!(
+            expand_str("MAP_CDR { PUSH nat 1 ; ADD }"),
+            Ok(parse_str("DUP ; CDR ; { PUSH nat 1 ; ADD } ; SWAP ; CAR ; PAIR"))
+        );
+    }
+
+    #[test]
+    fn expand_pair_macros() {
+        assert_eq!(expand_str("PAIR"), Ok(parse_str("PAIR")));
+        assert_eq!(expand_str("PAPAIR"), Ok(parse_str("DIP { PAIR } ; PAIR")));
+        assert_eq!(expand_str("PPAIIR"), Ok(parse_str("PAIR ; PAIR")));
+        assert_eq!(
+            expand_str("PPAIPAIR"),
+            Ok(parse_str("PAIR ; DIP { PAIR } ; PAIR"))
+        );
+        assert_eq!(expand_str("UNPAIR"), Ok(parse_str("UNPAIR")));
+        assert_eq!(
+            expand_str("UNPAPAIR"),
+            Ok(parse_str("UNPAIR ; DIP { UNPAIR }"))
+        );
+        assert_eq!(expand_str("UNPPAIIR"), Ok(parse_str("UNPAIR ; UNPAIR")));
+    }
+
+    #[test]
+    fn expand_nested_macros_and_annotations() {
+        assert_eq!(
+            expand_str("IF { CMPGT @greater } { DUUP ; ASSERT }"),
+            Ok(parse_str(
+                "IF { COMPARE ; GT @greater } { DUP 2 ; IF {} { UNIT ; FAILWITH } }"
+            ))
+        );
+    }
+
+    #[test]
+    fn expand_with_macro_locations() {
+        let code = "PUSH nat 1 ;\nASSERT_CMPEQ ;\nDROP";
+        let (code, source_map) = parser::parse_with_locations(code).unwrap();
+        let (expanded, source_map) = expand_with_locations(&code, &source_map).unwrap();
+        // PUSH nat 1 ; COMPARE ; EQ ; IF {} { UNIT ; FAILWITH } ; DROP
+        assert_eq!(expanded.len(), 5);
+        // PUSH keeps its location
+        assert_eq!(source_map.get(1).map(|span| span.line), Some(1));
+        assert_eq!(source_map.macro_name(1), None);
+        // COMPARE comes from the macro
+        assert_eq!(source_map.get(4).map(|span| span.line), Some(2));
+        assert_eq!(source_map.macro_name(4), Some(&String::from("ASSERT_CMPEQ")));
+        // FAILWITH comes from the macro
+        assert_eq!(source_map.macro_name(10), Some(&String::from("ASSERT_CMPEQ")));
+        // DROP keeps its location
+        assert_eq!(source_map.get(11).map(|span| span.line), Some(3));
+        assert_eq!(source_map.macro_name(11), None);
+    }
+
+    // FAILING
+    #[test]
+    #[should_panic(expected = "Unexpected number of arguments for macro IFCMPEQ, expected 2, got 1 at line 2, column 1")]
+    fn expand_wrong_args_number() {
+        let code = "DROP ;\nIFCMPEQ { DROP }";
+        let (code, source_map) = parser::parse_with_locations(code).unwrap();
+        match expand_with_locations(&code, &source_map) {
+            Ok(_) => assert!(false),
+            Err(err) => panic!("{}", err),
+        }
+    }
+}
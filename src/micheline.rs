@@ -0,0 +1,325 @@
+use crate::parser;
+use serde_json::{json, Map, Value};
+use std::fmt;
+use std::str::FromStr;
+
+// https://tezos.gitlab.io/shell/micheline.html
+
+// maximum width of a line for the pretty printer
+const LINE_WIDTH: usize = 80;
+
+/// Node of a Micheline expression, used for code, types and values
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Micheline {
+    Int(String),
+    String(String),
+    // hexadecimal representation, without the 0x prefix
+    Bytes(String),
+    Prim {
+        name: String,
+        args: Vec<Micheline>,
+        annots: Vec<String>,
+    },
+    Seq(Vec<Micheline>),
+}
+
+/// Escapes a string literal for the Michelson syntax
+fn escape_string(str: &str) -> String {
+    let mut escaped = String::with_capacity(str.len() + 2);
+    escaped.push('"');
+    for c in str.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            '\u{8}' => escaped.push_str("\\b"),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+impl Micheline {
+    /// Creates a primitive without annotations
+    pub fn prim(name: &str, args: Vec<Micheline>) -> Micheline {
+        Micheline::Prim {
+            name: String::from(name),
+            args,
+            annots: vec![],
+        }
+    }
+
+    /// Returns the number of nodes in the tree, including this one
+    pub fn size(&self) -> usize {
+        match self {
+            Micheline::Prim { args, .. } => 1 + args.iter().map(|arg| arg.size()).sum::<usize>(),
+            Micheline::Seq(nodes) => 1 + nodes.iter().map(|node| node.size()).sum::<usize>(),
+            _ => 1,
+        }
+    }
+
+    /// Builds a node from its Micheline JSON representation
+    pub fn from_json_value(json: &Value) -> Result<Micheline, String> {
+        match json {
+            Value::Array(nodes) => Ok(Micheline::Seq(
+                nodes
+                    .iter()
+                    .map(Micheline::from_json_value)
+                    .collect::<Result<Vec<Micheline>, String>>()?,
+            )),
+            Value::Object(obj) => {
+                if let Some(prim) = obj.get("prim") {
+                    let name = match prim.as_str() {
+                        None => return Err(format!("Expected a string for \"prim\" field, got {}", prim)),
+                        Some(name) => String::from(name),
+                    };
+                    let args = match obj.get("args") {
+                        None => vec![],
+                        Some(Value::Array(args)) => args
+                            .iter()
+                            .map(Micheline::from_json_value)
+                            .collect::<Result<Vec<Micheline>, String>>()?,
+                        Some(args) => return Err(format!("Expected an array for \"args\" field, got {}", args)),
+                    };
+                    let annots = match obj.get("annots") {
+                        None => vec![],
+                        Some(Value::Array(annots)) => annots
+                            .iter()
+                            .map(|annot| match annot.as_str() {
+                                None => Err(format!("Expected a string for annotation, got {}", annot)),
+                                Some(annot) => Ok(String::from(annot)),
+                            })
+                            .collect::<Result<Vec<String>, String>>()?,
+                        Some(annots) => return Err(format!("Expected an array for \"annots\" field, got {}", annots)),
+                    };
+                    Ok(Micheline::Prim { name, args, annots })
+                } else {
+                    match (obj.get("int"), obj.get("string"), obj.get("bytes")) {
+                        (Some(Value::String(val)), None, None) => Ok(Micheline::Int(val.clone())),
+                        (None, Some(Value::String(val)), None) => Ok(Micheline::String(val.clone())),
+                        (None, None, Some(Value::String(val))) => Ok(Micheline::Bytes(val.clone())),
+                        _ => Err(format!("Invalid Micheline JSON object: {}", json)),
+                    }
+                }
+            }
+            _ => Err(format!("Invalid Micheline JSON value: {}", json)),
+        }
+    }
+
+    /// Parses a Micheline JSON string
+    pub fn from_json(json: &str) -> Result<Micheline, String> {
+        let json_value: Value = serde_json::from_str(json).map_err(|err| err.to_string())?;
+        Micheline::from_json_value(&json_value)
+    }
+
+    /// Turns the node into its Micheline JSON representation
+    pub fn to_json_value(&self) -> Value {
+        match self {
+            Micheline::Int(val) => json!({ "int": val }),
+            Micheline::String(val) => json!({ "string": val }),
+            Micheline::Bytes(val) => json!({ "bytes": val }),
+            Micheline::Prim { name, args, annots } => {
+                let mut obj = Map::new();
+                obj.insert(String::from("prim"), json!(name));
+                if !args.is_empty() {
+                    obj.insert(
+                        String::from("args"),
+                        Value::Array(args.iter().map(|arg| arg.to_json_value()).collect()),
+                    );
+                }
+                if !annots.is_empty() {
+                    obj.insert(String::from("annots"), json!(annots));
+                }
+                Value::Object(obj)
+            }
+            Micheline::Seq(nodes) => {
+                Value::Array(nodes.iter().map(|node| node.to_json_value()).collect())
+            }
+        }
+    }
+
+    /// Turns the node into a Micheline JSON string
+    pub fn to_json(&self) -> String {
+        self.to_json_value().to_string()
+    }
+
+    /// Prints the node on a single line
+    /// Primitives with arguments or annotations are wrapped in parentheses if `nested` is true
+    pub fn to_flat_string(&self, nested: bool) -> String {
+        match self {
+            Micheline::Int(val) => val.clone(),
+            Micheline::String(val) => escape_string(val),
+            Micheline::Bytes(val) => format!("0x{}", val),
+            Micheline::Prim { name, args, annots } => {
+                let mut parts = vec![name.clone()];
+                parts.extend(annots.iter().cloned());
+                parts.extend(args.iter().map(|arg| arg.to_flat_string(true)));
+                if nested && parts.len() > 1 {
+                    format!("({})", parts.join(" "))
+                } else {
+                    parts.join(" ")
+                }
+            }
+            Micheline::Seq(nodes) if nodes.is_empty() => String::from("{}"),
+            Micheline::Seq(nodes) => format!(
+                "{{ {} }}",
+                nodes
+                    .iter()
+                    .map(|node| node.to_flat_string(false))
+                    .collect::<Vec<String>>()
+                    .join(" ; ")
+            ),
+        }
+    }
+
+    /// Prints the node starting at the provided indentation
+    /// Nodes that don't fit on the line are broken over several lines
+    fn to_pretty_string(&self, indent: usize, nested: bool) -> String {
+        let flat = self.to_flat_string(nested);
+        if indent + flat.len() <= LINE_WIDTH {
+            return flat;
+        }
+        match self {
+            Micheline::Seq(nodes) => format!(
+                "{{ {} }}",
+                nodes
+                    .iter()
+                    .map(|node| node.to_pretty_string(indent + 2, false))
+                    .collect::<Vec<String>>()
+                    .join(&format!(" ;\n{}", " ".repeat(indent + 2)))
+            ),
+            Micheline::Prim { name, args, annots } if !args.is_empty() => {
+                // the arguments are aligned under the first one
+                let (open, close, indent) = if nested {
+                    ("(", ")", indent + 1)
+                } else {
+                    ("", "", indent)
+                };
+                let mut head = vec![name.clone()];
+                head.extend(annots.iter().cloned());
+                let head = head.join(" ");
+                let args_indent = indent + head.len() + 1;
+                format!(
+                    "{}{} {}{}",
+                    open,
+                    head,
+                    args.iter()
+                        .map(|arg| arg.to_pretty_string(args_indent, true))
+                        .collect::<Vec<String>>()
+                        .join(&format!("\n{}", " ".repeat(args_indent))),
+                    close
+                )
+            }
+            _ => flat,
+        }
+    }
+}
+
+impl FromStr for Micheline {
+    type Err = String;
+
+    /// Parses a single Micheline expression from the Michelson syntax
+    fn from_str(code: &str) -> Result<Micheline, String> {
+        parser::parse_expression(code).map_err(|err| err.to_string())
+    }
+}
+
+impl fmt::Display for Micheline {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.to_pretty_string(0, false))
+    }
+}
+
+/**
+ * TESTS
+ */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // PASSING
+    #[test]
+    fn micheline_from_str() {
+        assert_eq!(
+            "pair (int %owner) (option nat)".parse::<Micheline>(),
+            Ok(Micheline::prim(
+                "pair",
+                vec![
+                    Micheline::Prim {
+                        name: String::from("int"),
+                        args: vec![],
+                        annots: vec![String::from("%owner")]
+                    },
+                    Micheline::prim("option", vec![Micheline::prim("nat", vec![])])
+                ]
+            ))
+        );
+        assert_eq!(
+            "{ 1 ; \"tezos\" ; 0x0501 }".parse::<Micheline>(),
+            Ok(Micheline::Seq(vec![
+                Micheline::Int(String::from("1")),
+                Micheline::String(String::from("tezos")),
+                Micheline::Bytes(String::from("0501"))
+            ]))
+        );
+    }
+
+    #[test]
+    fn micheline_json_round_trip() {
+        let json = r#"[{"prim":"PUSH","args":[{"prim":"nat"},{"int":"5"}],"annots":["@five"]},{"prim":"DIP","args":[[{"prim":"DROP"}]]},{"bytes":"05"},{"string":"tezos"}]"#;
+        let micheline = Micheline::from_json(json).unwrap();
+        assert_eq!(Micheline::from_json(&micheline.to_json()), Ok(micheline.clone()));
+        assert_eq!(
+            micheline,
+            "{ PUSH @five nat 5 ; DIP { DROP } ; 0x05 ; \"tezos\" }"
+                .parse::<Micheline>()
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn micheline_pretty_print_short() {
+        let micheline: Micheline = "Pair (Some \"a\\\"b\") { Elt 1 (Left Unit) } 0x00"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            micheline.to_string(),
+            "Pair (Some \"a\\\"b\") { Elt 1 (Left Unit) } 0x00"
+        );
+        assert_eq!(micheline.to_string().parse::<Micheline>(), Ok(micheline));
+    }
+
+    #[test]
+    fn micheline_pretty_print_long() {
+        let code = "{ parameter (or (or (int %decrement) (int %increment)) (unit %reset)) ; storage int ; code { UNPAIR ; IF_LEFT { IF_LEFT { SWAP ; SUB } { ADD } } { DROP 2 ; PUSH int 0 } ; NIL operation ; PAIR } }";
+        let micheline: Micheline = code.parse().unwrap();
+        assert_eq!(
+            micheline.to_string(),
+            "{ parameter (or (or (int %decrement) (int %increment)) (unit %reset)) ;\n  storage int ;\n  code { UNPAIR ;\n         IF_LEFT { IF_LEFT { SWAP ; SUB } { ADD } } { DROP 2 ; PUSH int 0 } ;\n         NIL operation ;\n         PAIR } }"
+        );
+        assert_eq!(micheline.to_string().parse::<Micheline>(), Ok(micheline));
+    }
+
+    // FAILING
+    #[test]
+    #[should_panic(expected = "Unexpected `;` at line 1, column 10")]
+    fn micheline_from_str_multiple_expressions() {
+        match "PUSH nat ; DROP".parse::<Micheline>() {
+            Ok(_) => assert!(false),
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid Micheline JSON object")]
+    fn micheline_from_invalid_json() {
+        match Micheline::from_json(r#"{ "int": 5 }"#) {
+            Ok(_) => assert!(false),
+            Err(err) => panic!("{}", err),
+        }
+    }
+}
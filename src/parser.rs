@@ -1,558 +1,1311 @@
-use regex::Regex;
-use serde_json::{Value};
-use crate::stack::{ StackElement, Stack, StackSnapshots };
-use crate::instructions::{Instruction, RunOptions, RunOptionsContext};
+use std::fmt;
+use crate::stack::{ Stack, StackSnapshots };
+use crate::instructions::{Instruction, RunOptions};
+use crate::compiler::{self, Block, Node};
+use crate::errors::MichelsonError;
+use crate::m_types::{MType, MValue};
+use crate::micheline::Micheline;
 
-#[derive(Debug)]
-enum ValType {
-    Simple,
-    OneParam,
-    TwoParam,
-    CodeBlockParam,
-    Condition,
-    Unknown
+/// Tokens produced by the Micheline lexer
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Prim(String),
+    Int(String),
+    String(String),
+    Bytes(String),
+    Annot(String),
+    OpenBrace,
+    CloseBrace,
+    OpenParen,
+    CloseParen,
+    Semicolon,
 }
 
-#[derive(Debug)]
-pub struct ParsedCode {
-    pub value: String,
-    val_type: ValType,
-    pub args: Option<Vec<Vec<ParsedCode>>>
+impl Token {
+    fn describe(&self) -> String {
+        match self {
+            Token::Prim(prim) => format!("primitive `{}`", prim),
+            Token::Int(int) => format!("int `{}`", int),
+            Token::String(str) => format!("string \"{}\"", str),
+            Token::Bytes(bytes) => format!("bytes `0x{}`", bytes),
+            Token::Annot(annot) => format!("annotation `{}`", annot),
+            Token::OpenBrace => String::from("`{`"),
+            Token::CloseBrace => String::from("`}`"),
+            Token::OpenParen => String::from("`(`"),
+            Token::CloseParen => String::from("`)`"),
+            Token::Semicolon => String::from("`;`"),
+        }
+    }
 }
 
-#[derive(Debug)]
-struct ParsedCondition {
-    left_branch_code: String,
-    right_branch_code: String,
-    new_code: String
+/// A token with its position (byte offsets) in the source code
+#[derive(Debug, Clone)]
+struct Lexeme {
+    token: Token,
+    start: usize,
+    end: usize,
 }
 
-#[derive(Debug)]
-struct ParsedInstrWithBlock {
-    code_block: String,
-    new_code: String
+/// Location of a node or a token in the source code
+#[derive(Debug, Clone, PartialEq)]
+pub struct Span {
+    // byte offsets
+    pub start: usize,
+    pub end: usize,
+    // line and column where the span starts, starting at 1
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Span {
+    /// creates a new span from byte offsets in the provided source code
+    pub fn new(start: usize, end: usize, source: &str) -> Span {
+        let before = &source[..start];
+        let line = before.matches('\n').count() + 1;
+        let line_start = before.rfind('\n').map(|pos| pos + 1).unwrap_or(0);
+        let column = source[line_start..start].chars().count() + 1;
+
+        Span {
+            start,
+            end,
+            line,
+            column,
+        }
+    }
+
+    /// returns the line of code where the span starts with carets under the span
+    pub fn excerpt(&self, source: &str) -> String {
+        let line_text = source.lines().nth(self.line - 1).unwrap_or("");
+        let line_length = line_text.chars().count();
+        let span_length = source[self.start..self.end.max(self.start)].chars().count();
+        let carets = span_length
+            .min(line_length.saturating_sub(self.column - 1))
+            .max(1);
+        let gutter = " ".repeat(self.line.to_string().len());
+
+        format!(
+            "{} | {}\n{} | {}{}",
+            self.line,
+            line_text,
+            gutter,
+            " ".repeat(self.column - 1),
+            "^".repeat(carets)
+        )
+    }
+}
+
+impl fmt::Display for Span {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}, column {}", self.line, self.column)
+    }
+}
+
+/// Spans of the parsed nodes, indexed by their location
+/// Locations are attributed to the nodes in pre-order, starting with 0 for the root sequence
+#[derive(Debug, Clone, Default)]
+pub struct SourceMap {
+    source: String,
+    spans: Vec<Span>,
+    // name of the macro that generated the node, if any
+    macros: Vec<Option<String>>,
+}
+
+impl SourceMap {
+    fn new(source: &str, ranges: &[(usize, usize)]) -> SourceMap {
+        SourceMap {
+            source: source.to_string(),
+            spans: ranges
+                .iter()
+                .map(|(start, end)| Span::new(*start, *end, source))
+                .collect(),
+            macros: vec![None; ranges.len()],
+        }
+    }
+
+    /// builds the source map of transformed code (e.g. after macro expansion)
+    /// from the original location of every node and the macro it comes from
+    pub fn remap(&self, origins: Vec<(usize, Option<String>)>) -> SourceMap {
+        let (spans, macros) = origins
+            .into_iter()
+            .filter_map(|(location, macro_name)| {
+                self.get(location).map(|span| (span.clone(), macro_name))
+            })
+            .unzip();
+        SourceMap {
+            source: self.source.clone(),
+            spans,
+            macros,
+        }
+    }
+
+    /// returns the source map of the node at the provided location and of its children,
+    /// the node being at location 0 in the new source map
+    pub fn sub_map(&self, location: usize) -> SourceMap {
+        SourceMap {
+            source: self.source.clone(),
+            spans: self.spans.get(location..).unwrap_or_default().to_vec(),
+            macros: self.macros.get(location..).unwrap_or_default().to_vec(),
+        }
+    }
+
+    /// returns the span of the node at the provided location
+    pub fn get(&self, location: usize) -> Option<&Span> {
+        self.spans.get(location)
+    }
+
+    /// returns the name of the macro that generated the node at the provided location
+    pub fn macro_name(&self, location: usize) -> Option<&String> {
+        self.macros.get(location).and_then(|name| name.as_ref())
+    }
+
+    /// attaches the position and an excerpt of the node at the provided location to an error
+    pub fn locate_error(&self, error: MichelsonError, location: usize) -> MichelsonError {
+        match self.get(location) {
+            None => error,
+            Some(span) => MichelsonError::Located {
+                error: Box::new(error),
+                span: span.clone(),
+                excerpt: span.excerpt(&self.source),
+                macro_name: self.macro_name(location).cloned(),
+            },
+        }
+    }
+}
+
+/// Error returned when the source code cannot be parsed
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    pub message: String,
+    pub span: Span,
+    excerpt: String,
+}
+
+impl ParseError {
+    fn new(message: &str, start: usize, end: usize, source: &str) -> ParseError {
+        let span = Span::new(start, end, source);
+        let excerpt = span.excerpt(source);
+
+        ParseError {
+            message: String::from(message),
+            span,
+            excerpt,
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} at {}\n{}", self.message, self.span, self.excerpt)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Outcome of a FAILWITH instruction that aborted the execution
+#[derive(Debug, Clone)]
+pub struct Failed {
+    // value passed to FAILWITH
+    pub value: MValue,
+    // location of the FAILWITH instruction in the code after macro expansion
+    pub location: usize,
+    // stack when FAILWITH was reached, the failing value is on top
+    pub stack_at_failure: Stack,
 }
 
 #[derive(Debug)]
 pub struct RunResult {
     pub stack: Stack,
     pub stack_snapshots: Vec<Stack>,
-    pub has_failed: bool,
+    // set when the execution was aborted by FAILWITH, the stack is then empty
+    pub failed: Option<Failed>,
 }
 
-fn parse_condition(code: &str) -> Result<ParsedCondition, String> {
-    // iterates through the string to find curly braces
-    let mut opening_curly_braces = 0;
-    let mut closing_curly_braces = 0;
-    let mut in_first_branch = true;
-    let mut condition_first_branch = String::new();
-    let mut condition_second_branch = String::new();
-    let mut char_count = 0;
-    for c in code.chars() {
-        // breaks the loop when the number of curly braces is the same
-        if opening_curly_braces == closing_curly_braces && opening_curly_braces > 0 && in_first_branch == false {
-            break;
-        }
-        // keeps track of opening curly braces
-        if c == '{' {
-            opening_curly_braces += 1;
-        }
-        // keeps track of closing curly braces
-        if c == '}' {
-            closing_curly_braces += 1;
-        }
-        // copies the string into the appropriate condition branch
-        if opening_curly_braces > 0 && in_first_branch == true {
-            condition_first_branch.push(c);
-        } else if opening_curly_braces > 0 && in_first_branch == false {
-            condition_second_branch.push(c);
-        }
-        // switch to the second branch
-        if opening_curly_braces == closing_curly_braces && 
-        opening_curly_braces > 0 && 
-        in_first_branch == true {
-            in_first_branch = false;
-            opening_curly_braces = 0;
-            closing_curly_braces = 0;
-        }
-
-        char_count += 1;
+impl RunResult {
+    /// returns true if the execution was aborted by FAILWITH
+    pub fn has_failed(&self) -> bool {
+        self.failed.is_some()
+    }
+}
+
+fn is_prim_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+fn is_annot_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == '.' || c == '%' || c == '@'
+}
+
+/// Splits Michelson source code into tokens, skipping white spaces and comments
+fn tokenize(code: &str) -> Result<Vec<Lexeme>, ParseError> {
+    let chars: Vec<(usize, char)> = code.char_indices().collect();
+    // byte offset of the char at the provided index
+    let offset = |index: usize| -> usize {
+        if index < chars.len() {
+            chars[index].0
+        } else {
+            code.len()
+        }
     };
-    // println!("`{}` / `{}`", condition_first_branch, condition_second_branch);
-    // TODO: add some safeguards to avoid curly braces that are not closed
-    if condition_first_branch.len() > 0 && condition_second_branch.len() > 0 {
-        // trims and removes leading and trailing curly braces, spaces and semi-colons
-        let front_cleanup_regex = Regex::new(r"^(\s|;|\{|\})+").unwrap();
-        let back_cleanup_regex = Regex::new(r"(\s|;|\{|\})+$").unwrap();
-        // first branch
-        let first_branch = front_cleanup_regex.replace(condition_first_branch.as_str(), "");
-        let first_branch = back_cleanup_regex.replace(&first_branch, "");
-        // second branch
-        let second_branch = front_cleanup_regex.replace(condition_second_branch.as_str(), "");
-        let second_branch = back_cleanup_regex.replace(&second_branch, "");
-        // code left without the condition
-        let new_code = &code[char_count..];
-        let new_code = front_cleanup_regex.replace(&new_code, "");
-        let new_code = back_cleanup_regex.replace(&new_code, "");
-
-        Ok(ParsedCondition {
-            left_branch_code: first_branch.to_string(),
-            right_branch_code: second_branch.to_string(),
-            new_code: new_code.to_string()
-        })
-    } else {
-        Err(String::from("An error has occurred while parsing a condition block"))
+    let mut lexemes: Vec<Lexeme> = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let (start, c) = chars[i];
+        let next = if i + 1 < chars.len() { Some(chars[i + 1].1) } else { None };
+
+        if c.is_whitespace() {
+            i += 1;
+        } else if c == '#' {
+            // line comment
+            while i < chars.len() && chars[i].1 != '\n' {
+                i += 1;
+            }
+        } else if c == '/' && next == Some('*') {
+            // block comment
+            i += 2;
+            loop {
+                if i + 1 >= chars.len() {
+                    return Err(ParseError::new("Unterminated comment", start, start + 2, code));
+                } else if chars[i].1 == '*' && chars[i + 1].1 == '/' {
+                    i += 2;
+                    break;
+                } else {
+                    i += 1;
+                }
+            }
+        } else {
+            let token = match c {
+                '{' => {
+                    i += 1;
+                    Token::OpenBrace
+                }
+                '}' => {
+                    i += 1;
+                    Token::CloseBrace
+                }
+                '(' => {
+                    i += 1;
+                    Token::OpenParen
+                }
+                ')' => {
+                    i += 1;
+                    Token::CloseParen
+                }
+                ';' => {
+                    i += 1;
+                    Token::Semicolon
+                }
+                '"' => {
+                    // string literal
+                    let mut value = String::new();
+                    i += 1;
+                    loop {
+                        if i >= chars.len() {
+                            return Err(ParseError::new("Unterminated string", start, start + 1, code));
+                        }
+                        match chars[i].1 {
+                            '"' => {
+                                i += 1;
+                                break;
+                            }
+                            '\\' => {
+                                let escaped = match chars.get(i + 1).map(|(_, c)| *c) {
+                                    Some('"') => '"',
+                                    Some('\\') => '\\',
+                                    Some('n') => '\n',
+                                    Some('r') => '\r',
+                                    Some('t') => '\t',
+                                    Some('b') => '\u{8}',
+                                    _ => {
+                                        return Err(ParseError::new(
+                                            "Invalid escape sequence in string",
+                                            chars[i].0,
+                                            offset(i + 2),
+                                            code,
+                                        ))
+                                    }
+                                };
+                                value.push(escaped);
+                                i += 2;
+                            }
+                            '\n' => {
+                                return Err(ParseError::new(
+                                    "Unexpected line break in string",
+                                    chars[i].0,
+                                    offset(i + 1),
+                                    code,
+                                ))
+                            }
+                            c => {
+                                value.push(c);
+                                i += 1;
+                            }
+                        }
+                    }
+                    Token::String(value)
+                }
+                '0' if next == Some('x') => {
+                    // bytes literal
+                    i += 2;
+                    while i < chars.len() && chars[i].1.is_ascii_hexdigit() {
+                        i += 1;
+                    }
+                    Token::Bytes(code[(start + 2)..offset(i)].to_string())
+                }
+                '-' | '0'..='9' => {
+                    // int literal
+                    i += 1;
+                    while i < chars.len() && chars[i].1.is_ascii_digit() {
+                        i += 1;
+                    }
+                    let int = &code[start..offset(i)];
+                    if int == "-" {
+                        return Err(ParseError::new("Invalid number", start, offset(i), code));
+                    }
+                    Token::Int(int.to_string())
+                }
+                '@' | ':' | '%' => {
+                    // annotation
+                    i += 1;
+                    while i < chars.len() && is_annot_char(chars[i].1) {
+                        i += 1;
+                    }
+                    Token::Annot(code[start..offset(i)].to_string())
+                }
+                c if c.is_ascii_alphabetic() || c == '_' => {
+                    // primitive
+                    i += 1;
+                    while i < chars.len() && is_prim_char(chars[i].1) {
+                        i += 1;
+                    }
+                    Token::Prim(code[start..offset(i)].to_string())
+                }
+                c => {
+                    return Err(ParseError::new(
+                        &format!("Unexpected character `{}`", c),
+                        start,
+                        offset(i + 1),
+                        code,
+                    ))
+                }
+            };
+            // literals and primitives must be separated from what follows
+            if let (Token::Int(_) | Token::Bytes(_) | Token::Prim(_) | Token::Annot(_), Some((pos, c))) =
+                (&token, chars.get(i))
+            {
+                if is_prim_char(*c) {
+                    return Err(ParseError::new(
+                        &format!("Unexpected character `{}`", c),
+                        *pos,
+                        offset(i + 1),
+                        code,
+                    ));
+                }
+            }
+            lexemes.push(Lexeme {
+                token,
+                start,
+                end: offset(i),
+            });
+        }
     }
+
+    Ok(lexemes)
+}
+
+/// Recursive descent parser walking through the tokens
+struct Parser<'a> {
+    lexemes: Vec<Lexeme>,
+    index: usize,
+    source: &'a str,
+    // byte ranges of the nodes, in the order they are created (pre-order)
+    ranges: Vec<(usize, usize)>,
 }
 
-fn parse_instr_with_code(code: &str) -> Result<ParsedInstrWithBlock, String> {
-    // iterates through the string to find curly braces
-    let mut opening_curly_braces = 0;
-    let mut closing_curly_braces = 0;
-    let mut instruction_parameter = String::new();
-    let mut char_count = 0;
-    for c in code.chars() {
-        // breaks the loop when the number of curly braces is the same
-        if opening_curly_braces == closing_curly_braces && opening_curly_braces > 0 {
-            break;
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<&Token> {
+        self.lexemes.get(self.index).map(|lexeme| &lexeme.token)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.lexemes.get(self.index).map(|lexeme| lexeme.token.clone());
+        self.index += 1;
+        token
+    }
+
+    /// start of the current token, or the end of the code
+    fn position(&self) -> usize {
+        match self.lexemes.get(self.index) {
+            None => self.source.len(),
+            Some(lexeme) => lexeme.start,
         }
-        // keeps track of opening curly braces
-        if c == '{' {
-            opening_curly_braces += 1;
+    }
+
+    /// end of the last consumed token
+    fn last_end(&self) -> usize {
+        match self.index.checked_sub(1).and_then(|i| self.lexemes.get(i)) {
+            None => 0,
+            Some(lexeme) => lexeme.end,
         }
-        // keeps track of closing curly braces
-        if c == '}' {
-            closing_curly_braces += 1;
+    }
+
+    /// registers a new node starting at the provided position and returns its location
+    fn open_node(&mut self, start: usize) -> usize {
+        self.ranges.push((start, start));
+        self.ranges.len() - 1
+    }
+
+    /// sets the end of the node at the provided location
+    fn close_node(&mut self, location: usize) {
+        let end = self.last_end();
+        self.ranges[location].1 = end;
+    }
+
+    fn unexpected(&self) -> ParseError {
+        match self.lexemes.get(self.index) {
+            None => ParseError::new(
+                "Unexpected end of code",
+                self.source.len(),
+                self.source.len(),
+                self.source,
+            ),
+            Some(lexeme) => ParseError::new(
+                &format!("Unexpected {}", lexeme.token.describe()),
+                lexeme.start,
+                lexeme.end,
+                self.source,
+            ),
         }
-        // copies the string into the appropriate condition branch
-        if opening_curly_braces > 0 {
-            instruction_parameter.push(c);
+    }
+
+    fn expect(&mut self, token: Token) -> Result<(), ParseError> {
+        if self.peek() == Some(&token) {
+            self.index += 1;
+            Ok(())
+        } else {
+            let mut err = self.unexpected();
+            err.message = format!("Expected {}: {}", token.describe(), err.message);
+            Err(err)
         }
+    }
 
-        char_count += 1;
-    };
-    // println!("`{}` / `{}`", condition_first_branch, condition_second_branch);
-    // TODO: add some safeguards to avoid curly braces that are not closed
-    if instruction_parameter.len() > 0 {
-        // trims and removes leading and trailing curly braces, spaces and semi-colons
-        let front_cleanup_regex = Regex::new(r"^(\s|;|\{|\})+").unwrap();
-        let back_cleanup_regex = Regex::new(r"(\s|;|\{|\})+$").unwrap();
-        // first branch
-        let code_block = front_cleanup_regex.replace(instruction_parameter.as_str(), "");
-        let code_block = back_cleanup_regex.replace(&code_block, "");
-        // code left without the condition
-        let new_code = &code[char_count..];
-        let new_code = front_cleanup_regex.replace(&new_code, "");
-        let new_code = back_cleanup_regex.replace(&new_code, "");
-
-        Ok(ParsedInstrWithBlock {
-            code_block: code_block.to_string(),
-            new_code: new_code.to_string()
-        })
-    } else {
-        Err(String::from("An error has occurred while parsing a condition block"))
+    /// parses the annotations following a primitive
+    fn parse_annots(&mut self) -> Vec<String> {
+        let mut annots = Vec::new();
+        while let Some(Token::Annot(annot)) = self.peek() {
+            annots.push(annot.clone());
+            self.index += 1;
+        }
+        annots
     }
-}
 
-/// Parses Micheline code into AST
-pub fn parse(code: String) -> Result<Vec<ParsedCode>, (String, Vec<ParsedCode>)> {
-    // trim the code
-    let code_ = code.trim();
-    // removes all the new lines
-    let re = Regex::new(r"\s+").unwrap();
-    let formatted_code = re.replace_all(code_, " ");
-    let mut code_loop: String = formatted_code.to_string();
-    // REGEX instruction patterns
-    let simple_instr_regex = Regex::new(r"^([A-Z_]+)\s*(;|$)").unwrap();
-    let one_param_instr_regex = Regex::new(r"^([A-Z_]+)\s+([^\s]+)\s*(;|$)").unwrap();
-    let two_param_instr_regex = Regex::new(r#"^([A-Z_]+)\s+([^\s{};]+)\s+"?([^;\s]+?)"?\s*(;|$)"#).unwrap();
-    let instr_with_code_regex = Regex::new(r#"(MAP|DIP|LOOP|LOOP_LEFT|ITER)\s*\{"#).unwrap();
-    // let if_instr_regex = Regex::new(r"^(IF.*?)\s*\{(.*)\}\s*\{(.*)\}\s*(;|$)").unwrap();
-    let if_instr_regex = Regex::new(r#"^(IF_LEFT|IF_SOME|IF_NONE|IF_CONS|IF)[\s|\{]"#).unwrap();
-
-    let mut instructions: Vec<ParsedCode> = Vec::new();
-    let mut error = (false, String::new());
-
-    while code_loop.len() > 0 {
-        // checks for simple instructions
-        if simple_instr_regex.is_match(&code_loop) {
-            match simple_instr_regex.captures(&code_loop) {
-                None => panic!("An error has occured while parsing the Michelson code (simple instruction regex)"),
-                Some (m) => {
-                    match m.get(1) {
-                        None => panic!("An error has occured while parsing the Michelson code (simple instruction regex)"),
-                        Some (v) =>  {
-                            // pushes the new instruction
-                            instructions.push(ParsedCode {
-                                value: String::from(v.as_str()),
-                                val_type: ValType::Simple,
-                                args: None
-                            });
-                            // updates the code loop
-                            let new_code = code_loop.replace(m.get(0).unwrap().as_str(), "");
-                            code_loop = String::from(new_code.trim());
+    /// parses the elements of a sequence until `}` or the end of the code
+    fn parse_seq_items(&mut self, nodes: &mut Vec<Micheline>) -> Result<(), ParseError> {
+        loop {
+            match self.peek() {
+                None | Some(Token::CloseBrace) => return Ok(()),
+                _ => {
+                    nodes.push(self.parse_expr()?);
+                    match self.peek() {
+                        Some(Token::Semicolon) => self.index += 1,
+                        None | Some(Token::CloseBrace) => return Ok(()),
+                        _ => {
+                            let mut err = self.unexpected();
+                            err.message = format!("Expected `;`: {}", err.message);
+                            return Err(err);
                         }
-                    }                    
+                    }
                 }
             }
         }
-        // checks for instructions with one parameter
-        else if one_param_instr_regex.is_match(&code_loop) {
-            match one_param_instr_regex.captures(&code_loop) {
-                None => panic!("An error has occured while parsing the Michelson code (one-param instruction regex)"),
-                Some (m) => {
-                    match (m.get(1), m.get(2)) {
-                        (Some (instr), Some (param)) => {
-                            instructions.push(ParsedCode {
-                                value: String::from(instr.as_str()),
-                                val_type: ValType::OneParam,
-                                args: Some(vec!(vec!(ParsedCode {
-                                    value: String::from(param.as_str().trim()),
-                                    val_type: ValType::Simple,
-                                    args: None
-                                })))
-                            });
-                            // updates the code loop
-                            let new_code = code_loop.replace(m.get(0).unwrap().as_str(), "");
-                            code_loop = String::from(new_code.trim());
-                        },
-                        _ => panic!("An error has occured while parsing the Michelson code (one-param instruction regex)"),
-                    }                    
+    }
+
+    /// parses a sequence between curly braces
+    fn parse_seq(&mut self) -> Result<Micheline, ParseError> {
+        let location = self.open_node(self.position());
+        self.expect(Token::OpenBrace)?;
+        let mut nodes = Vec::new();
+        self.parse_seq_items(&mut nodes)?;
+        self.expect(Token::CloseBrace)?;
+        self.close_node(location);
+        Ok(Micheline::Seq(nodes))
+    }
+
+    /// parses an expression where a primitive can be applied to arguments
+    fn parse_expr(&mut self) -> Result<Micheline, ParseError> {
+        match self.peek() {
+            Some(Token::Prim(_)) => {
+                let location = self.open_node(self.position());
+                let name = match self.next() {
+                    Some(Token::Prim(name)) => name,
+                    _ => return Err(self.unexpected()),
+                };
+                let annots = self.parse_annots();
+                let mut args = Vec::new();
+                while let Some(
+                    Token::Prim(_)
+                    | Token::Int(_)
+                    | Token::String(_)
+                    | Token::Bytes(_)
+                    | Token::OpenBrace
+                    | Token::OpenParen,
+                ) = self.peek()
+                {
+                    args.push(self.parse_arg()?);
                 }
+                self.close_node(location);
+                Ok(Micheline::Prim { name, args, annots })
             }
+            _ => self.parse_arg(),
         }
-        // checks for instructions with two parameters
-        else if two_param_instr_regex.is_match(&code_loop) {
-            match two_param_instr_regex.captures(&code_loop) {
-                None => panic!("An error has occured while parsing the Michelson code (two-param instruction regex)"),
-                Some (m) => {
-                    match (m.get(1), m.get(2), m.get(3)) {
-                        (Some (instr), Some (param1), Some(param2)) => {
-                            instructions.push(ParsedCode {
-                                value: String::from(instr.as_str()),
-                                val_type: ValType::TwoParam,
-                                args: Some(vec!(
-                                    vec!(ParsedCode {
-                                    value: String::from(param1.as_str()),
-                                    val_type: ValType::Unknown,
-                                    args: None
-                                }), vec!(ParsedCode {
-                                    value: String::from(param2.as_str()),
-                                    val_type: ValType::Unknown,
-                                    args: None
-                                })))
-                            });
-                            // updates the code loop
-                            let new_code = code_loop.replace(m.get(0).unwrap().as_str(), "");
-                            code_loop = String::from(new_code.trim());
-                        },
-                        _ => panic!("An error has occured while parsing the Michelson code (two-param instruction regex)"),
-                    }                    
-                }
+    }
+
+    /// parses the argument of a primitive
+    /// primitives with arguments must be wrapped in parentheses
+    fn parse_arg(&mut self) -> Result<Micheline, ParseError> {
+        match self.peek() {
+            Some(Token::OpenBrace) => self.parse_seq(),
+            Some(Token::OpenParen) => {
+                self.index += 1;
+                let expr = self.parse_expr()?;
+                self.expect(Token::CloseParen)?;
+                Ok(expr)
+            }
+            _ => {
+                let location = self.open_node(self.position());
+                let node = match self.next() {
+                    Some(Token::Int(val)) => Micheline::Int(val),
+                    Some(Token::String(val)) => Micheline::String(val),
+                    Some(Token::Bytes(val)) => Micheline::Bytes(val),
+                    Some(Token::Prim(name)) => Micheline::Prim {
+                        name,
+                        args: vec![],
+                        annots: vec![],
+                    },
+                    _ => {
+                        self.index -= 1;
+                        return Err(self.unexpected());
+                    }
+                };
+                self.close_node(location);
+                Ok(node)
             }
         }
-        // checks for instructions with code block as parameter
-        else if instr_with_code_regex.is_match(&code_loop) {
-            // gets the current instruction
-            let instruction = match instr_with_code_regex.captures(&code_loop) {
-                None => panic!("An error has occured while parsing the Michelson code (instruction with code block regex capture)"),
-                Some (m) => m.get(1).unwrap().as_str()
-            };
-            // parses the code in the instruction block
-            // println!("\ninstruction: {}, code loop: {}", instruction, code_loop);
-            match parse_instr_with_code(&code) {
-                Err(err) => panic!("{}", err),
-                Ok(res) => {
-                    let code_block = parse(res.code_block);
-                    let instrs: Vec<ParsedCode> = 
-                        match code_block {
-                            Ok(set) => set,
-                            Err(err) => panic!("{}", err.0)
-                        };
-
-                    instructions.push(ParsedCode {
-                        value: String::from(instruction),
-                        val_type: ValType::CodeBlockParam,
-                        args: Some(vec!(
-                            instrs
-                        ))
-                    });
-                    // cleans up the string
-                    code_loop = String::from(res.new_code.trim());
+    }
+}
+
+/// Parses Micheline code and keeps track of the location of every node
+/// The first instruction is at location 1, location 0 being the whole sequence
+fn parse_code(code: &str) -> Result<(Vec<Micheline>, SourceMap), (ParseError, Vec<Micheline>)> {
+    let lexemes = tokenize(code).map_err(|err| (err, vec![]))?;
+    let mut parser = Parser {
+        lexemes,
+        index: 0,
+        source: code,
+        ranges: vec![],
+    };
+    // the root sequence
+    parser.open_node(0);
+    let mut instructions: Vec<Micheline> = Vec::new();
+
+    match parser.parse_seq_items(&mut instructions) {
+        Err(err) => Err((err, instructions)),
+        Ok(_) => {
+            if parser.peek().is_some() {
+                // a closing brace was found without a matching opening brace
+                Err((parser.unexpected(), instructions))
+            } else {
+                parser.ranges[0].1 = code.len();
+                match (instructions.len(), instructions.first()) {
+                    // unwraps the code surrounded by curly braces
+                    (1, Some(Micheline::Seq(nodes))) => {
+                        // the sequence becomes the root node
+                        parser.ranges.remove(0);
+                        Ok((nodes.clone(), SourceMap::new(code, &parser.ranges)))
+                    }
+                    _ => Ok((instructions, SourceMap::new(code, &parser.ranges))),
                 }
             }
         }
-        // checks for condition instructions
-        else if if_instr_regex.is_match(&code_loop) {
-            // gets the current instruction
-            let instruction = match if_instr_regex.captures(&code_loop) {
-                None => panic!("An error has occured while parsing the Michelson code (if instruction regex capture)"),
-                Some (m) => m.get(1).unwrap().as_str()
-            };
-            // parses the code in the condition block
-            println!("\ncode loop: {}", code_loop);
-            match parse_condition(&code_loop) {
-                Err(err) => panic!("{}", err),
-                Ok(res) => {
-                    let sub_instr1 = parse(res.left_branch_code);
-                    let sub_instr2 = parse(res.right_branch_code);
-                    let instrs: (Vec<ParsedCode>, Vec<ParsedCode>) = 
-                        match (sub_instr1, sub_instr2) {
-                            (Ok(set1), Ok(set2)) => (set1, set2),
-                            (Ok(_), Err(err)) => panic!("{}", err.0),
-                            (Err(err), Ok(_)) => panic!("{}", err.0),
-                            (Err(err1), Err(err2)) => panic!("{}/{}", err1.0, err2.0)
-                        };
-
-                    instructions.push(ParsedCode {
-                        value: String::from(instruction),
-                        val_type: ValType::Condition,
-                        args: Some(vec!(
-                            instrs.0, 
-                            instrs.1
-                        ))
-                    });
-                    // cleans up the string
-                    println!("\n parsed code: `{}` \n new code: `{}`", code_loop, res.new_code);
-                    code_loop = String::from(res.new_code.trim());
-                }
+    }
+}
+
+/// Parses Micheline code into AST
+/// The code can be a sequence of instructions with or without surrounding curly braces
+pub fn parse(code: String) -> Result<Vec<Micheline>, (String, Vec<Micheline>)> {
+    match parse_code(&code) {
+        Ok((instructions, _)) => Ok(instructions),
+        Err((err, instructions)) => Err((err.to_string(), instructions)),
+    }
+}
+
+/// Parses a single Micheline expression, e.g. a type or a value
+pub fn parse_expression(code: &str) -> Result<Micheline, ParseError> {
+    let lexemes = tokenize(code)?;
+    let mut parser = Parser {
+        lexemes,
+        index: 0,
+        source: code,
+        ranges: vec![],
+    };
+    let expression = parser.parse_expr()?;
+    match parser.peek() {
+        None => Ok(expression),
+        Some(_) => Err(parser.unexpected()),
+    }
+}
+
+/// Parses Micheline code into AST along with the source map of the parsed nodes
+pub fn parse_with_locations(code: &str) -> Result<(Vec<Micheline>, SourceMap), ParseError> {
+    parse_code(code).map_err(|(err, _)| err)
+}
+
+/// runs a single instruction
+/// a failure inside the instruction (e.g. FAILWITH in a branch of IF) is returned in the result
+fn run_instruction(
+    node: &Node,
+    stack: Stack,
+    mut stack_snapshots: StackSnapshots,
+    options: &RunOptions,
+) -> Result<RunResult, MichelsonError> {
+    match &node.instruction {
+        &Instruction::FAILWITH => {
+            // aborts the execution of the contract
+            // the trait is imported here as its methods clash with the parser's vectors
+            use crate::stack::StackFuncs;
+            stack.check_depth(options.pos + 1, Instruction::FAILWITH)?;
+            // gets the value on top of the stack
+            let value = stack[options.pos].value.clone();
+            // updates the stack snapshots
+            stack_snapshots.push(stack.clone());
+            Ok(RunResult {
+                stack: vec![],
+                stack_snapshots,
+                failed: Some(Failed {
+                    value,
+                    location: node.location,
+                    stack_at_failure: stack,
+                }),
+            })
+        }
+        instruction => {
+            let mut result = instruction.run(&node.args, stack, stack_snapshots, options)?;
+            if !result.has_failed() {
+                annotate(node, &mut result.stack, options.pos);
             }
+            Ok(result)
         }
-        /* else if if_instr_regex.is_match(&code_loop) {
-            match if_instr_regex.captures(&code_loop) {
-                None => panic!("An error has occured while parsing the Michelson code (if instruction regex capture)"),
-                Some (m) => {
-                    match (m.get(1), m.get(2), m.get(3)) {
-                        (Some (instr), Some (param1), Some(param2)) => {
-                            // parse subfields
-                            let sub_instr1 = parse(String::from(param1.as_str()));
-                            let sub_instr2 = parse(String::from(param2.as_str()));
-                            let instrs: (Vec<ParsedCode>, Vec<ParsedCode>) = 
-                                match (sub_instr1, sub_instr2) {
-                                    (Ok(set1), Ok(set2)) => (set1, set2),
-                                    (Ok(_), Err(err)) => panic!("{}", err.0),
-                                    (Err(err), Ok(_)) => panic!("{}", err.0),
-                                    (Err(err1), Err(err2)) => panic!("{}/{}", err1.0, err2.0)
-                                };
+    }
+}
 
-                            instructions.push(ParsedCode {
-                                value: String::from(instr.as_str()),
-                                val_type: ValType::Condition,
-                                args: Some(vec!(
-                                    instrs.0, 
-                                    instrs.1
-                                ))
-                            });
-                            // updates the code loop
-                            let new_code = code_loop.replace(m.get(0).unwrap().as_str(), "");
-                            code_loop = String::from(new_code.trim());
-                        },
-                        _ => panic!("An error has occured while parsing the Michelson code (if instruction regex parsing)"),
-                    }                    
+/// applies the annotations of an instruction to the element it left on top of the stack
+/// `@var` names the element, `:type` names its type and `%field` names the fields of a pair
+/// UNPAIR leaves two elements on the stack, its two variable annotations name them in order
+/// field annotations of CAR, CDR and UNPAIR are ignored, the fields keep the annotations of the pair type
+/// special annotations like `@%` or `%@` are ignored
+fn annotate(node: &Node, stack: &mut Stack, pos: usize) {
+    let mut var_index = 0;
+    let mut field_index = 0;
+    for annot in &node.annots {
+        // malformed annotations, e.g. empty ones, have no prefix and are ignored
+        let mut chars = annot.chars();
+        let prefix = chars.next();
+        let name = match chars.as_str() {
+            "" => None,
+            name if name.starts_with(['@', '%']) => None,
+            name => Some(String::from(name)),
+        };
+        match prefix {
+            Some('@') => {
+                let offset = match node.instruction {
+                    Instruction::UNPAIR => var_index,
+                    _ => 0,
+                };
+                if let (Some(el), Some(_)) = (stack.get_mut(pos + offset), &name) {
+                    el.annot = name;
+                }
+                var_index += 1;
+            }
+            Some(':') if name.is_some() => {
+                if let Some(el) = stack.get_mut(pos) {
+                    el.type_annots.type_name = name;
+                }
+            }
+            Some('%') if node.instruction == Instruction::PAIR => {
+                // the field annotations follow the order of the fields, even when left empty
+                if let (Some(el), Some(_)) = (stack.get_mut(pos), &name) {
+                    let value = &el.value;
+                    if let MType::Pair(_, annots) = el.m_type.get_or_insert_with(|| value.get_type()) {
+                        match field_index {
+                            0 => annots.0.field = name,
+                            _ => annots.1.field = name,
+                        }
+                    }
                 }
+                field_index += 1;
             }
-        } */
-        else {
-            error = (true, String::from("The code didn't match against the patterns"));
-            break;
+            _ => (),
         }
     }
+}
 
-    if error.0 {
-        Err((error.1, instructions))
-    } else {
-        Ok(instructions)
+/// runs a block of compiled instructions, stops at the first instruction that fails
+/// used for the code passed as argument to an instruction (e.g. the branches of IF)
+/// the options of the calling instruction are passed down so the block runs in the same context
+pub fn run_block(
+    block: &Block,
+    mut stack: Stack,
+    mut stack_snapshots: StackSnapshots,
+    options: &RunOptions,
+) -> Result<RunResult, MichelsonError> {
+    for node in block {
+        let result = run_instruction(node, stack, stack_snapshots, options)?;
+        if result.has_failed() {
+            // breaks from the contract execution loop
+            return Ok(result);
+        }
+        stack = result.stack;
+        stack_snapshots = result.stack_snapshots;
     }
+
+    Ok(RunResult {
+        stack,
+        stack_snapshots,
+        failed: None,
+    })
 }
 
-/// Parses AST into JSON
-pub fn to_json(ast: &Vec<ParsedCode>) -> Result<String, String> {
-    if ast.len() > 0 {
-        // loops through the different instructions
-        let mut json: String = String::from("[");
-
-        for (index, instruction) in ast.iter().enumerate() {
-            match instruction.val_type {
-                ValType::Simple => {
-                    json.push_str(format!(r#"{{"prim": "{}"}}"#, instruction.value).as_str());
-                    // adds a comma separator
-                    if index != ast.len() - 1 {
-                        json.push_str(", ")
-                    }
-                },
-                ValType::OneParam => {
-                    let args: &ParsedCode = 
-                        match &instruction.args {
-                            None => panic!("Unexpected None value for args"),
-                            Some (args_) =>
-                                if args_.len() != 1 {
-                                    panic!("Unexpected length of vector for instruction arguments")
-                                } else {
-                                    &args_[0][0]
-                                }
-                        };
-                    let formatted_args: String =
-                        match args.value.parse::<f64>() {
-                            Ok (v) => format!(r#"{{"int": "{}"}}"#, v),
-                            Err (_) => format!(r#"{{"prim": "{}"}}"#, args.value)
-                        };
-                    json.push_str(format!(
-                        r#"{{"prim": "{}", "args": [{}]}}"#, 
-                        instruction.value, 
-                        formatted_args).as_str());
-                    // adds a comma separator
-                    if index != ast.len() - 1 {
-                        json.push_str(", ")
-                    }
-                },
-                ValType::TwoParam => {
-                    let args: (&ParsedCode, &ParsedCode) = 
-                        match &instruction.args {
-                            None => panic!("Unexpected None value for args"),
-                            Some (args_) =>
-                                if args_.len() != 2 {
-                                    panic!("Unexpected length of vector for instruction arguments")
-                                } else {
-                                    (&args_[0][0], &args_[1][0])
-                                }
-                        };
-                    let val_type = 
-                        match args.1.value.parse::<f64>() {
-                            Ok (_) => String::from("int"),
-                            Err (_) => String::from("string")
-                        };
-                    json.push_str(format!(
-                        r#"{{"prim": "{}", "args": [{{"prim": "{}"}}, {{"{}": "{}"}} ]}}"#, 
-                        instruction.value, 
-                        args.0.value,
-                        val_type,
-                        args.1.value
-                    ).as_str());
-                    // adds a comma separator
-                    if index != ast.len() - 1 {
-                        json.push_str(", ")
-                    }
-                },
-                ValType::CodeBlockParam => {
-                    let args: String = 
-                        match &instruction.args {
-                            None => panic!("Unexpected None value for args"),
-                            Some (args_) =>
-                                if args_.len() != 1 {
-                                    panic!("Unexpected length of vector for instruction arguments")
-                                } else {
-                                    match to_json(&args_[0]) {
-                                        Ok(json) => json,
-                                        Err(err) => panic!("{}", err),
-                                    }
-                                }
-                        };
-                    json.push_str(format!(
-                        r#"{{"prim": "{}", "args": [{}]}}"#,
-                        instruction.value,
-                        args
-                    ).as_str());
-                    // adds a comma separator
-                    if index != ast.len() - 1 {
-                        json.push_str(", ")
-                    }
-                },
-                ValType::Condition => {
-                    let args: (String, String) = 
-                        match &instruction.args {
-                            None => panic!("Unexpected None value for args"),
-                            Some (args_) =>
-                                if args_.len() != 2 {
-                                    panic!("Unexpected length of vector for instruction arguments")
-                                } else {
-                                    let args_left = &args_[0];
-                                    let args_right = &args_[1];
-                                    match (to_json(args_left), to_json(args_right)) {
-                                        (Ok(json1), Ok(json2)) => (json1, json2),
-                                        (Ok(_), Err(err)) => panic!("{}", err),
-                                        (Err(err), Ok(_)) => panic!("{}", err),
-                                        (Err(err1), Err(err2)) => panic!("{}/{}", err1, err2),
-                                    }
+/// runs JSON Michelson code provided a parameter value and a storage
+/// the context of the call (amount, sender, level, etc.) is provided in the options
+pub fn run(
+    json: &str,
+    stack: Stack,
+    stack_snapshots: StackSnapshots,
+    options: &RunOptions,
+) -> Result<RunResult, MichelsonError> {
+    match Micheline::from_json(json).map_err(MichelsonError::InvalidJson)? {
+        Micheline::Seq(code) => run_block(&compiler::compile(&code)?, stack, stack_snapshots, options),
+        _ => Err(MichelsonError::InvalidJson(String::from(
+            "Unexpected type output for JSON value, expected an array",
+        ))),
+    }
+}
+
+/// runs parsed Michelson code provided a parameter value and a storage
+/// errors report the position in the source code of the instruction that failed
+pub fn run_code(
+    code: &[Micheline],
+    source_map: &SourceMap,
+    stack: Stack,
+    stack_snapshots: StackSnapshots,
+    options: &RunOptions,
+) -> Result<RunResult, MichelsonError> {
+    let (block, source_map) = compiler::compile_with_locations(code, source_map)?;
+    run_block_with_locations(&block, &source_map, stack, stack_snapshots, options)
+}
+
+/// runs a block of compiled instructions, stops at the first instruction that fails
+/// errors report the position in the source code of the instruction that caused them
+pub fn run_block_with_locations(
+    block: &Block,
+    source_map: &SourceMap,
+    mut stack: Stack,
+    mut stack_snapshots: StackSnapshots,
+    options: &RunOptions,
+) -> Result<RunResult, MichelsonError> {
+    for node in block {
+        let result = run_instruction(node, stack, stack_snapshots, options)
+            .map_err(|err| source_map.locate_error(err, node.location))?;
+        if result.has_failed() {
+            return Ok(result);
+        }
+        stack = result.stack;
+        stack_snapshots = result.stack_snapshots;
+    }
+
+    Ok(RunResult {
+        stack,
+        stack_snapshots,
+        failed: None,
+    })
+}
+
+/**
+ * TESTS
+ */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::m_types::PairValue;
+
+
+    fn prim(name: &str, args: Vec<Micheline>) -> Micheline {
+        Micheline::Prim {
+            name: String::from(name),
+            args,
+            annots: vec![],
+        }
+    }
+
+    // PASSING
+    #[test]
+    fn parse_simple_instructions() {
+        match parse(String::from("UNPAIR ; DROP 2 ; PUSH string \"test\" ;")) {
+            Err((err, _)) => panic!("{}", err),
+            Ok(code) => {
+                assert_eq!(code.len(), 3);
+                assert_eq!(code[0], prim("UNPAIR", vec![]));
+                assert_eq!(
+                    code[1],
+                    prim("DROP", vec![Micheline::Int(String::from("2"))])
+                );
+                assert_eq!(
+                    code[2],
+                    prim(
+                        "PUSH",
+                        vec![
+                            prim("string", vec![]),
+                            Micheline::String(String::from("test"))
+                        ]
+                    )
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn parse_nested_sequences() {
+        let code = r#"{ IF_LEFT { IF_LEFT { SWAP ; SUB } { ADD } } { DROP 2 ; PUSH int 0 } ; NIL operation ; PAIR }"#;
+        match parse(String::from(code)) {
+            Err((err, _)) => panic!("{}", err),
+            Ok(code) => {
+                assert_eq!(code.len(), 3);
+                assert_eq!(
+                    code[0],
+                    prim(
+                        "IF_LEFT",
+                        vec![
+                            Micheline::Seq(vec![prim(
+                                "IF_LEFT",
+                                vec![
+                                    Micheline::Seq(vec![prim("SWAP", vec![]), prim("SUB", vec![])]),
+                                    Micheline::Seq(vec![prim("ADD", vec![])])
+                                ]
+                            )]),
+                            Micheline::Seq(vec![
+                                prim("DROP", vec![Micheline::Int(String::from("2"))]),
+                                prim(
+                                    "PUSH",
+                                    vec![prim("int", vec![]), Micheline::Int(String::from("0"))]
+                                )
+                            ])
+                        ]
+                    )
+                );
+                assert_eq!(code[1], prim("NIL", vec![prim("operation", vec![])]));
+                assert_eq!(code[2], prim("PAIR", vec![]));
+            }
+        }
+    }
+
+    #[test]
+    fn parse_complex_type_arguments() {
+        match parse(String::from("NIL (pair nat (option %opt string)) ; EMPTY_MAP string (list int)")) {
+            Err((err, _)) => panic!("{}", err),
+            Ok(code) => {
+                assert_eq!(
+                    code[0],
+                    prim(
+                        "NIL",
+                        vec![prim(
+                            "pair",
+                            vec![
+                                prim("nat", vec![]),
+                                Micheline::Prim {
+                                    name: String::from("option"),
+                                    args: vec![prim("string", vec![])],
+                                    annots: vec![String::from("%opt")]
                                 }
-                        };
-                    json.push_str(format!(
-                        r#"{{"prim": "{}", "args": [{}, {}]}}"#,
-                        instruction.value,
-                        args.0,
-                        args.1
-                    ).as_str());
-                    // adds a comma separator
-                    if index != ast.len() - 1 {
-                        json.push_str(", ")
-                    }
-                },
-                ValType::Unknown => ()
+                            ]
+                        )]
+                    )
+                );
+                assert_eq!(
+                    code[1],
+                    prim(
+                        "EMPTY_MAP",
+                        vec![prim("string", vec![]), prim("list", vec![prim("int", vec![])])]
+                    )
+                );
             }
         }
+    }
 
-        json.push_str("]");
+    #[test]
+    fn parse_strings_with_special_chars_and_comments() {
+        let code = r#"
+            # pushes a tricky string
+            PUSH string "a;b}c{\"d\"" ; /* multi-line
+            comment */ PUSH bytes 0x05ab ;
+            PUSH int -42
+        "#;
+        match parse(String::from(code)) {
+            Err((err, _)) => panic!("{}", err),
+            Ok(code) => {
+                assert_eq!(code.len(), 3);
+                assert_eq!(
+                    code[0],
+                    prim(
+                        "PUSH",
+                        vec![
+                            prim("string", vec![]),
+                            Micheline::String(String::from("a;b}c{\"d\""))
+                        ]
+                    )
+                );
+                assert_eq!(
+                    code[1],
+                    prim(
+                        "PUSH",
+                        vec![prim("bytes", vec![]), Micheline::Bytes(String::from("05ab"))]
+                    )
+                );
+                assert_eq!(
+                    code[2],
+                    prim(
+                        "PUSH",
+                        vec![prim("int", vec![]), Micheline::Int(String::from("-42"))]
+                    )
+                );
+            }
+        }
+    }
 
-        Ok(json)
-    } else {
-        Err(String::from("Vector is empty"))
+    #[test]
+    fn parse_annotations_and_to_json() {
+        match parse(String::from("CAR @first ; PAIR %a %b")) {
+            Err((err, _)) => panic!("{}", err),
+            Ok(code) => {
+                assert_eq!(
+                    Micheline::Seq(code).to_json(),
+                    r#"[{"annots":["@first"],"prim":"CAR"},{"annots":["%a","%b"],"prim":"PAIR"}]"#
+                );
+            }
+        }
     }
-}
 
-/// runs JSON Michelson code provided a parameter value and a storage
-pub fn run(json: &str, mut stack: Stack, mut stack_snapshots: StackSnapshots) -> Result<RunResult, String> {    
-    // sets default options
-    // TODO: it may be better to pass these options as a parameter
-    let options = RunOptions {
-        context: RunOptionsContext::mock(), 
-        pos: 0
-    };
-    // loops through the JSON value
-    let json_array: Value = 
-        match serde_json::from_str(json) {
-            Ok (val) => val,
-            Err (err) => panic!("{:?}", err)
+    #[test]
+    fn parse_with_locations_spans() {
+        let code = "UNPAIR ;\nIF { DROP 2 }\n   { PUSH nat 3 }";
+        match parse_with_locations(code) {
+            Err(err) => panic!("{}", err),
+            Ok((code, source_map)) => {
+                assert_eq!(code.len(), 2);
+                // UNPAIR
+                assert_eq!(
+                    source_map.get(1),
+                    Some(&Span { start: 0, end: 6, line: 1, column: 1 })
+                );
+                // IF
+                assert_eq!(
+                    source_map.get(2),
+                    Some(&Span { start: 9, end: 40, line: 2, column: 1 })
+                );
+                // DROP 2
+                assert_eq!(
+                    source_map.get(4),
+                    Some(&Span { start: 14, end: 20, line: 2, column: 6 })
+                );
+                // PUSH nat 3
+                assert_eq!(
+                    source_map.get(7),
+                    Some(&Span { start: 28, end: 38, line: 3, column: 6 })
+                );
+                assert_eq!(source_map.get(8).map(|span| span.line), Some(3));
+            }
+        }
+    }
+
+    #[test]
+    fn parse_error_excerpt() {
+        match parse_with_locations("DROP ;\nPUSH nat 3 )") {
+            Ok(_) => assert!(false),
+            Err(err) => {
+                assert_eq!(err.span.line, 2);
+                assert_eq!(err.span.column, 12);
+                assert_eq!(
+                    err.to_string(),
+                    "Expected `;`: Unexpected `)` at line 2, column 12\n2 | PUSH nat 3 )\n  |            ^"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn run_code_error_location() {
+        let code = "PUSH nat 3 ;\n  GET 1 ;\n  DROP";
+        let (code, source_map) = parse_with_locations(code).unwrap();
+        match run_code(&code, &source_map, vec![], vec![], &RunOptions::mock()) {
+            Ok(_) => assert!(false),
+            Err(err) => assert_eq!(
+                err.to_string(),
+                "Wrong type, expected `pair` for instruction GET, got `nat` at line 2, column 3\n2 |   GET 1 ;\n  |   ^^^^^"
+            ),
+        }
+    }
+
+    #[test]
+    fn run_code_with_macros() {
+        let code = "PUSH nat 3 ;\nPUSH nat 5 ;\nPAIR ;\nDUP ;\nCDR ;\nSWAP ;\nCAR ;\nIFCMPGT { PUSH nat 1 } { PUSH nat 0 }";
+        let (code, source_map) = parse_with_locations(code).unwrap();
+        match run_code(&code, &source_map, vec![], vec![], &RunOptions::mock()) {
+            Err(err) => panic!("{}", err),
+            Ok(result) => {
+                assert_eq!(result.stack.len(), 1);
+                assert_eq!(result.stack[0].value, MValue::new_nat(1));
+            }
+        }
+    }
+
+    #[test]
+    fn run_code_keeps_annotations() {
+        let code = "PUSH @amount nat 5 ;\nPUSH int 3 ;\nPAIR @params %count %amount ;\nDUP @copy ;\nCDR ;\nSWAP ;\nCAR";
+        let (code, source_map) = parse_with_locations(code).unwrap();
+        match run_code(&code, &source_map, vec![], vec![], &RunOptions::mock()) {
+            Err(err) => panic!("{}", err),
+            Ok(result) => {
+                assert_eq!(result.stack.len(), 2);
+                assert_eq!(result.stack[0].value, MValue::new_int(3));
+                assert_eq!(result.stack[0].annot, Some(String::from("params.count")));
+                assert_eq!(result.stack[1].value, MValue::new_nat(5));
+                assert_eq!(result.stack[1].annot, Some(String::from("copy.amount")));
+                assert_eq!(result.stack[1].type_annots.field, Some(String::from("amount")));
+            }
+        }
+    }
+
+    #[test]
+    fn run_code_unpair_annotations() {
+        let code = "PUSH nat 5 ;\nPUSH int 3 ;\nPAIR %count %amount ;\nDUP ;\nUNPAIR @first @second ;\nDIG 2 ;\nUNPAIR";
+        let (code, source_map) = parse_with_locations(code).unwrap();
+        match run_code(&code, &source_map, vec![], vec![], &RunOptions::mock()) {
+            Err(err) => panic!("{}", err),
+            Ok(result) => {
+                assert_eq!(result.stack.len(), 4);
+                // the fields keep the annotations of the pair
+                assert_eq!(result.stack[0].annot, Some(String::from("count")));
+                assert_eq!(result.stack[1].annot, Some(String::from("amount")));
+                assert_eq!(result.stack[1].type_annots.field, Some(String::from("amount")));
+                // variable annotations of UNPAIR name both fields
+                assert_eq!(result.stack[2].annot, Some(String::from("first")));
+                assert_eq!(result.stack[3].annot, Some(String::from("second")));
+                assert_eq!(result.stack[3].type_annots.field, Some(String::from("amount")));
+            }
+        }
+    }
+
+    #[test]
+    fn run_code_ignores_field_annotations_of_accessors() {
+        // field annotations of CAR, CDR and UNPAIR don't rename the fields
+        let code = "PUSH nat 5 ;\nPUSH int 3 ;\nPAIR %count %amount ;\nDUP ;\nCAR %left ;\nSWAP ;\nCDR %right";
+        let (code, source_map) = parse_with_locations(code).unwrap();
+        match run_code(&code, &source_map, vec![], vec![], &RunOptions::mock()) {
+            Err(err) => panic!("{}", err),
+            Ok(result) => {
+                assert_eq!(result.stack.len(), 2);
+                assert_eq!(result.stack[0].annot, Some(String::from("amount")));
+                assert_eq!(result.stack[0].type_annots.field, Some(String::from("amount")));
+                assert_eq!(result.stack[1].annot, Some(String::from("count")));
+                assert_eq!(result.stack[1].type_annots.field, Some(String::from("count")));
+            }
+        }
+    }
+
+    #[test]
+    fn run_code_malformed_annotations() {
+        // empty annotations and annotations without a prefix are ignored
+        let code = Micheline::from_json(
+            r#"[{ "prim": "PUSH", "args": [{ "prim": "nat" }, { "int": "5" }], "annots": ["", "éé", "@total"] }]"#,
+        )
+        .unwrap();
+        let code = match code {
+            Micheline::Seq(code) => code,
+            _ => panic!("Expected an array of instructions"),
         };
-    if json_array.is_array() {
-        let mut failed = false;
-        //println!("{:#?}", json_array);
-        'instructions_loop:
-        for val in json_array.as_array().unwrap() {
-            let prim = &val["prim"].to_string();
-            let instruction = 
-                match Instruction::from_str(prim) {
-                    Ok (i) => i,
-                    Err (err) => panic!("{}", err)
-                };
-            println!("{:?}", instruction);
-            // TODO: add support for macros
-            match &instruction {
-                &Instruction::FAILWITH => {
-                    // aborts the execution of the contract
-                    // gets the value on top of the stack
-                    let failwith_error = stack[0].value.clone();
-                    // updates the stack snapshots
-                    stack_snapshots.push(stack.clone());
-                    // creates a new stack with one value to return
-                    stack = vec![StackElement::new(failwith_error, Instruction::FAILWITH)];
-                    // breaks from the contract execution loop
-                    failed = true;
-                    break 'instructions_loop;
-                }
-                _ => {
-                    let args = val["args"].as_array();
-                    // println!("snapshot: {:?}", stack_snapshots);
-                    (stack, stack_snapshots) = instruction.run(args, stack, stack_snapshots, &options);
-                }
+        match run_code(&code, &SourceMap::default(), vec![], vec![], &RunOptions::mock()) {
+            Err(err) => panic!("{}", err),
+            Ok(result) => {
+                assert_eq!(result.stack.len(), 1);
+                assert_eq!(result.stack[0].annot, Some(String::from("total")));
+                assert!(result.stack[0].type_annots.is_empty());
+            }
+        }
+    }
+
+    #[test]
+    fn run_code_with_dip() {
+        let code = "PUSH nat 1 ;\nPUSH nat 2 ;\nPUSH nat 3 ;\nPUSH string \"top\" ;\nDIP { ADD } ;\nDIIP { PUSH nat 10 ; MUL } ;\nPAPAIR";
+        let (code, source_map) = parse_with_locations(code).unwrap();
+        match run_code(&code, &source_map, vec![], vec![], &RunOptions::mock()) {
+            Err(err) => panic!("{}", err),
+            Ok(result) => {
+                assert_eq!(result.stack.len(), 1);
+                assert_eq!(
+                    result.stack[0].value,
+                    MValue::Pair(PairValue::new(
+                        MValue::new_string("top"),
+                        MValue::Pair(PairValue::new(MValue::new_nat(5), MValue::new_nat(10)))
+                    ))
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn run_code_with_option_and_list_branches() {
+        let code = "PUSH (option nat) (Some 4) ;\nIF_SOME { PUSH nat 1 ; ADD } { PUSH nat 0 } ;\nPUSH (list nat) { 7 ; 8 } ;\nIF_CONS { DIP { DROP } ; ADD } { } ;\nNONE nat ;\nIF_NONE { } { DROP }";
+        let (code, source_map) = parse_with_locations(code).unwrap();
+        match run_code(&code, &source_map, vec![], vec![], &RunOptions::mock()) {
+            Err(err) => panic!("{}", err),
+            Ok(result) => {
+                assert_eq!(result.stack.len(), 1);
+                assert_eq!(result.stack[0].value, MValue::new_nat(12));
+            }
+        }
+    }
+
+    #[test]
+    fn run_code_failwith_in_if_cons() {
+        let code = "NIL nat ;\nIF_CONS { DROP 2 } { PUSH string \"EMPTY\" ; FAILWITH }";
+        let (code, source_map) = parse_with_locations(code).unwrap();
+        match run_code(&code, &source_map, vec![], vec![], &RunOptions::mock()) {
+            Err(err) => panic!("{}", err),
+            Ok(result) => {
+                assert!(result.has_failed());
+                assert_eq!(result.failed.unwrap().value, MValue::new_string("EMPTY"));
+            }
+        }
+    }
+
+    #[test]
+    fn run_code_error_in_macro() {
+        let code = "PUSH nat 3 ;\nCADR";
+        let (code, source_map) = parse_with_locations(code).unwrap();
+        match run_code(&code, &source_map, vec![], vec![], &RunOptions::mock()) {
+            Ok(_) => assert!(false),
+            Err(err) => assert!(err.to_string().contains("at line 2, column 1 (in macro CADR)\n2 | CADR\n  | ^^^^")),
+        }
+    }
+
+    #[test]
+    fn run_code_failwith_in_nested_blocks() {
+        let code = "PUSH nat 2 ;\nNIL nat ;\nPUSH nat 1 ;\nCONS ;\nMAP { PUSH int 1 ; GT ; IF { PUSH string \"nested\" ; FAILWITH } {} } ;\nPUSH nat 7";
+        let (code, source_map) = parse_with_locations(code).unwrap();
+        match run_code(&code, &source_map, vec![], vec![], &RunOptions::mock()) {
+            Err(err) => panic!("{}", err),
+            Ok(result) => {
+                assert!(result.has_failed());
+                assert!(result.stack.is_empty());
+                let failed = result.failed.unwrap();
+                assert_eq!(failed.value, MValue::String(String::from("nested")));
+                let span = source_map.get(failed.location).unwrap();
+                assert_eq!((span.line, span.column), (5, 53));
+                assert_eq!(failed.stack_at_failure.len(), 3);
+                assert_eq!(failed.stack_at_failure[1].value, MValue::new_nat(1));
+                assert_eq!(failed.stack_at_failure[2].value, MValue::new_nat(2));
             }
         }
+    }
 
-        Ok(RunResult {
-            stack,
-            stack_snapshots,
-            has_failed: failed
-        })
-    } else {
-        Err(String::from("Unexpected type output for JSON value, expected an array"))
+    #[test]
+    fn run_code_lambdas() {
+        let code = "PUSH nat 3 ;\nLAMBDA (pair nat nat) nat { DUP ; CAR ; SWAP ; CDR ; CMPLT ; IF { PUSH nat 1 } { PUSH nat 0 } } ;\nPUSH nat 4 ;\nAPPLY ;\nSWAP ;\nEXEC";
+        let (code, source_map) = parse_with_locations(code).unwrap();
+        match run_code(&code, &source_map, vec![], vec![], &RunOptions::mock()) {
+            Err(err) => panic!("{}", err),
+            Ok(result) => {
+                assert!(!result.has_failed());
+                assert_eq!(result.stack.len(), 1);
+                assert_eq!(result.stack[0].value, MValue::new_nat(1));
+            }
+        }
     }
 
-}
\ No newline at end of file
+    // FAILING
+    #[test]
+    #[should_panic(
+        expected = "Unexpected stack length, expected a length of 1 for instruction FAILWITH, got 0 at line 1, column 1"
+    )]
+    fn run_code_failwith_empty_stack() {
+        let (code, source_map) = parse_with_locations("FAILWITH").unwrap();
+        match run_code(&code, &source_map, vec![], vec![], &RunOptions::mock()) {
+            Ok(_) => assert!(false),
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    #[test]
+    fn run_code_invalid_values_return_errors() {
+        let code = "PUSH nat 1 ;\nPUSH string \"one\" ;\nADD";
+        let (code, source_map) = parse_with_locations(code).unwrap();
+        match run_code(&code, &source_map, vec![], vec![], &RunOptions::mock()) {
+            Ok(_) => assert!(false),
+            Err(MichelsonError::Located { error, span, .. }) => {
+                assert_eq!(
+                    error.to_string(),
+                    "Cannot add together values of type string and nat"
+                );
+                assert_eq!((span.line, span.column), (3, 1));
+            }
+            Err(err) => panic!("Unexpected error: {:?}", err),
+        }
+    }
+
+    #[test]
+    fn run_unsupported_instruction_case() {
+        let json = r#"[{ "prim": "UPDATE", "args": [{ "int": "1" }] }]"#;
+        match run(json, vec![], vec![], &RunOptions::mock()) {
+            Ok(_) => assert!(false),
+            Err(err) => assert_eq!(
+                err,
+                MichelsonError::Unsupported(String::from("UPDATE instruction on field 1 of a pair"))
+            ),
+        }
+    }
+
+    #[test]
+    fn run_invalid_json() {
+        match run("[{ \"prim\": ", vec![], vec![], &RunOptions::mock()) {
+            Ok(_) => assert!(false),
+            Err(err) => assert!(matches!(err, MichelsonError::InvalidJson(_))),
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Expected `}`: Unexpected end of code")]
+    fn parse_unclosed_brace() {
+        match parse(String::from("IF { DROP } { PUSH nat 2")) {
+            Ok(_) => assert!(false),
+            Err((err, _)) => panic!("{}", err),
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Unexpected `}` at line 1, column 13")]
+    fn parse_unexpected_closing_brace() {
+        match parse(String::from("DROP ; SWAP } ; DUP")) {
+            Ok(_) => assert!(false),
+            Err((err, _)) => panic!("{}", err),
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Unterminated string at line 1, column 13")]
+    fn parse_unterminated_string() {
+        match parse(String::from("PUSH string \"test ; DROP")) {
+            Ok(_) => assert!(false),
+            Err((err, _)) => panic!("{}", err),
+        }
+    }
+}
@@ -1,17 +1,79 @@
-use crate::errors::{display_error, ErrorCode};
+use crate::errors::MichelsonError;
 use crate::instructions::Instruction;
-use crate::m_types::MValue;
+use crate::m_types::{MType, MValue, TypeAnnots};
 
 #[derive(Debug, Clone)]
 pub struct StackElement {
     pub value: MValue,
     pub instruction: Instruction, // keeps track of the instruction that pushed the value to the stack
+    pub annot: Option<String>,    // variable annotation, without the `@` prefix
+    pub type_annots: TypeAnnots,  // field and type annotations of the type of the value
+    pub m_type: Option<MType>,    // type of the value with the annotations of its fields, if any
 }
 
 impl StackElement {
     pub fn new(value: MValue, instruction: Instruction) -> StackElement {
         // TODO: create a function to validate the Michelson type
-        StackElement { value, instruction }
+        StackElement {
+            value,
+            instruction,
+            annot: None,
+            type_annots: TypeAnnots::default(),
+            m_type: None,
+        }
+    }
+
+    /// sets the variable annotation of the element
+    pub fn with_annot(self, annot: Option<String>) -> StackElement {
+        StackElement { annot, ..self }
+    }
+
+    /// sets the field and type annotations of the type of the element
+    pub fn with_type_annots(self, type_annots: TypeAnnots) -> StackElement {
+        StackElement {
+            type_annots,
+            ..self
+        }
+    }
+
+    /// sets the type of the element along with the annotations of its fields
+    pub fn with_type(self, m_type: MType) -> StackElement {
+        StackElement {
+            m_type: Some(m_type),
+            ..self
+        }
+    }
+
+    /// returns the type of the value with the annotations of its fields
+    pub fn annotated_type(&self) -> MType {
+        self.m_type.clone().unwrap_or_else(|| self.value.get_type())
+    }
+
+    /// checks if the element carries field or type annotations
+    pub fn has_type_annots(&self) -> bool {
+        !self.type_annots.is_empty() || self.m_type.is_some()
+    }
+
+    /// gives an element holding a field of the pair held by this element the annotations of the field,
+    /// the variable annotation is made of the field annotation, prefixed with the variable annotation of the pair
+    /// e.g. accessing the field `%owner` of `@storage` returns `storage.owner`
+    pub fn annotate_field(&self, index: usize, field_el: StackElement) -> StackElement {
+        let (field_type, type_annots) = match &self.m_type {
+            Some(MType::Pair(types, annots)) if index == 0 => (&types.0, &annots.0),
+            Some(MType::Pair(types, annots)) => (&types.1, &annots.1),
+            _ => return field_el,
+        };
+        let annot = match (&self.annot, &type_annots.field) {
+            (Some(annot), Some(field)) => Some(format!("{}.{}", annot, field)),
+            (None, Some(field)) => Some(field.clone()),
+            (_, None) => None,
+        };
+        StackElement {
+            annot,
+            type_annots: type_annots.clone(),
+            m_type: field_type.branch_annots().map(|_| field_type.clone()),
+            ..field_el
+        }
     }
 
     pub fn get_val(&self) -> MValue {
@@ -19,7 +81,10 @@ impl StackElement {
     }
 
     pub fn change_instruction(self, instruction: Instruction) -> StackElement {
-        StackElement::new(self.value, instruction)
+        StackElement {
+            instruction,
+            ..self
+        }
     }
 }
 
@@ -42,7 +107,7 @@ pub trait StackFuncs {
     /// Returns the new stack
     fn push(&self, el_to_push: MValue, instruction: Instruction) -> Stack;
     /// Helper function to check if the stack has the correct properties
-    fn check_depth(&self, expected_size: usize, instruction: Instruction) -> Result<(), String>;
+    fn check_depth(&self, expected_size: usize, instruction: Instruction) -> Result<(), MichelsonError>;
 }
 
 impl StackFuncs for Stack {
@@ -95,13 +160,13 @@ impl StackFuncs for Stack {
             .collect()
     }
     /// Helper function to check if the stack has the correct properties
-    fn check_depth(&self, expected_size: usize, instruction: Instruction) -> Result<(), String> {
+    fn check_depth(&self, expected_size: usize, instruction: Instruction) -> Result<(), MichelsonError> {
         if self.len() < expected_size {
-            return Err(display_error(ErrorCode::StackNotDeepEnough((
+            return Err(MichelsonError::StackNotDeepEnough((
                 expected_size,
                 self.len(),
                 instruction,
-            ))));
+            )));
         }
         Ok(())
     }
@@ -0,0 +1,1038 @@
+use crate::compiler::{self, Args, Block, Node};
+use crate::errors::MichelsonError;
+use crate::instructions::Instruction;
+use crate::m_types::{LambdaValue, MType, MValue};
+use crate::micheline::Micheline;
+use crate::parser::SourceMap;
+
+// https://tezos.gitlab.io/active/michelson.html#type-system
+
+/// Types of the elements of the stack, the top of the stack comes first
+pub type StackType = Vec<MType>;
+
+/// Type of the stack after an instruction
+#[derive(Debug, Clone, PartialEq)]
+pub enum Typed {
+    Stack(StackType),
+    // the instruction always fails (e.g. FAILWITH), the following code is never reached
+    Failed,
+}
+
+/// Types inferred for a block of code
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypecheckResult {
+    // type of the stack at the end of the code
+    pub stack: Typed,
+    // type of the stack after every instruction, including the instructions of nested blocks,
+    // along with the location of the instruction
+    pub stack_types: Vec<(usize, Typed)>,
+}
+
+/// Type of the stack expected by an instruction, in the notation of the Michelson reference
+fn expected_stack(instruction: &Instruction) -> &'static str {
+    match instruction {
+        Instruction::ABS | Instruction::ISNAT => "int : S",
+        Instruction::ADD => "int | nat | mutez | timestamp : int | nat | mutez | timestamp : S",
+        Instruction::ADDRESS => "contract 'p : S",
+        Instruction::AND => "bool | nat | int : bool | nat : S",
+        Instruction::APPLY => "'a : lambda (pair 'a 'b) 'c : S",
+        Instruction::CAR | Instruction::CDR | Instruction::UNPAIR => "pair 'a 'b : S",
+        Instruction::COMPARE => "'a : 'a : S",
+        Instruction::CONCAT => "string | bytes : string | bytes : S | list string | list bytes : S",
+        Instruction::CONS => "'a : list 'a : S",
+        Instruction::DIG | Instruction::DIP | Instruction::DUG | Instruction::DROP | Instruction::DUP => {
+            "'a1 : ... : 'an : S"
+        }
+        Instruction::EDIV => "int | nat | mutez : int | nat | mutez : S",
+        Instruction::EQ
+        | Instruction::GE
+        | Instruction::GT
+        | Instruction::LE
+        | Instruction::LT
+        | Instruction::NEQ => "int : S",
+        Instruction::EXEC => "'a : lambda 'a 'b : S",
+        Instruction::FAILWITH | Instruction::LEFT | Instruction::RIGHT | Instruction::SOME => "'a : S",
+        Instruction::GET => "'k : map 'k 'v | big_map 'k 'v : S | pair 'a 'b : S",
+        Instruction::IF => "bool : S",
+        Instruction::IF_CONS => "list 'a : S",
+        Instruction::IF_LEFT => "or 'a 'b : S",
+        Instruction::IF_NONE => "option 'a : S",
+        Instruction::INT => "nat : S",
+        Instruction::ITER => "list 'a | set 'a | map 'k 'v : S",
+        Instruction::KECCAK => "bytes : S",
+        Instruction::LOOP => "bool : S",
+        Instruction::LOOP_LEFT => "or 'a 'b : S",
+        Instruction::MAP => "list 'a | map 'k 'v | option 'a : S",
+        Instruction::MEM => "'a : set 'a | map 'a 'v | big_map 'a 'v : S",
+        Instruction::MUL => "int | nat | mutez : int | nat | mutez : S",
+        Instruction::NEG => "int | nat : S",
+        Instruction::NEVER => "never : S",
+        Instruction::NOT => "bool | nat | int : S",
+        Instruction::OR | Instruction::XOR => "bool | nat : bool | nat : S",
+        Instruction::PAIR | Instruction::SWAP => "'a : 'b : S",
+        Instruction::SIZE => "string | bytes | list 'a | set 'a | map 'k 'v : S",
+        Instruction::SLICE => "nat : nat : string | bytes : S",
+        Instruction::SUB => "int | nat | timestamp : int | nat | timestamp : S",
+        Instruction::SUB_MUTEZ => "mutez : mutez : S",
+        Instruction::TICKET => "'a : nat : S",
+        Instruction::UPDATE => {
+            "'a : bool : set 'a : S | 'k : option 'v : map 'k 'v | big_map 'k 'v : S | 'a : pair 'b 'c : S"
+        }
+        _ => "S",
+    }
+}
+
+/// builds a stack type from the new elements on top of the stack and the rest of the stack
+fn push(top: Vec<MType>, rest: &[MType]) -> StackType {
+    top.into_iter().chain(rest.iter().cloned()).collect()
+}
+
+/// returns the type of the field at the provided index of a right-combed pair,
+/// using the indices of the GET instruction
+fn comb_field(m_type: &MType, index: usize) -> Option<MType> {
+    match (index, m_type) {
+        (0, _) => Some(m_type.clone()),
+        (1, MType::Pair(types, _)) => Some(types.0.clone()),
+        (_, MType::Pair(types, _)) => comb_field(&types.1, index - 2),
+        _ => None,
+    }
+}
+
+/// returns the type of a right-combed pair after the field at the provided index is replaced,
+/// using the indices of the UPDATE instruction
+fn update_comb_field(m_type: &MType, index: usize, new_type: &MType) -> Option<MType> {
+    match (index, m_type) {
+        (0, _) => Some(new_type.clone()),
+        (1, MType::Pair(types, annots)) => Some(MType::Pair(
+            Box::new((new_type.clone(), types.1.clone())),
+            annots.clone(),
+        )),
+        (_, MType::Pair(types, annots)) => Some(MType::Pair(
+            Box::new((
+                types.0.clone(),
+                update_comb_field(&types.1, index - 2, new_type)?,
+            )),
+            annots.clone(),
+        )),
+        _ => None,
+    }
+}
+
+/// merges the types of the stacks at the end of the two branches of an instruction
+/// a branch that fails takes the type of the other branch
+fn merge_branches(
+    branch_if: Typed,
+    branch_else: Typed,
+    instruction: Instruction,
+) -> Result<Typed, MichelsonError> {
+    match (branch_if, branch_else) {
+        (Typed::Failed, typed) | (typed, Typed::Failed) => Ok(typed),
+        (Typed::Stack(stack_if), Typed::Stack(stack_else)) => {
+            if stack_if == stack_else {
+                Ok(Typed::Stack(stack_if))
+            } else {
+                Err(MichelsonError::BranchesMismatch((
+                    stack_if,
+                    stack_else,
+                    instruction,
+                )))
+            }
+        }
+    }
+}
+
+/// typechecks the body of MAP, the body must replace the element on top of the stack
+/// and leave the rest of the stack unchanged
+/// returns the type of the new element, errors are reported at the location of MAP
+fn typecheck_map_body(
+    body: &Block,
+    element: MType,
+    rest: &[MType],
+    location: usize,
+    stack_types: &mut Vec<(usize, Typed)>,
+) -> Result<MType, (MichelsonError, usize)> {
+    match typecheck_block(body, push(vec![element], rest), stack_types)? {
+        Typed::Stack(stack) if stack.len() == rest.len() + 1 && &stack[1..] == rest => {
+            Ok(stack[0].clone())
+        }
+        Typed::Stack(stack) => Err((
+            MichelsonError::InvalidStackType((
+                format!(
+                    "'b : {}",
+                    rest.iter()
+                        .map(|m_type| m_type.to_string())
+                        .collect::<Vec<String>>()
+                        .join(" : ")
+                ),
+                stack,
+                Instruction::MAP,
+            )),
+            location,
+        )),
+        Typed::Failed => Err((
+            MichelsonError::Message(String::from(
+                "The body of MAP always fails, the type of its elements cannot be inferred",
+            )),
+            location,
+        )),
+    }
+}
+
+/// checks that the body of a loop ends with the expected stack type, e.g. the stack below the element for ITER
+fn typecheck_loop_body(
+    body: &Block,
+    input: StackType,
+    expected: &[MType],
+    instruction: Instruction,
+    location: usize,
+    stack_types: &mut Vec<(usize, Typed)>,
+) -> Result<(), (MichelsonError, usize)> {
+    match typecheck_block(body, input, stack_types)? {
+        Typed::Stack(stack) if stack == expected => Ok(()),
+        Typed::Stack(stack) => Err((
+            MichelsonError::InvalidStackType((
+                expected
+                    .iter()
+                    .map(|m_type| m_type.to_string())
+                    .collect::<Vec<String>>()
+                    .join(" : "),
+                stack,
+                instruction,
+            )),
+            location,
+        )),
+        // the body always fails, the stack after the loop is the stack when the loop doesn't run
+        Typed::Failed => Ok(()),
+    }
+}
+
+/// checks that the code of a lambda turns its parameter into its result
+/// the code is typechecked on its own stack, errors are reported at the location of the instruction
+/// pushing the lambda as the locations inside the code are relative to the code
+fn typecheck_lambda_body(
+    lambda: &LambdaValue,
+    instruction: Instruction,
+    location: usize,
+) -> Result<(), (MichelsonError, usize)> {
+    let (param_type, return_type) = lambda.m_type.clone();
+    // a recursive lambda receives itself below its parameter
+    let input = if lambda.recursive {
+        vec![param_type.clone(), MType::Lambda(Box::new(lambda.m_type.clone()))]
+    } else {
+        vec![param_type]
+    };
+    match typecheck_block(lambda.body.block(), input, &mut vec![]).map_err(|(err, _)| (err, location))? {
+        Typed::Stack(stack) if stack == [return_type.clone()] => Ok(()),
+        Typed::Stack(stack) => Err((
+            MichelsonError::InvalidStackType((return_type.to_string(), stack, instruction)),
+            location,
+        )),
+        Typed::Failed => Ok(()),
+    }
+}
+
+/// infers the type of the stack after an instruction
+/// the error is returned with the location of the instruction that doesn't typecheck
+fn typecheck_node(
+    node: &Node,
+    stack: StackType,
+    stack_types: &mut Vec<(usize, Typed)>,
+) -> Result<Typed, (MichelsonError, usize)> {
+    let instruction = node.instruction;
+    let mismatch = |stack: &StackType| {
+        (
+            MichelsonError::InvalidStackType((
+                String::from(expected_stack(&instruction)),
+                stack.clone(),
+                instruction,
+            )),
+            node.location,
+        )
+    };
+
+    let new_stack: Option<StackType> = match (&instruction, &node.args, stack.as_slice()) {
+        // instructions running blocks of code
+        (Instruction::DIP, Args::CountAndBlock(count, body), _) if stack.len() >= *count => {
+            let (protected, rest) = stack.split_at(*count);
+            return match typecheck_block(body, rest.to_vec(), stack_types)? {
+                Typed::Stack(new_rest) => Ok(Typed::Stack(push(protected.to_vec(), &new_rest))),
+                Typed::Failed => Ok(Typed::Failed),
+            };
+        }
+        (Instruction::IF, Args::Branches(branch_if, branch_else), [MType::Bool, rest @ ..]) => {
+            let typed_if = typecheck_block(branch_if, rest.to_vec(), stack_types)?;
+            let typed_else = typecheck_block(branch_else, rest.to_vec(), stack_types)?;
+            return merge_branches(typed_if, typed_else, instruction)
+                .map_err(|err| (err, node.location));
+        }
+        (
+            Instruction::IF_LEFT,
+            Args::Branches(branch_if, branch_else),
+            [MType::Or(types, _), rest @ ..],
+        ) => {
+            let typed_if =
+                typecheck_block(branch_if, push(vec![types.0.clone()], rest), stack_types)?;
+            let typed_else =
+                typecheck_block(branch_else, push(vec![types.1.clone()], rest), stack_types)?;
+            return merge_branches(typed_if, typed_else, instruction)
+                .map_err(|err| (err, node.location));
+        }
+        (
+            Instruction::IF_NONE,
+            Args::Branches(branch_none, branch_some),
+            [MType::Option(m_type), rest @ ..],
+        ) => {
+            let typed_none = typecheck_block(branch_none, rest.to_vec(), stack_types)?;
+            let typed_some =
+                typecheck_block(branch_some, push(vec![*m_type.clone()], rest), stack_types)?;
+            return merge_branches(typed_none, typed_some, instruction)
+                .map_err(|err| (err, node.location));
+        }
+        (
+            Instruction::IF_CONS,
+            Args::Branches(branch_cons, branch_nil),
+            [MType::List(el_type), rest @ ..],
+        ) => {
+            let typed_cons = typecheck_block(
+                branch_cons,
+                push(vec![*el_type.clone(), stack[0].clone()], rest),
+                stack_types,
+            )?;
+            let typed_nil = typecheck_block(branch_nil, rest.to_vec(), stack_types)?;
+            return merge_branches(typed_cons, typed_nil, instruction)
+                .map_err(|err| (err, node.location));
+        }
+        (
+            Instruction::ITER,
+            Args::Block(body),
+            [MType::List(el_type) | MType::Set(el_type), rest @ ..],
+        ) => {
+            let input = push(vec![*el_type.clone()], rest);
+            typecheck_loop_body(body, input, rest, instruction, node.location, stack_types)?;
+            Some(rest.to_vec())
+        }
+        (Instruction::ITER, Args::Block(body), [MType::Map(types), rest @ ..]) => {
+            let input = push(vec![MType::pair(types.0.clone(), types.1.clone())], rest);
+            typecheck_loop_body(body, input, rest, instruction, node.location, stack_types)?;
+            Some(rest.to_vec())
+        }
+        (Instruction::LOOP, Args::Block(body), [MType::Bool, rest @ ..]) => {
+            let expected = push(vec![MType::Bool], rest);
+            typecheck_loop_body(
+                body,
+                rest.to_vec(),
+                &expected,
+                instruction,
+                node.location,
+                stack_types,
+            )?;
+            Some(rest.to_vec())
+        }
+        (Instruction::LOOP_LEFT, Args::Block(body), [MType::Or(types, _), rest @ ..]) => {
+            let input = push(vec![types.0.clone()], rest);
+            typecheck_loop_body(body, input, &stack, instruction, node.location, stack_types)?;
+            Some(push(vec![types.1.clone()], rest))
+        }
+        (Instruction::MAP, Args::Block(body), [MType::List(el_type), rest @ ..]) => {
+            let new_el_type =
+                typecheck_map_body(body, *el_type.clone(), rest, node.location, stack_types)?;
+            Some(push(vec![MType::List(Box::new(new_el_type))], rest))
+        }
+        (Instruction::MAP, Args::Block(body), [MType::Map(types), rest @ ..]) => {
+            let (key_type, value_type) = *types.clone();
+            let element = MType::pair(key_type.clone(), value_type);
+            let new_value_type =
+                typecheck_map_body(body, element, rest, node.location, stack_types)?;
+            Some(push(
+                vec![MType::Map(Box::new((key_type, new_value_type)))],
+                rest,
+            ))
+        }
+        (Instruction::MAP, Args::Block(body), [MType::Option(el_type), rest @ ..]) => {
+            let new_el_type =
+                typecheck_map_body(body, *el_type.clone(), rest, node.location, stack_types)?;
+            Some(push(vec![MType::Option(Box::new(new_el_type))], rest))
+        }
+        // instructions that always fail
+        (Instruction::FAILWITH, _, [_, ..]) | (Instruction::NEVER, _, [MType::Never, ..]) => {
+            return Ok(Typed::Failed)
+        }
+        // stack manipulation
+        (Instruction::DIG, Args::Count(n), _) if stack.len() > *n => {
+            let mut new_stack = stack.clone();
+            let el = new_stack.remove(*n);
+            new_stack.insert(0, el);
+            Some(new_stack)
+        }
+        (Instruction::DUG, Args::Count(n), _) if stack.len() > *n => {
+            let mut new_stack = stack.clone();
+            let el = new_stack.remove(0);
+            new_stack.insert(*n, el);
+            Some(new_stack)
+        }
+        (Instruction::DROP, Args::Count(n), _) if stack.len() >= *n => Some(stack[*n..].to_vec()),
+        (Instruction::DUP, Args::Count(n), _) if *n > 0 && stack.len() >= *n => match &stack[n - 1]
+        {
+            // tickets cannot be duplicated
+            MType::Ticket(_) => None,
+            m_type => Some(push(vec![m_type.clone()], &stack)),
+        },
+        (Instruction::SWAP, _, [a, b, rest @ ..]) => Some(push(vec![b.clone(), a.clone()], rest)),
+        // lambdas
+        (
+            Instruction::LAMBDA | Instruction::LAMBDA_REC | Instruction::PUSH,
+            Args::Value(value @ MValue::Lambda(lambda)),
+            _,
+        ) => {
+            typecheck_lambda_body(lambda, instruction, node.location)?;
+            Some(push(vec![value.get_type()], &stack))
+        }
+        (Instruction::EXEC, _, [param_type, MType::Lambda(types), rest @ ..]) if *param_type == types.0 => {
+            Some(push(vec![types.1.clone()], rest))
+        }
+        (Instruction::APPLY, _, [value_type, MType::Lambda(types), rest @ ..]) => match &types.0 {
+            MType::Pair(param_types, _) if param_types.0 == *value_type => Some(push(
+                vec![MType::Lambda(Box::new((param_types.1.clone(), types.1.clone())))],
+                rest,
+            )),
+            _ => None,
+        },
+        // values pushed onto the stack
+        (Instruction::PUSH, Args::Value(value), _) => Some(push(vec![value.get_type()], &stack)),
+        (Instruction::UNIT, _, _) => Some(push(vec![MType::Unit], &stack)),
+        (Instruction::NIL, Args::Type(m_type), _) => {
+            Some(push(vec![MType::List(Box::new(m_type.clone()))], &stack))
+        }
+        (Instruction::NONE, Args::Type(m_type), _) => {
+            Some(push(vec![MType::Option(Box::new(m_type.clone()))], &stack))
+        }
+        (Instruction::EMPTY_SET, Args::Types(m_type, _), _) => {
+            Some(push(vec![MType::Set(Box::new(m_type.clone()))], &stack))
+        }
+        (Instruction::EMPTY_MAP, Args::Types(key_type, Some(value_type)), _) => Some(push(
+            vec![MType::Map(Box::new((key_type.clone(), value_type.clone())))],
+            &stack,
+        )),
+        (Instruction::EMPTY_BIG_MAP, Args::Types(key_type, Some(value_type)), _) => Some(push(
+            vec![MType::Big_map(Box::new((
+                key_type.clone(),
+                value_type.clone(),
+            )))],
+            &stack,
+        )),
+        // context of the operation
+        (Instruction::AMOUNT | Instruction::BALANCE, _, _) => {
+            Some(push(vec![MType::Mutez], &stack))
+        }
+        (Instruction::CHAIN_ID, _, _) => Some(push(vec![MType::ChainId], &stack)),
+        (Instruction::LEVEL, _, _) => Some(push(vec![MType::Nat], &stack)),
+        (Instruction::NOW, _, _) => Some(push(vec![MType::Timestamp], &stack)),
+        (Instruction::SELF_ADDRESS | Instruction::SENDER | Instruction::SOURCE, _, _) => {
+            Some(push(vec![MType::Address], &stack))
+        }
+        // arithmetic
+        (Instruction::ABS, _, [MType::Int, rest @ ..]) => Some(push(vec![MType::Nat], rest)),
+        (Instruction::ADD, _, [left, right, rest @ ..]) => match (left, right) {
+            (MType::Nat, MType::Nat) => Some(MType::Nat),
+            (MType::Int | MType::Nat, MType::Int | MType::Nat) => Some(MType::Int),
+            (MType::Timestamp, MType::Int) | (MType::Int, MType::Timestamp) => {
+                Some(MType::Timestamp)
+            }
+            (MType::Mutez, MType::Mutez) => Some(MType::Mutez),
+            _ => None,
+        }
+        .map(|m_type| push(vec![m_type], rest)),
+        (Instruction::SUB, _, [left, right, rest @ ..]) => match (left, right) {
+            (MType::Int | MType::Nat, MType::Int | MType::Nat) => Some(MType::Int),
+            (MType::Timestamp, MType::Int) => Some(MType::Timestamp),
+            (MType::Timestamp, MType::Timestamp) => Some(MType::Int),
+            _ => None,
+        }
+        .map(|m_type| push(vec![m_type], rest)),
+        (Instruction::SUB_MUTEZ, _, [MType::Mutez, MType::Mutez, rest @ ..]) => {
+            Some(push(vec![MType::Option(Box::new(MType::Mutez))], rest))
+        }
+        (Instruction::MUL, _, [left, right, rest @ ..]) => match (left, right) {
+            (MType::Nat, MType::Nat) => Some(MType::Nat),
+            (MType::Int | MType::Nat, MType::Int | MType::Nat) => Some(MType::Int),
+            (MType::Mutez, MType::Nat) | (MType::Nat, MType::Mutez) => Some(MType::Mutez),
+            _ => None,
+        }
+        .map(|m_type| push(vec![m_type], rest)),
+        (Instruction::EDIV, _, [left, right, rest @ ..]) => match (left, right) {
+            (MType::Nat, MType::Nat) => Some((MType::Nat, MType::Nat)),
+            (MType::Int | MType::Nat, MType::Int | MType::Nat) => Some((MType::Int, MType::Nat)),
+            (MType::Mutez, MType::Nat) => Some((MType::Mutez, MType::Mutez)),
+            (MType::Mutez, MType::Mutez) => Some((MType::Nat, MType::Mutez)),
+            _ => None,
+        }
+        .map(|types| push(vec![MType::Option(Box::new(MType::pair(types.0, types.1)))], rest)),
+        (Instruction::INT, _, [MType::Nat, rest @ ..]) => Some(push(vec![MType::Int], rest)),
+        (Instruction::ISNAT, _, [MType::Int, rest @ ..]) => {
+            Some(push(vec![MType::Option(Box::new(MType::Nat))], rest))
+        }
+        (Instruction::NEG, _, [MType::Int | MType::Nat, rest @ ..]) => {
+            Some(push(vec![MType::Int], rest))
+        }
+        // boolean and bitwise operations
+        (Instruction::NOT, _, [MType::Bool, rest @ ..]) => Some(push(vec![MType::Bool], rest)),
+        (Instruction::NOT, _, [MType::Int | MType::Nat, rest @ ..]) => {
+            Some(push(vec![MType::Int], rest))
+        }
+        (Instruction::AND, _, [MType::Bool, MType::Bool, rest @ ..])
+        | (Instruction::OR | Instruction::XOR, _, [MType::Bool, MType::Bool, rest @ ..]) => {
+            Some(push(vec![MType::Bool], rest))
+        }
+        (Instruction::AND, _, [MType::Int | MType::Nat, MType::Nat, rest @ ..])
+        | (Instruction::OR | Instruction::XOR, _, [MType::Nat, MType::Nat, rest @ ..]) => {
+            Some(push(vec![MType::Nat], rest))
+        }
+        // comparisons
+        (Instruction::COMPARE, _, [left, right, rest @ ..])
+            if left == right && left.is_comparable() =>
+        {
+            Some(push(vec![MType::Int], rest))
+        }
+        (
+            Instruction::EQ
+            | Instruction::GE
+            | Instruction::GT
+            | Instruction::LE
+            | Instruction::LT
+            | Instruction::NEQ,
+            _,
+            [MType::Int, rest @ ..],
+        ) => Some(push(vec![MType::Bool], rest)),
+        // pairs and unions
+        (Instruction::PAIR, _, [left, right, rest @ ..]) => Some(push(
+            vec![MType::pair(left.clone(), right.clone())],
+            rest,
+        )),
+        (Instruction::UNPAIR, _, [MType::Pair(types, _), rest @ ..]) => {
+            Some(push(vec![types.0.clone(), types.1.clone()], rest))
+        }
+        (Instruction::CAR, _, [MType::Pair(types, _), rest @ ..]) => {
+            Some(push(vec![types.0.clone()], rest))
+        }
+        (Instruction::CDR, _, [MType::Pair(types, _), rest @ ..]) => {
+            Some(push(vec![types.1.clone()], rest))
+        }
+        (Instruction::LEFT, Args::Type(right_type), [left_type, rest @ ..]) => Some(push(
+            vec![MType::or(left_type.clone(), right_type.clone())],
+            rest,
+        )),
+        (Instruction::RIGHT, Args::Type(left_type), [right_type, rest @ ..]) => Some(push(
+            vec![MType::or(left_type.clone(), right_type.clone())],
+            rest,
+        )),
+        (Instruction::SOME, _, [m_type, rest @ ..]) => {
+            Some(push(vec![MType::Option(Box::new(m_type.clone()))], rest))
+        }
+        // collections
+        (Instruction::CONS, _, [el_type, MType::List(list_type), rest @ ..])
+            if el_type == &**list_type =>
+        {
+            Some(push(vec![MType::List(list_type.clone())], rest))
+        }
+        (
+            Instruction::SIZE,
+            _,
+            [MType::String | MType::Bytes | MType::List(_) | MType::Set(_) | MType::Map(_), rest @ ..],
+        ) => Some(push(vec![MType::Nat], rest)),
+        (Instruction::MEM, _, [el_type, MType::Set(set_type), rest @ ..])
+            if el_type == &**set_type =>
+        {
+            Some(push(vec![MType::Bool], rest))
+        }
+        (Instruction::MEM, _, [key_type, MType::Map(types) | MType::Big_map(types), rest @ ..])
+            if key_type == &types.0 =>
+        {
+            Some(push(vec![MType::Bool], rest))
+        }
+        (
+            Instruction::GET,
+            Args::Index(None),
+            [key_type, MType::Map(types) | MType::Big_map(types), rest @ ..],
+        ) if key_type == &types.0 => {
+            Some(push(vec![MType::Option(Box::new(types.1.clone()))], rest))
+        }
+        (Instruction::GET, Args::Index(Some(0)), [pair @ MType::Pair(..), rest @ ..]) => {
+            Some(push(vec![pair.clone()], rest))
+        }
+        (Instruction::GET, Args::Index(Some(index)), [pair @ MType::Pair(..), rest @ ..]) => {
+            comb_field(pair, *index).map(|field_type| push(vec![field_type], rest))
+        }
+        (
+            Instruction::UPDATE,
+            Args::Index(None),
+            [el_type, MType::Bool, set @ MType::Set(set_type), rest @ ..],
+        ) if el_type == &**set_type => Some(push(vec![set.clone()], rest)),
+        (
+            Instruction::UPDATE,
+            Args::Index(None),
+            [key_type, MType::Option(value_type), map @ (MType::Map(types) | MType::Big_map(types)), rest @ ..],
+        ) if key_type == &types.0 && **value_type == types.1 => Some(push(vec![map.clone()], rest)),
+        (
+            Instruction::UPDATE,
+            Args::Index(Some(index)),
+            [new_type, pair @ MType::Pair(..), rest @ ..],
+        ) => update_comb_field(pair, *index, new_type).map(|pair| push(vec![pair], rest)),
+        // strings and bytes
+        (Instruction::CONCAT, _, [MType::String, MType::String, rest @ ..]) => {
+            Some(push(vec![MType::String], rest))
+        }
+        (Instruction::CONCAT, _, [MType::Bytes, MType::Bytes, rest @ ..]) => {
+            Some(push(vec![MType::Bytes], rest))
+        }
+        (Instruction::CONCAT, _, [MType::List(list_type), rest @ ..]) => match **list_type {
+            MType::String => Some(push(vec![MType::String], rest)),
+            MType::Bytes => Some(push(vec![MType::Bytes], rest)),
+            _ => None,
+        },
+        (
+            Instruction::SLICE,
+            _,
+            [MType::Nat, MType::Nat, m_type @ (MType::String | MType::Bytes), rest @ ..],
+        ) => Some(push(vec![MType::Option(Box::new(m_type.clone()))], rest)),
+        (Instruction::KECCAK, _, [MType::Bytes, rest @ ..]) => Some(push(vec![MType::Bytes], rest)),
+        // addresses and tickets
+        (Instruction::ADDRESS, _, [MType::Contract(_), rest @ ..]) => {
+            Some(push(vec![MType::Address], rest))
+        }
+        (Instruction::TICKET, _, [m_type, MType::Nat, rest @ ..]) => Some(push(
+            vec![MType::Option(Box::new(MType::Ticket(Box::new((
+                m_type.clone(),
+                MType::Nat,
+                MType::Address,
+            )))))],
+            rest,
+        )),
+        _ => None,
+    };
+
+    match new_stack {
+        Some(new_stack) => Ok(Typed::Stack(new_stack)),
+        None => Err(mismatch(&stack)),
+    }
+}
+
+/// infers the type of the stack after every instruction of a block of code
+/// the types of the stack are saved in `stack_types`
+fn typecheck_block(
+    block: &Block,
+    mut stack: StackType,
+    stack_types: &mut Vec<(usize, Typed)>,
+) -> Result<Typed, (MichelsonError, usize)> {
+    for (i, node) in block.iter().enumerate() {
+        let typed = typecheck_node(node, stack, stack_types)?;
+        stack_types.push((node.location, typed.clone()));
+        match typed {
+            Typed::Stack(new_stack) => stack = new_stack,
+            // instructions that fail must be the last of their block
+            Typed::Failed => {
+                return match block.get(i + 1) {
+                    None => Ok(Typed::Failed),
+                    Some(next_node) => Err((
+                        MichelsonError::Message(format!(
+                            "Unreachable code after {:?} instruction",
+                            node.instruction
+                        )),
+                        next_node.location,
+                    )),
+                };
+            }
+        }
+    }
+
+    Ok(Typed::Stack(stack))
+}
+
+/// checks that the stack at the end of a contract is `pair (list operation) storage`
+pub fn check_contract_stack(typed: &Typed, storage: &MType) -> Result<(), MichelsonError> {
+    let expected = MType::pair(
+        MType::List(Box::new(MType::Operation)),
+        storage.clone(),
+    );
+    match typed {
+        Typed::Failed => Ok(()),
+        Typed::Stack(stack) if stack.len() == 1 && stack[0] == expected => Ok(()),
+        Typed::Stack(stack) => Err(MichelsonError::Message(format!(
+            "Unexpected stack type at the end of the contract, expected [pair (list operation) {}], but got [{}]",
+            storage.to_string(),
+            stack.iter().map(|m_type| m_type.to_string()).collect::<Vec<String>>().join(" : ")
+        ))),
+    }
+}
+
+/// Infers the type of the stack after every instruction of a compiled block of code
+pub fn typecheck(block: &Block, stack: StackType) -> Result<TypecheckResult, MichelsonError> {
+    let mut stack_types = vec![];
+    let typed = typecheck_block(block, stack, &mut stack_types).map_err(|(err, _)| err)?;
+
+    Ok(TypecheckResult {
+        stack: typed,
+        stack_types,
+    })
+}
+
+/// Infers the type of the stack after every instruction of parsed Michelson code
+/// errors report the position in the source code of the instruction that doesn't typecheck
+pub fn typecheck_code(
+    code: &[Micheline],
+    source_map: &SourceMap,
+    stack: StackType,
+) -> Result<TypecheckResult, MichelsonError> {
+    let (block, source_map) = compiler::compile_with_locations(code, source_map)?;
+    typecheck_with_locations(&block, &source_map, stack)
+}
+
+/// Infers the type of the stack after every instruction of a compiled block of code
+/// errors report the position in the source code of the instruction that doesn't typecheck
+pub fn typecheck_with_locations(
+    block: &Block,
+    source_map: &SourceMap,
+    stack: StackType,
+) -> Result<TypecheckResult, MichelsonError> {
+    let mut stack_types = vec![];
+    let typed = typecheck_block(block, stack, &mut stack_types)
+        .map_err(|(err, location)| source_map.locate_error(err, location))?;
+
+    Ok(TypecheckResult {
+        stack: typed,
+        stack_types,
+    })
+}
+
+/// Infers the type of the element returned by the body of MAP, e.g. to type the result of MAP on `None`
+pub fn map_body_type(body: &Block, element: MType, rest: &[MType]) -> Result<MType, MichelsonError> {
+    typecheck_map_body(body, element, rest, 0, &mut vec![]).map_err(|(err, _)| err)
+}
+
+/// Typechecks the code of a contract provided its parameter and storage types
+/// the code starts with `pair parameter storage` and must end with `pair (list operation) storage`
+pub fn typecheck_contract(
+    code: &[Micheline],
+    source_map: &SourceMap,
+    parameter: &MType,
+    storage: &MType,
+) -> Result<TypecheckResult, MichelsonError> {
+    let (block, source_map) = compiler::compile_with_locations(code, source_map)?;
+    typecheck_compiled_contract(&block, &source_map, parameter, storage)
+}
+
+/// Typechecks the compiled code of a contract provided its parameter and storage types
+pub fn typecheck_compiled_contract(
+    block: &Block,
+    source_map: &SourceMap,
+    parameter: &MType,
+    storage: &MType,
+) -> Result<TypecheckResult, MichelsonError> {
+    let input = MType::pair(parameter.clone(), storage.clone());
+    let result = typecheck_with_locations(block, source_map, vec![input])?;
+    check_contract_stack(&result.stack, storage)?;
+
+    Ok(result)
+}
+
+/**
+ * TESTS
+ */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser;
+
+    fn typecheck_str(code: &str, stack: StackType) -> Result<TypecheckResult, MichelsonError> {
+        let (code, source_map) = parser::parse_with_locations(code)?;
+        typecheck_code(&code, &source_map, stack)
+    }
+
+    fn pair(left: MType, right: MType) -> MType {
+        MType::pair(left, right)
+    }
+
+    // PASSING
+    #[test]
+    fn typecheck_stack_after_every_instruction() {
+        let result = typecheck_str(
+            "PUSH nat 5 ; ADD ; DUP ; NIL int ; SWAP",
+            vec![MType::Nat, MType::Unit],
+        )
+        .unwrap();
+        assert_eq!(
+            result.stack,
+            Typed::Stack(vec![
+                MType::Nat,
+                MType::List(Box::new(MType::Int)),
+                MType::Nat,
+                MType::Unit
+            ])
+        );
+        assert_eq!(
+            result.stack_types,
+            vec![
+                (1, Typed::Stack(vec![MType::Nat, MType::Nat, MType::Unit])),
+                (4, Typed::Stack(vec![MType::Nat, MType::Unit])),
+                (5, Typed::Stack(vec![MType::Nat, MType::Nat, MType::Unit])),
+                (
+                    6,
+                    Typed::Stack(vec![
+                        MType::List(Box::new(MType::Int)),
+                        MType::Nat,
+                        MType::Nat,
+                        MType::Unit
+                    ])
+                ),
+                (
+                    8,
+                    Typed::Stack(vec![
+                        MType::Nat,
+                        MType::List(Box::new(MType::Int)),
+                        MType::Nat,
+                        MType::Unit
+                    ])
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn typecheck_branches_and_failures() {
+        let code = "IF_LEFT { INT } { DROP ; PUSH string \"error\" ; FAILWITH } ; PUSH int 1 ; COMPARE ; GT";
+        let input = vec![MType::or(MType::Nat, MType::Unit)];
+        let result = typecheck_str(code, input).unwrap();
+        assert_eq!(result.stack, Typed::Stack(vec![MType::Bool]));
+        assert!(result
+            .stack_types
+            .iter()
+            .any(|(_, typed)| typed == &Typed::Failed));
+    }
+
+    #[test]
+    fn typecheck_map_bodies() {
+        let code = "MAP { PUSH int 1 ; ADD } ; SWAP ; MAP { CDR ; SIZE }";
+        let input = vec![
+            MType::List(Box::new(MType::Nat)),
+            MType::Map(Box::new((MType::String, MType::String))),
+        ];
+        let result = typecheck_str(code, input).unwrap();
+        assert_eq!(
+            result.stack,
+            Typed::Stack(vec![
+                MType::Map(Box::new((MType::String, MType::Nat))),
+                MType::List(Box::new(MType::Int)),
+            ])
+        );
+    }
+
+    #[test]
+    fn typecheck_counter_contract() {
+        let code = "UNPAIR ; IF_LEFT { ADD } { DROP 2 ; PUSH int 0 } ; NIL operation ; PAIR";
+        let (code, source_map) = parser::parse_with_locations(code).unwrap();
+        let parameter = MType::or(MType::Int, MType::Unit);
+        let result = typecheck_contract(&code, &source_map, &parameter, &MType::Int).unwrap();
+        assert_eq!(
+            result.stack,
+            Typed::Stack(vec![pair(
+                MType::List(Box::new(MType::Operation)),
+                MType::Int
+            )])
+        );
+    }
+
+    #[test]
+    fn typecheck_pair_fields() {
+        let input = vec![pair(MType::Nat, pair(MType::String, MType::Bool))];
+        let result = typecheck_str("DUP ; GET 3 ; SWAP ; PUSH int 5 ; UPDATE 4", input).unwrap();
+        assert_eq!(
+            result.stack,
+            Typed::Stack(vec![
+                pair(MType::Nat, pair(MType::String, MType::Int)),
+                MType::String,
+            ])
+        );
+    }
+
+    #[test]
+    fn typecheck_keeps_annotations() {
+        let input = vec![MType::from_string("pair (address %owner) (or (nat %add) (nat %sub))").unwrap()];
+        let result = typecheck_str("CDR ; PUSH (option (pair (int %count) nat)) None ; PAIR", input).unwrap();
+        match result.stack {
+            Typed::Stack(stack) => assert_eq!(
+                stack[0].to_string(),
+                "pair (option (pair (int %count) nat)) (or (nat %add) (nat %sub))"
+            ),
+            Typed::Failed => panic!("Expected a stack type"),
+        }
+    }
+
+    #[test]
+    fn typecheck_option_and_list_branches() {
+        let input = vec![
+            MType::Option(Box::new(MType::Nat)),
+            MType::List(Box::new(MType::Int)),
+        ];
+        let code = "IF_NONE { PUSH int 0 } { INT } ; SWAP ; IF_CONS { DIP { DROP } } { PUSH int 1 } ; ADD";
+        let result = typecheck_str(code, input).unwrap();
+        assert_eq!(result.stack, Typed::Stack(vec![MType::Int]));
+    }
+
+    #[test]
+    fn typecheck_iter_bodies() {
+        let input = vec![
+            MType::Set(Box::new(MType::Nat)),
+            MType::Nat,
+            MType::Map(Box::new((MType::Address, MType::Nat))),
+        ];
+        let code = "ITER { ADD } ; SWAP ; ITER { CDR ; ADD }";
+        let result = typecheck_str(code, input).unwrap();
+        assert_eq!(result.stack, Typed::Stack(vec![MType::Nat]));
+    }
+
+    #[test]
+    fn typecheck_loops() {
+        let input = vec![MType::Bool, MType::Int, MType::or(MType::Nat, MType::String)];
+        let code = "LOOP { PUSH int 1 ; ADD ; DUP ; GT } ; DROP ; LOOP_LEFT { DROP ; PUSH string \"done\" ; RIGHT nat }";
+        let result = typecheck_str(code, input).unwrap();
+        assert_eq!(result.stack, Typed::Stack(vec![MType::String]));
+    }
+
+    #[test]
+    fn typecheck_dip() {
+        let input = vec![MType::String, MType::Nat, MType::Int, MType::Unit];
+        let result = typecheck_str("DIP { ADD } ; DIP 2 { DROP ; PUSH bool True }", input).unwrap();
+        assert_eq!(
+            result.stack,
+            Typed::Stack(vec![MType::String, MType::Int, MType::Bool])
+        );
+    }
+
+    #[test]
+    fn typecheck_lambdas() {
+        let code = "LAMBDA (pair string nat) nat { CDR } ; SWAP ; APPLY ; SWAP ; EXEC ; \
+                    LAMBDA_REC nat nat { DUP ; INT ; EQ ; IF { DROP 2 ; PUSH nat 1 } { DROP ; PUSH nat 1 ; EXEC } } ; \
+                    SWAP ; EXEC";
+        let result = typecheck_str(code, vec![MType::String, MType::Nat]).unwrap();
+        assert_eq!(result.stack, Typed::Stack(vec![MType::Nat]));
+    }
+
+    // FAILING
+    #[test]
+    #[should_panic(
+        expected = "Invalid stack type for instruction ADD, expected [int | nat | mutez | timestamp : int | nat | mutez | timestamp : S], but got [string : nat] at line 2, column 1"
+    )]
+    fn typecheck_wrong_operands() {
+        match typecheck_str("PUSH string \"one\" ;\nADD", vec![MType::Nat]) {
+            Ok(_) => assert!(false),
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Branches of instruction IF end with different stack types: [nat] and [int]"
+    )]
+    fn typecheck_branches_mismatch() {
+        match typecheck_str("IF { PUSH nat 1 } { PUSH int 1 }", vec![MType::Bool]) {
+            Ok(_) => assert!(false),
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Invalid stack type for instruction LAMBDA, expected [int], but got [nat] at line 1, column 1"
+    )]
+    fn typecheck_lambda_wrong_result() {
+        match typecheck_str("LAMBDA nat int { PUSH nat 1 ; ADD }", vec![]) {
+            Ok(_) => assert!(false),
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Invalid stack type for instruction EXEC, expected ['a : lambda 'a 'b : S], but got [int : lambda nat nat]"
+    )]
+    fn typecheck_exec_wrong_param() {
+        match typecheck_str("EXEC", vec![MType::Int, MType::Lambda(Box::new((MType::Nat, MType::Nat)))]) {
+            Ok(_) => assert!(false),
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    #[test]
+    fn typecheck_error_in_untaken_branch() {
+        match typecheck_str("IF { UNIT } { UNIT ; NEG }", vec![MType::Bool]) {
+            Ok(_) => assert!(false),
+            Err(MichelsonError::Located { error, span, .. }) => {
+                assert_eq!(
+                    *error,
+                    MichelsonError::InvalidStackType((
+                        String::from("int | nat : S"),
+                        vec![MType::Unit],
+                        Instruction::NEG
+                    ))
+                );
+                assert_eq!((span.line, span.column), (1, 22));
+            }
+            Err(err) => panic!("Unexpected error: {:?}", err),
+        }
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Unexpected stack type at the end of the contract, expected [pair (list operation) nat], but got [nat]"
+    )]
+    fn typecheck_contract_final_stack() {
+        let (code, source_map) = parser::parse_with_locations("CDR").unwrap();
+        match typecheck_contract(&code, &source_map, &MType::Unit, &MType::Nat) {
+            Ok(_) => assert!(false),
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Unreachable code after FAILWITH instruction")]
+    fn typecheck_code_after_failwith() {
+        match typecheck_str("FAILWITH ; DROP", vec![MType::Unit]) {
+            Ok(_) => assert!(false),
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Invalid stack type for instruction DIP, expected ['a1 : ... : 'an : S], but got [nat] at line 1, column 1"
+    )]
+    fn typecheck_dip_stack_too_short() {
+        match typecheck_str("DIP 2 { DROP }", vec![MType::Nat]) {
+            Ok(_) => assert!(false),
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Invalid stack type for instruction ITER, expected [nat], but got [int] at line 1, column 1"
+    )]
+    fn typecheck_iter_changes_stack() {
+        let input = vec![MType::List(Box::new(MType::Int)), MType::Nat];
+        match typecheck_str("ITER { ADD }", input) {
+            Ok(_) => assert!(false),
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Invalid stack type for instruction LOOP, expected [bool : int], but got [int] at line 1, column 1"
+    )]
+    fn typecheck_loop_without_condition() {
+        match typecheck_str("LOOP { PUSH int 1 ; ADD }", vec![MType::Bool, MType::Int]) {
+            Ok(_) => assert!(false),
+            Err(err) => panic!("{}", err),
+        }
+    }
+}